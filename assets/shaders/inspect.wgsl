@@ -0,0 +1,39 @@
+// the demo's inspection views, picked by `mode`: 0 checkers the uvs, 1 shows world
+// normals as colors and 2 shows vertex colors
+
+struct InspectionMaterial {
+    mode: u32,
+    checker_scale: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: InspectionMaterial;
+
+@fragment
+fn fragment(
+    #import bevy_pbr::mesh_vertex_output
+) -> @location(0) vec4<f32> {
+    if material.mode == 0u {
+#ifdef VERTEX_UVS
+        let cell = floor(uv * material.checker_scale);
+        let parity = abs(fract((cell.x + cell.y) * 0.5)) * 2.0;
+        // a tint that changes across the texture, so stretching and flips show up
+        let tint = vec3<f32>(fract(uv), 0.5);
+        return vec4<f32>(mix(vec3<f32>(0.15), tint, parity), 1.0);
+#else
+        // no uvs to show
+        return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+#endif
+    }
+
+    if material.mode == 1u {
+        return vec4<f32>(normalize(world_normal) * 0.5 + 0.5, 1.0);
+    }
+
+#ifdef VERTEX_COLORS
+    return color;
+#else
+    // no vertex colors to show
+    return vec4<f32>(1.0, 0.0, 1.0, 1.0);
+#endif
+}
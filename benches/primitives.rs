@@ -0,0 +1,134 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_procedural_geometry::{
+    extrude::extrude_glyph,
+    hex::{generate_hex_grid, HexGridParams},
+    path::generate_ribbon,
+    plane::{generate_radial_lod_plane, RadialLodPlaneParams},
+    rock::{generate_rock, RockParams},
+    sphere::{generate_uv_sphere, UvSphereParams},
+    stairs::{generate_spiral_stairs, SpiralStairsParams},
+    terrain::{generate_heightmap, HeightmapParams},
+    tree::{generate_tree, TreeParams},
+};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+// throughput is counted in triangles, so criterion's elements/s reads as triangles per
+// second; divide by 1000 for triangles per millisecond
+fn triangles(mesh: &Mesh) -> u64 {
+    let corners = match mesh.indices() {
+        Some(indices) => indices.len(),
+        None => mesh.count_vertices(),
+    };
+    assert!(corners > 0, "benchmarked a mesh with no triangles");
+    corners as u64 / 3
+}
+
+// generates the mesh once to size the throughput, then benchmarks generating it
+fn bench_sizes<P: Copy + std::fmt::Display>(
+    c: &mut Criterion,
+    name: &str,
+    sizes: &[P],
+    generate: impl Fn(P) -> Mesh,
+) {
+    let mut group = c.benchmark_group(name);
+    for &size in sizes {
+        group.throughput(Throughput::Elements(triangles(&generate(size))));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| black_box(generate(black_box(size))))
+        });
+    }
+    group.finish();
+}
+
+fn primitives(c: &mut Criterion) {
+    let height = |x: f32, z: f32| (x * 0.3).sin() * (z * 0.2).cos();
+
+    bench_sizes(c, "generate_heightmap", &[16, 64, 256], |cells| {
+        let params = HeightmapParams {
+            rows: cells,
+            cols: cells,
+            ..default()
+        };
+        generate_heightmap(&params, height)
+    });
+
+    bench_sizes(
+        c,
+        "generate_radial_lod_plane",
+        &[16, 64, 256],
+        |center_res| {
+            generate_radial_lod_plane(&RadialLodPlaneParams {
+                center_res,
+                ..default()
+            })
+        },
+    );
+
+    // by stacks, with twice as many sectors so the quads stay roughly square
+    bench_sizes(c, "generate_uv_sphere", &[8, 32, 128], |stacks| {
+        generate_uv_sphere(&UvSphereParams {
+            stacks,
+            sectors: stacks * 2,
+            ..default()
+        })
+        .unwrap()
+    });
+
+    bench_sizes(c, "generate_hex_grid", &[4, 16, 64], |rings| {
+        generate_hex_grid(&HexGridParams { rings, ..default() }).0
+    });
+
+    bench_sizes(c, "generate_spiral_stairs", &[16, 64, 256], |steps| {
+        generate_spiral_stairs(&SpiralStairsParams { steps, ..default() })
+    });
+
+    bench_sizes(c, "generate_rock", &[1, 2, 4], |subdivisions| {
+        generate_rock(
+            7,
+            &RockParams {
+                subdivisions,
+                ..default()
+            },
+        )
+        .0
+    });
+
+    bench_sizes(c, "generate_tree", &[1, 2, 4], |max_depth| {
+        generate_tree(
+            7,
+            &TreeParams {
+                max_depth,
+                ..default()
+            },
+        )
+    });
+
+    bench_sizes(c, "generate_ribbon", &[100, 1000, 10000], |points| {
+        let points: Vec<Vec3> = (0..points)
+            .map(|i| {
+                let t = i as f32 * 0.05;
+                Vec3::new(t.cos() * 10.0, t * 0.1, -t.sin() * 10.0)
+            })
+            .collect();
+        generate_ribbon(&points, 0.5, Vec3::Y).unwrap()
+    });
+
+    // a ring, so the hole bridging is exercised along with the ear clipping
+    bench_sizes(c, "extrude_glyph", &[16, 64, 256], |corners| {
+        let circle = |radius: f32, clockwise: bool| -> Vec<Vec2> {
+            (0..corners)
+                .map(|i| {
+                    let angle = i as f32 / corners as f32 * TAU;
+                    let angle = if clockwise { -angle } else { angle };
+                    Vec2::new(angle.cos(), angle.sin()) * radius
+                })
+                .collect()
+        };
+        extrude_glyph(&[circle(1.0, false), circle(0.5, true)], 0.2)
+    });
+}
+
+criterion_group!(benches, primitives);
+criterion_main!(benches);
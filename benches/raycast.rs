@@ -0,0 +1,71 @@
+use bevy::{prelude::*, render::mesh::PrimitiveTopology};
+use bevy_procedural_geometry::{bvh::MeshBvh, raycast::raycast_mesh};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// 224 * 224 quads, a little over 100k triangles
+const TERRAIN_QUADS: usize = 224;
+const TERRAIN_SIDE_LEN: f32 = 100.0;
+
+fn terrain() -> Mesh {
+    let step = TERRAIN_SIDE_LEN / TERRAIN_QUADS as f32;
+    let height = |x: f32, z: f32| (x * 0.3).sin() * (z * 0.2).cos() * 3.0;
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    for row in 0..TERRAIN_QUADS {
+        for col in 0..TERRAIN_QUADS {
+            let (x0, z0) = (col as f32 * step, row as f32 * step);
+            let (x1, z1) = (x0 + step, z0 + step);
+
+            let a = [x0, height(x0, z0), z0];
+            let b = [x0, height(x0, z1), z1];
+            let c = [x1, height(x1, z1), z1];
+            let d = [x1, height(x1, z0), z0];
+
+            positions.extend([a, b, c, c, d, a]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh
+}
+
+// a fixed spread of downward rays across the terrain
+fn ray_origins() -> Vec<Vec3> {
+    (0..64)
+        .map(|i| {
+            let x = (i % 8) as f32 / 8.0 * TERRAIN_SIDE_LEN + 1.0;
+            let z = (i / 8) as f32 / 8.0 * TERRAIN_SIDE_LEN + 1.0;
+            Vec3::new(x, 50.0, z)
+        })
+        .collect()
+}
+
+fn raycast(c: &mut Criterion) {
+    let mesh = terrain();
+    let origins = ray_origins();
+
+    c.bench_function("raycast_mesh 64 rays", |b| {
+        b.iter(|| {
+            for origin in &origins {
+                black_box(raycast_mesh(&mesh, *origin, Vec3::NEG_Y));
+            }
+        })
+    });
+
+    let bvh = MeshBvh::build(&mesh);
+    c.bench_function("MeshBvh::raycast 64 rays", |b| {
+        b.iter(|| {
+            for origin in &origins {
+                black_box(bvh.raycast(*origin, Vec3::NEG_Y));
+            }
+        })
+    });
+
+    c.bench_function("MeshBvh::build", |b| {
+        b.iter(|| MeshBvh::build(black_box(&mesh)))
+    });
+}
+
+criterion_group!(benches, raycast);
+criterion_main!(benches);
@@ -0,0 +1,527 @@
+//! Renders every generator off-screen and writes each one to a PNG, for checking
+//! generators by eye and comparing against earlier runs.
+//!
+//! ```sh
+//! cargo run --example gallery --features gallery -- target/gallery
+//! ```
+//!
+//! Exits with an error if any primitive renders as nothing but background.
+
+use std::{num::NonZeroU32, path::PathBuf, sync::mpsc};
+
+use bevy::{
+    app::AppExit,
+    math::Rect,
+    prelude::*,
+    render::{
+        camera::RenderTarget,
+        extract_resource::{ExtractResource, ExtractResourcePlugin},
+        main_graph::node::CAMERA_DRIVER,
+        render_asset::RenderAssets,
+        render_graph::{self, NodeRunError, RenderGraph, RenderGraphContext},
+        render_resource::{
+            Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageDataLayout,
+            Maintain, MapMode, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        renderer::{RenderContext, RenderDevice},
+        RenderApp,
+    },
+    utils::HashMap,
+    window::WindowPlugin,
+};
+use bevy_procedural_geometry::{
+    bounds::bounding_sphere,
+    cylinder::{
+        generate_cone, generate_cylinder, generate_half_pipe, CapStyle, ConeParams, CylinderParams,
+    },
+    extrude::extrude_glyph,
+    fence::generate_fence,
+    gizmo::generate_axis_gizmo,
+    grass::generate_grass_clump,
+    hex::{generate_hex_columns, generate_hex_grid, HexCoord, HexGridParams},
+    noise::{domain_warp, fbm},
+    ocean::{generate_ocean, GerstnerWave},
+    parametric::generate_parametric_surface,
+    path::{generate_ribbon, generate_torus_knot},
+    plane::{generate_radial_lod_plane, RadialLodPlaneParams},
+    polyhedra::{
+        generate_dodecahedron, generate_frustum, generate_octahedron, generate_pyramid,
+        generate_ramp, generate_tetrahedron, PlatonicParams, PyramidParams,
+    },
+    rock::{generate_rock, RockParams},
+    sdf::mesh_from_sdf,
+    sphere::{generate_cube_sphere, generate_uv_sphere, CubeSphereParams, Poles, UvSphereParams},
+    stairs::{generate_spiral_stairs, generate_staircase, FlightSpec, SpiralStairsParams},
+    terrain::{
+        generate_heightmap, generate_heightmap_adaptive, generate_terrain_with_rivers,
+        HeightmapParams,
+    },
+    tree::{generate_tree, TreeParams},
+    uv::UvMode,
+    wall::generate_brick_wall,
+};
+
+const SIZE: u32 = 512;
+// rows of a buffer that a texture is copied into have to be a multiple of this long
+const ROW_ALIGNMENT: u32 = 256;
+// frames to let a newly spawned mesh settle (pipelines compiling, the copy trailing a
+// frame behind) before its image counts as blank
+const SETTLE_FRAMES: u32 = 5;
+const MAX_FRAMES: u32 = 120;
+
+fn main() {
+    let out_dir = std::env::args()
+        .nth(1)
+        .map_or_else(|| PathBuf::from("target/gallery"), PathBuf::from);
+    std::fs::create_dir_all(&out_dir).expect("couldn't create the output directory");
+
+    let mut app = App::new();
+    app.insert_resource(ClearColor(Color::BLACK))
+        .insert_resource(Gallery {
+            out_dir,
+            primitives: primitives(),
+            current: 0,
+            frames: 0,
+            spawned: None,
+            blank: vec![],
+        })
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            add_primary_window: false,
+            exit_on_all_closed: false,
+            ..default()
+        }))
+        .add_plugin(ExtractResourcePlugin::<Capture>::default())
+        .add_startup_system(setup)
+        .add_system(photograph);
+
+    let render_app = app.sub_app_mut(RenderApp);
+    let mut graph = render_app.world.resource_mut::<RenderGraph>();
+    graph.add_node("gallery_copy", CopyNode);
+    graph
+        .add_node_edge(CAMERA_DRIVER, "gallery_copy")
+        .expect("the camera driver node is always there");
+
+    app.run();
+}
+
+fn primitives() -> Vec<(&'static str, Mesh)> {
+    let heights: HashMap<HexCoord, f32> = (-2..=2)
+        .flat_map(|q| (-2..=2).map(move |r| HexCoord::new(q, r)))
+        .map(|coord| {
+            (
+                coord,
+                ((coord.q * 3 + coord.r * 5).rem_euclid(4)) as f32 * 0.3,
+            )
+        })
+        .collect();
+    let helix: Vec<Vec3> = (0..64)
+        .map(|i| {
+            let t = i as f32 * 0.2;
+            Vec3::new(t.cos() * 2.0, t * 0.2, -t.sin() * 2.0)
+        })
+        .collect();
+    // a gentle curve over rolling ground
+    let fence: Vec<Vec3> = (0..=16)
+        .map(|i| {
+            let x = i as f32 * 0.5 - 4.0;
+            Vec3::new(x, (x * 0.8).sin() * 0.3, (x * 0.4).cos() * 1.5)
+        })
+        .collect();
+    let circle = |radius: f32| -> Vec<Vec2> {
+        (0..32)
+            .map(|i| {
+                let angle = i as f32 / 32.0 * std::f32::consts::TAU;
+                Vec2::new(angle.cos(), angle.sin()) * radius
+            })
+            .collect()
+    };
+    let terrain = HeightmapParams {
+        rows: 64,
+        cols: 64,
+        cell_size: 0.25,
+        ..default()
+    };
+
+    vec![
+        (
+            "pyramid",
+            generate_pyramid(&PyramidParams::default()).unwrap(),
+        ),
+        ("ramp", generate_ramp(1.0, 2.0, 0.75).unwrap()),
+        (
+            "frustum",
+            generate_frustum(Vec2::new(1.0, 0.75), Vec2::new(0.5, 0.25), 1.0).unwrap(),
+        ),
+        (
+            "tetrahedron",
+            generate_tetrahedron(&PlatonicParams::default()).unwrap(),
+        ),
+        (
+            "octahedron",
+            generate_octahedron(&PlatonicParams::default()).unwrap(),
+        ),
+        (
+            "dodecahedron",
+            generate_dodecahedron(&PlatonicParams::default()).unwrap(),
+        ),
+        (
+            "heightmap",
+            generate_heightmap(&HeightmapParams::default(), |x, z| {
+                (x * 0.5).sin() * (z * 0.4).cos()
+            }),
+        ),
+        // the same noise before and after warping
+        (
+            "fbm_terrain",
+            generate_heightmap(&terrain, |x, z| fbm(7, Vec3::new(x, 0.0, z) / 4.0, 5) * 2.0),
+        ),
+        (
+            "warped_terrain",
+            generate_heightmap(&terrain, |x, z| {
+                domain_warp(7, Vec3::new(x, 0.0, z) / 4.0, 5, 1.5, 2) * 2.0
+            }),
+        ),
+        (
+            "adaptive_terrain",
+            generate_heightmap_adaptive(
+                |x, z| domain_warp(7, Vec3::new(x, 0.0, z) / 4.0, 5, 1.5, 2) * 2.0,
+                Rect::new(-8.0, -8.0, 8.0, 8.0),
+                4000,
+            ),
+        ),
+        (
+            "river_terrain",
+            generate_terrain_with_rivers(16.0, 0.25, 7, 5),
+        ),
+        (
+            "ocean",
+            generate_ocean(
+                16.0,
+                64,
+                &[
+                    GerstnerWave::default(),
+                    GerstnerWave {
+                        direction: Vec2::new(0.6, 1.0),
+                        wavelength: 1.5,
+                        amplitude: 0.05,
+                        steepness: 0.3,
+                    },
+                ],
+            ),
+        ),
+        (
+            "radial_lod_plane",
+            generate_radial_lod_plane(&RadialLodPlaneParams::default()),
+        ),
+        ("hex_grid", generate_hex_grid(&HexGridParams::default()).0),
+        ("hex_columns", generate_hex_columns(&heights, 1.0)),
+        (
+            "spiral_stairs",
+            generate_spiral_stairs(&SpiralStairsParams::default()),
+        ),
+        (
+            "staircase",
+            generate_staircase(&[
+                FlightSpec::default(),
+                FlightSpec {
+                    turn: -std::f32::consts::FRAC_PI_2,
+                    ..default()
+                },
+                FlightSpec::default(),
+            ]),
+        ),
+        (
+            "cube_sphere",
+            generate_cube_sphere(&CubeSphereParams::default()).unwrap(),
+        ),
+        (
+            "cylinder",
+            generate_cylinder(&CylinderParams {
+                cap: CapStyle::Grid,
+                ..default()
+            })
+            .unwrap(),
+        ),
+        ("cone", generate_cone(&ConeParams::default()).unwrap()),
+        (
+            "half_pipe",
+            generate_half_pipe(1.0, 2.0, std::f32::consts::PI, 24).unwrap(),
+        ),
+        (
+            "uv_sphere",
+            generate_uv_sphere(&UvSphereParams {
+                poles: Poles::Cap,
+                ..default()
+            })
+            .unwrap(),
+        ),
+        ("rock", generate_rock(1, &RockParams::default()).0),
+        ("grass_clump", generate_grass_clump(24, 1, 0.5)),
+        ("tree", generate_tree(1, &TreeParams::default())),
+        (
+            "brick_wall",
+            generate_brick_wall(6, 8, Vec3::new(0.4, 0.12, 0.2), 0.02, UvMode::PerFace),
+        ),
+        ("fence", generate_fence(&fence, 1.0, 1.0, 2)),
+        ("ribbon", generate_ribbon(&helix, 0.5, Vec3::Y).unwrap()),
+        ("torus_knot", generate_torus_knot(2, 3, 1.0, 0.2, 256)),
+        (
+            "parametric_torus",
+            generate_parametric_surface(24, 48, true, |u, v| {
+                let (tube, around) = (u * std::f32::consts::TAU, v * std::f32::consts::TAU);
+                let reach = 1.0 + 0.4 * tube.cos();
+                Vec3::new(reach * around.sin(), 0.4 * tube.sin(), reach * around.cos())
+            }),
+        ),
+        ("glyph", extrude_glyph(&[circle(1.0), circle(0.5)], 0.3)),
+        ("axis_gizmo", generate_axis_gizmo(1.5).unwrap()),
+        (
+            "sdf",
+            mesh_from_sdf(
+                |p| {
+                    // two spheres blended with a smooth minimum
+                    let a = (p - Vec3::X * 0.6).length() - 0.7;
+                    let b = (p + Vec3::X * 0.6).length() - 0.5;
+                    let h = (0.5 + 0.5 * (b - a) / 0.4).clamp(0.0, 1.0);
+                    b + (a - b) * h - 0.4 * h * (1.0 - h)
+                },
+                (Vec3::splat(-1.6), Vec3::splat(1.6)),
+                48,
+            ),
+        ),
+    ]
+}
+
+#[derive(Resource)]
+struct Gallery {
+    out_dir: PathBuf,
+    primitives: Vec<(&'static str, Mesh)>,
+    current: usize,
+    frames: u32,
+    spawned: Option<Entity>,
+    blank: Vec<&'static str>,
+}
+
+// the off-screen target and the buffer its pixels are copied back into every frame
+#[derive(Resource, Clone, ExtractResource)]
+struct Capture {
+    image: Handle<Image>,
+    buffer: Buffer,
+}
+
+#[derive(Component)]
+struct GalleryCamera;
+
+fn padded_row_bytes() -> u32 {
+    (SIZE * 4 + ROW_ALIGNMENT - 1) / ROW_ALIGNMENT * ROW_ALIGNMENT
+}
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>, device: Res<RenderDevice>) {
+    let size = Extent3d {
+        width: SIZE,
+        height: SIZE,
+        depth_or_array_layers: 1,
+    };
+    let mut target = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("gallery"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_SRC
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+        },
+        ..default()
+    };
+    target.resize(size);
+    let image = images.add(target);
+
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("gallery readback"),
+        size: (padded_row_bytes() * SIZE) as u64,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    commands.insert_resource(Capture {
+        image: image.clone(),
+        buffer,
+    });
+
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Image(image),
+                ..default()
+            },
+            ..default()
+        },
+        GalleryCamera,
+    ));
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(1.0, 2.0, 1.5).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+    commands.insert_resource(AmbientLight {
+        brightness: 0.3,
+        ..default()
+    });
+}
+
+// spawns each primitive in turn, framed by its bounding sphere, and saves it once it's
+// had time to render
+fn photograph(
+    mut commands: Commands,
+    mut gallery: ResMut<Gallery>,
+    capture: Res<Capture>,
+    device: Res<RenderDevice>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut camera: Query<&mut Transform, With<GalleryCamera>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let Some((name, mesh)) = gallery.primitives.get(gallery.current) else {
+        if gallery.blank.is_empty() {
+            exit.send(AppExit);
+            return;
+        }
+        eprintln!("rendered blank: {}", gallery.blank.join(", "));
+        std::process::exit(1);
+    };
+    let name = *name;
+
+    let Some(entity) = gallery.spawned else {
+        let mesh = mesh.clone();
+        let (center, radius) = bounding_sphere(&mesh).unwrap_or((Vec3::ZERO, 1.0));
+        // far enough back for the sphere to fit the default 45 degree field of view
+        let distance = radius / (std::f32::consts::FRAC_PI_8).sin() * 1.1;
+        let eye = center + Vec3::new(1.0, 0.8, 1.0).normalize() * distance;
+        for mut transform in &mut camera {
+            *transform = Transform::from_translation(eye).looking_at(center, Vec3::Y);
+        }
+
+        gallery.spawned = Some(
+            commands
+                .spawn(PbrBundle {
+                    mesh: meshes.add(mesh),
+                    material: materials.add(StandardMaterial {
+                        base_color: Color::rgb(0.8, 0.8, 0.8),
+                        double_sided: true,
+                        cull_mode: None,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .id(),
+        );
+        gallery.frames = 0;
+        return;
+    };
+
+    gallery.frames += 1;
+    if gallery.frames < SETTLE_FRAMES {
+        return;
+    }
+
+    let pixels = read_pixels(&capture.buffer, &device);
+    let lit = pixels.chunks(4).any(|pixel| pixel[..3] != [0, 0, 0]);
+    if !lit && gallery.frames < MAX_FRAMES {
+        return;
+    }
+
+    if lit {
+        let path = gallery.out_dir.join(format!("{name}.png"));
+        if let Err(err) = image::save_buffer(&path, &pixels, SIZE, SIZE, image::ColorType::Rgba8) {
+            eprintln!("couldn't write {}: {err}", path.display());
+            std::process::exit(1);
+        }
+    } else {
+        gallery.blank.push(name);
+    }
+
+    commands.entity(entity).despawn();
+    gallery.spawned = None;
+    gallery.current += 1;
+}
+
+// the last frame copied back, with the row padding stripped
+fn read_pixels(buffer: &Buffer, device: &RenderDevice) -> Vec<u8> {
+    let slice = buffer.slice(..);
+    let (sender, receiver) = mpsc::channel();
+    device.map_buffer(&slice, MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.wgpu_device().poll(Maintain::Wait);
+    receiver
+        .recv()
+        .expect("mapping finishes once the device is polled")
+        .expect("couldn't map the readback buffer");
+
+    let pixels = slice
+        .get_mapped_range()
+        .chunks(padded_row_bytes() as usize)
+        .flat_map(|row| row[..(SIZE * 4) as usize].to_vec())
+        .collect();
+    buffer.unmap();
+    pixels
+}
+
+struct CopyNode;
+
+impl render_graph::Node for CopyNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let Some(capture) = world.get_resource::<Capture>() else {
+            return Ok(());
+        };
+        let Some(image) = world.resource::<RenderAssets<Image>>().get(&capture.image) else {
+            return Ok(());
+        };
+
+        render_context.command_encoder.copy_texture_to_buffer(
+            image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &capture.buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(padded_row_bytes()),
+                    rows_per_image: None,
+                },
+            },
+            Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // an empty mesh would only ever photograph as background, and a repeated name
+    // would overwrite another primitive's image
+    #[test]
+    fn every_primitive_has_a_name_and_something_to_frame() {
+        let primitives = primitives();
+        let mut names: Vec<&str> = primitives.iter().map(|(name, _)| *name).collect();
+        names.sort_unstable();
+        names.dedup();
+        assert_eq!(names.len(), primitives.len());
+
+        for (name, mesh) in &primitives {
+            let (_, radius) = bounding_sphere(mesh).unwrap_or_else(|| panic!("{name} is empty"));
+            assert!(radius > 0.0, "{name}");
+        }
+    }
+}
@@ -0,0 +1,43 @@
+//! A generated mesh whose params can be edited live from `bevy-inspector-egui`'s world
+//! inspector: pick the entity, change any field of its `ProceduralMesh`, and the mesh
+//! is rebuilt.
+//!
+//! ```sh
+//! cargo run --example inspector --features inspector
+//! ```
+
+use bevy::prelude::*;
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+use bevy_procedural_geometry::{
+    live::{ProceduralMesh, ProceduralMeshPlugin},
+    sphere::UvSphereParams,
+};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(WorldInspectorPlugin)
+        .add_plugin(ProceduralMeshPlugin)
+        .add_startup_system(setup)
+        .run();
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    // the mesh handle is filled in by `ProceduralMeshPlugin`
+    commands.spawn((
+        PbrBundle {
+            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            ..default()
+        },
+        ProceduralMesh::UvSphere(UvSphereParams::default()),
+        Name::new("procedural mesh"),
+    ));
+    commands.spawn(PointLightBundle {
+        transform: Transform::from_xyz(4.0, 6.0, 4.0),
+        ..default()
+    });
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 1.5, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
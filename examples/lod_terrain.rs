@@ -0,0 +1,48 @@
+//! Rolling hills meshed in chunks around a camera flying over them, with fine chunks close
+//! up and coarse ones far away, rebuilt in the background as it goes.
+//!
+//! ```sh
+//! cargo run --example lod_terrain
+//! ```
+
+use bevy::prelude::*;
+use bevy_procedural_geometry::chunks::{LodTerrain, LodTerrainParams, LodTerrainPlugin, LodViewer};
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LodTerrainPlugin::<Handle<StandardMaterial>>::default())
+        .add_startup_system(setup)
+        .add_system(fly)
+        .run();
+}
+
+fn hills(x: f32, z: f32) -> f32 {
+    12.0 * (x * 0.02).sin() * (z * 0.03).cos() + 2.0 * (x * 0.11 + z * 0.07).sin()
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    // the chunks are drawn with the terrain's material
+    commands.spawn((
+        SpatialBundle::default(),
+        materials.add(Color::rgb(0.4, 0.6, 0.3).into()),
+        LodTerrain::new(LodTerrainParams::default(), hills),
+    ));
+    commands.spawn(DirectionalLightBundle {
+        transform: Transform::from_xyz(1.0, 2.0, 1.0).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+    commands.spawn((Camera3dBundle::default(), LodViewer));
+}
+
+// flies the camera around a wide circle, a little above the hills
+fn fly(time: Res<Time>, mut cameras: Query<&mut Transform, With<LodViewer>>) {
+    let angle = time.elapsed_seconds() * 0.05;
+    let (sin, cos) = angle.sin_cos();
+    let ground = Vec3::new(cos, 0.0, sin) * 300.0;
+    let position = ground + Vec3::Y * (hills(ground.x, ground.z) + 8.0);
+    let ahead = Vec3::new(-sin, -0.2, cos);
+    for mut transform in &mut cameras {
+        *transform = Transform::from_translation(position).looking_at(position + ahead, Vec3::Y);
+    }
+}
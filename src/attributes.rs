@@ -0,0 +1,413 @@
+//! Typed access to the standard vertex attributes, without matching on
+//! `VertexAttributeValues` at every use, and helpers for moving attributes between
+//! channels.
+//!
+//! Each accessor returns `None` when the mesh doesn't have the attribute, or has it in a
+//! format other than the one Bevy's own meshes use.
+
+use std::fmt;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{MeshVertexAttribute, VertexAttributeValues},
+        render_resource::VertexFormat,
+    },
+};
+
+use crate::util::triangle_indices;
+
+/// How far each vertex moved over the last frame, for motion blur and other effects
+/// that need to know where a surface was, written by [`record_velocities`] and by
+/// animation such as [`update_ocean`](crate::ocean::update_ocean) whenever the mesh
+/// has it.
+pub const ATTRIBUTE_VELOCITY: MeshVertexAttribute =
+    MeshVertexAttribute::new("Vertex_Velocity", 372_915_480, VertexFormat::Float32x3);
+
+pub fn positions(mesh: &Mesh) -> Option<&[[f32; 3]]> {
+    match mesh.attribute(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => Some(positions),
+        _ => None,
+    }
+}
+
+pub fn normals(mesh: &Mesh) -> Option<&[[f32; 3]]> {
+    match mesh.attribute(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+        _ => None,
+    }
+}
+
+pub fn uvs(mesh: &Mesh) -> Option<&[[f32; 2]]> {
+    match mesh.attribute(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+        _ => None,
+    }
+}
+
+/// Vertex colors, in linear space.
+pub fn colors(mesh: &Mesh) -> Option<&[[f32; 4]]> {
+    match mesh.attribute(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => Some(colors),
+        _ => None,
+    }
+}
+
+pub fn velocities(mesh: &Mesh) -> Option<&[[f32; 3]]> {
+    match mesh.attribute(ATTRIBUTE_VELOCITY) {
+        Some(VertexAttributeValues::Float32x3(velocities)) => Some(velocities),
+        _ => None,
+    }
+}
+
+pub fn positions_mut(mesh: &mut Mesh) -> Option<&mut [[f32; 3]]> {
+    match mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION) {
+        Some(VertexAttributeValues::Float32x3(positions)) => Some(positions),
+        _ => None,
+    }
+}
+
+pub fn normals_mut(mesh: &mut Mesh) -> Option<&mut [[f32; 3]]> {
+    match mesh.attribute_mut(Mesh::ATTRIBUTE_NORMAL) {
+        Some(VertexAttributeValues::Float32x3(normals)) => Some(normals),
+        _ => None,
+    }
+}
+
+pub fn uvs_mut(mesh: &mut Mesh) -> Option<&mut [[f32; 2]]> {
+    match mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0) {
+        Some(VertexAttributeValues::Float32x2(uvs)) => Some(uvs),
+        _ => None,
+    }
+}
+
+pub fn colors_mut(mesh: &mut Mesh) -> Option<&mut [[f32; 4]]> {
+    match mesh.attribute_mut(Mesh::ATTRIBUTE_COLOR) {
+        Some(VertexAttributeValues::Float32x4(colors)) => Some(colors),
+        _ => None,
+    }
+}
+
+pub fn velocities_mut(mesh: &mut Mesh) -> Option<&mut [[f32; 3]]> {
+    match mesh.attribute_mut(ATTRIBUTE_VELOCITY) {
+        Some(VertexAttributeValues::Float32x3(velocities)) => Some(velocities),
+        _ => None,
+    }
+}
+
+/// Sets [`ATTRIBUTE_VELOCITY`] to how far each vertex has moved from where it was in
+/// `previous`, the positions it had a frame ago, adding the attribute if the mesh
+/// doesn't have it yet. Vertices without a previous position get a velocity of zero.
+///
+/// Taking a copy of the positions before moving them by hand and calling this after
+/// works for any deformation, like a [`morph`](crate::morph::morph) between meshes with
+/// matching vertices.
+pub fn record_velocities(mesh: &mut Mesh, previous: &[[f32; 3]]) {
+    let velocities: Vec<[f32; 3]> = positions(mesh)
+        .unwrap_or_default()
+        .iter()
+        .enumerate()
+        .map(|(i, position)| match previous.get(i) {
+            Some(previous) => (Vec3::from(*position) - Vec3::from(*previous)).into(),
+            None => [0.0; 3],
+        })
+        .collect();
+    mesh.insert_attribute(ATTRIBUTE_VELOCITY, velocities);
+}
+
+/// The corners of each triangle of a `TriangleList` mesh, in index buffer order, or
+/// vertex order for a non-indexed mesh. Other topologies have no triangles, and
+/// triangles with an index past the last vertex are skipped.
+pub fn triangles(mesh: &Mesh) -> impl Iterator<Item = [Vec3; 3]> + '_ {
+    let positions = positions(mesh).unwrap_or_default();
+    triangle_indices(mesh).into_iter().filter_map(move |tri| {
+        let [a, b, c] = tri.map(|v| positions.get(v).copied());
+        Some([a?, b?, c?].map(Vec3::from))
+    })
+}
+
+/// [`triangles`] with every standard attribute of each corner, not just its position.
+pub fn triangles_with_attrs(mesh: &Mesh) -> impl Iterator<Item = [SampledVertex; 3]> + '_ {
+    let positions = positions(mesh).unwrap_or_default();
+    let (normals, uvs, colors) = (normals(mesh), uvs(mesh), colors(mesh));
+    triangle_indices(mesh).into_iter().filter_map(move |tri| {
+        let [a, b, c] = tri.map(|v| {
+            Some(SampledVertex {
+                position: (*positions.get(v)?).into(),
+                normal: normals
+                    .and_then(|normals| normals.get(v))
+                    .map(|n| Vec3::from(*n)),
+                uv: uvs.and_then(|uvs| uvs.get(v)).map(|uv| Vec2::from(*uv)),
+                color: colors
+                    .and_then(|colors| colors.get(v))
+                    .map(|c| Vec4::from(*c)),
+            })
+        });
+        Some([a?, b?, c?])
+    })
+}
+
+/// The standard attributes at one vertex, or blended across one triangle as returned by
+/// [`sample_attribute_at`]. Attributes the mesh doesn't have are `None`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SampledVertex {
+    pub position: Vec3,
+    /// blended but not renormalized, so it's shorter than 1 between diverging normals
+    pub normal: Option<Vec3>,
+    pub uv: Option<Vec2>,
+    pub color: Option<Vec4>,
+}
+
+/// The attributes at a point inside a triangle of a `TriangleList` mesh, given by its
+/// `barycentric` weights for the triangle's first, second and third vertex, such as
+/// [`RayHit::barycentric`](crate::raycast::RayHit::barycentric).
+///
+/// `triangle` counts in threes through the index (or vertex) buffer. Returns `None` if
+/// the mesh has no triangle with that index or no positions.
+pub fn sample_attribute_at(
+    mesh: &Mesh,
+    triangle: usize,
+    barycentric: Vec3,
+) -> Option<SampledVertex> {
+    let tri = *triangle_indices(mesh).get(triangle)?;
+    let weights = barycentric.to_array();
+
+    Some(SampledVertex {
+        position: blend(positions(mesh)?, tri, weights)?.into(),
+        normal: normals(mesh)
+            .and_then(|normals| blend(normals, tri, weights))
+            .map(Vec3::from),
+        uv: uvs(mesh)
+            .and_then(|uvs| blend(uvs, tri, weights))
+            .map(Vec2::from),
+        color: colors(mesh)
+            .and_then(|colors| blend(colors, tri, weights))
+            .map(Vec4::from),
+    })
+}
+
+fn blend<const N: usize>(
+    values: &[[f32; N]],
+    tri: [usize; 3],
+    weights: [f32; 3],
+) -> Option<[f32; N]> {
+    let mut sum = [0.0; N];
+    for (v, weight) in tri.into_iter().zip(weights) {
+        for (total, value) in sum.iter_mut().zip(values.get(v)?) {
+            *total += value * weight;
+        }
+    }
+    Some(sum)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeError {
+    /// the mesh has no attribute with this name
+    Missing(&'static str),
+    /// the source attribute's values can't go in the destination, which takes another
+    /// format
+    IncompatibleFormat {
+        from: VertexFormat,
+        to: VertexFormat,
+    },
+    /// there's a different number of values than the mesh has vertices
+    WrongLength { expected: usize, found: usize },
+}
+
+impl fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AttributeError::Missing(name) => write!(f, "the mesh has no {name} attribute"),
+            AttributeError::IncompatibleFormat { from, to } => {
+                write!(f, "can't copy {from:?} values into a {to:?} attribute")
+            }
+            AttributeError::WrongLength { expected, found } => {
+                write!(
+                    f,
+                    "expected a value for each of {expected} vertices, got {found}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+/// Copies the values of attribute `from` into attribute `to`, replacing whatever `to`
+/// held, such as UV0 into a second UV channel for lightmaps:
+///
+/// ```ignore
+/// const ATTRIBUTE_UV_1: MeshVertexAttribute =
+///     MeshVertexAttribute::new("Vertex_Uv_1", 988540917, VertexFormat::Float32x2);
+/// copy_attribute(&mut mesh, Mesh::ATTRIBUTE_UV_0, ATTRIBUTE_UV_1)?;
+/// ```
+///
+/// Both attributes have to use the same format, which is checked against the values the
+/// mesh actually holds for `from` rather than the format it was declared with, since
+/// Bevy would otherwise panic on inserting them.
+pub fn copy_attribute(
+    mesh: &mut Mesh,
+    from: MeshVertexAttribute,
+    to: MeshVertexAttribute,
+) -> Result<(), AttributeError> {
+    let values = mesh
+        .attribute(from)
+        .ok_or(AttributeError::Missing(from.name))?;
+    let format = VertexFormat::from(values);
+    if format != to.format {
+        return Err(AttributeError::IncompatibleFormat {
+            from: format,
+            to: to.format,
+        });
+    }
+    let values = values.clone();
+    mesh.insert_attribute(to, values);
+    Ok(())
+}
+
+/// An attribute for a custom shader to read, like how much each vertex of a plant sways
+/// in the wind, with an id made from `name` so it doesn't have to be picked by hand:
+///
+/// ```ignore
+/// const ATTRIBUTE_WIND: MeshVertexAttribute =
+///     custom_attribute("Vertex_Wind", VertexFormat::Float32);
+/// ```
+///
+/// Bevy only hands a mesh's attributes to the shader locations a material asks for, so
+/// the material's `specialize` has to lay out the vertex buffer with it:
+///
+/// ```ignore
+/// let layout = layout.get_layout(&[
+///     Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+///     Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+///     ATTRIBUTE_WIND.at_shader_location(2),
+/// ])?;
+/// descriptor.vertex.buffers = vec![layout];
+/// ```
+///
+/// and its vertex shader take it as `@location(2) wind: f32`. Meshes without the
+/// attribute then fail to specialize rather than drawing with it missing.
+pub const fn custom_attribute(name: &'static str, format: VertexFormat) -> MeshVertexAttribute {
+    // FNV-1a, folded to 32 bits to stay clear of how Bevy numbers its own attributes
+    let bytes = name.as_bytes();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = (hash ^ bytes[i] as u64).wrapping_mul(0x0100_0000_01b3);
+        i += 1;
+    }
+    MeshVertexAttribute::new(name, ((hash >> 32) ^ hash) as u32 as usize, format)
+}
+
+/// Sets `attribute` to `values`, one per vertex, replacing any it had, for per-vertex
+/// parameters of a custom shader like [`custom_attribute`]'s.
+///
+/// Fails without changing the mesh if there isn't a value for every vertex, or the
+/// values aren't in the format `attribute` was declared with, either of which Bevy
+/// would otherwise panic on when inserting or drawing them. Clones of the mesh and
+/// changes that move vertices around, like welding, keep the attribute, and
+/// [`merge_meshes_keeping`](crate::merge::merge_meshes_keeping) carries it over.
+pub fn set_custom_attribute(
+    mesh: &mut Mesh,
+    attribute: MeshVertexAttribute,
+    values: impl Into<VertexAttributeValues>,
+) -> Result<(), AttributeError> {
+    let values = values.into();
+    let format = VertexFormat::from(&values);
+    if format != attribute.format {
+        return Err(AttributeError::IncompatibleFormat {
+            from: format,
+            to: attribute.format,
+        });
+    }
+    let expected = mesh.count_vertices();
+    if values.len() != expected {
+        return Err(AttributeError::WrongLength {
+            expected,
+            found: values.len(),
+        });
+    }
+    mesh.insert_attribute(attribute, values);
+    Ok(())
+}
+
+/// Takes `attribute` out of the mesh and returns its values, for stripping channels an
+/// exporter or shader doesn't want, like normals that will be recomputed on import.
+pub fn remove_attribute(
+    mesh: &mut Mesh,
+    attribute: MeshVertexAttribute,
+) -> Result<VertexAttributeValues, AttributeError> {
+    mesh.remove_attribute(attribute)
+        .ok_or(AttributeError::Missing(attribute.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        merge::merge_meshes_keeping,
+        terrain::{generate_heightmap, HeightmapParams},
+    };
+
+    #[test]
+    fn sampling_a_triangle_corner_gives_that_vertex() {
+        let mut mesh = generate_heightmap(&HeightmapParams::default(), |x, z| (x * z).sin());
+        assert_eq!(sample_attribute_at(&mesh, 0, Vec3::X).unwrap().color, None);
+
+        let shades: Vec<[f32; 4]> = (0..mesh.count_vertices())
+            .map(|v| [v as f32 / 100.0, 0.5, 0.25, 1.0])
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, shades);
+        for (triangle, [first, ..]) in triangle_indices(&mesh).into_iter().enumerate().take(5) {
+            let sampled = sample_attribute_at(&mesh, triangle, Vec3::X).unwrap();
+            assert_eq!(sampled.position, positions(&mesh).unwrap()[first].into());
+            assert_eq!(sampled.normal, Some(normals(&mesh).unwrap()[first].into()));
+            assert_eq!(sampled.uv, Some(uvs(&mesh).unwrap()[first].into()));
+            assert_eq!(sampled.color, Some(colors(&mesh).unwrap()[first].into()));
+        }
+        assert_eq!(sample_attribute_at(&mesh, 200, Vec3::X), None);
+    }
+
+    #[test]
+    fn velocities_are_zero_at_rest_and_the_delta_once_moved() {
+        let mut mesh = generate_heightmap(&HeightmapParams::default(), |x, z| (x * z).sin());
+        let previous = positions(&mesh).unwrap().to_vec();
+        record_velocities(&mut mesh, &previous);
+        assert!(velocities(&mesh).unwrap().iter().all(|v| *v == [0.0; 3]));
+
+        let delta = Vec3::new(1.5, 0.0, -0.5);
+        for position in positions_mut(&mut mesh).unwrap() {
+            *position = (Vec3::from(*position) + delta).into();
+        }
+        record_velocities(&mut mesh, &previous);
+        let velocities = velocities(&mesh).unwrap();
+        assert_eq!(velocities.len(), previous.len());
+        assert!(velocities
+            .iter()
+            .all(|v| Vec3::from(*v).distance(delta) < 1e-5));
+    }
+
+    #[test]
+    fn custom_attributes_survive_cloning_and_merging() {
+        const WIND: MeshVertexAttribute = custom_attribute("Vertex_Wind", VertexFormat::Float32);
+        let params = HeightmapParams {
+            rows: 2,
+            cols: 2,
+            ..default()
+        };
+        let mut tile = generate_heightmap(&params, |_, _| 0.0);
+        let wind: Vec<f32> = (0..tile.count_vertices()).map(|i| i as f32 / 8.0).collect();
+        assert!(set_custom_attribute(&mut tile, WIND, vec![0.0_f32; 3]).is_err());
+        set_custom_attribute(&mut tile, WIND, wind.clone()).unwrap();
+
+        let copy = tile.clone();
+        let merged = merge_meshes_keeping(&[tile, copy], &[WIND]);
+        match merged.attribute(WIND) {
+            Some(VertexAttributeValues::Float32(merged)) => {
+                assert_eq!(merged[..wind.len()], wind[..]);
+                assert_eq!(merged[wind.len()..], wind[..]);
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -0,0 +1,338 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+
+use crate::{
+    attributes::triangles,
+    util::{positions, positions_mut, Rng},
+};
+
+/// Centers the mesh on the origin and uniformly scales it so its longest side is 1.0,
+/// returning the scale that was applied.
+///
+/// A mesh with no extent (empty, or every vertex in the same place) is only centered,
+/// and the returned scale is 1.0.
+pub fn fit_to_unit_box(mesh: &mut Mesh) -> f32 {
+    let Some((min, max)) = position_bounds(positions(mesh)) else {
+        return 1.0;
+    };
+
+    let center = (min + max) / 2.0;
+    let longest_side = (max - min).max_element();
+    let scale = if longest_side > f32::EPSILON {
+        1.0 / longest_side
+    } else {
+        1.0
+    };
+
+    for position in positions_mut(mesh) {
+        *position = ((Vec3::from(*position) - center) * scale).into();
+    }
+
+    scale
+}
+
+/// A sphere around every vertex of the mesh, as its center and radius, for culling or
+/// framing the camera on it. `None` when the mesh has no vertices.
+///
+/// This is Ritter's approximation: usually within a few percent of the smallest
+/// enclosing sphere and much tighter than one around the mesh's AABB.
+pub fn bounding_sphere(mesh: &Mesh) -> Option<(Vec3, f32)> {
+    let positions: Vec<Vec3> = positions(mesh).iter().map(|p| Vec3::from(*p)).collect();
+    let farthest_from = |from: Vec3| {
+        positions.iter().copied().max_by(|a, b| {
+            a.distance_squared(from)
+                .total_cmp(&b.distance_squared(from))
+        })
+    };
+
+    // start from the two far ends of a roughly longest span...
+    let a = farthest_from(*positions.first()?)?;
+    let b = farthest_from(a)?;
+    let mut center = (a + b) / 2.0;
+    let mut radius = a.distance(b) / 2.0;
+
+    // ...then grow just enough to take in each vertex left outside
+    for position in &positions {
+        let distance = position.distance(center);
+        if distance > radius {
+            let grown = (radius + distance) / 2.0;
+            center += (*position - center) * ((grown - radius) / distance);
+            radius = grown;
+        }
+    }
+
+    // rounding while growing can leave a vertex a hair outside
+    let radius = positions
+        .iter()
+        .map(|position| position.distance(center))
+        .fold(radius, f32::max);
+
+    Some((center, radius))
+}
+
+/// The narrowest cylinder around every vertex of the mesh standing along `axis`, as the
+/// middle of its axis, its radius and its height, for fitting a capsule collider to a
+/// character or a trigger volume around a prop. `None` when the mesh has no vertices or
+/// `axis` is zero.
+///
+/// The height is how far the vertices reach along `axis`, and the radius that of the
+/// smallest circle around them seen end on, found exactly rather than from their
+/// bounds, so a mesh standing off to one side of the axis through its origin gets the
+/// same cylinder as one centered on it. A capsule with the same radius and middle fits
+/// the mesh when it's as tall as the cylinder, round ends included.
+pub fn bounding_cylinder(mesh: &Mesh, axis: Vec3) -> Option<(Vec3, f32, f32)> {
+    let axis = axis.try_normalize()?;
+    let u = axis.any_orthonormal_vector();
+    let v = axis.cross(u);
+
+    let positions = positions(mesh);
+    let (low, high) = positions
+        .iter()
+        .map(|position| Vec3::from(*position).dot(axis))
+        .fold(None, |range, along| match range {
+            None => Some((along, along)),
+            Some((low, high)) => Some((f32::min(low, along), f32::max(high, along))),
+        })?;
+    let mut across: Vec<Vec2> = positions
+        .iter()
+        .map(|position| Vec2::new(Vec3::from(*position).dot(u), Vec3::from(*position).dot(v)))
+        .collect();
+
+    // visiting points in random order keeps the expected time linear
+    let mut rng = Rng::new(0);
+    for i in (1..across.len()).rev() {
+        across.swap(i, rng.next_u32() as usize % (i + 1));
+    }
+    let (center, radius) = smallest_circle(&across);
+
+    let middle = u * center.x + v * center.y + axis * (low + high) / 2.0;
+    Some((middle, radius, high - low))
+}
+
+/// The volume a closed mesh encloses, summed from the tetrahedra between the origin and
+/// each triangle.
+///
+/// Only meaningful for closed meshes wound outward, where the parts of those tetrahedra
+/// outside the mesh cancel out; a mesh wound inside out comes back negative.
+pub fn volume(mesh: &Mesh) -> f32 {
+    triangles(mesh)
+        .map(|[a, b, c]| a.dot(b.cross(c)) / 6.0)
+        .sum()
+}
+
+/// The center of mass of a closed mesh filled with something of even density, for
+/// placing a generated rigid body's origin where it balances.
+///
+/// Found from the same tetrahedra as [`volume`], so the mesh has to be closed, which
+/// [`is_watertight`](crate::topology::is_watertight) checks, with every triangle wound
+/// the same way. A mesh with no volume gives the middle of its bounds, or the origin
+/// if it has no vertices.
+pub fn center_of_mass(mesh: &Mesh) -> Vec3 {
+    if let Some((_, center, _)) = mass_moments(mesh) {
+        return center;
+    }
+    match position_bounds(positions(mesh)) {
+        Some((min, max)) => (min + max) / 2.0,
+        None => Vec3::ZERO,
+    }
+}
+
+/// The inertia tensor of a closed mesh weighing `mass` spread evenly through it, about
+/// its [`center_of_mass`] and along the mesh's own axes, for giving a generated rigid
+/// body the right resistance to turning instead of a box's.
+///
+/// Like [`center_of_mass`] it needs a closed, consistently wound mesh, though one wound
+/// inside out gives the same tensor. A mesh with no volume gives a zero tensor.
+pub fn inertia_tensor(mesh: &Mesh, mass: f32) -> Mat3 {
+    let Some((volume, _, second)) = mass_moments(mesh) else {
+        return Mat3::ZERO;
+    };
+    let trace = second.x_axis.x + second.y_axis.y + second.z_axis.z;
+    (Mat3::from_diagonal(Vec3::splat(trace)) - second) * (mass / volume)
+}
+
+/// The axis-aligned box around every vertex of the mesh, in the form Bevy culls with.
+/// `None` when the mesh has no vertices.
+pub fn mesh_aabb(mesh: &Mesh) -> Option<Aabb> {
+    let (min, max) = position_bounds(positions(mesh))?;
+    Some(Aabb::from_min_max(min, max))
+}
+
+pub(crate) fn position_bounds(positions: &[[f32; 3]]) -> Option<(Vec3, Vec3)> {
+    positions
+        .iter()
+        .map(|position| Vec3::from(*position))
+        .fold(None, |bounds, position| match bounds {
+            None => Some((position, position)),
+            Some((min, max)) => Some((min.min(position), max.max(position))),
+        })
+}
+
+// the signed volume of a closed mesh, its center of mass, and the second moment of its
+// volume about that center (the integral of `x xᵀ` over it), summed over the tetrahedra
+// from the middle of its bounds to each triangle, or `None` if it has no volume
+fn mass_moments(mesh: &Mesh) -> Option<(f32, Vec3, Mat3)> {
+    let (min, max) = position_bounds(positions(mesh))?;
+    let origin = (min + max) / 2.0;
+    // the second moment of the tetrahedron between the origin and the three unit axes,
+    // which every other one is a linear map of
+    let unit = Mat3::from_cols(
+        Vec3::new(2.0, 1.0, 1.0),
+        Vec3::new(1.0, 2.0, 1.0),
+        Vec3::new(1.0, 1.0, 2.0),
+    ) * (1.0 / 120.0);
+
+    let (mut volume, mut first, mut second) = (0.0, Vec3::ZERO, Mat3::ZERO);
+    for [a, b, c] in triangles(mesh) {
+        let corners = Mat3::from_cols(a - origin, b - origin, c - origin);
+        // six times the tetrahedron's signed volume
+        let det = corners.determinant();
+        volume += det / 6.0;
+        first += (corners.x_axis + corners.y_axis + corners.z_axis) * (det / 24.0);
+        second += corners * unit * corners.transpose() * det;
+    }
+    if volume.abs() <= f32::EPSILON * (max - min).max_element().powi(3) {
+        return None;
+    }
+
+    let offset = first / volume;
+    let around = Mat3::from_cols(offset * offset.x, offset * offset.y, offset * offset.z);
+    Some((volume, origin + offset, second - around * volume))
+}
+
+// Welzl's smallest enclosing circle, grown one point at a time: whenever a point falls
+// outside, it has to be on the circle, and the circle is rebuilt through it and the
+// points found so far
+fn smallest_circle(points: &[Vec2]) -> (Vec2, f32) {
+    // rounding can leave a point that was put on a circle a hair outside it
+    let outside = |(center, radius): (Vec2, f32), point: Vec2| {
+        point.distance(center) > radius + 1e-6 * (1.0 + radius)
+    };
+    let mut circle = (points[0], 0.0);
+    for i in 1..points.len() {
+        if !outside(circle, points[i]) {
+            continue;
+        }
+        circle = (points[i], 0.0);
+        for j in 0..i {
+            if !outside(circle, points[j]) {
+                continue;
+            }
+            circle = diameter(points[i], points[j]);
+            for k in 0..j {
+                if outside(circle, points[k]) {
+                    circle = circumcircle(points[i], points[j], points[k]);
+                }
+            }
+        }
+    }
+    circle
+}
+
+fn diameter(a: Vec2, b: Vec2) -> (Vec2, f32) {
+    ((a + b) / 2.0, a.distance(b) / 2.0)
+}
+
+// the circle through three points, or around the two farthest apart when they're in a
+// line
+fn circumcircle(a: Vec2, b: Vec2, c: Vec2) -> (Vec2, f32) {
+    let (ab, ac) = (b - a, c - a);
+    let d = 2.0 * ab.perp_dot(ac);
+    if d.abs() <= f32::EPSILON * ab.length_squared().max(ac.length_squared()) {
+        return [diameter(a, b), diameter(a, c), diameter(b, c)]
+            .into_iter()
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+            .unwrap_or((a, 0.0));
+    }
+    let offset = Vec2::new(
+        ac.y * ab.length_squared() - ab.y * ac.length_squared(),
+        ab.x * ac.length_squared() - ac.x * ab.length_squared(),
+    ) / d;
+    let center = a + offset;
+    (
+        center,
+        [a, b, c]
+            .map(|p| p.distance(center))
+            .into_iter()
+            .fold(0.0, f32::max),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::FRAC_PI_2;
+
+    use bevy::render::mesh::PrimitiveTopology;
+
+    use super::*;
+    use crate::{
+        compose::cuboid,
+        cylinder::{generate_cylinder, CylinderParams},
+        merge::apply_transform,
+        polyhedra::{generate_dodecahedron, PlatonicParams},
+        terrain::{generate_heightmap, HeightmapParams},
+    };
+
+    #[test]
+    fn bounding_sphere_contains_every_vertex() {
+        let meshes = [
+            generate_dodecahedron(&PlatonicParams::default()).unwrap(),
+            generate_heightmap(&HeightmapParams::default(), |x, z| (x * z).sin()),
+        ];
+        for mesh in meshes {
+            let (center, radius) = bounding_sphere(&mesh).unwrap();
+            for position in positions(&mesh) {
+                assert!(Vec3::from(*position).distance(center) <= radius);
+            }
+        }
+        let empty = Mesh::new(PrimitiveTopology::TriangleList);
+        assert_eq!(bounding_sphere(&empty), None);
+    }
+
+    #[test]
+    fn bounding_cylinder_fits_a_generated_cylinder() {
+        let params = CylinderParams {
+            radius: 0.7,
+            height: 2.5,
+            segments: 24,
+            ..default()
+        };
+        let mut cylinder = generate_cylinder(&params).unwrap();
+        let (center, radius, height) = bounding_cylinder(&cylinder, Vec3::Y).unwrap();
+        assert!(center.length() < 1e-5, "{center}");
+        assert!((radius - params.radius).abs() < 1e-5, "{radius}");
+        assert!((height - params.height).abs() < 1e-5, "{height}");
+
+        // laid along X and moved off the origin, it should fit the same way along X
+        let moved =
+            Transform::from_xyz(3.0, -1.0, 2.0).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+        apply_transform(&mut cylinder, moved);
+        let (center, radius, height) = bounding_cylinder(&cylinder, Vec3::X).unwrap();
+        assert!(center.distance(moved.translation) < 1e-4, "{center}");
+        assert!((radius - params.radius).abs() < 1e-4, "{radius}");
+        assert!((height - params.height).abs() < 1e-4, "{height}");
+    }
+
+    #[test]
+    fn box_mass_properties_match_the_analytic_formulas() {
+        let size = Vec3::new(2.0, 1.0, 0.5);
+        let mass = 3.0;
+        let analytic = Mat3::from_diagonal(
+            Vec3::new(
+                size.y * size.y + size.z * size.z,
+                size.x * size.x + size.z * size.z,
+                size.x * size.x + size.y * size.y,
+            ) * mass
+                / 12.0,
+        );
+
+        let mut block = cuboid(size).unwrap();
+        assert!(center_of_mass(&block).length() < 1e-5);
+        assert!(inertia_tensor(&block, mass).abs_diff_eq(analytic, 1e-4));
+
+        // moving the box moves its center of mass but not the tensor about it
+        let offset = Vec3::new(4.0, -1.0, 2.5);
+        apply_transform(&mut block, Transform::from_translation(offset));
+        assert!(center_of_mass(&block).distance(offset) < 1e-4);
+        assert!(inertia_tensor(&block, mass).abs_diff_eq(analytic, 1e-3));
+    }
+}
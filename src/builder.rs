@@ -0,0 +1,114 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::geometry::GeometryData;
+
+/// Assembles an indexed mesh one vertex and triangle at a time, for shapes none of the
+/// generators cover.
+///
+/// [`CustomMeshBuilder::vertex`] hands back the index of the vertex it adds, and
+/// [`CustomMeshBuilder::triangle`] joins three of those indices, counter-clockwise
+/// around its front face. Vertices can be shared by any number of triangles.
+#[derive(Clone, Debug, Default)]
+pub struct CustomMeshBuilder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+/// A triangle referring to a vertex that was never added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvalidIndex {
+    /// which triangle, in the order they were added
+    pub triangle: usize,
+    pub index: u32,
+    pub vertex_count: u32,
+}
+
+impl fmt::Display for InvalidIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "triangle {} uses vertex {}, but there are only {} vertices",
+            self.triangle, self.index, self.vertex_count
+        )
+    }
+}
+
+impl std::error::Error for InvalidIndex {}
+
+impl CustomMeshBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn vertex(&mut self, position: Vec3, uv: Vec2, normal: Vec3) -> u32 {
+        self.positions.push(position.into());
+        self.uvs.push(uv.into());
+        self.normals.push(normal.into());
+        self.positions.len() as u32 - 1
+    }
+
+    pub fn triangle(&mut self, i0: u32, i1: u32, i2: u32) -> &mut Self {
+        self.indices.extend([i0, i1, i2]);
+        self
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.positions.len() as u32
+    }
+
+    /// Fails on the first triangle using an index no vertex was added for.
+    pub fn build(self) -> Result<Mesh, InvalidIndex> {
+        self.build_raw().map(GeometryData::into_mesh)
+    }
+
+    /// [`CustomMeshBuilder::build`] as plain arrays rather than a `Mesh`.
+    pub fn build_raw(self) -> Result<GeometryData, InvalidIndex> {
+        let vertex_count = self.vertex_count();
+        if let Some((i, &index)) = self
+            .indices
+            .iter()
+            .enumerate()
+            .find(|(_, &index)| index >= vertex_count)
+        {
+            return Err(InvalidIndex {
+                triangle: i / 3,
+                index,
+                vertex_count,
+            });
+        }
+
+        Ok(GeometryData {
+            positions: self.positions,
+            normals: self.normals,
+            uvs: self.uvs,
+            indices: self.indices,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_range_index_fails_the_build() {
+        let mut builder = CustomMeshBuilder::new();
+        let a = builder.vertex(Vec3::ZERO, Vec2::ZERO, Vec3::Y);
+        let b = builder.vertex(Vec3::X, Vec2::X, Vec3::Y);
+        let c = builder.vertex(Vec3::Z, Vec2::Y, Vec3::Y);
+        builder.triangle(a, c, b).triangle(a, b, 3);
+
+        assert_eq!(
+            builder.build().err(),
+            Some(InvalidIndex {
+                triangle: 1,
+                index: 3,
+                vertex_count: 3,
+            })
+        );
+    }
+}
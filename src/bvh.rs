@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+
+use crate::{
+    attributes::{normals, uvs},
+    raycast::{intersect_triangle, HitAttributes, RayHit},
+    util::{positions, triangle_indices},
+};
+
+const MAX_LEAF_TRIANGLES: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    // leaves: the first of `count` entries in `MeshBvh::order`
+    // inner nodes: the left child, with the right child right after it
+    first: usize,
+    count: usize,
+}
+
+/// A bounding volume hierarchy over a mesh's triangles, for answering many raycasts
+/// against the same mesh without testing every triangle each time.
+///
+/// Building it costs a copy of the mesh's vertex data plus an `O(n log n)` split of
+/// its triangles, so it pays off once a mesh is queried more than a handful of times.
+/// The hierarchy doesn't track changes to the mesh, rebuild it after editing.
+#[derive(Clone, Debug)]
+pub struct MeshBvh {
+    nodes: Vec<BvhNode>,
+    // triangle numbers, grouped so each leaf's triangles are contiguous
+    order: Vec<usize>,
+    triangles: Vec<[usize; 3]>,
+    positions: Vec<[f32; 3]>,
+    normals: Option<Vec<[f32; 3]>>,
+    uvs: Option<Vec<[f32; 2]>>,
+}
+
+impl MeshBvh {
+    pub fn build(mesh: &Mesh) -> Self {
+        let positions = positions(mesh).to_vec();
+        let triangles = triangle_indices(mesh);
+
+        let bounds: Vec<(Vec3, Vec3)> = triangles
+            .iter()
+            .map(|tri| {
+                let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+                (a.min(b).min(c), a.max(b).max(c))
+            })
+            .collect();
+
+        let mut bvh = MeshBvh {
+            nodes: vec![],
+            order: (0..triangles.len()).collect(),
+            triangles,
+            positions,
+            normals: normals(mesh).map(|normals| normals.to_vec()),
+            uvs: uvs(mesh).map(|uvs| uvs.to_vec()),
+        };
+
+        if !bvh.triangles.is_empty() {
+            bvh.nodes.push(BvhNode {
+                min: Vec3::ZERO,
+                max: Vec3::ZERO,
+                first: 0,
+                count: bvh.triangles.len(),
+            });
+            bvh.split(0, &bounds);
+        }
+
+        bvh
+    }
+
+    /// Same result as [`raycast_mesh`](crate::raycast::raycast_mesh) on the mesh the
+    /// hierarchy was built from.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3) -> Option<RayHit> {
+        let dir = dir.try_normalize()?;
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let inv_dir = dir.recip();
+        let mut nearest: Option<(usize, f32, Vec2)> = None;
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = self.nodes[node];
+            let limit = nearest.map_or(f32::INFINITY, |(_, t, _)| t);
+            if !hits_aabb(origin, inv_dir, node.min, node.max, limit) {
+                continue;
+            }
+
+            if node.count > 0 {
+                for &triangle in &self.order[node.first..node.first + node.count] {
+                    let [a, b, c] = self.triangles[triangle].map(|v| Vec3::from(self.positions[v]));
+                    if let Some((t, u, v)) = intersect_triangle(origin, dir, a, b, c) {
+                        if nearest.map_or(true, |(_, nearest_t, _)| t < nearest_t) {
+                            nearest = Some((triangle, t, Vec2::new(u, v)));
+                        }
+                    }
+                }
+            } else {
+                stack.push(node.first);
+                stack.push(node.first + 1);
+            }
+        }
+
+        let (triangle, distance, uv) = nearest?;
+        let attributes = HitAttributes {
+            positions: &self.positions,
+            normals: self.normals.as_deref(),
+            uvs: self.uvs.as_deref(),
+        };
+        Some(attributes.hit(
+            triangle,
+            self.triangles[triangle],
+            origin + dir * distance,
+            distance,
+            uv,
+        ))
+    }
+
+    /// Surface height at a world XZ position, see
+    /// [`sample_terrain_height`](crate::terrain::sample_terrain_height).
+    pub fn sample_height(&self, x: f32, z: f32) -> Option<f32> {
+        let top = self.nodes.first()?.max.y + 1.0;
+        self.raycast(Vec3::new(x, top, z), Vec3::NEG_Y)
+            .map(|hit| hit.point.y)
+    }
+
+    // fits the node to its triangles, then splits them at the median centroid along
+    // the longest axis until the leaves are small enough
+    fn split(&mut self, node: usize, bounds: &[(Vec3, Vec3)]) {
+        let BvhNode { first, count, .. } = self.nodes[node];
+        let triangles = &mut self.order[first..first + count];
+
+        let (mut min, mut max) = (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY));
+        let (mut centroid_min, mut centroid_max) = (min, max);
+        for &triangle in triangles.iter() {
+            let (tri_min, tri_max) = bounds[triangle];
+            min = min.min(tri_min);
+            max = max.max(tri_max);
+            centroid_min = centroid_min.min((tri_min + tri_max) * 0.5);
+            centroid_max = centroid_max.max((tri_min + tri_max) * 0.5);
+        }
+        self.nodes[node].min = min;
+        self.nodes[node].max = max;
+
+        if count <= MAX_LEAF_TRIANGLES {
+            return;
+        }
+
+        let extent = centroid_max - centroid_min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let centroid = |triangle: &usize| {
+            let (tri_min, tri_max) = bounds[*triangle];
+            (tri_min[axis] + tri_max[axis]) * 0.5
+        };
+
+        let half = count / 2;
+        triangles.select_nth_unstable_by(half, |a, b| centroid(a).total_cmp(&centroid(b)));
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            first,
+            count: half,
+        });
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            first: first + half,
+            count: count - half,
+        });
+        self.nodes[node].first = left;
+        self.nodes[node].count = 0;
+
+        self.split(left, bounds);
+        self.split(left + 1, bounds);
+    }
+}
+
+// slab test, rejecting boxes that only start past `limit`. An axis the ray runs
+// parallel to is checked on its own, since the origin sitting right on a face of the
+// box would make its slab 0 * infinity
+fn hits_aabb(origin: Vec3, inv_dir: Vec3, min: Vec3, max: Vec3, limit: f32) -> bool {
+    let (mut t_near, mut t_far) = (f32::NEG_INFINITY, f32::INFINITY);
+    for axis in 0..3 {
+        if inv_dir[axis].is_infinite() {
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let t0 = (min[axis] - origin[axis]) * inv_dir[axis];
+        let t1 = (max[axis] - origin[axis]) * inv_dir[axis];
+        t_near = t_near.max(t0.min(t1));
+        t_far = t_far.min(t0.max(t1));
+    }
+
+    t_near <= t_far && t_far >= 0.0 && t_near <= limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        raycast::raycast_mesh,
+        terrain::{generate_heightmap, HeightmapParams},
+    };
+
+    // straight down rays run along a plane of the boxes around the triangles they land
+    // between, where the slab test divides zero by zero
+    #[test]
+    fn downward_rays_over_vertices_and_edges_agree_with_the_linear_scan() {
+        let terrain = generate_heightmap(&HeightmapParams::default(), |x, z| {
+            (x * 0.3).sin() + z * 0.2
+        });
+        let bvh = MeshBvh::build(&terrain);
+        let top = 10.0;
+
+        let mut points = vec![];
+        for i in -5..=5 {
+            for j in -5..=5 {
+                let (x, z) = (i as f32, j as f32);
+                points.extend([(x, z), (x + 0.5, z), (x, z + 0.5), (x + 0.5, z + 0.5)]);
+            }
+        }
+        // past the edges, where nothing is under the ray
+        points.extend([(5.5, 0.0), (0.0, -5.5), (7.0, 7.0)]);
+
+        for (x, z) in points {
+            let origin = Vec3::new(x, top, z);
+            let scanned = raycast_mesh(&terrain, origin, Vec3::NEG_Y);
+            let through_bvh = bvh.raycast(origin, Vec3::NEG_Y);
+            assert_eq!(scanned.is_some(), through_bvh.is_some(), "{x} {z}");
+            assert_eq!(
+                scanned.is_some(),
+                x.abs() <= 5.0 && z.abs() <= 5.0,
+                "{x} {z}"
+            );
+            if let (Some(scanned), Some(through_bvh)) = (scanned, through_bvh) {
+                assert!(
+                    (scanned.distance - through_bvh.distance).abs() < 1e-5,
+                    "{x} {z}"
+                );
+                assert!(scanned.point.distance(through_bvh.point) < 1e-5, "{x} {z}");
+            }
+            assert_eq!(
+                bvh.sample_height(x, z).is_some(),
+                scanned.is_some(),
+                "{x} {z}"
+            );
+            if let (Some(height), Some(scanned)) = (bvh.sample_height(x, z), scanned) {
+                assert!((height - scanned.point.y).abs() < 1e-5, "{x} {z}");
+            }
+        }
+
+        // pointing away from the terrain
+        assert!(bvh.raycast(Vec3::new(0.0, top, 0.0), Vec3::Y).is_none());
+    }
+}
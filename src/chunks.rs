@@ -0,0 +1,615 @@
+use std::{
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{prelude::*, render::primitives::Aabb, tasks::AsyncComputeTaskPool, utils::HashMap};
+
+use crate::{bounds::mesh_aabb, geometry::GeometryData, terrain::grid_triangles};
+
+// the neighbors of a chunk, in the order a `ChunkKey` lists their levels
+const NEIGHBORS: [IVec2; 4] = [
+    IVec2::new(-1, 0),
+    IVec2::new(1, 0),
+    IVec2::new(0, -1),
+    IVec2::new(0, 1),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LodTerrainParams {
+    /// width of each square chunk along X and Z
+    pub chunk_size: f32,
+    /// cells along each side of the nearest chunks, rounded up to a power of two; each
+    /// level of detail after the first has half as many as the one before
+    pub resolution: u32,
+    /// how many levels of detail there are, the last one reaching out to the edge
+    pub levels: u32,
+    /// how far from the viewer chunks keep full resolution; each level after the first
+    /// reaches twice as far as the one before
+    pub lod_distance: f32,
+    /// share of each level's reach, at its far end, over which it blends into the next
+    /// one, 0..0.5
+    pub morph_range: f32,
+    /// chunks out from the one the viewer is over, each way
+    pub radius: u32,
+    /// how far the skirt around each chunk hangs below its edges
+    pub skirt_depth: f32,
+    /// least time between updates, in seconds
+    pub update_interval: f32,
+}
+
+impl Default for LodTerrainParams {
+    // 9 x 9 chunks of 32, the nearest few 64 cells across and the farthest 8
+    fn default() -> Self {
+        LodTerrainParams {
+            chunk_size: 32.0,
+            resolution: 64,
+            levels: 4,
+            lod_distance: 48.0,
+            morph_range: 0.3,
+            radius: 4,
+            skirt_depth: 1.0,
+            update_interval: 0.1,
+        }
+    }
+}
+
+/// Ground raised to `height_fn(x, z)` everywhere, meshed in square chunks around the
+/// [`LodViewer`] with more detail close up than far away, for worlds too big to mesh at
+/// full resolution in one go. Spawn it with a `SpatialBundle` and the material its
+/// chunks are drawn with, and add a [`LodTerrainPlugin`] for that material's type.
+///
+/// Every `update_interval` seconds, the terrain works out which level of detail each
+/// chunk within `radius` of the viewer should have from how far away its nearest point
+/// is, and rebuilds the ones that changed on Bevy's `AsyncComputeTaskPool`. The whole
+/// batch is swapped in at once when the last of them is done, so neighbors are never
+/// drawn from two different updates, and the next update waits for it. Chunks are
+/// children of the terrain, placed in its own space, with a [`LodTerrainChunk`] saying
+/// which they are.
+///
+/// Nothing pops when a chunk changes level: over the last `morph_range` of a level's
+/// reach, its vertices slide towards where the next level's triangles are, arriving
+/// there just as the chunk switches over. Nothing cracks either. Where a chunk meets a
+/// coarser neighbor, its edge follows the neighbor's, and a skirt `skirt_depth` deep
+/// hangs down around every chunk to cover what rounding leaves between them. Normals
+/// come from `height_fn` itself at the finest resolution, so the shading keeps its
+/// detail far away and doesn't change with the level. UVs span 0..1 across each chunk.
+///
+/// `params` can be changed while the app runs; every chunk is rebuilt with the new ones.
+#[derive(Component)]
+pub struct LodTerrain {
+    pub params: LodTerrainParams,
+    height_fn: Arc<dyn Fn(f32, f32) -> f32 + Send + Sync>,
+    // the entity of each chunk and what its mesh was built for
+    chunks: HashMap<IVec2, (Entity, ChunkKey)>,
+    // the chunks being rebuilt, swapped in together once they're all done
+    pending: Option<Batch>,
+    // the params and the viewer's finest cell the last update was for
+    updated_for: Option<(LodTerrainParams, IVec2)>,
+    last_update: f32,
+}
+
+impl LodTerrain {
+    pub fn new(
+        params: LodTerrainParams,
+        height_fn: impl Fn(f32, f32) -> f32 + Send + Sync + 'static,
+    ) -> Self {
+        LodTerrain {
+            params,
+            height_fn: Arc::new(height_fn),
+            chunks: HashMap::new(),
+            pending: None,
+            updated_for: None,
+            last_update: f32::NEG_INFINITY,
+        }
+    }
+
+    // starts rebuilding the chunks that need it for a viewer at `eye`, along the
+    // terrain's X and Z
+    fn start_update(&mut self, eye: Vec2) {
+        let params = self.params;
+        let surface = Surface {
+            params,
+            height_fn: self.height_fn.clone(),
+            eye,
+        };
+        let rebuild_all = self.updated_for.map(|(built, _)| built) != Some(params);
+        let snapped = surface.snapped(0);
+        if self.updated_for == Some((params, snapped)) {
+            return;
+        }
+        self.updated_for = Some((params, snapped));
+
+        let eye = surface.eye(0) / params.chunk_size;
+        let center = IVec2::new(eye.x.floor() as i32, eye.y.floor() as i32);
+        let radius = params.radius as i32;
+        let levels: HashMap<IVec2, u32> = (-radius..=radius)
+            .flat_map(|z| (-radius..=radius).map(move |x| center + IVec2::new(x, z)))
+            .map(|chunk| (chunk, surface.level_of(chunk)))
+            .collect();
+
+        let mut building: Vec<(IVec2, ChunkKey)> = vec![];
+        for (chunk, level) in &levels {
+            let key = ChunkKey {
+                level: *level,
+                neighbors: NEIGHBORS.map(|d| levels.get(&(*chunk + d)).copied().unwrap_or(*level)),
+                eye: surface
+                    .moves_with_eye(*chunk, *level)
+                    .then(|| surface.snapped(*level)),
+            };
+            if rebuild_all || self.chunks.get(chunk).map(|(_, built)| *built) != Some(key) {
+                building.push((*chunk, key));
+            }
+        }
+        let removed: Vec<IVec2> = self
+            .chunks
+            .keys()
+            .filter(|chunk| !levels.contains_key(*chunk))
+            .copied()
+            .collect();
+        if building.is_empty() && removed.is_empty() {
+            return;
+        }
+
+        let built = Arc::new(Mutex::new(vec![]));
+        let pool = AsyncComputeTaskPool::get();
+        for (chunk, key) in &building {
+            let (surface, built, chunk, key) = (surface.clone(), built.clone(), *chunk, *key);
+            pool.spawn(async move {
+                let mesh = chunk_mesh(&surface, chunk, key.level, key.neighbors);
+                let aabb = mesh_aabb(&mesh).unwrap_or_default();
+                if let Ok(mut built) = built.lock() {
+                    built.push((chunk, mesh, aabb));
+                }
+            })
+            .detach();
+        }
+        self.pending = Some(Batch {
+            building,
+            removed,
+            built,
+        });
+    }
+
+    // swaps in the pending batch if all of its chunks are built, spawning the chunks that
+    // are new and despawning the ones that are no longer needed
+    fn finish_update<M: Component + Clone>(
+        &mut self,
+        terrain: Entity,
+        material: &M,
+        commands: &mut Commands,
+        meshes: &mut Assets<Mesh>,
+    ) {
+        let Some(batch) = &self.pending else {
+            return;
+        };
+        let built = match batch.built.lock() {
+            Ok(mut built) if built.len() == batch.building.len() => std::mem::take(&mut *built),
+            _ => return,
+        };
+        let Some(batch) = self.pending.take() else {
+            return;
+        };
+
+        let keys: HashMap<IVec2, ChunkKey> = batch.building.into_iter().collect();
+        for (chunk, mesh, aabb) in built {
+            let key = keys[&chunk];
+            let marker = LodTerrainChunk {
+                coord: chunk,
+                level: key.level,
+            };
+            let mesh = meshes.add(mesh);
+            if let Some((entity, built)) = self.chunks.get_mut(&chunk) {
+                commands.entity(*entity).insert((mesh, aabb, marker));
+                *built = key;
+            } else {
+                let entity = commands
+                    .spawn((
+                        material.clone(),
+                        mesh,
+                        aabb,
+                        marker,
+                        SpatialBundle::default(),
+                    ))
+                    .id();
+                commands.entity(terrain).add_child(entity);
+                self.chunks.insert(chunk, (entity, key));
+            }
+        }
+        for chunk in batch.removed {
+            if let Some((entity, _)) = self.chunks.remove(&chunk) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// Marks what a [`LodTerrain`] puts its detail around, usually the camera. Only the
+/// first one found is followed.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct LodViewer;
+
+/// On each chunk a [`LodTerrain`] spawns: which chunk it is, counting in chunks along X
+/// and Z from the one at the terrain's origin, and the level of detail its mesh has,
+/// for hanging colliders or anything else off the chunks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Component)]
+pub struct LodTerrainChunk {
+    pub coord: IVec2,
+    pub level: u32,
+}
+
+/// Keeps the chunks of every [`LodTerrain`] up to date around the [`LodViewer`], each
+/// drawn with a clone of the terrain's `M`, such as `Handle<StandardMaterial>`, and
+/// registers [`LodTerrainParams`] for inspectors.
+pub struct LodTerrainPlugin<M>(PhantomData<M>);
+
+impl<M> Default for LodTerrainPlugin<M> {
+    fn default() -> Self {
+        LodTerrainPlugin(PhantomData)
+    }
+}
+
+impl<M: Component + Clone> Plugin for LodTerrainPlugin<M> {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LodTerrainParams>()
+            .add_system(update_lod_terrains::<M>);
+    }
+}
+
+// swaps in finished batches of chunks, and starts new ones once the terrain is due an
+// update, with the viewer moved into the terrain's space
+fn update_lod_terrains<M: Component + Clone>(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    time: Res<Time>,
+    viewers: Query<&GlobalTransform, With<LodViewer>>,
+    mut terrains: Query<(Entity, &mut LodTerrain, &GlobalTransform, &M)>,
+) {
+    let Some(viewer) = viewers.iter().next() else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+    for (entity, mut terrain, transform, material) in &mut terrains {
+        if terrain.pending.is_some() {
+            terrain.finish_update(entity, material, &mut commands, &mut meshes);
+        } else if now - terrain.last_update >= terrain.params.update_interval {
+            terrain.last_update = now;
+            let eye = transform
+                .compute_matrix()
+                .inverse()
+                .transform_point3(viewer.translation());
+            terrain.start_update(Vec2::new(eye.x, eye.z));
+        }
+    }
+}
+
+// what a chunk's mesh depends on: its level, the levels of its neighbors in the order
+// of `NEIGHBORS`, and the viewer's cell at its level if some of it is blending
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct ChunkKey {
+    level: u32,
+    neighbors: [u32; 4],
+    eye: Option<IVec2>,
+}
+
+// chunks being built on the task pool, and the ones to despawn when they're swapped in
+struct Batch {
+    building: Vec<(IVec2, ChunkKey)>,
+    removed: Vec<IVec2>,
+    built: Arc<Mutex<Vec<(IVec2, Mesh, Aabb)>>>,
+}
+
+// the terrain as seen from `eye`: the height of each level's grid points, blended
+// towards the next level with distance, and the level each chunk gets. Each level sees
+// the viewer at the middle of the cell of its own grid it's over, so a level only
+// changes as the viewer moves from one of its cells to the next, and coarse levels far
+// away are rebuilt less often than fine ones close up
+#[derive(Clone)]
+struct Surface {
+    params: LodTerrainParams,
+    height_fn: Arc<dyn Fn(f32, f32) -> f32 + Send + Sync>,
+    eye: Vec2,
+}
+
+impl Surface {
+    fn levels(&self) -> u32 {
+        self.params.levels.clamp(1, 16)
+    }
+
+    // cells along each side of a chunk at `level`
+    fn resolution(&self, level: u32) -> u32 {
+        let finest = self.params.resolution.clamp(1, 1 << 16).next_power_of_two();
+        (finest >> level).max(1)
+    }
+
+    fn cell(&self, level: u32) -> f32 {
+        self.params.chunk_size / self.resolution(level) as f32
+    }
+
+    // where point `g` of `level`'s grid is, counting in cells from the origin; the same
+    // point comes out bit-identical from every chunk and level that has it
+    fn point(&self, level: u32, g: IVec2) -> Vec2 {
+        let resolution = self.resolution(level) as f32;
+        Vec2::new(g.x as f32 / resolution, g.y as f32 / resolution) * self.params.chunk_size
+    }
+
+    // the cell of `level`'s grid the viewer is over
+    fn snapped(&self, level: u32) -> IVec2 {
+        let cell = (self.eye / self.cell(level)).floor();
+        IVec2::new(cell.x as i32, cell.y as i32)
+    }
+
+    // where `level` sees the viewer
+    fn eye(&self, level: u32) -> Vec2 {
+        let cell = self.snapped(level);
+        (Vec2::new(cell.x as f32, cell.y as f32) + 0.5) * self.cell(level)
+    }
+
+    // how far out `level` reaches
+    fn reach(&self, level: u32) -> f32 {
+        self.params.lod_distance.max(f32::EPSILON) * 2f32.powi(level as i32)
+    }
+
+    // the distances over which `level` blends into the next one, if there is a coarser
+    // one to blend into
+    fn morph_zone(&self, level: u32) -> Option<(f32, f32)> {
+        if level + 1 >= self.levels() || self.resolution(level + 1) == self.resolution(level) {
+            return None;
+        }
+        let end = self.reach(level);
+        Some((end * (1.0 - self.params.morph_range.clamp(0.0, 0.5)), end))
+    }
+
+    // how far `level` has blended into the next one at `at`, 0..1
+    fn morph(&self, level: u32, at: Vec2) -> f32 {
+        let Some((start, end)) = self.morph_zone(level) else {
+            return 0.0;
+        };
+        let distance = self.eye(level).distance(at);
+        if end > start {
+            ((distance - start) / (end - start)).clamp(0.0, 1.0)
+        } else if distance >= end {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    // the height of point `g` of `level`'s grid: the terrain's own, blended towards the
+    // next level's triangles around it. Points the next level shares take its blended
+    // height in turn, so a fully blended level is exactly the next one
+    fn height(&self, level: u32, g: IVec2) -> f32 {
+        let at = self.point(level, g);
+        let own = (self.height_fn)(at.x, at.y);
+        let blend = self.morph(level, at);
+        if blend <= 0.0 {
+            return own;
+        }
+        let coarse =
+            |x: i32, z: i32| self.height(level + 1, IVec2::new(x, z).div_euclid(IVec2::splat(2)));
+        let next = match (g.x.rem_euclid(2), g.y.rem_euclid(2)) {
+            (0, 0) => coarse(g.x, g.y),
+            (1, 0) => (coarse(g.x - 1, g.y) + coarse(g.x + 1, g.y)) / 2.0,
+            (0, _) => (coarse(g.x, g.y - 1) + coarse(g.x, g.y + 1)) / 2.0,
+            // the middle of a coarse cell is on its rising diagonal
+            _ => (coarse(g.x - 1, g.y + 1) + coarse(g.x + 1, g.y - 1)) / 2.0,
+        };
+        own * (1.0 - blend) + next * blend
+    }
+
+    // the normal of the terrain itself at `at`, over the finest cells
+    fn normal(&self, at: Vec2) -> Vec3 {
+        let step = self.cell(0);
+        let height = |x: f32, z: f32| (self.height_fn)(at.x + x, at.y + z);
+        Vec3::new(
+            height(-step, 0.0) - height(step, 0.0),
+            2.0 * step,
+            height(0.0, -step) - height(0.0, step),
+        )
+        .normalize_or_zero()
+    }
+
+    // the nearest and farthest a chunk's points are from where the finest level sees the
+    // viewer
+    fn span(&self, chunk: IVec2) -> (f32, f32) {
+        let (eye, size) = (self.eye(0), self.params.chunk_size);
+        let min = Vec2::new(chunk.x as f32, chunk.y as f32) * size;
+        let max = Vec2::new((chunk.x + 1) as f32, (chunk.y + 1) as f32) * size;
+        let near = eye.clamp(min, max).distance(eye);
+        let far = (eye - (min + max) / 2.0).abs() + Vec2::splat(size / 2.0);
+        (near, far.length())
+    }
+
+    // the first level whose reach is past the chunk's nearest point
+    fn level_of(&self, chunk: IVec2) -> u32 {
+        let (near, _) = self.span(chunk);
+        (0..self.levels())
+            .find(|level| near < self.reach(*level))
+            .unwrap_or(self.levels() - 1)
+    }
+
+    // whether any of a chunk at `level`, or the coarser edges it takes from its
+    // neighbors, is blending, so that its mesh changes as the viewer moves; coarser
+    // levels see the viewer up to a cell of theirs away from where the finest one does
+    fn moves_with_eye(&self, chunk: IVec2, level: u32) -> bool {
+        let (near, far) = self.span(chunk);
+        (level..self.levels()).any(|level| match self.morph_zone(level) {
+            Some((start, end)) => {
+                let slack = self.cell(level);
+                near - slack <= end && far + slack >= start
+            }
+            None => false,
+        })
+    }
+}
+
+// the mesh of `chunk` at `level`, each edge following the neighbor in `neighbors` (the
+// levels of the ones in `NEIGHBORS`) where that one's coarser, with a skirt around it
+fn chunk_mesh(surface: &Surface, chunk: IVec2, level: u32, neighbors: [u32; 4]) -> Mesh {
+    let resolution = surface.resolution(level);
+    let columns = resolution + 1;
+    let first = chunk * resolution as i32;
+    let grid = |i: u32, j: u32| first + IVec2::new(i as i32, j as i32);
+
+    let mut heights: Vec<f32> = (0..=resolution)
+        .flat_map(|j| (0..=resolution).map(move |i| (i, j)))
+        .map(|(i, j)| surface.height(level, grid(i, j)))
+        .collect();
+
+    for (side, neighbor) in neighbors.into_iter().enumerate() {
+        let coarse = surface.resolution(neighbor);
+        if neighbor <= level || coarse >= resolution {
+            continue;
+        }
+        // the neighbor's grid points along the edge, with the vertices between them
+        // moved onto the straight line joining them
+        let step = resolution / coarse;
+        let shared = |k: u32| {
+            let (i, j) = edge_vertex(side, resolution, k);
+            surface.height(neighbor, grid(i, j).div_euclid(IVec2::splat(step as i32)))
+        };
+        for k in 0..=resolution {
+            let before = k / step * step;
+            let height = if before == k {
+                shared(k)
+            } else {
+                let t = (k - before) as f32 / step as f32;
+                shared(before) + (shared(before + step) - shared(before)) * t
+            };
+            let (i, j) = edge_vertex(side, resolution, k);
+            heights[(j * columns + i) as usize] = height;
+        }
+    }
+
+    let mut data = GeometryData::default();
+    for j in 0..=resolution {
+        for i in 0..=resolution {
+            let at = surface.point(level, grid(i, j));
+            data.positions
+                .push([at.x, heights[(j * columns + i) as usize], at.y]);
+            data.normals.push(surface.normal(at).into());
+            data.uvs
+                .push([i as f32 / resolution as f32, j as f32 / resolution as f32]);
+        }
+    }
+    data.indices = grid_triangles(resolution, resolution)
+        .into_iter()
+        .flatten()
+        .map(|v| v as u32)
+        .collect();
+
+    // a strip hanging down from each edge, facing out of the chunk
+    let depth = surface.params.skirt_depth.max(0.0);
+    for side in 0..NEIGHBORS.len() {
+        let mut top: Vec<u32> = (0..=resolution)
+            .map(|k| edge_vertex(side, resolution, k))
+            .map(|(i, j)| j * columns + i)
+            .collect();
+        // running -Z along the -X edge and -X along the +Z one instead, so the strip
+        // faces out there too
+        if side == 0 || side == 3 {
+            top.reverse();
+        }
+        let bottom = data.positions.len() as u32;
+        for v in &top {
+            let [x, y, z] = data.positions[*v as usize];
+            data.positions.push([x, y - depth, z]);
+            data.normals.push(data.normals[*v as usize]);
+            data.uvs.push(data.uvs[*v as usize]);
+        }
+        for k in 0..top.len() - 1 {
+            let (a, b) = (top[k], top[k + 1]);
+            let (c, d) = (bottom + k as u32, bottom + k as u32 + 1);
+            data.indices.extend([a, b, c, b, d, c]);
+        }
+    }
+    data.into_mesh()
+}
+
+// vertex `k` along the edge of a chunk `resolution` cells across that faces the
+// neighbor `NEIGHBORS[side]`, counting towards +X or +Z
+fn edge_vertex(side: usize, resolution: u32, k: u32) -> (u32, u32) {
+    match side {
+        0 => (0, k),
+        1 => (resolution, k),
+        2 => (k, 0),
+        _ => (k, resolution),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::positions;
+
+    fn surface(lod_distance: f32, eye: Vec2) -> Surface {
+        Surface {
+            params: LodTerrainParams {
+                chunk_size: 8.0,
+                resolution: 8,
+                levels: 3,
+                lod_distance,
+                morph_range: 0.5,
+                ..default()
+            },
+            height_fn: Arc::new(|x, z| (x * 0.7).sin() * 2.0 + (z * 1.3).cos() * 0.5),
+            eye,
+        }
+    }
+
+    #[test]
+    fn fine_edges_meet_coarser_neighbors_exactly() {
+        // the neighbor is partway through blending into the level after it
+        let surface = surface(12.0, Vec2::new(1.0, 3.0));
+        let fine = chunk_mesh(&surface, IVec2::ZERO, 0, [0, 1, 0, 0]);
+        let coarse = chunk_mesh(&surface, IVec2::new(1, 0), 1, [0, 1, 1, 1]);
+        let (fine, coarse) = (positions(&fine), positions(&coarse));
+
+        let (resolution, step) = (surface.resolution(0), 2);
+        let columns = resolution + 1;
+        let coarse_columns = resolution / step + 1;
+        for k in 0..=resolution {
+            let edge = fine[(k * columns + resolution) as usize];
+            let (below, above) = (k / step, (k + step - 1) / step);
+            let (a, b) = (
+                coarse[(below * coarse_columns) as usize],
+                coarse[(above * coarse_columns) as usize],
+            );
+            if below == above {
+                assert_eq!(edge, a, "vertex {k} of the shared edge");
+            } else {
+                let between = Vec3::from(a).lerp(b.into(), 0.5);
+                assert!(Vec3::from(edge).abs_diff_eq(between, 1e-5), "vertex {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn fully_blended_level_is_the_next_one() {
+        // every chunk is far past the end of the first two levels' reach
+        let surface = surface(0.5, Vec2::new(-40.0, -40.0));
+        let chunk = IVec2::new(1, 2);
+        assert_eq!(surface.morph(0, surface.point(0, chunk * 8)), 1.0);
+        let fine = chunk_mesh(&surface, chunk, 0, [0; 4]);
+        let coarse = chunk_mesh(&surface, chunk, 1, [1; 4]);
+        let (fine, coarse) = (positions(&fine), positions(&coarse));
+
+        // each fine point is on the coarse triangles, the in-between ones halfway along an
+        // edge or the rising diagonal of a coarse cell
+        let height = |i: usize, j: usize| coarse[j * 5 + i][1];
+        for j in 0..=8 {
+            for i in 0..=8 {
+                let (x, z) = (i / 2, j / 2);
+                let expected = match (i % 2, j % 2) {
+                    (0, 0) => height(x, z),
+                    (1, 0) => (height(x, z) + height(x + 1, z)) / 2.0,
+                    (0, _) => (height(x, z) + height(x, z + 1)) / 2.0,
+                    _ => (height(x, z + 1) + height(x + 1, z)) / 2.0,
+                };
+                assert_eq!(fine[j * 9 + i][1], expected, "point {i}, {j}");
+            }
+        }
+    }
+}
@@ -0,0 +1,206 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    bounds::bounding_sphere,
+    util::{positions, triangle_indices, welded_position_ids, Rng},
+};
+
+/// Gives every triangle its own random vertex color, which makes individual faces easy
+/// to pick out when checking for degenerate triangles or that a subdivision worked.
+///
+/// A face's three corners can only share a color if no other face uses them, so an
+/// indexed mesh is split into separate vertices first and comes back without an index
+/// buffer. The same `seed` always produces the same colors.
+pub fn apply_random_face_colors(mesh: &mut Mesh, seed: u32) {
+    if mesh.indices().is_some() {
+        mesh.duplicate_vertices();
+    }
+
+    let mut rng = Rng::new(seed);
+    let triangles = positions(mesh).len() / 3;
+    let mut colors = Vec::with_capacity(triangles * 3);
+    for _ in 0..triangles {
+        let color = Color::hsl(
+            rng.range(0.0, 360.0),
+            rng.range(0.5, 1.0),
+            rng.range(0.35, 0.65),
+        );
+        colors.extend([color.as_linear_rgba_f32(); 3]);
+    }
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+/// Colors each vertex by its distance from `center`, blending from `inner` there to
+/// `outer` at `radius` and beyond, for effect meshes like shockwaves, auras and spawn
+/// markers. Replaces any vertex colors the mesh had.
+///
+/// The blend is linear in distance and done in linear space, like the colors Bevy
+/// stores. A `radius` of zero or less gives every vertex `outer` except ones right on
+/// `center`.
+pub fn apply_radial_gradient(
+    mesh: &mut Mesh,
+    center: Vec3,
+    inner: Color,
+    outer: Color,
+    radius: f32,
+) {
+    let (inner, outer) = (
+        Vec4::from(inner.as_linear_rgba_f32()),
+        Vec4::from(outer.as_linear_rgba_f32()),
+    );
+    let colors: Vec<[f32; 4]> = positions(mesh)
+        .iter()
+        .map(|position| {
+            let distance = Vec3::from(*position).distance(center);
+            let t = if radius > 0.0 {
+                (distance / radius).min(1.0)
+            } else if distance > 0.0 {
+                1.0
+            } else {
+                0.0
+            };
+            inner.lerp(outer, t).into()
+        })
+        .collect();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+/// Colors each vertex by how curved the surface is around it: gray where it's flat or
+/// saddle-shaped, warming to red where it bulges out and cooling to blue where it
+/// pinches in.
+///
+/// Curvature is the discrete mean curvature from the cotangent weights of each vertex's
+/// ring of neighbors, measured relative to the mesh's bounding sphere so a sphere comes
+/// out an even, middling red at any size. Vertices with the same position are treated
+/// as one, so flat-shaded meshes color the same as smooth ones. Vertices on an open
+/// boundary are left gray, since only part of their ring is there.
+pub fn color_by_curvature(mesh: &mut Mesh) {
+    let positions = positions(mesh);
+    let ids = welded_position_ids(positions);
+    let welded = ids.iter().max().map_or(0, |max| max + 1);
+    let mut points = vec![Vec3::ZERO; welded];
+    for (vertex, id) in ids.iter().enumerate() {
+        points[*id] = positions[vertex].into();
+    }
+
+    // sum of cot-weighted edges, area and normal around each welded vertex
+    let mut laplacian = vec![Vec3::ZERO; welded];
+    let mut areas = vec![0.0; welded];
+    let mut normals = vec![Vec3::ZERO; welded];
+    let mut edges: HashMap<(usize, usize), u32> = HashMap::new();
+
+    for tri in triangle_indices(mesh) {
+        let tri = tri.map(|v| ids[v]);
+        let [a, b, c] = tri.map(|id| points[id]);
+        let normal = (b - a).cross(c - a);
+        let area = normal.length() / 2.0;
+        if area <= f32::EPSILON {
+            continue;
+        }
+
+        for k in 0..3 {
+            let (i, j, opposite) = (tri[k], tri[(k + 1) % 3], tri[(k + 2) % 3]);
+            let (u, v) = (points[i] - points[opposite], points[j] - points[opposite]);
+            let cot = u.dot(v) / u.cross(v).length();
+            laplacian[i] += cot * (points[j] - points[i]);
+            laplacian[j] += cot * (points[i] - points[j]);
+
+            areas[tri[k]] += area / 3.0;
+            normals[tri[k]] += normal;
+            *edges.entry((i.min(j), i.max(j))).or_default() += 1;
+        }
+    }
+
+    let mut boundary = vec![false; welded];
+    for ((i, j), count) in edges {
+        if count == 1 {
+            boundary[i] = true;
+            boundary[j] = true;
+        }
+    }
+
+    let scale = bounding_sphere(mesh).map_or(1.0, |(_, radius)| radius);
+    let curvature: Vec<f32> = (0..welded)
+        .map(|id| {
+            if boundary[id] || areas[id] <= 0.0 {
+                return 0.0;
+            }
+            // the laplacian points into a bulge, against the normal
+            -laplacian[id].dot(normals[id].normalize_or_zero()) / (4.0 * areas[id])
+        })
+        .collect();
+
+    let neutral = Vec4::from(Color::GRAY.as_linear_rgba_f32());
+    let (hot, cold) = (
+        Vec4::from(Color::RED.as_linear_rgba_f32()),
+        Vec4::from(Color::BLUE.as_linear_rgba_f32()),
+    );
+    let colors: Vec<[f32; 4]> = ids
+        .iter()
+        .map(|id| {
+            // squashed into -1..1, with a sphere landing on 0.5
+            let x = curvature[*id] * scale;
+            let t = x / (1.0 + x.abs());
+            let end = if t < 0.0 { cold } else { hot };
+            neutral.lerp(end, t.abs()).into()
+        })
+        .collect();
+
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::colors,
+        terrain::{generate_heightmap, HeightmapParams},
+    };
+
+    #[test]
+    fn sphere_curvature_colors_are_even_at_any_size() {
+        let mut reds = vec![];
+        for radius in [0.5, 10.0] {
+            let mut sphere = Mesh::from(shape::Icosphere {
+                radius,
+                subdivisions: 3,
+            });
+            color_by_curvature(&mut sphere);
+            let colors = colors(&sphere).unwrap();
+            let (min, max) = colors
+                .iter()
+                .fold((f32::MAX, f32::MIN), |(min, max), [red, ..]| {
+                    (min.min(*red), max.max(*red))
+                });
+            assert!(max - min < 0.05, "{min}..{max}");
+            reds.push(min);
+        }
+        assert!((reds[0] - reds[1]).abs() < 0.05);
+
+        let mut flat = generate_heightmap(&HeightmapParams::default(), |_, _| 0.0);
+        color_by_curvature(&mut flat);
+        let gray = Color::GRAY.as_linear_rgba_f32();
+        assert!(colors(&flat).unwrap().iter().all(|color| *color == gray));
+    }
+
+    #[test]
+    fn radial_gradient_is_inner_at_the_center_and_outer_past_the_radius() {
+        let mut mesh = generate_heightmap(&HeightmapParams::default(), |_, _| 0.0);
+        let inner = Color::rgba(0.2, 0.9, 0.4, 1.0);
+        apply_radial_gradient(&mut mesh, Vec3::ZERO, inner, Color::BLUE, 3.0);
+
+        let center = positions(&mesh)
+            .iter()
+            .position(|p| *p == [0.0; 3])
+            .unwrap();
+        let colors = colors(&mesh).unwrap();
+        assert_eq!(colors[center], inner.as_linear_rgba_f32());
+        for (position, color) in positions(&mesh).iter().zip(colors) {
+            if Vec3::from(*position).length() >= 3.0 {
+                assert_eq!(*color, Color::BLUE.as_linear_rgba_f32());
+            }
+        }
+    }
+}
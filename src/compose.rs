@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::{
+    error::{positive, GeometryError},
+    merge::{apply_transform, merge_meshes},
+    polyhedra::generate_frustum,
+    sphere::{generate_uv_sphere, UvSphereParams},
+};
+
+/// Static props put together from primitives in one expression, as a front end to
+/// [`apply_transform`] and [`merge_meshes`]:
+///
+/// ```ignore
+/// let lamp = Composition::new()
+///     .part(cuboid(Vec3::new(0.4, 0.1, 0.4))?)
+///     .part(cylinder)
+///     .at(Vec3::Y * 0.55)
+///     .group(Composition::new().part(sphere(0.25)?).part(shade).at(Vec3::Y * 0.2))
+///     .at(Vec3::Y * 1.1)
+///     .build();
+/// ```
+///
+/// [`Composition::at`], [`Composition::rotated`] and [`Composition::scaled`] move the
+/// part added last, about its own origin, and [`Composition::group`] adds a whole other
+/// composition as one part, so transforms given after it move everything in it
+/// together and anything inside it is placed relative to the group.
+#[derive(Clone, Debug, Default)]
+pub struct Composition {
+    parts: Vec<(Mesh, Transform)>,
+}
+
+impl Composition {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `mesh` as it is, at the composition's origin until moved.
+    pub fn part(mut self, mesh: Mesh) -> Self {
+        self.parts.push((mesh, Transform::IDENTITY));
+        self
+    }
+
+    /// Adds everything in `group` as one part.
+    pub fn group(self, group: Composition) -> Self {
+        self.part(group.build())
+    }
+
+    /// Moves the last part so its origin is at `translation`.
+    pub fn at(self, translation: Vec3) -> Self {
+        self.with_last(|transform| transform.translation = translation)
+    }
+
+    /// Turns the last part by `rotation`, on top of any turn it already has.
+    pub fn rotated(self, rotation: Quat) -> Self {
+        self.with_last(|transform| transform.rotation = rotation * transform.rotation)
+    }
+
+    /// Scales the last part by `scale`, on top of any scale it already has.
+    pub fn scaled(self, scale: Vec3) -> Self {
+        self.with_last(|transform| transform.scale *= scale)
+    }
+
+    /// Replaces the last part's transform outright.
+    pub fn transformed(self, transform: Transform) -> Self {
+        self.with_last(|last| *last = transform)
+    }
+
+    /// Every part with its transform baked in, merged into one mesh that keeps the
+    /// attributes they all have.
+    pub fn build(self) -> Mesh {
+        let parts: Vec<Mesh> = self
+            .parts
+            .into_iter()
+            .map(|(mut mesh, transform)| {
+                apply_transform(&mut mesh, transform);
+                mesh
+            })
+            .collect();
+        merge_meshes(&parts)
+    }
+
+    // does nothing before the first part is added
+    fn with_last(mut self, change: impl FnOnce(&mut Transform)) -> Self {
+        if let Some((_, transform)) = self.parts.last_mut() {
+            change(transform);
+        }
+        self
+    }
+}
+
+/// [`generate_uv_sphere`] with its default detail, for [`Composition::part`]. Fails
+/// with [`GeometryError::InvalidParameter`] unless `radius` is positive.
+pub fn sphere(radius: f32) -> Result<Mesh, GeometryError> {
+    let params = UvSphereParams {
+        radius,
+        ..default()
+    };
+    generate_uv_sphere(&params)
+}
+
+/// Box `size` across, centered on the origin, with its own vertices, flat normals and
+/// UVs on each face, for [`Composition::part`]. Fails with
+/// [`GeometryError::InvalidParameter`] unless the size is positive along every axis.
+pub fn cuboid(size: Vec3) -> Result<Mesh, GeometryError> {
+    positive("size.x", size.x)?;
+    positive("size.y", size.y)?;
+    positive("size.z", size.z)?;
+    let base = Vec2::new(size.x, size.z);
+    let mut mesh = generate_frustum(base, base, size.y)?;
+    apply_transform(&mut mesh, Transform::from_xyz(0.0, -size.y / 2.0, 0.0));
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::geometry::GeometryData;
+
+    #[test]
+    fn two_part_composition_equals_merging_by_hand() {
+        let turned = Quat::from_rotation_z(0.4);
+        let built = Composition::new()
+            .part(sphere(0.5).unwrap())
+            .at(Vec3::X)
+            .part(cuboid(Vec3::ONE).unwrap())
+            .rotated(turned)
+            .build();
+
+        let mut ball = sphere(0.5).unwrap();
+        apply_transform(&mut ball, Transform::from_translation(Vec3::X));
+        let mut block = cuboid(Vec3::ONE).unwrap();
+        apply_transform(&mut block, Transform::from_rotation(turned));
+        let by_hand = merge_meshes(&[ball, block]);
+
+        assert_eq!(
+            GeometryData::from_mesh(&built),
+            GeometryData::from_mesh(&by_hand)
+        );
+    }
+
+    #[test]
+    fn flat_parts_are_errors_not_empty_meshes() {
+        assert!(matches!(
+            sphere(0.0),
+            Err(GeometryError::InvalidParameter { name: "radius", .. })
+        ));
+        assert!(matches!(
+            cuboid(Vec3::new(1.0, 0.0, 1.0)),
+            Err(GeometryError::InvalidParameter { name: "size.y", .. })
+        ));
+    }
+}
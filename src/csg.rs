@@ -0,0 +1,352 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::{
+    attributes::{normals, uvs},
+    util::{positions, triangle_indices},
+};
+
+// how far from a plane a point can be and still count as on it
+const PLANE_TOLERANCE: f32 = 1e-5;
+
+/// Everything inside either mesh, as one closed mesh with the parts of each surface
+/// that were inside the other removed.
+///
+/// The boolean operations take closed meshes wound outward, like the generators make,
+/// and split their triangles against each other with a BSP tree. Normals and UVs are
+/// carried over (interpolated where triangles are split) and vertex colors are dropped;
+/// the result has no shared vertices between separate source triangles. Surfaces that
+/// touch or overlap exactly only work within a small distance tolerance, so shapes
+/// that meet face to face can leave slivers or gaps, meshes that aren't closed give
+/// meaningless results, and the tree is built by recursion, so very large meshes are
+/// better simplified first.
+pub fn csg_union(a: &Mesh, b: &Mesh) -> Mesh {
+    let (mut a, mut b) = (Node::new(polygons(a)), Node::new(polygons(b)));
+    a.clip_to(&b);
+    b.clip_to(&a);
+    b.invert();
+    b.clip_to(&a);
+    b.invert();
+    a.build(b.all_polygons());
+    into_mesh(a.all_polygons())
+}
+
+/// What's inside `a` but not `b`, like drilling a hole in a box with a cylinder. See
+/// [`csg_union`] for what the meshes need to be.
+pub fn csg_difference(a: &Mesh, b: &Mesh) -> Mesh {
+    let (mut a, mut b) = (Node::new(polygons(a)), Node::new(polygons(b)));
+    a.invert();
+    a.clip_to(&b);
+    b.clip_to(&a);
+    b.invert();
+    b.clip_to(&a);
+    b.invert();
+    a.build(b.all_polygons());
+    a.invert();
+    into_mesh(a.all_polygons())
+}
+
+/// What's inside both meshes. See [`csg_union`] for what the meshes need to be.
+pub fn csg_intersection(a: &Mesh, b: &Mesh) -> Mesh {
+    let (mut a, mut b) = (Node::new(polygons(a)), Node::new(polygons(b)));
+    a.invert();
+    b.clip_to(&a);
+    b.invert();
+    a.clip_to(&b);
+    b.clip_to(&a);
+    a.build(b.all_polygons());
+    a.invert();
+    into_mesh(a.all_polygons())
+}
+
+#[derive(Clone, Copy)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+}
+
+impl Vertex {
+    fn lerp(self, other: Vertex, t: f32) -> Vertex {
+        Vertex {
+            position: self.position.lerp(other.position, t),
+            normal: self.normal.lerp(other.normal, t).normalize_or_zero(),
+            uv: self.uv.lerp(other.uv, t),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Plane {
+    normal: Vec3,
+    // distance from the origin along `normal`
+    w: f32,
+}
+
+impl Plane {
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+
+    // sorts `polygon` into the lists for each side of the plane, cutting it in two if it
+    // crosses
+    fn split(&self, polygon: Polygon, sides: &mut Sides) {
+        const COPLANAR: u8 = 0;
+        const FRONT: u8 = 1;
+        const BACK: u8 = 2;
+        const SPANNING: u8 = 3;
+
+        let side = |vertex: &Vertex| {
+            let t = self.normal.dot(vertex.position) - self.w;
+            if t < -PLANE_TOLERANCE {
+                BACK
+            } else if t > PLANE_TOLERANCE {
+                FRONT
+            } else {
+                COPLANAR
+            }
+        };
+        let types: Vec<u8> = polygon.vertices.iter().map(side).collect();
+
+        match types.iter().fold(COPLANAR, |all, side| all | side) {
+            COPLANAR if self.normal.dot(polygon.plane.normal) > 0.0 => {
+                sides.coplanar_front.push(polygon)
+            }
+            COPLANAR => sides.coplanar_back.push(polygon),
+            FRONT => sides.front.push(polygon),
+            BACK => sides.back.push(polygon),
+            _ => {
+                let (mut front, mut back) = (vec![], vec![]);
+                let count = polygon.vertices.len();
+                for i in 0..count {
+                    let (vi, vj) = (polygon.vertices[i], polygon.vertices[(i + 1) % count]);
+                    let (ti, tj) = (types[i], types[(i + 1) % count]);
+                    if ti != BACK {
+                        front.push(vi);
+                    }
+                    if ti != FRONT {
+                        back.push(vi);
+                    }
+                    if ti | tj == SPANNING {
+                        let t = (self.w - self.normal.dot(vi.position))
+                            / self.normal.dot(vj.position - vi.position);
+                        let cut = vi.lerp(vj, t);
+                        front.push(cut);
+                        back.push(cut);
+                    }
+                }
+                if front.len() >= 3 {
+                    sides.front.push(Polygon {
+                        vertices: front,
+                        plane: polygon.plane,
+                    });
+                }
+                if back.len() >= 3 {
+                    sides.back.push(Polygon {
+                        vertices: back,
+                        plane: polygon.plane,
+                    });
+                }
+            }
+        }
+    }
+}
+
+// a convex polygon, all in its plane
+#[derive(Clone)]
+struct Polygon {
+    vertices: Vec<Vertex>,
+    plane: Plane,
+}
+
+impl Polygon {
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        for vertex in &mut self.vertices {
+            vertex.normal = -vertex.normal;
+        }
+        self.plane.flip();
+    }
+}
+
+#[derive(Default)]
+struct Sides {
+    coplanar_front: Vec<Polygon>,
+    coplanar_back: Vec<Polygon>,
+    front: Vec<Polygon>,
+    back: Vec<Polygon>,
+}
+
+// a BSP tree: each node splits space along the plane of its first polygon, keeping the
+// polygons in that plane and passing the rest to the side they're on
+#[derive(Default)]
+struct Node {
+    plane: Option<Plane>,
+    front: Option<Box<Node>>,
+    back: Option<Box<Node>>,
+    polygons: Vec<Polygon>,
+}
+
+impl Node {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = Node::default();
+        node.build(polygons);
+        node
+    }
+
+    // swaps inside for outside
+    fn invert(&mut self) {
+        for polygon in &mut self.polygons {
+            polygon.flip();
+        }
+        if let Some(plane) = &mut self.plane {
+            plane.flip();
+        }
+        for child in [&mut self.front, &mut self.back].into_iter().flatten() {
+            child.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    // the parts of `polygons` outside the solid this tree bounds
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let Some(plane) = self.plane else {
+            return polygons;
+        };
+
+        let mut sides = Sides::default();
+        for polygon in polygons {
+            plane.split(polygon, &mut sides);
+        }
+        let mut front = sides.front;
+        front.extend(sides.coplanar_front);
+        let mut back = sides.back;
+        back.extend(sides.coplanar_back);
+
+        let mut kept = match &self.front {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        // past the last plane on the back side is inside the solid
+        if let Some(node) = &self.back {
+            kept.extend(node.clip_polygons(back));
+        }
+        kept
+    }
+
+    // removes every polygon in this tree that's inside the solid `other` bounds
+    fn clip_to(&mut self, other: &Node) {
+        self.polygons = other.clip_polygons(std::mem::take(&mut self.polygons));
+        for child in [&mut self.front, &mut self.back].into_iter().flatten() {
+            child.clip_to(other);
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = self.polygons.clone();
+        for child in [&self.front, &self.back].into_iter().flatten() {
+            polygons.extend(child.all_polygons());
+        }
+        polygons
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        let Some(first) = polygons.first() else {
+            return;
+        };
+        let plane = *self.plane.get_or_insert(first.plane);
+
+        let mut sides = Sides::default();
+        for polygon in polygons {
+            plane.split(polygon, &mut sides);
+        }
+        self.polygons.extend(sides.coplanar_front);
+        self.polygons.extend(sides.coplanar_back);
+        if !sides.front.is_empty() {
+            self.front
+                .get_or_insert_with(Default::default)
+                .build(sides.front);
+        }
+        if !sides.back.is_empty() {
+            self.back
+                .get_or_insert_with(Default::default)
+                .build(sides.back);
+        }
+    }
+}
+
+// a polygon per triangle of the mesh, leaving out ones with no area
+fn polygons(mesh: &Mesh) -> Vec<Polygon> {
+    let positions = positions(mesh);
+    let normals = normals(mesh);
+    let uvs = uvs(mesh);
+
+    triangle_indices(mesh)
+        .into_iter()
+        .filter_map(|tri| {
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+            let normal = (b - a).cross(c - a).try_normalize()?;
+            let vertices = tri
+                .map(|v| Vertex {
+                    position: positions[v].into(),
+                    normal: normals.map_or(normal, |normals| normals[v].into()),
+                    uv: uvs.map_or(Vec2::ZERO, |uvs| uvs[v].into()),
+                })
+                .to_vec();
+            Some(Polygon {
+                vertices,
+                plane: Plane {
+                    normal,
+                    w: normal.dot(a),
+                },
+            })
+        })
+        .collect()
+}
+
+fn into_mesh(polygons: Vec<Polygon>) -> Mesh {
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+    for polygon in polygons {
+        let first = positions.len() as u32;
+        for vertex in &polygon.vertices {
+            positions.push(vertex.position.into());
+            normals.push(vertex.normal.into());
+            uvs.push(vertex.uv.into());
+        }
+        // the polygons stay convex, so a fan covers them
+        for k in 1..polygon.vertices.len() as u32 - 1 {
+            indices.extend([first, first + k, first + k + 1]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bounds::volume, merge::apply_transform};
+
+    // two 2x2x2 cubes overlapping in a unit cube
+    #[test]
+    fn overlapping_cubes_combine_to_the_expected_volumes() {
+        let a = Mesh::from(shape::Cube { size: 2.0 });
+        let mut b = a.clone();
+        apply_transform(&mut b, Transform::from_translation(Vec3::ONE));
+
+        assert!((volume(&csg_difference(&a, &b)) - 7.0).abs() < 1e-3);
+        assert!((volume(&csg_union(&a, &b)) - 15.0).abs() < 1e-3);
+        assert!((volume(&csg_intersection(&a, &b)) - 1.0).abs() < 1e-3);
+    }
+}
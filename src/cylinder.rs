@@ -0,0 +1,528 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    error::{at_least, positive, GeometryError},
+    geometry::GeometryData,
+    normals::NormalMode,
+    util::transform_mesh,
+};
+
+/// How the flat, round ends of [`generate_disc`], [`generate_cylinder`] and
+/// [`generate_cone`] are filled in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CapStyle {
+    /// a triangle from each segment of the edge to one vertex in the middle, for just
+    /// one vertex more than there are segments; every triangle meets at that vertex,
+    /// which shows as streaks in anything interpolated across a big cap, like vertex
+    /// lighting, vertex colors or displacement
+    #[default]
+    Fan,
+    /// rings about a segment's width apart, each with fewer vertices the nearer it is
+    /// to the middle, so the triangles are all about the same size and shape, at the
+    /// cost of roughly `segments² / 12` vertices per cap
+    Grid,
+    /// this many evenly spaced rings out to the edge, like a dartboard: each ring keeps
+    /// the vertex count of the one outside it, filling the band between them with quads,
+    /// until it can be halved and leave its vertices no more than about 1.4 times as far
+    /// apart as they are around the edge. The middle vertex only joins the innermost
+    /// ring, and with around `segments / 6` rings the cells come out about square, with
+    /// no thin slivers however many segments there are. Zero rings is taken as one,
+    /// which is a fan
+    Rings(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct DiscParams {
+    pub radius: f32,
+    /// segments around the edge
+    pub segments: u32,
+    pub cap: CapStyle,
+    pub normals: NormalMode,
+}
+
+impl Default for DiscParams {
+    fn default() -> Self {
+        DiscParams {
+            radius: 0.5,
+            segments: 32,
+            cap: CapStyle::Fan,
+            normals: NormalMode::Flat,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CylinderParams {
+    pub radius: f32,
+    pub height: f32,
+    /// segments around the side and the edge of each cap
+    pub segments: u32,
+    pub cap: CapStyle,
+    pub normals: NormalMode,
+}
+
+impl Default for CylinderParams {
+    fn default() -> Self {
+        CylinderParams {
+            radius: 0.5,
+            height: 1.0,
+            segments: 32,
+            cap: CapStyle::Fan,
+            normals: NormalMode::Analytic,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ConeParams {
+    /// radius of the base
+    pub radius: f32,
+    pub height: f32,
+    /// segments around the side and the edge of the base
+    pub segments: u32,
+    /// how the base is filled in
+    pub cap: CapStyle,
+    pub normals: NormalMode,
+}
+
+impl Default for ConeParams {
+    fn default() -> Self {
+        ConeParams {
+            radius: 0.5,
+            height: 1.0,
+            segments: 32,
+            cap: CapStyle::Fan,
+            normals: NormalMode::Analytic,
+        }
+    }
+}
+
+/// Flat circle on the XZ plane, centered on the origin and facing +Y.
+///
+/// UVs map the unit square onto the circle's bounding square, seen from above with -Z
+/// up, so a round texture fits exactly.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the radius is positive and
+/// there are at least 3 segments.
+pub fn generate_disc(params: &DiscParams) -> Result<Mesh, GeometryError> {
+    generate_disc_raw(params).map(GeometryData::into_mesh)
+}
+
+/// [`generate_disc`] as plain arrays rather than a `Mesh`.
+pub fn generate_disc_raw(params: &DiscParams) -> Result<GeometryData, GeometryError> {
+    positive("radius", params.radius)?;
+    at_least("segments", params.segments, 3)?;
+    let mut disc = Builder::default();
+    disc.cap(
+        Vec3::ZERO,
+        params.radius,
+        params.segments,
+        Vec3::Y,
+        params.cap,
+    );
+    let mut disc = disc.into_geometry();
+    // it's flat, so its own normals already are
+    if params.normals != NormalMode::Flat {
+        params.normals.apply_raw(&mut disc);
+    }
+    Ok(disc)
+}
+
+/// Closed cylinder standing on the Y axis, centered on the origin.
+///
+/// The side's UVs wrap once around, with U increasing towards +Z from +X and V running
+/// down from the top. The caps are mapped like [`generate_disc`] and have their own
+/// vertices, so with [`NormalMode::Analytic`] the side is smooth shaded and the edges
+/// around the caps stay hard.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the radius and height are
+/// positive and there are at least 3 segments.
+pub fn generate_cylinder(params: &CylinderParams) -> Result<Mesh, GeometryError> {
+    generate_cylinder_raw(params).map(GeometryData::into_mesh)
+}
+
+/// [`generate_cylinder`] as plain arrays rather than a `Mesh`.
+pub fn generate_cylinder_raw(params: &CylinderParams) -> Result<GeometryData, GeometryError> {
+    positive("radius", params.radius)?;
+    positive("height", params.height)?;
+    at_least("segments", params.segments, 3)?;
+    let segments = params.segments;
+    let half = params.height / 2.0;
+    let mut cylinder = Builder::default();
+
+    let mut columns = vec![];
+    for i in 0..=segments {
+        let outward = around(i, segments);
+        let u = i as f32 / segments as f32;
+        let top = cylinder.vertex(outward * params.radius + Vec3::Y * half, outward, [u, 0.0]);
+        let bottom = cylinder.vertex(outward * params.radius - Vec3::Y * half, outward, [u, 1.0]);
+        columns.push((top, bottom, outward));
+    }
+    for pair in columns.windows(2) {
+        let [(a, b, outward), (d, c, _)] = [pair[0], pair[1]];
+        cylinder.triangle([a, b, d], outward);
+        cylinder.triangle([d, b, c], outward);
+    }
+
+    cylinder.cap(Vec3::Y * half, params.radius, segments, Vec3::Y, params.cap);
+    cylinder.cap(
+        -Vec3::Y * half,
+        params.radius,
+        segments,
+        Vec3::NEG_Y,
+        params.cap,
+    );
+    let mut cylinder = cylinder.into_geometry();
+    params.normals.apply_raw(&mut cylinder);
+    Ok(cylinder)
+}
+
+/// Cone standing on the Y axis with its tip up, centered on the origin between the
+/// tip and the middle of the base.
+///
+/// With [`NormalMode::Analytic`], the side is smooth shaded, with the tip repeated for
+/// each segment so every triangle gets a normal halfway around its own edge. Its UVs
+/// wrap once around, like [`generate_cylinder`]'s, with V running from the tip down to
+/// the base. The base is mapped like [`generate_disc`].
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the radius and height are
+/// positive and there are at least 3 segments.
+pub fn generate_cone(params: &ConeParams) -> Result<Mesh, GeometryError> {
+    generate_cone_raw(params).map(GeometryData::into_mesh)
+}
+
+/// [`generate_cone`] as plain arrays rather than a `Mesh`.
+pub fn generate_cone_raw(params: &ConeParams) -> Result<GeometryData, GeometryError> {
+    positive("radius", params.radius)?;
+    positive("height", params.height)?;
+    at_least("segments", params.segments, 3)?;
+    let segments = params.segments;
+    let half = params.height / 2.0;
+    let mut cone = Builder::default();
+
+    // leaning back towards the axis as much as the side leans in
+    let normal = |outward: Vec3| (outward * params.height + Vec3::Y * params.radius).normalize();
+    let base: Vec<u32> = (0..=segments)
+        .map(|i| {
+            let outward = around(i, segments);
+            let u = i as f32 / segments as f32;
+            cone.vertex(
+                outward * params.radius - Vec3::Y * half,
+                normal(outward),
+                [u, 1.0],
+            )
+        })
+        .collect();
+    for i in 0..segments {
+        let middle = Vec3::from(cone.normals[base[i as usize] as usize])
+            .lerp(cone.normals[base[i as usize + 1] as usize].into(), 0.5)
+            .normalize();
+        let u = (i as f32 + 0.5) / segments as f32;
+        let tip = cone.vertex(Vec3::Y * half, middle, [u, 0.0]);
+        cone.triangle([tip, base[i as usize], base[i as usize + 1]], middle);
+    }
+
+    cone.cap(
+        -Vec3::Y * half,
+        params.radius,
+        segments,
+        Vec3::NEG_Y,
+        params.cap,
+    );
+    let mut cone = cone.into_geometry();
+    params.normals.apply_raw(&mut cone);
+    Ok(cone)
+}
+
+/// [`generate_cylinder`] reaching from `start` to `end`, for bones, bonds and other
+/// struts between two points, with `params.height` replaced by the distance between
+/// them. Fails like [`generate_cylinder`], including when the points are the same.
+pub fn generate_cylinder_between(
+    start: Vec3,
+    end: Vec3,
+    params: &CylinderParams,
+) -> Result<Mesh, GeometryError> {
+    let mut mesh = generate_cylinder(&CylinderParams {
+        height: start.distance(end),
+        ..*params
+    })?;
+    align_to_axis(&mut mesh, end - start);
+    transform_mesh(&mut mesh, Transform::from_translation((start + end) / 2.0));
+    Ok(mesh)
+}
+
+/// Capped rod `radius` thick from `from` to `to`, for molecular bonds, trusses and
+/// frames built out of cylinders: [`generate_cylinder_between`] with `segments` around
+/// it and the other [`CylinderParams`] left at their defaults.
+///
+/// Endpoints that coincide give a mesh with no vertices rather than an error, so a
+/// frame with a zero-length edge still builds, and so do a `radius` that isn't positive
+/// and fewer than 3 `segments`.
+pub fn generate_strut(from: Vec3, to: Vec3, radius: f32, segments: u32) -> Mesh {
+    let params = CylinderParams {
+        radius,
+        segments,
+        ..default()
+    };
+    generate_cylinder_between(from, to, &params)
+        .unwrap_or_else(|_| GeometryData::default().into_mesh())
+}
+
+/// Turns a mesh built along +Y, like [`generate_cylinder`] or [`generate_cone`], about
+/// the origin so that +Y points along `axis` instead, taking its normals with it. A
+/// zero `axis` leaves the mesh as it is.
+pub fn align_to_axis(mesh: &mut Mesh, axis: Vec3) {
+    let Some(axis) = axis.try_normalize() else {
+        return;
+    };
+    transform_mesh(
+        mesh,
+        Transform::from_rotation(Quat::from_rotation_arc(Vec3::Y, axis)),
+    );
+}
+
+/// Open, curved trough `length` long along the Z axis, like a ramp, gutter or skate
+/// track: the inside of `arc_radians` of a cylinder's side, split into `segments` strips
+/// along its length and centered on its lowest line, which runs through the origin.
+///
+/// Normals point inward, towards the cylinder's axis, and only the concave side faces
+/// out. U runs around the arc from the -X edge to the +X edge and V along the length
+/// from -Z to +Z. The surface ends with its last strip, so there are no slivers or caps
+/// along the open edges.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless `radius` and `length` are
+/// positive, the arc is more than nothing and at most a full turn, and there's at least
+/// one segment.
+pub fn generate_half_pipe(
+    radius: f32,
+    length: f32,
+    arc_radians: f32,
+    segments: u32,
+) -> Result<Mesh, GeometryError> {
+    positive("radius", radius)?;
+    positive("length", length)?;
+    positive("arc_radians", arc_radians)?;
+    if arc_radians > TAU {
+        return Err(GeometryError::InvalidParameter {
+            name: "arc_radians",
+            reason: format!("must be at most a full turn, got {arc_radians}"),
+        });
+    }
+    at_least("segments", segments, 1)?;
+    let arc = arc_radians;
+    let half = length / 2.0;
+    let mut trough = Builder::default();
+
+    let mut columns = vec![];
+    for i in 0..=segments {
+        let u = i as f32 / segments as f32;
+        let angle = (u - 0.5) * arc;
+        let inward = Vec3::new(-angle.sin(), angle.cos(), 0.0);
+        let point = (Vec3::Y - inward) * radius;
+        let back = trough.vertex(point - Vec3::Z * half, inward, [u, 0.0]);
+        let front = trough.vertex(point + Vec3::Z * half, inward, [u, 1.0]);
+        columns.push((back, front, inward));
+    }
+    for pair in columns.windows(2) {
+        let [(a, b, inward), (d, c, _)] = [pair[0], pair[1]];
+        trough.triangle([a, b, d], inward);
+        trough.triangle([d, b, c], inward);
+    }
+
+    Ok(trough.into_geometry().into_mesh())
+}
+
+// the direction out from the Y axis to the `i`th of `segments` points around it, with
+// the last of a seam's copies landing exactly on the first
+fn around(i: u32, segments: u32) -> Vec3 {
+    let angle = (i % segments) as f32 / segments as f32 * TAU;
+    Vec3::new(angle.cos(), 0.0, angle.sin())
+}
+
+#[derive(Default)]
+struct Builder {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl Builder {
+    fn vertex(&mut self, position: Vec3, normal: Vec3, uv: [f32; 2]) -> u32 {
+        self.positions.push(position.into());
+        self.normals.push(normal.into());
+        self.uvs.push(uv);
+        self.positions.len() as u32 - 1
+    }
+
+    // wound so the front faces `facing`
+    fn triangle(&mut self, mut tri: [u32; 3], facing: Vec3) {
+        let [a, b, c] = tri.map(|v| Vec3::from(self.positions[v as usize]));
+        if (b - a).cross(c - a).dot(facing) < 0.0 {
+            tri.swap(1, 2);
+        }
+        self.indices.extend(tri);
+    }
+
+    // a flat round cap around `center`, facing straight up or down
+    fn cap(&mut self, center: Vec3, radius: f32, segments: u32, facing: Vec3, style: CapStyle) {
+        let steps = match style {
+            CapStyle::Fan => 1,
+            CapStyle::Grid => (segments as f32 / 6.0).round().max(1.0) as u32,
+            CapStyle::Rings(rings) => rings.max(1),
+        };
+        // seen from below, X runs the other way
+        let mirror = facing.y.signum();
+        let uv = |offset: Vec3| {
+            [
+                0.5 + mirror * offset.x / radius / 2.0,
+                0.5 + offset.z / radius / 2.0,
+            ]
+        };
+
+        let middle = self.vertex(center, facing, [0.5, 0.5]);
+        let mut inner = vec![middle];
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            let count = match style {
+                _ if step == steps => segments,
+                CapStyle::Rings(_) => {
+                    // halved, rounding up, for as long as that leaves the vertices close enough
+                    let mut count = segments;
+                    let ideal = segments as f32 * t / 2f32.sqrt();
+                    while count - count / 2 >= 3 && (count - count / 2) as f32 >= ideal {
+                        count -= count / 2;
+                    }
+                    count
+                }
+                _ => ((segments as f32 * t).round() as u32).max(3),
+            };
+            let ring: Vec<u32> = (0..count)
+                .map(|i| {
+                    let offset = around(i, count) * radius * t;
+                    self.vertex(center + offset, facing, uv(offset))
+                })
+                .collect();
+            self.zip(&inner, &ring, facing);
+            inner = ring;
+        }
+    }
+
+    // fills between two closed rings whose first vertices are in the same direction
+    // from the middle, stepping along whichever ring's next vertex comes first around
+    fn zip(&mut self, inner: &[u32], outer: &[u32], facing: Vec3) {
+        let (n_inner, n_outer) = (inner.len(), outer.len());
+        let (mut i, mut j) = (0, 0);
+        while j < n_outer || (n_inner > 1 && i < n_inner) {
+            let step_outer = n_inner == 1
+                || i == n_inner
+                || (j < n_outer && (j + 1) * n_inner <= (i + 1) * n_outer);
+            let tri = if step_outer {
+                j += 1;
+                [inner[i % n_inner], outer[j - 1], outer[j % n_outer]]
+            } else {
+                i += 1;
+                [inner[i - 1], inner[i % n_inner], outer[j % n_outer]]
+            };
+            self.triangle(tri, facing);
+        }
+    }
+
+    fn into_geometry(self) -> GeometryData {
+        GeometryData {
+            positions: self.positions,
+            normals: self.normals,
+            uvs: self.uvs,
+            indices: self.indices,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::{
+        attributes::normals,
+        bounds::position_bounds,
+        util::{positions, triangle_indices},
+    };
+
+    #[test]
+    fn aligning_to_x_lays_the_cylinder_along_x() {
+        let params = CylinderParams {
+            radius: 0.2,
+            height: 3.0,
+            ..default()
+        };
+        let mut cylinder = generate_cylinder(&params).unwrap();
+        align_to_axis(&mut cylinder, Vec3::X);
+        let (min, max) = position_bounds(positions(&cylinder)).unwrap();
+        let extent = max - min;
+        assert!(
+            extent.x > 2.9 && extent.y < 0.5 && extent.z < 0.5,
+            "{extent}"
+        );
+        // normals turned with it, so still pointing away from the axis or out of the caps
+        let normals = normals(&cylinder).unwrap();
+        for (position, normal) in positions(&cylinder).iter().zip(normals) {
+            assert!(Vec3::from(*position).dot(Vec3::from(*normal)) > -1e-4);
+        }
+    }
+
+    #[test]
+    fn strut_runs_from_one_point_to_the_other() {
+        let (from, to) = (Vec3::new(0.5, -1.0, 2.0), Vec3::new(3.0, 1.0, -1.0));
+        let strut = generate_strut(from, to, 0.1, 12);
+        let points: Vec<Vec3> = positions(&strut).iter().map(|p| Vec3::from(*p)).collect();
+        // the centers of its caps
+        assert!(points.iter().any(|p| p.distance(from) < 1e-4));
+        assert!(points.iter().any(|p| p.distance(to) < 1e-4));
+        let along = (to - from).normalize();
+        for point in points {
+            let t = (point - from).dot(along);
+            assert!(t > -1e-4 && t < from.distance(to) + 1e-4);
+            assert!((point - from - along * t).length() < 0.1 + 1e-4);
+        }
+
+        assert_eq!(generate_strut(from, from, 0.1, 12).count_vertices(), 0);
+    }
+
+    #[test]
+    fn ring_caps_have_no_slivers_where_a_fan_does() {
+        let smallest_angle = |cap: CapStyle| {
+            let params = DiscParams {
+                segments: 96,
+                cap,
+                ..default()
+            };
+            let disc = generate_disc(&params).unwrap();
+            let positions = positions(&disc);
+            triangle_indices(&disc)
+                .iter()
+                .flat_map(|tri| {
+                    let corners = tri.map(|v| Vec3::from(positions[v]));
+                    (0..3).map(move |i| {
+                        let [a, b, c] = [0, 1, 2].map(|k| corners[(i + k) % 3]);
+                        (b - a).angle_between(c - a)
+                    })
+                })
+                .fold(PI, f32::min)
+        };
+
+        let fan = smallest_angle(CapStyle::Fan);
+        assert!(fan < 5_f32.to_radians(), "{}", fan.to_degrees());
+        let rings = smallest_angle(CapStyle::Rings(16));
+        assert!(rings > 15_f32.to_radians(), "{}", rings.to_degrees());
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::prelude::*;
+
+use crate::{
+    attributes::{normals, normals_mut},
+    terrain::{generate_heightmap, GridLayout, HeightmapParams},
+    util::{positions, positions_mut},
+};
+
+/// Inclusive range of grid vertices, by row (along Z) and column (along X).
+///
+/// Vertex `(row, col)` is at index `row * (cols + 1) + col`, so each row of the region
+/// is one contiguous run of vertices.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GridRegion {
+    pub min_row: u32,
+    pub min_col: u32,
+    pub max_row: u32,
+    pub max_col: u32,
+}
+
+impl GridRegion {
+    fn union(self, other: GridRegion) -> GridRegion {
+        GridRegion {
+            min_row: self.min_row.min(other.min_row),
+            min_col: self.min_col.min(other.min_col),
+            max_row: self.max_row.max(other.max_row),
+            max_col: self.max_col.max(other.max_col),
+        }
+    }
+}
+
+/// Sculpts a [`generate_heightmap`] grid with circular brushes.
+///
+/// Each stroke only visits the vertices under the brush and redoes the normals around
+/// them, and the vertices it changed are collected into a dirty region so the copy of
+/// the mesh in `Assets<Mesh>` can be patched with [`TerrainEditor::write_dirty`]
+/// instead of replaced. Brushes ease out from full strength at `center` (a world XZ
+/// position) to nothing at `radius`. Cells keep the diagonals they were split along
+/// when the grid was made, however the ground is reshaped.
+pub struct TerrainEditor {
+    mesh: Mesh,
+    params: HeightmapParams,
+    dirty: Option<GridRegion>,
+}
+
+impl TerrainEditor {
+    pub fn new(params: &HeightmapParams, height_fn: impl Fn(f32, f32) -> f32) -> Self {
+        TerrainEditor {
+            mesh: generate_heightmap(params, height_fn),
+            params: *params,
+            dirty: None,
+        }
+    }
+
+    pub fn mesh(&self) -> &Mesh {
+        &self.mesh
+    }
+
+    pub fn into_mesh(self) -> Mesh {
+        self.mesh
+    }
+
+    /// Everything changed since the last [`TerrainEditor::write_dirty`], normals
+    /// included.
+    pub fn dirty_region(&self) -> Option<GridRegion> {
+        self.dirty
+    }
+
+    pub fn raise(&mut self, center: Vec2, radius: f32, amount: f32) {
+        self.stroke(center, radius, |height, weight, _| height + amount * weight);
+    }
+
+    pub fn lower(&mut self, center: Vec2, radius: f32, amount: f32) {
+        self.raise(center, radius, -amount);
+    }
+
+    /// Pulls each vertex towards the average of its neighbors, by `strength` (0..1) at
+    /// the center of the brush.
+    pub fn smooth(&mut self, center: Vec2, radius: f32, strength: f32) {
+        self.stroke(center, radius, |height, weight, average| {
+            height + (average - height) * (strength * weight).clamp(0.0, 1.0)
+        });
+    }
+
+    /// Levels the terrain to `height`, fully at the center of the brush and blending
+    /// into the surrounding terrain towards its edge.
+    pub fn flatten(&mut self, center: Vec2, radius: f32, height: f32) {
+        self.stroke(center, radius, |old, weight, _| {
+            old + (height - old) * weight
+        });
+    }
+
+    /// Copies the positions and normals in the dirty region into `target`, which should
+    /// be a mesh this editor was created alongside, and clears the region, returning
+    /// whether it did. A `target` with a different number of vertices can't be that
+    /// mesh, so it's left alone and the region stays dirty.
+    pub fn write_dirty(&mut self, target: &mut Mesh) -> bool {
+        if positions(target).len() != positions(&self.mesh).len() {
+            return false;
+        }
+        let Some(region) = self.dirty.take() else {
+            return true;
+        };
+
+        let grid = self.grid();
+        let positions = positions(&self.mesh);
+        let normals = normals(&self.mesh);
+        for row in region.min_row..=region.max_row {
+            let run = grid.vertex(row, region.min_col)..=grid.vertex(row, region.max_col);
+            positions_mut(target)[run.clone()].copy_from_slice(&positions[run.clone()]);
+            if let (Some(normals), Some(target)) = (normals, normals_mut(target)) {
+                if target.len() == normals.len() {
+                    target[run.clone()].copy_from_slice(&normals[run]);
+                }
+            }
+        }
+        true
+    }
+
+    fn grid(&self) -> GridLayout {
+        let HeightmapParams {
+            rows,
+            cols,
+            cell_size,
+            anchor,
+            ..
+        } = self.params;
+        GridLayout {
+            rows,
+            cols,
+            origin: anchor.min_corner(Vec2::new(cols as f32, rows as f32) * cell_size),
+            cell_size,
+        }
+    }
+
+    // `brush(height, weight, neighbor_average)` gives the new height of each vertex
+    // under the brush
+    fn stroke(&mut self, center: Vec2, radius: f32, brush: impl Fn(f32, f32, f32) -> f32) {
+        let grid = self.grid();
+        let Some(region) = grid.region(center, radius) else {
+            return;
+        };
+        let GridLayout {
+            rows,
+            cols,
+            origin,
+            cell_size,
+        } = grid;
+
+        // every new height is worked out before any are written, so neighbor averages
+        // don't depend on the order vertices are visited in
+        let before = positions(&self.mesh);
+        let height = |row: u32, col: u32| before[grid.vertex(row, col)][1];
+
+        let mut updates = vec![];
+        for row in region.min_row..=region.max_row {
+            for col in region.min_col..=region.max_col {
+                let offset = origin + Vec2::new(col as f32, row as f32) * cell_size - center;
+                let t2 = offset.length_squared() / (radius * radius);
+                if t2 >= 1.0 {
+                    continue;
+                }
+                let weight = (1.0 - t2) * (1.0 - t2);
+
+                let neighbors = [
+                    (row > 0).then(|| height(row - 1, col)),
+                    (row < rows).then(|| height(row + 1, col)),
+                    (col > 0).then(|| height(row, col - 1)),
+                    (col < cols).then(|| height(row, col + 1)),
+                ];
+                let (sum, count) = neighbors
+                    .iter()
+                    .flatten()
+                    .fold((0.0, 0.0), |(sum, count), h| (sum + h, count + 1.0));
+                let average = if count > 0.0 {
+                    sum / count
+                } else {
+                    height(row, col)
+                };
+
+                let new = brush(height(row, col), weight, average);
+                updates.push((grid.vertex(row, col), new));
+            }
+        }
+
+        let positions = positions_mut(&mut self.mesh);
+        for (vertex, height) in updates {
+            positions[vertex][1] = height;
+        }
+
+        // moving a vertex changes the normals of the ring of vertices around it
+        let region = grid.ring_around(region);
+        grid.refresh_normals(&mut self.mesh, region);
+        self.dirty = Some(match self.dirty {
+            Some(dirty) => dirty.union(region),
+            None => region,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_dirty_patches_the_matching_mesh() {
+        let params = HeightmapParams::default();
+        let mut editor = TerrainEditor::new(&params, |_, _| 0.0);
+        let mut target = editor.mesh().clone();
+        editor.raise(Vec2::ZERO, 2.0, 1.0);
+
+        assert!(editor.write_dirty(&mut target));
+        assert_eq!(positions(&target), positions(editor.mesh()));
+        assert_eq!(normals(&target), normals(editor.mesh()));
+        assert_eq!(editor.dirty_region(), None);
+    }
+
+    #[test]
+    fn write_dirty_leaves_another_mesh_alone() {
+        let mut editor = TerrainEditor::new(&HeightmapParams::default(), |_, _| 0.0);
+        let small = HeightmapParams {
+            rows: 2,
+            cols: 2,
+            ..default()
+        };
+        let mut target = generate_heightmap(&small, |_, _| 0.0);
+        let before = positions(&target).to_vec();
+        editor.raise(Vec2::ZERO, 2.0, 1.0);
+
+        assert!(!editor.write_dirty(&mut target));
+        assert_eq!(positions(&target), before);
+        assert!(editor.dirty_region().is_some());
+    }
+}
@@ -0,0 +1,96 @@
+use std::fmt;
+
+use crate::{
+    attributes::AttributeError, builder::InvalidIndex, halfedge::HalfEdgeError, morph::MorphError,
+};
+
+/// What went wrong generating or working on a mesh, for callers that want one error type
+/// across the crate.
+///
+/// Generators return it when they're given parameters no shape can be built from, like
+/// a sphere with no stacks or a cylinder with a negative radius, rather than quietly
+/// clamping them. Operations with errors of their own, like
+/// [`morph`](crate::morph::morph), convert into it with `?`. Functions that return a
+/// plain `Mesh` or value never fail: they take any input and say in their docs what they
+/// do with degenerate cases, usually by leaving the mesh alone or returning an empty
+/// one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GeometryError {
+    /// a parameter outside the range the generator can build anything from
+    InvalidParameter { name: &'static str, reason: String },
+    /// nothing to work with, like a path without enough points to have a length
+    EmptyInput,
+    /// meshes or attributes that had to match and don't, like two meshes with different
+    /// vertex counts, or an attribute that's missing or in the wrong format
+    AttributeMismatch,
+    /// a mesh that isn't a closed or open manifold surface, where the operation needs one
+    NonManifold,
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GeometryError::InvalidParameter { name, reason } => {
+                write!(f, "invalid `{name}`: {reason}")
+            }
+            GeometryError::EmptyInput => write!(f, "there's nothing to build from"),
+            GeometryError::AttributeMismatch => {
+                write!(f, "the meshes' vertices or attributes don't match")
+            }
+            GeometryError::NonManifold => write!(f, "the mesh isn't a manifold surface"),
+        }
+    }
+}
+
+impl std::error::Error for GeometryError {}
+
+impl From<AttributeError> for GeometryError {
+    fn from(_: AttributeError) -> Self {
+        GeometryError::AttributeMismatch
+    }
+}
+
+impl From<MorphError> for GeometryError {
+    fn from(_: MorphError) -> Self {
+        GeometryError::AttributeMismatch
+    }
+}
+
+impl From<HalfEdgeError> for GeometryError {
+    fn from(_: HalfEdgeError) -> Self {
+        GeometryError::NonManifold
+    }
+}
+
+impl From<InvalidIndex> for GeometryError {
+    fn from(err: InvalidIndex) -> Self {
+        GeometryError::InvalidParameter {
+            name: "index",
+            reason: err.to_string(),
+        }
+    }
+}
+
+// `value` as a size, which has to be a finite number above zero
+pub(crate) fn positive(name: &'static str, value: f32) -> Result<(), GeometryError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(())
+    } else {
+        Err(GeometryError::InvalidParameter {
+            name,
+            reason: format!("must be positive, got {value}"),
+        })
+    }
+}
+
+// `value` as a count of segments or cells, of which there have to be at least `min`
+pub(crate) fn at_least(name: &'static str, value: u32, min: u32) -> Result<(), GeometryError> {
+    if value >= min {
+        Ok(())
+    } else {
+        Err(GeometryError::InvalidParameter {
+            name,
+            reason: format!("must be at least {min}, got {value}"),
+        })
+    }
+}
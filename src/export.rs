@@ -0,0 +1,292 @@
+use std::fmt::{self, Write};
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, MeshVertexAttribute},
+        render_resource::VertexFormat,
+    },
+};
+
+use crate::{
+    attributes::{self, normals},
+    geometry::QuadMesh,
+    util::positions,
+};
+
+/// One of the standard vertex attributes, in the format Bevy's own meshes use.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// `Float32x3`
+    Position,
+    /// `Float32x3`
+    Normal,
+    /// `Float32x2`
+    Uv,
+    /// `Float32x4`, in linear space
+    Color,
+    /// `Float32x4`
+    Tangent,
+}
+
+impl AttributeKind {
+    pub fn attribute(self) -> MeshVertexAttribute {
+        match self {
+            AttributeKind::Position => Mesh::ATTRIBUTE_POSITION,
+            AttributeKind::Normal => Mesh::ATTRIBUTE_NORMAL,
+            AttributeKind::Uv => Mesh::ATTRIBUTE_UV_0,
+            AttributeKind::Color => Mesh::ATTRIBUTE_COLOR,
+            AttributeKind::Tangent => Mesh::ATTRIBUTE_TANGENT,
+        }
+    }
+
+    /// Bytes one vertex's value takes up.
+    pub fn size(self) -> usize {
+        self.attribute().format.size() as usize
+    }
+}
+
+/// Bytes the mesh's vertex attributes and index buffer take up, each at its own element
+/// size (12 for a `Float32x3` position, 8 for a `Float32x2` UV, 2 or 4 for an index),
+/// for budgeting generated content or seeing what indexing saves on a given mesh.
+///
+/// This is what the GPU buffers hold once the mesh is uploaded. Bevy keeps its own
+/// copy of the data on the CPU as well, until the mesh asset is dropped.
+pub fn mesh_memory_bytes(mesh: &Mesh) -> usize {
+    let attributes: usize = mesh
+        .attributes()
+        .map(|(_, values)| values.get_bytes().len())
+        .sum();
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() * 2,
+        Some(Indices::U32(indices)) => indices.len() * 4,
+        None => 0,
+    };
+    attributes + indices
+}
+
+/// Bytes per vertex in a buffer from [`to_interleaved_buffer`] with this layout.
+pub fn interleaved_stride(layout: &[AttributeKind]) -> usize {
+    layout.iter().map(|kind| kind.size()).sum()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InterleaveError {
+    /// the mesh doesn't have the attribute, or has it in another format
+    MissingAttribute(AttributeKind),
+    /// the attribute has a value for a different number of vertices than there are
+    /// positions
+    WrongLength {
+        attribute: AttributeKind,
+        len: usize,
+        vertex_count: usize,
+    },
+}
+
+impl fmt::Display for InterleaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterleaveError::MissingAttribute(attribute) => {
+                write!(
+                    f,
+                    "the mesh has no {attribute:?} attribute in the standard format"
+                )
+            }
+            InterleaveError::WrongLength {
+                attribute,
+                len,
+                vertex_count,
+            } => write!(
+                f,
+                "the mesh has {len} {attribute:?} values for {vertex_count} vertices"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InterleaveError {}
+
+/// Packs the attributes in `layout` into one vertex buffer, each vertex's values one
+/// after the other in that order with no padding, along with the index buffer, for
+/// handing a mesh to a custom wgpu pipeline or a renderer other than Bevy's.
+///
+/// Each vertex takes [`interleaved_stride`] bytes, and an attribute starts at the sum
+/// of the sizes of the ones before it, so a wgpu `VertexBufferLayout` can be built from
+/// the same list. Values are in native byte order, which is little-endian everywhere
+/// wgpu runs. Meshes without an index buffer get one counting up through the vertices.
+pub fn to_interleaved_buffer(
+    mesh: &Mesh,
+    layout: &[AttributeKind],
+) -> Result<(Vec<u8>, Vec<u32>), InterleaveError> {
+    let vertex_count = positions(mesh).len();
+
+    let mut sources = Vec::with_capacity(layout.len());
+    for &kind in layout {
+        let attribute = kind.attribute();
+        let values = mesh
+            .attribute(attribute)
+            .filter(|values| VertexFormat::from(*values) == attribute.format)
+            .ok_or(InterleaveError::MissingAttribute(kind))?;
+        if values.len() != vertex_count {
+            return Err(InterleaveError::WrongLength {
+                attribute: kind,
+                len: values.len(),
+                vertex_count,
+            });
+        }
+        sources.push((values.get_bytes(), kind.size()));
+    }
+
+    let mut bytes = Vec::with_capacity(vertex_count * interleaved_stride(layout));
+    for vertex in 0..vertex_count {
+        for (source, size) in &sources {
+            bytes.extend_from_slice(&source[vertex * size..(vertex + 1) * size]);
+        }
+    }
+
+    Ok((bytes, indices_as_u32(mesh)))
+}
+
+/// The mesh's positions widened to `f64`, for formats and tools that want double
+/// precision, such as CAD exchange formats. `None` when the mesh has no positions in
+/// the standard format.
+pub fn positions_as_f64(mesh: &Mesh) -> Option<Vec<[f64; 3]>> {
+    let positions = attributes::positions(mesh)?;
+    Some(positions.iter().map(|p| p.map(f64::from)).collect())
+}
+
+/// The mesh's index buffer with every index as a `u32`, whether it's stored as `U16` or
+/// `U32`, for exporters that only write one width. Meshes without an index buffer get
+/// one counting up through the vertices, as [`to_interleaved_buffer`] does.
+pub fn indices_as_u32(mesh: &Mesh) -> Vec<u32> {
+    match mesh.indices() {
+        Some(indices) => indices.iter().map(|i| i as u32).collect(),
+        None => (0..positions(mesh).len() as u32).collect(),
+    }
+}
+
+/// The mesh's normals packed into four signed bytes each, `x`, `y` and `z` scaled to
+/// -127..=127 and a 0 after them to keep each one 4-byte aligned, as a `Snorm8x4`
+/// vertex attribute or glTF's quantized normals store them, at a quarter of the size.
+///
+/// Normals are normalized first, so each comes back as a unit vector to within a 127th
+/// along every axis, and zero normals stay zero. `None` when the mesh has no normals in
+/// the standard format.
+pub fn normals_packed_snorm(mesh: &Mesh) -> Option<Vec<[i8; 4]>> {
+    let normals = normals(mesh)?;
+    let pack = |value: f32| (value * 127.0).round() as i8;
+    Some(
+        normals
+            .iter()
+            .map(|normal| {
+                let [x, y, z] = Vec3::from(*normal).normalize_or_zero().to_array();
+                [pack(x), pack(y), pack(z), 0]
+            })
+            .collect(),
+    )
+}
+
+/// The quads as the text of a Wavefront OBJ file, with an `f` line of four corners for
+/// each, so Blender and other modeling tools import them as quads rather than pairs of
+/// triangles.
+///
+/// Texture coordinates and normals are written when the mesh has them, with V flipped
+/// since OBJ puts V = 0 at the bottom of the texture where Bevy puts it at the top.
+/// Values are written in full, so reading the file back gives exactly the same floats.
+pub fn to_obj_quads(mesh: &QuadMesh) -> String {
+    let mut obj = String::new();
+    // writing to a `String` can't fail
+    for [x, y, z] in &mesh.positions {
+        let _ = writeln!(obj, "v {x} {y} {z}");
+    }
+    for [u, v] in &mesh.uvs {
+        let _ = writeln!(obj, "vt {u} {}", 1.0 - v);
+    }
+    for [x, y, z] in &mesh.normals {
+        let _ = writeln!(obj, "vn {x} {y} {z}");
+    }
+
+    let (uvs, normals) = (!mesh.uvs.is_empty(), !mesh.normals.is_empty());
+    for quad in &mesh.quads {
+        obj.push('f');
+        for corner in quad {
+            // OBJ counts from 1
+            let i = corner + 1;
+            let _ = match (uvs, normals) {
+                (true, true) => write!(obj, " {i}/{i}/{i}"),
+                (true, false) => write!(obj, " {i}/{i}"),
+                (false, true) => write!(obj, " {i}//{i}"),
+                (false, false) => write!(obj, " {i}"),
+            };
+        }
+        obj.push('\n');
+    }
+    obj
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::mesh::PrimitiveTopology;
+
+    use super::*;
+    use crate::terrain::{generate_heightmap_quads, HeightmapParams};
+
+    #[test]
+    fn interleaved_buffer_is_one_stride_a_vertex() {
+        let sphere = Mesh::from(shape::Icosphere {
+            radius: 1.0,
+            subdivisions: 2,
+        });
+        let layout = [
+            AttributeKind::Position,
+            AttributeKind::Normal,
+            AttributeKind::Uv,
+        ];
+        assert_eq!(interleaved_stride(&layout), 32);
+        let (bytes, indices) = to_interleaved_buffer(&sphere, &layout).unwrap();
+        assert_eq!(bytes.len(), sphere.count_vertices() * 32);
+        assert_eq!(indices.len(), sphere.indices().unwrap().len());
+        // the second vertex starts with its position
+        let x = f32::from_ne_bytes(bytes[32..36].try_into().unwrap());
+        assert_eq!(x, positions(&sphere)[1][0]);
+
+        assert_eq!(
+            to_interleaved_buffer(&sphere, &[AttributeKind::Color]),
+            Err(InterleaveError::MissingAttribute(AttributeKind::Color))
+        );
+    }
+
+    #[test]
+    fn grid_exports_a_quad_face_per_cell() {
+        let params = HeightmapParams {
+            rows: 3,
+            cols: 4,
+            ..default()
+        };
+        let quads = generate_heightmap_quads(&params, |x, z| x * z * 0.1);
+        let obj = to_obj_quads(&quads);
+        let faces: Vec<&str> = obj.lines().filter(|line| line.starts_with("f ")).collect();
+        assert_eq!(faces.len(), (params.rows * params.cols) as usize);
+        assert!(faces
+            .iter()
+            .all(|face| face.split_whitespace().count() == 5));
+        assert_eq!(
+            obj.lines().filter(|line| line.starts_with("v ")).count(),
+            ((params.rows + 1) * (params.cols + 1)) as usize
+        );
+
+        let triangles = Mesh::from(quads).indices().unwrap().len() / 3;
+        assert_eq!(triangles, faces.len() * 2);
+    }
+
+    #[test]
+    fn indices_as_u32_widens_u16_indices() {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![[0.0_f32; 3]; 4]);
+        mesh.set_indices(Some(Indices::U16(vec![0, 1, 2, 2, 1, 3])));
+        assert_eq!(indices_as_u32(&mesh), vec![0, 1, 2, 2, 1, 3]);
+
+        mesh.set_indices(None);
+        assert_eq!(indices_as_u32(&mesh), vec![0, 1, 2, 3]);
+    }
+}
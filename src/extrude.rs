@@ -0,0 +1,325 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+/// Solid 3D glyph from its pre-tessellated contours, such as a font's outline after its
+/// curves have been flattened into line segments.
+///
+/// Contours can be given in any order and winding. A contour inside an odd number of
+/// others is a hole (the counter of an 'O' or 'A'), anything else is a solid outline, so
+/// glyphs with several pieces like 'i' work too. A closing point that repeats the first
+/// one is ignored. The front face lies in the XY plane facing +Z and the glyph extends
+/// `depth` back along -Z. Caps are UV-mapped across the glyph's bounds and the flat
+/// sides get U along each contour and V from front to back.
+pub fn extrude_glyph(outline: &[Vec<Vec2>], depth: f32) -> Mesh {
+    let regions = triangulate_contours(outline);
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    let (min, max) = regions
+        .iter()
+        .flat_map(|region| region.rings.iter().flatten())
+        .fold(
+            (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+    let size = (max - min).max(Vec2::splat(f32::EPSILON));
+
+    for Region { rings, triangles } in &regions {
+        let points: Vec<Vec2> = rings.iter().flatten().copied().collect();
+
+        for (z, normal) in [(0.0, Vec3::Z), (-depth, Vec3::NEG_Z)] {
+            let first = positions.len() as u32;
+            for point in &points {
+                positions.push([point.x, point.y, z]);
+                normals.push(normal.into());
+                let uv = (*point - min) / size;
+                uvs.push([uv.x, 1.0 - uv.y]);
+            }
+            for [a, b, c] in triangles {
+                let [a, b, c] = [a, b, c].map(|v| first + *v as u32);
+                if normal.z > 0.0 {
+                    indices.extend([a, b, c]);
+                } else {
+                    indices.extend([a, c, b]);
+                }
+            }
+        }
+
+        for ring in rings {
+            let length: f32 = (0..ring.len())
+                .map(|i| ring[i].distance(ring[(i + 1) % ring.len()]))
+                .sum();
+            let mut travelled = 0.0;
+
+            //  p (front) -- q (front)
+            //      |            |
+            //  p (back) --- q (back)
+            for i in 0..ring.len() {
+                let p = ring[i];
+                let q = ring[(i + 1) % ring.len()];
+                let outward = Vec3::new(q.y - p.y, p.x - q.x, 0.0).normalize_or_zero();
+                let u = [travelled / length, (travelled + p.distance(q)) / length];
+                travelled += p.distance(q);
+
+                let first = positions.len() as u32;
+                for (point, z, uv) in [
+                    (p, 0.0, [u[0], 0.0]),
+                    (p, -depth, [u[0], 1.0]),
+                    (q, -depth, [u[1], 1.0]),
+                    (q, 0.0, [u[1], 0.0]),
+                ] {
+                    positions.push([point.x, point.y, z]);
+                    normals.push(outward.into());
+                    uvs.push(uv);
+                }
+                indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+            }
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+// a solid outline and the holes in it, wound so the solid is on the left of every edge,
+// with `triangles` indexing into all the rings' points in order
+pub(crate) struct Region {
+    pub(crate) rings: Vec<Vec<Vec2>>,
+    pub(crate) triangles: Vec<[usize; 3]>,
+}
+
+// sorts contours into outlines and holes by even-odd nesting, as `extrude_glyph`
+// describes, and triangulates each outline with its holes cut out
+pub(crate) fn triangulate_contours(contours: &[Vec<Vec2>]) -> Vec<Region> {
+    let contours: Vec<Vec<Vec2>> = contours
+        .iter()
+        .map(|contour| {
+            let mut contour = contour.clone();
+            if contour.len() > 1 && contour.first() == contour.last() {
+                contour.pop();
+            }
+            contour
+        })
+        .filter(|contour| contour.len() >= 3 && signed_area(contour) != 0.0)
+        .collect();
+
+    // how many other contours each one sits inside
+    let nesting: Vec<usize> = contours
+        .iter()
+        .enumerate()
+        .map(|(i, contour)| {
+            (0..contours.len())
+                .filter(|&j| j != i && contains(&contours[j], contour[0]))
+                .count()
+        })
+        .collect();
+
+    let mut regions = vec![];
+    for outer in (0..contours.len()).filter(|&i| nesting[i] % 2 == 0) {
+        // each hole belongs to the smallest outline around it
+        let holes: Vec<usize> = (0..contours.len())
+            .filter(|&hole| {
+                nesting[hole] % 2 == 1
+                    && (0..contours.len())
+                        .filter(|&j| nesting[j] == nesting[hole] - 1)
+                        .filter(|&j| contains(&contours[j], contours[hole][0]))
+                        .min_by(|&a, &b| {
+                            signed_area(&contours[a])
+                                .abs()
+                                .total_cmp(&signed_area(&contours[b]).abs())
+                        })
+                        == Some(outer)
+            })
+            .collect();
+
+        // solid outlines counter-clockwise and holes clockwise
+        let mut rings = vec![oriented(&contours[outer], true)];
+        rings.extend(holes.iter().map(|&hole| oriented(&contours[hole], false)));
+
+        let points: Vec<Vec2> = rings.iter().flatten().copied().collect();
+        let mut ring_indices = vec![];
+        let mut start = 0;
+        for ring in &rings {
+            ring_indices.push((start..start + ring.len()).collect::<Vec<usize>>());
+            start += ring.len();
+        }
+
+        let polygon = bridge_holes(&points, ring_indices[0].clone(), &ring_indices[1..]);
+        let triangles = ear_clip(&points, polygon);
+        regions.push(Region { rings, triangles });
+    }
+
+    regions
+}
+
+// twice the area, positive when counter-clockwise
+fn signed_area(contour: &[Vec2]) -> f32 {
+    (0..contour.len())
+        .map(|i| contour[i].perp_dot(contour[(i + 1) % contour.len()]))
+        .sum()
+}
+
+fn oriented(contour: &[Vec2], counter_clockwise: bool) -> Vec<Vec2> {
+    let mut contour = contour.to_vec();
+    if (signed_area(&contour) > 0.0) != counter_clockwise {
+        contour.reverse();
+    }
+    contour
+}
+
+// even-odd test, casting a ray towards +X
+fn contains(contour: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for i in 0..contour.len() {
+        let a = contour[i];
+        let b = contour[(i + 1) % contour.len()];
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+// whether segments ab and cd cross at a point that isn't an end of either
+fn segments_cross(a: Vec2, b: Vec2, c: Vec2, d: Vec2) -> bool {
+    let side = |from: Vec2, to: Vec2, point: Vec2| (to - from).perp_dot(point - from);
+    side(a, b, c) * side(a, b, d) < 0.0 && side(c, d, a) * side(c, d, b) < 0.0
+}
+
+// joins each hole to the outline with a pair of coincident edges, turning the outline
+// and its holes into one polygon that can be ear clipped
+fn bridge_holes(points: &[Vec2], outline: Vec<usize>, holes: &[Vec<usize>]) -> Vec<usize> {
+    let rightmost = |hole: &Vec<usize>| {
+        (0..hole.len())
+            .max_by(|&a, &b| points[hole[a]].x.total_cmp(&points[hole[b]].x))
+            .unwrap_or(0)
+    };
+
+    // rightmost holes first, so a hole can bridge across to one further right that has
+    // already been joined in
+    let mut holes: Vec<&Vec<usize>> = holes.iter().collect();
+    holes.sort_by(|a, b| {
+        points[b[rightmost(b)]]
+            .x
+            .total_cmp(&points[a[rightmost(a)]].x)
+    });
+
+    let mut polygon = outline;
+    for (joined, hole) in holes.iter().enumerate() {
+        let start = rightmost(hole);
+        let from = hole[start];
+
+        let mut edges: Vec<(usize, usize)> = (0..polygon.len())
+            .map(|i| (polygon[i], polygon[(i + 1) % polygon.len()]))
+            .collect();
+        for other in &holes[joined..] {
+            edges.extend((0..other.len()).map(|i| (other[i], other[(i + 1) % other.len()])));
+        }
+
+        let visible = |k: usize| {
+            let to = polygon[k];
+            let (prev, next) = (
+                points[polygon[(k + polygon.len() - 1) % polygon.len()]],
+                points[polygon[(k + 1) % polygon.len()]],
+            );
+            let (corner, direction) = (points[to], points[from] - points[to]);
+            let inside_corner = if (corner - prev).perp_dot(next - corner) > 0.0 {
+                (next - corner).perp_dot(direction) > 0.0 && direction.perp_dot(prev - corner) > 0.0
+            } else {
+                !((prev - corner).perp_dot(direction) >= 0.0
+                    && direction.perp_dot(next - corner) >= 0.0)
+            };
+
+            inside_corner
+                && edges.iter().all(|&(a, b)| {
+                    [a, b].contains(&from)
+                        || [a, b].contains(&to)
+                        || !segments_cross(points[from], points[to], points[a], points[b])
+                })
+        };
+
+        let Some(target) = (0..polygon.len()).filter(|&k| visible(k)).min_by(|&a, &b| {
+            let distance = |k: usize| points[polygon[k]].distance_squared(points[from]);
+            distance(a).total_cmp(&distance(b))
+        }) else {
+            continue;
+        };
+
+        let mut bridged = polygon[..=target].to_vec();
+        bridged.extend((0..=hole.len()).map(|i| hole[(start + i) % hole.len()]));
+        bridged.extend(&polygon[target..]);
+        polygon = bridged;
+    }
+
+    polygon
+}
+
+// triangulates a counter-clockwise polygon by repeatedly cutting off a corner that no
+// other vertex lies inside
+fn ear_clip(points: &[Vec2], mut polygon: Vec<usize>) -> Vec<[usize; 3]> {
+    let turn =
+        |a: usize, b: usize, c: usize| (points[b] - points[a]).perp_dot(points[c] - points[b]);
+    let mut triangles = vec![];
+
+    while polygon.len() > 3 {
+        let len = polygon.len();
+        let corner = |i: usize| {
+            (
+                polygon[(i + len - 1) % len],
+                polygon[i],
+                polygon[(i + 1) % len],
+            )
+        };
+
+        let ear = (0..len).find(|&i| {
+            let (a, b, c) = corner(i);
+            turn(a, b, c) > 0.0
+                && polygon.iter().all(|&p| {
+                    let point = points[p];
+                    [a, b, c].contains(&p)
+                        || [a, b, c].iter().any(|&v| points[v] == point)
+                        || turn(a, b, p) < 0.0
+                        || turn(b, c, p) < 0.0
+                        || turn(c, a, p) < 0.0
+                })
+        });
+
+        // a self-touching or degenerate polygon can run out of clean ears, so cut off
+        // the most convex corner instead of giving up
+        let i = ear.unwrap_or_else(|| {
+            (0..len)
+                .max_by(|&x, &y| {
+                    let (a, b, c) = corner(x);
+                    let (d, e, f) = corner(y);
+                    turn(a, b, c).total_cmp(&turn(d, e, f))
+                })
+                .unwrap_or(0)
+        });
+
+        let (a, b, c) = corner(i);
+        if turn(a, b, c) > 0.0 {
+            triangles.push([a, b, c]);
+        }
+        polygon.remove(i);
+    }
+
+    if let [a, b, c] = polygon[..] {
+        if turn(a, b, c) > 0.0 {
+            triangles.push([a, b, c]);
+        }
+    }
+
+    triangles
+}
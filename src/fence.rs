@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::{cylinder::generate_strut, merge::merge_meshes};
+
+// around each post and rail
+const SEGMENTS: u32 = 8;
+
+/// Fence of round posts `post_height` tall along `path`, with `rail_count` rails between
+/// each pair of neighboring posts, as one mesh.
+///
+/// Posts stand straight up from the path, so they follow the ground wherever its points
+/// rise and fall. They're spread evenly along its length, at most `post_spacing` apart
+/// and with one at each end: a path 10 long with a spacing of 2 gets 6 posts, and one
+/// that's 11 long gets 7. Rails run straight from post to post, evenly spaced up to the
+/// tops of the posts. Posts are a twentieth of `post_height` across and rails half
+/// that. A path with no length gets a single post, and an empty one, or a spacing that
+/// isn't positive, a mesh with no vertices.
+pub fn generate_fence(path: &[Vec3], post_spacing: f32, post_height: f32, rail_count: u32) -> Mesh {
+    let posts = post_positions(path, post_spacing);
+    let post_radius = post_height / 40.0;
+    let up = Vec3::Y * post_height;
+
+    let mut parts: Vec<Mesh> = posts
+        .iter()
+        .map(|post| generate_strut(*post, *post + up, post_radius, SEGMENTS))
+        .collect();
+    for pair in posts.windows(2) {
+        for rail in 1..=rail_count {
+            let lift = up * rail as f32 / rail_count as f32;
+            parts.push(generate_strut(
+                pair[0] + lift,
+                pair[1] + lift,
+                post_radius / 2.0,
+                SEGMENTS,
+            ));
+        }
+    }
+    merge_meshes(&parts)
+}
+
+// the feet of the posts, evenly spread along `path` at most `spacing` apart
+fn post_positions(path: &[Vec3], spacing: f32) -> Vec<Vec3> {
+    let (Some(first), Some(last)) = (path.first(), path.last()) else {
+        return vec![];
+    };
+    if spacing.is_nan() || spacing <= 0.0 {
+        return vec![];
+    }
+    let length: f32 = path.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+    if length <= 0.0 {
+        return vec![*first];
+    }
+
+    let sections = (length / spacing).ceil().max(1.0) as usize;
+    let step = length / sections as f32;
+    let mut posts = vec![*first];
+    // how far along the path `pair` starts
+    let mut start = 0.0;
+    for pair in path.windows(2) {
+        let span = pair[0].distance(pair[1]);
+        let end = start + span;
+        while posts.len() < sections && (posts.len() as f32 * step) <= end {
+            let t = (posts.len() as f32 * step - start) / span;
+            posts.push(pair[0].lerp(pair[1], t));
+        }
+        start = end;
+    }
+    posts.push(*last);
+    posts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn posts_are_spread_by_length_over_spacing() {
+        let straight = |length: f32| [Vec3::ZERO, Vec3::X * length];
+        assert_eq!(post_positions(&straight(10.0), 2.0).len(), 6);
+        assert_eq!(post_positions(&straight(11.0), 2.0).len(), 7);
+
+        // a bend, and a climb the posts should follow
+        let path = [
+            Vec3::ZERO,
+            Vec3::new(4.0, 1.0, 0.0),
+            Vec3::new(4.0, 0.0, 6.0),
+        ];
+        let length = path[0].distance(path[1]) + path[1].distance(path[2]);
+        let posts = post_positions(&path, 2.0);
+        assert_eq!(posts.len(), (length / 2.0).ceil() as usize + 1);
+        assert_eq!((posts[0], posts[posts.len() - 1]), (path[0], path[2]));
+        assert!(posts.iter().any(|post| post.y > 0.5));
+
+        let post = generate_strut(Vec3::ZERO, Vec3::Y, 0.025, SEGMENTS).count_vertices();
+        let fence = generate_fence(&path, 2.0, 1.0, 2);
+        assert_eq!(
+            fence.count_vertices(),
+            posts.len() * post + (posts.len() - 1) * 2 * post
+        );
+    }
+}
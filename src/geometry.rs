@@ -0,0 +1,180 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::{
+    attributes::{normals, uvs},
+    util::{positions, triangle_indices},
+};
+
+/// A triangle mesh as plain arrays, for handing generated geometry to code that doesn't
+/// use Bevy's `Mesh`, such as a physics crate's trimesh collider or a file writer.
+///
+/// Every three `indices` make a triangle, counter-clockwise around its front face.
+/// `normals` and `uvs` have one entry per position, or none at all when the mesh doesn't
+/// have them.
+///
+/// Generators that put their arrays together directly have a `_raw` variant handing
+/// them over as they are, such as
+/// [`generate_uv_sphere_raw`](crate::sphere::generate_uv_sphere_raw),
+/// [`generate_cylinder_raw`](crate::cylinder::generate_cylinder_raw),
+/// [`generate_heightmap_raw`](crate::terrain::generate_heightmap_raw) and
+/// [`CustomMeshBuilder::build_raw`](crate::builder::CustomMeshBuilder::build_raw), and
+/// their `Mesh` versions just call [`GeometryData::into_mesh`] on the result, so the two
+/// always agree. Any other generated mesh converts with [`GeometryData::from_mesh`], as
+/// [`ProceduralMesh::generate_raw`](crate::live::ProceduralMesh::generate_raw) does for
+/// the generators without a `_raw` variant.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GeometryData {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub indices: Vec<u32>,
+}
+
+impl GeometryData {
+    /// The triangles of `mesh` and its positions, normals and UVs, leaving any other
+    /// attribute behind. A mesh without an index buffer gets one listing its vertices in
+    /// order, and one that isn't a triangle list has no triangles.
+    pub fn from_mesh(mesh: &Mesh) -> Self {
+        GeometryData {
+            positions: positions(mesh).to_vec(),
+            normals: normals(mesh).map(<[_]>::to_vec).unwrap_or_default(),
+            uvs: uvs(mesh).map(<[_]>::to_vec).unwrap_or_default(),
+            indices: triangle_indices(mesh)
+                .iter()
+                .flatten()
+                .map(|v| *v as u32)
+                .collect(),
+        }
+    }
+
+    /// An indexed triangle list with these attributes, moving the arrays into it
+    /// without copying them.
+    pub fn into_mesh(self) -> Mesh {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        if !self.normals.is_empty() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        }
+        if !self.uvs.is_empty() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, self.uvs);
+        }
+        mesh.set_indices(Some(Indices::U32(self.indices)));
+        mesh
+    }
+}
+
+impl From<&Mesh> for GeometryData {
+    fn from(mesh: &Mesh) -> Self {
+        GeometryData::from_mesh(mesh)
+    }
+}
+
+impl From<GeometryData> for Mesh {
+    fn from(data: GeometryData) -> Self {
+        data.into_mesh()
+    }
+}
+
+/// A mesh of four-sided faces as plain arrays, for tools that would rather have quads
+/// than triangles: editing the mesh further in Blender, subdivision surfaces, or
+/// [`to_obj_quads`](crate::export::to_obj_quads). Generators made of grids of cells
+/// have a `_quads` variant handing their cells over unsplit, such as
+/// [`generate_heightmap_quads`](crate::terrain::generate_heightmap_quads) and
+/// [`generate_subdivided_cube_quads`](crate::sphere::generate_subdivided_cube_quads).
+///
+/// Each of the `quads` lists its corners counter-clockwise around its front face. The
+/// attributes are as in [`GeometryData`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QuadMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+    pub quads: Vec<[u32; 4]>,
+}
+
+impl QuadMesh {
+    /// The same faces as triangles, each quad split from its second corner to its
+    /// fourth, the way the `_quads` generators' own meshes are split.
+    pub fn into_geometry(self) -> GeometryData {
+        GeometryData {
+            positions: self.positions,
+            normals: self.normals,
+            uvs: self.uvs,
+            indices: self
+                .quads
+                .iter()
+                .flat_map(|[a, b, c, d]| [*a, *b, *d, *d, *b, *c])
+                .collect(),
+        }
+    }
+
+    /// An indexed triangle list split as [`QuadMesh::into_geometry`] does.
+    pub fn into_mesh(self) -> Mesh {
+        self.into_geometry().into_mesh()
+    }
+}
+
+impl From<QuadMesh> for GeometryData {
+    fn from(quads: QuadMesh) -> Self {
+        quads.into_geometry()
+    }
+}
+
+impl From<QuadMesh> for Mesh {
+    fn from(quads: QuadMesh) -> Self {
+        quads.into_mesh()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cylinder::{generate_cone, generate_cone_raw, ConeParams},
+        normals::NormalMode,
+        sphere::{generate_uv_sphere, generate_uv_sphere_raw, UvSphereParams},
+    };
+
+    #[test]
+    fn raw_variants_convert_to_the_same_mesh_as_the_generators() {
+        let sphere = UvSphereParams::default();
+        let raw = generate_uv_sphere_raw(&sphere).unwrap();
+        let mesh = raw.clone().into_mesh();
+        assert_eq!(GeometryData::from_mesh(&mesh), raw);
+        assert_eq!(
+            GeometryData::from_mesh(&generate_uv_sphere(&sphere).unwrap()),
+            raw
+        );
+
+        let cone = ConeParams::default();
+        assert_eq!(
+            GeometryData::from_mesh(&generate_cone(&cone).unwrap()),
+            generate_cone_raw(&cone).unwrap()
+        );
+    }
+
+    // the raw normal modes have to match what `NormalMode::apply` does to a `Mesh`
+    #[test]
+    fn raw_normal_modes_match_applying_them_to_a_mesh() {
+        for normals in [NormalMode::Flat, NormalMode::Smooth, NormalMode::None] {
+            let mut mesh = generate_uv_sphere(&UvSphereParams {
+                normals: NormalMode::Analytic,
+                ..default()
+            })
+            .unwrap();
+            normals.apply(&mut mesh);
+
+            let params = UvSphereParams {
+                normals,
+                ..default()
+            };
+            assert_eq!(
+                GeometryData::from_mesh(&mesh),
+                generate_uv_sphere_raw(&params).unwrap()
+            );
+        }
+    }
+}
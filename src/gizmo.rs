@@ -0,0 +1,92 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    cylinder::{generate_cone, generate_cylinder, ConeParams, CylinderParams},
+    error::GeometryError,
+    merge::merge_meshes,
+    util::{positions, transform_mesh},
+};
+
+/// Arrow `length` long pointing up +Y from the origin: a round shaft `radius` thick
+/// with a cone three times as wide for a head.
+///
+/// The head is six times `radius` long, or the whole arrow if it's shorter than that.
+/// The shaft and head are [`generate_cylinder`] and [`generate_cone`] with their
+/// default segments and normals, put together with [`merge_meshes`], and it fails the
+/// way they do, unless `length` and `radius` are positive.
+pub fn generate_arrow(length: f32, radius: f32) -> Result<Mesh, GeometryError> {
+    let head = (radius * 6.0).min(length);
+    let shaft = length - head;
+
+    let mut parts = vec![];
+    if shaft > 0.0 {
+        let mut cylinder = generate_cylinder(&CylinderParams {
+            radius,
+            height: shaft,
+            ..default()
+        })?;
+        transform_mesh(&mut cylinder, Transform::from_xyz(0.0, shaft / 2.0, 0.0));
+        parts.push(cylinder);
+    }
+    let mut cone = generate_cone(&ConeParams {
+        radius: radius * 3.0,
+        height: head,
+        ..default()
+    })?;
+    transform_mesh(&mut cone, Transform::from_xyz(0.0, shaft + head / 2.0, 0.0));
+    parts.push(cone);
+    Ok(merge_meshes(&parts))
+}
+
+/// Three arrows `size` long from the origin along +X, +Y and +Z, colored red, green and
+/// blue with vertex colors, as one mesh to drop into a scene for seeing which way is
+/// which.
+///
+/// Each is a [`generate_arrow`] with a shaft a fiftieth of `size` thick, so it fails
+/// unless `size` is positive.
+pub fn generate_axis_gizmo(size: f32) -> Result<Mesh, GeometryError> {
+    let arrow = generate_arrow(size, size / 50.0)?;
+    let count = positions(&arrow).len();
+
+    let axes = [
+        (Quat::from_rotation_z(-FRAC_PI_2), Color::RED),
+        (Quat::IDENTITY, Color::GREEN),
+        (Quat::from_rotation_x(FRAC_PI_2), Color::BLUE),
+    ];
+    let arrows: Vec<Mesh> = axes
+        .into_iter()
+        .map(|(rotation, color)| {
+            let mut arrow = arrow.clone();
+            transform_mesh(&mut arrow, Transform::from_rotation(rotation));
+            arrow.insert_attribute(
+                Mesh::ATTRIBUTE_COLOR,
+                vec![color.as_linear_rgba_f32(); count],
+            );
+            arrow
+        })
+        .collect();
+    Ok(merge_meshes(&arrows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attributes::colors;
+
+    #[test]
+    fn axis_gizmo_reaches_along_every_axis_in_its_color() {
+        let gizmo = generate_axis_gizmo(2.0).unwrap();
+        let colors = colors(&gizmo).unwrap();
+        let axes = [(0, Color::RED), (1, Color::GREEN), (2, Color::BLUE)];
+        for (axis, color) in axes {
+            assert!(positions(&gizmo).iter().any(|p| p[axis] > 1.99));
+            for (position, vertex_color) in positions(&gizmo).iter().zip(colors) {
+                if position[axis] > 1.0 {
+                    assert_eq!(*vertex_color, color.as_linear_rgba_f32());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::util::Rng;
+
+// sections along each blade, the last of which narrows to the tip
+const SECTIONS: u32 = 4;
+
+/// Tuft of `blade_count` curved grass blades up to `height` tall, all growing from the
+/// origin and fanning out in every direction, to be instanced across a field, such as
+/// at [`sample_surface_points`](crate::sampling::sample_surface_points) with
+/// [`scatter_transforms`](crate::instancing::scatter_transforms).
+///
+/// Each blade is a strip that narrows from a twentieth of `height` across at its root
+/// to a point, bending over as it rises. Their directions go evenly around the Y axis
+/// with some jitter, and how tall they are and how far they lean varies from blade to
+/// blade; the same `seed` always gives the same tuft. Vertex colors run from a dark
+/// green at the root to a light one at the tip, for multiplying with a material's
+/// color. U runs across each blade and V from its tip down to its root. The blades are
+/// one-sided, facing away from the way they lean so the side that turns to the sky as
+/// they bend over is the front; draw them with `cull_mode: None` to see the back too.
+pub fn generate_grass_clump(blade_count: u32, seed: u32, height: f32) -> Mesh {
+    let root = Color::rgb(0.1, 0.25, 0.04).as_linear_rgba_f32();
+    let tip = Color::rgb(0.45, 0.7, 0.2).as_linear_rgba_f32();
+    let mut rng = Rng::new(seed);
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut colors: Vec<[f32; 4]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    for blade in 0..blade_count {
+        let yaw = TAU * (blade as f32 + rng.range(-0.3, 0.3)) / blade_count as f32;
+        let lean_towards = Vec3::new(yaw.sin(), 0.0, yaw.cos());
+        let side = lean_towards.cross(Vec3::Y);
+        let tall = height * rng.range(0.6, 1.0);
+        let lean = tall * rng.range(0.2, 0.6);
+        let width = height / 20.0;
+
+        let first = positions.len() as u32;
+        for section in 0..=SECTIONS {
+            let t = section as f32 / SECTIONS as f32;
+            // a parabola, straight up at the root and leaning over more towards the tip
+            let center = lean_towards * lean * t * t + Vec3::Y * tall * t;
+            let along = lean_towards * 2.0 * lean * t + Vec3::Y * tall;
+            let normal = side.cross(along).normalize_or_zero();
+            let color = Vec4::from(root).lerp(Vec4::from(tip), t).into();
+
+            let corners: &[(f32, f32)] = if section == SECTIONS {
+                &[(0.0, 0.5)]
+            } else {
+                &[(-0.5, 0.0), (0.5, 1.0)]
+            };
+            for (offset, u) in corners {
+                positions.push((center + side * width * (1.0 - t) * *offset).into());
+                normals.push(normal.into());
+                uvs.push([*u, 1.0 - t]);
+                colors.push(color);
+            }
+        }
+
+        //  left     right
+        //  2i+2 --- 2i+3      tip
+        //    |   \    |      /   \
+        //    2i --- 2i+1   2i --- 2i+1
+        for section in 0..SECTIONS {
+            let (left, right) = (first + section * 2, first + section * 2 + 1);
+            if section + 1 == SECTIONS {
+                indices.extend([left, right, left + 2]);
+            } else {
+                indices.extend([left, right, left + 2, left + 2, right, right + 2]);
+            }
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{positions, triangle_indices};
+
+    #[test]
+    fn every_blade_grows_from_the_origin() {
+        let blades = 12;
+        let mesh = generate_grass_clump(blades, 3, 0.5);
+        let per_blade = (SECTIONS * 2 + 1) as usize;
+        let positions = positions(&mesh);
+        assert_eq!(positions.len(), blades as usize * per_blade);
+        assert_eq!(
+            triangle_indices(&mesh).len(),
+            blades as usize * (SECTIONS * 2 - 1) as usize
+        );
+
+        for blade in positions.chunks(per_blade) {
+            let root = (Vec3::from(blade[0]) + Vec3::from(blade[1])) / 2.0;
+            assert!(root.length() < 1e-6);
+            assert!(blade[per_blade - 1][1] > 0.0);
+        }
+    }
+}
@@ -0,0 +1,360 @@
+use std::fmt;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    utils::HashMap,
+};
+
+use crate::{
+    attributes::{normals, uvs},
+    util::{positions, triangle_indices, welded_position_ids},
+};
+
+/// Connectivity of a triangle mesh, for walking from faces to their neighbors and
+/// around vertices without rebuilding an adjacency map for every operation.
+///
+/// Vertices are the mesh's distinct positions, so faces that only share corners by
+/// value (like a cube with a separate set of vertices per side) are still connected.
+/// Each face keeps the source vertices its corners came from, so
+/// [`HalfEdgeMesh::to_mesh`] hands back the same vertices and faces with their normals
+/// and UVs, and positions moved with [`HalfEdgeMesh::set_position`].
+///
+/// Every edge has to border one or two faces that agree on winding, and the faces
+/// around each vertex have to form a single fan; anything else is rejected by
+/// [`HalfEdgeMesh::from_mesh`].
+#[derive(Clone, Debug)]
+pub struct HalfEdgeMesh {
+    positions: Vec<Vec3>,
+    // an outgoing half-edge per vertex, on the boundary if the vertex is
+    outgoing: Vec<usize>,
+    // the half-edges of face `f` are `3 * f`, `3 * f + 1` and `3 * f + 2`, in winding
+    // order, each starting at the corner with the same index
+    origins: Vec<usize>,
+    twins: Vec<Option<usize>>,
+    // the source vertex at the start of each half-edge
+    corners: Vec<u32>,
+    source_normals: Option<Vec<[f32; 3]>>,
+    source_uvs: Option<Vec<[f32; 2]>>,
+    source_vertex: Vec<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HalfEdgeError {
+    /// a face with two corners at the same position
+    DegenerateFace { face: usize },
+    /// an edge bordering more than two faces, or two faces that disagree on winding
+    NonManifoldEdge { from: Vec3, to: Vec3 },
+    /// a point where separate fans of faces meet
+    NonManifoldVertex { position: Vec3 },
+}
+
+impl fmt::Display for HalfEdgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HalfEdgeError::DegenerateFace { face } => {
+                write!(f, "face {face} has two corners at the same position")
+            }
+            HalfEdgeError::NonManifoldEdge { from, to } => write!(
+                f,
+                "the edge from {from} to {to} borders more than two faces, or faces that \
+                 disagree on winding"
+            ),
+            HalfEdgeError::NonManifoldVertex { position } => {
+                write!(f, "separate fans of faces meet at {position}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HalfEdgeError {}
+
+impl HalfEdgeMesh {
+    pub fn from_mesh(mesh: &Mesh) -> Result<Self, HalfEdgeError> {
+        let source = positions(mesh);
+        let welded = welded_position_ids(source);
+        let vertex_count = welded.iter().max().map_or(0, |max| max + 1);
+
+        let mut positions = vec![Vec3::ZERO; vertex_count];
+        let mut source_vertex = vec![0; vertex_count];
+        for (vertex, id) in welded.iter().enumerate().rev() {
+            positions[*id] = source[vertex].into();
+            source_vertex[*id] = vertex;
+        }
+
+        let triangles = triangle_indices(mesh);
+        let mut origins = Vec::with_capacity(triangles.len() * 3);
+        let mut corners = Vec::with_capacity(triangles.len() * 3);
+        for (face, tri) in triangles.iter().enumerate() {
+            let [a, b, c] = tri.map(|v| welded[v]);
+            if a == b || b == c || c == a {
+                return Err(HalfEdgeError::DegenerateFace { face });
+            }
+            origins.extend([a, b, c]);
+            corners.extend(tri.map(|v| v as u32));
+        }
+
+        let destination = |half_edge: usize| origins[next(half_edge)];
+        let mut directed: HashMap<(usize, usize), usize> = HashMap::new();
+        for (half_edge, &origin) in origins.iter().enumerate() {
+            let key = (origin, destination(half_edge));
+            if directed.insert(key, half_edge).is_some() {
+                return Err(HalfEdgeError::NonManifoldEdge {
+                    from: positions[key.0],
+                    to: positions[key.1],
+                });
+            }
+        }
+        let twins: Vec<Option<usize>> = (0..origins.len())
+            .map(|half_edge| {
+                directed
+                    .get(&(destination(half_edge), origins[half_edge]))
+                    .copied()
+            })
+            .collect();
+
+        // vertices no face uses are left without one
+        let mut outgoing = vec![usize::MAX; vertex_count];
+        let mut outgoing_count = vec![0; vertex_count];
+        for half_edge in 0..origins.len() {
+            let vertex = origins[half_edge];
+            outgoing_count[vertex] += 1;
+            if outgoing[vertex] == usize::MAX || twins[half_edge].is_none() {
+                outgoing[vertex] = half_edge;
+            }
+        }
+
+        let mesh = HalfEdgeMesh {
+            positions,
+            outgoing,
+            origins,
+            twins,
+            corners,
+            source_normals: normals(mesh).map(<[_]>::to_vec),
+            source_uvs: uvs(mesh).map(<[_]>::to_vec),
+            source_vertex,
+        };
+
+        // a vertex with more than one boundary between its faces has several fans, and
+        // walking around it from one can't reach all its outgoing half-edges
+        for (vertex, &count) in outgoing_count.iter().enumerate() {
+            if mesh.outgoing_half_edges(vertex).count() != count {
+                return Err(HalfEdgeError::NonManifoldVertex {
+                    position: mesh.positions[vertex],
+                });
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    /// A mesh with the source vertices that any face used, in their original order,
+    /// and the faces in theirs.
+    pub fn to_mesh(&self) -> Mesh {
+        let source_count = self.corners.iter().max().map_or(0, |max| *max as usize + 1);
+        let mut position_of = vec![None; source_count];
+        for (half_edge, corner) in self.corners.iter().enumerate() {
+            position_of[*corner as usize] = Some(self.origins[half_edge]);
+        }
+
+        let mut remap = vec![u32::MAX; source_count];
+        let mut positions: Vec<[f32; 3]> = vec![];
+        let mut normals: Vec<[f32; 3]> = vec![];
+        let mut uvs: Vec<[f32; 2]> = vec![];
+        for (corner, vertex) in position_of.iter().enumerate() {
+            let Some(vertex) = vertex else {
+                continue;
+            };
+            remap[corner] = positions.len() as u32;
+            positions.push(self.positions[*vertex].into());
+            if let Some(source) = &self.source_normals {
+                normals.push(source[corner]);
+            }
+            if let Some(source) = &self.source_uvs {
+                uvs.push(source[corner]);
+            }
+        }
+
+        let indices: Vec<u32> = self
+            .corners
+            .iter()
+            .map(|corner| remap[*corner as usize])
+            .collect();
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        if self.source_normals.is_some() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        }
+        if self.source_uvs.is_some() {
+            mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        }
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.origins.len() / 3
+    }
+
+    pub fn position(&self, vertex: usize) -> Vec3 {
+        self.positions[vertex]
+    }
+
+    /// Moves a vertex, along with every source vertex at its position.
+    pub fn set_position(&mut self, vertex: usize, position: Vec3) {
+        self.positions[vertex] = position;
+    }
+
+    /// The first source vertex at this vertex's position.
+    pub fn source_vertex(&self, vertex: usize) -> usize {
+        self.source_vertex[vertex]
+    }
+
+    /// Each face's vertices in winding order.
+    pub fn faces(&self) -> impl Iterator<Item = [usize; 3]> + '_ {
+        self.origins
+            .chunks_exact(3)
+            .map(|face| [face[0], face[1], face[2]])
+    }
+
+    /// Every edge once, as the vertices at either end.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.origins.len())
+            .filter(|&half_edge| self.twins[half_edge].map_or(true, |twin| half_edge < twin))
+            .map(|half_edge| (self.origins[half_edge], self.origins[next(half_edge)]))
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges().count()
+    }
+
+    /// The faces on either side of the edge between two vertices, the one it runs
+    /// counter-clockwise around first, or `None` if they aren't joined by an edge.
+    pub fn edge_faces(&self, from: usize, to: usize) -> Option<(Option<usize>, Option<usize>)> {
+        let half_edge = self
+            .outgoing_half_edges(from)
+            .find(|&half_edge| self.origins[next(half_edge)] == to);
+        match half_edge {
+            Some(half_edge) => Some((
+                Some(half_edge / 3),
+                self.twins[half_edge].map(|twin| twin / 3),
+            )),
+            None => self
+                .outgoing_half_edges(to)
+                .find(|&half_edge| self.origins[next(half_edge)] == from)
+                .map(|half_edge| (None, Some(half_edge / 3))),
+        }
+    }
+
+    /// The faces sharing an edge with `face`, across the edges starting at each of its
+    /// corners in turn.
+    pub fn face_neighbors(&self, face: usize) -> [Option<usize>; 3] {
+        [0, 1, 2].map(|k| self.twins[face * 3 + k].map(|twin| twin / 3))
+    }
+
+    /// Whether a vertex is on the edge of an open surface.
+    pub fn is_boundary_vertex(&self, vertex: usize) -> bool {
+        self.outgoing.get(vertex).map_or(false, |&half_edge| {
+            half_edge != usize::MAX && self.twins[half_edge].is_none()
+        })
+    }
+
+    /// The vertices sharing an edge with `vertex`, counter-clockwise around it, starting
+    /// from the boundary if it's on one.
+    pub fn one_ring(&self, vertex: usize) -> Vec<usize> {
+        let mut ring = vec![];
+        let mut last = None;
+        for half_edge in self.outgoing_half_edges(vertex) {
+            ring.push(self.origins[next(half_edge)]);
+            last = Some(half_edge);
+        }
+        // an open fan ends on an edge with no face beyond it, whose far end is one more
+        // neighbor
+        if let Some(previous) = last.map(previous) {
+            if self.twins[previous].is_none() {
+                ring.push(self.origins[previous]);
+            }
+        }
+        ring
+    }
+
+    /// The faces around `vertex`, counter-clockwise.
+    pub fn vertex_faces(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        self.outgoing_half_edges(vertex)
+            .map(|half_edge| half_edge / 3)
+    }
+
+    // the half-edges leaving `vertex`, counter-clockwise around it
+    fn outgoing_half_edges(&self, vertex: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self
+            .outgoing
+            .get(vertex)
+            .copied()
+            .filter(|&half_edge| half_edge != usize::MAX);
+        let mut current = start;
+        std::iter::from_fn(move || {
+            let half_edge = current?;
+            current = self.twins[previous(half_edge)].filter(|&next| Some(next) != start);
+            Some(half_edge)
+        })
+    }
+}
+
+fn next(half_edge: usize) -> usize {
+    half_edge - half_edge % 3 + (half_edge % 3 + 1) % 3
+}
+
+fn previous(half_edge: usize) -> usize {
+    half_edge - half_edge % 3 + (half_edge % 3 + 2) % 3
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_round_trips_with_its_vertices_and_faces() {
+        let cube = Mesh::from(shape::Cube { size: 2.0 });
+        let half_edges = HalfEdgeMesh::from_mesh(&cube).unwrap();
+        assert_eq!(
+            (
+                half_edges.vertex_count(),
+                half_edges.face_count(),
+                half_edges.edge_count()
+            ),
+            (8, 12, 18)
+        );
+        assert!((0..8).all(|vertex| !half_edges.is_boundary_vertex(vertex)));
+
+        let back = half_edges.to_mesh();
+        assert_eq!(back.count_vertices(), cube.count_vertices());
+        assert_eq!(triangle_indices(&back), triangle_indices(&cube));
+        assert_eq!(positions(&back), positions(&cube));
+        assert_eq!(uvs(&back), uvs(&cube));
+    }
+
+    #[test]
+    fn bowtie_vertex_is_rejected() {
+        let mut bowtie = Mesh::new(PrimitiveTopology::TriangleList);
+        bowtie.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 0.0, -1.0],
+                [-1.0, 0.0, 0.0],
+                [-1.0, 0.0, 1.0],
+            ],
+        );
+        bowtie.set_indices(Some(Indices::U32(vec![0, 1, 2, 0, 3, 4])));
+        assert!(matches!(
+            HalfEdgeMesh::from_mesh(&bowtie),
+            Err(HalfEdgeError::NonManifoldVertex { .. })
+        ));
+    }
+}
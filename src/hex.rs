@@ -0,0 +1,194 @@
+use std::f32::consts::PI;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::builder::CustomMeshBuilder;
+
+/// Axial coordinates of a pointy-topped hex: `q` steps along +X, and `r` along the
+/// diagonal towards +Z.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexCoord {
+    pub q: i32,
+    pub r: i32,
+}
+
+impl HexCoord {
+    pub fn new(q: i32, r: i32) -> Self {
+        HexCoord { q, r }
+    }
+
+    /// Center of the hex on the XZ plane, for hexes with corners `radius` from their
+    /// centers.
+    /// The six hexes sharing an edge with this one, counter-clockwise seen from above
+    /// starting with the one towards +X.
+    pub fn neighbors(self) -> [HexCoord; 6] {
+        [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)]
+            .map(|(q, r)| HexCoord::new(self.q + q, self.r + r))
+    }
+
+    pub fn center(self, radius: f32) -> Vec3 {
+        let (q, r) = (self.q as f32, self.r as f32);
+        Vec3::new(
+            radius * 3.0_f32.sqrt() * (q + r / 2.0),
+            0.0,
+            radius * 1.5 * r,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HexGridParams {
+    /// distance from each hex's center to its corners
+    pub radius: f32,
+    /// rings of hexes around the center one, so 0 is a single hex
+    pub rings: u32,
+}
+
+impl Default for HexGridParams {
+    fn default() -> Self {
+        HexGridParams {
+            radius: 1.0,
+            rings: 3,
+        }
+    }
+}
+
+/// Flat board of pointy-topped hex tiles on the XZ plane facing +Y, with the center
+/// tile on the origin, along with the coordinates and center of every tile so gameplay
+/// data can be attached to them.
+///
+/// Each tile is a fan of six triangles with its own vertices, UV-mapped to a hexagon
+/// filling the 0..1 square so a single hex texture repeats on every tile.
+pub fn generate_hex_grid(params: &HexGridParams) -> (Mesh, Vec<(HexCoord, Vec3)>) {
+    let rings = params.rings as i32;
+    let mut builder = HexBuilder::default();
+    let mut tiles = vec![];
+
+    for q in -rings..=rings {
+        for r in (-rings).max(-q - rings)..=rings.min(-q + rings) {
+            let coord = HexCoord::new(q, r);
+            let center = coord.center(params.radius);
+            builder.top(center, params.radius);
+            tiles.push((coord, center));
+        }
+    }
+
+    (builder.into_mesh(), tiles)
+}
+
+/// A board of hex columns, each tile raised to its own entry in `heights`, like a
+/// strategy map with cliffs between tiles.
+///
+/// Tiles are laid out as in [`generate_hex_grid`] with the same UVs on their tops. A
+/// wall runs down from a tile to each lower neighbor, and neighbors at the same height
+/// just meet at an edge. Along the edge of the board, tiles above 0 get walls down to
+/// 0, so a flat board at height 0 has no walls at all. Wall UVs run 0..1 along the edge
+/// and down in multiples of `radius`.
+pub fn generate_hex_columns(heights: &HashMap<HexCoord, f32>, radius: f32) -> Mesh {
+    let mut builder = HexBuilder::default();
+
+    for (coord, height) in heights {
+        let top = coord.center(radius) + Vec3::Y * *height;
+        builder.top(top, radius);
+
+        for (side, neighbor) in coord.neighbors().into_iter().enumerate() {
+            let below = heights.get(&neighbor).copied().unwrap_or(0.0);
+            if below < *height {
+                // neighbor `side` lies across the edge between these two corners
+                let from = top + hex_corner((side + 5) % 6) * radius;
+                let to = top + hex_corner(side) * radius;
+                builder.wall(from, to, *height - below, radius);
+            }
+        }
+    }
+
+    builder.into_mesh()
+}
+
+// the corner at `angle` radians counter-clockwise from +X, seen from above
+fn corner(angle: f32) -> Vec3 {
+    Vec3::new(angle.cos(), 0.0, -angle.sin())
+}
+
+// corner k of a pointy-topped hex, the first pointing 30 degrees from +X
+fn hex_corner(k: usize) -> Vec3 {
+    corner(PI / 6.0 + PI / 3.0 * k as f32)
+}
+
+#[derive(Default)]
+struct HexBuilder {
+    mesh: CustomMeshBuilder,
+}
+
+impl HexBuilder {
+    // a hex facing +Y, fanned from its center
+    fn top(&mut self, center: Vec3, radius: f32) {
+        let hex_uv = |offset: Vec3| {
+            // a pointy-topped hex is sqrt(3) wide and 2 tall, in units of its radius
+            Vec2::new(0.5 + offset.x / 3.0_f32.sqrt(), 0.5 + offset.z / 2.0)
+        };
+
+        let middle = self.mesh.vertex(center, Vec2::splat(0.5), Vec3::Y);
+        let corners = [0, 1, 2, 3, 4, 5].map(|k| {
+            self.mesh.vertex(
+                center + hex_corner(k) * radius,
+                hex_uv(hex_corner(k)),
+                Vec3::Y,
+            )
+        });
+        for k in 0..6 {
+            self.mesh.triangle(middle, corners[k], corners[(k + 1) % 6]);
+        }
+    }
+
+    // hangs `drop` down from the top edge `from`-`to`, facing out of the hex whose
+    // corners run counter-clockwise through them
+    fn wall(&mut self, from: Vec3, to: Vec3, drop: f32, radius: f32) {
+        let down = Vec3::NEG_Y * drop;
+        let normal = (to - from).cross(Vec3::Y).normalize();
+        let v = drop / radius;
+
+        let [a, b, c, d] = [
+            (from, Vec2::new(0.0, 0.0)),
+            (from + down, Vec2::new(0.0, v)),
+            (to + down, Vec2::new(1.0, v)),
+            (to, Vec2::new(1.0, 0.0)),
+        ]
+        .map(|(position, uv)| self.mesh.vertex(position, uv, normal));
+        self.mesh.triangle(a, b, c).triangle(a, c, d);
+    }
+
+    fn into_mesh(self) -> Mesh {
+        self.mesh
+            .build()
+            .expect("hex triangles only use vertices added alongside them")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::attributes::normals;
+
+    #[test]
+    fn flat_hex_board_has_no_walls() {
+        let (_, tiles) = generate_hex_grid(&HexGridParams {
+            radius: 1.0,
+            rings: 2,
+        });
+        let flat: HashMap<HexCoord, f32> = tiles.iter().map(|(coord, _)| (*coord, 0.0)).collect();
+        let board = generate_hex_columns(&flat, 1.0);
+        assert_eq!(board.count_vertices(), tiles.len() * 7);
+        assert!(normals(&board)
+            .unwrap()
+            .iter()
+            .all(|n| *n == [0.0, 1.0, 0.0]));
+
+        let mut raised = flat;
+        raised.insert(tiles[0].0, 1.0);
+        assert!(generate_hex_columns(&raised, 1.0).count_vertices() > tiles.len() * 7);
+    }
+}
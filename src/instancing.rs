@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::{
+    attributes::{normals, uvs},
+    merge::{apply_transform, merge_meshes},
+    raycast::HitAttributes,
+    util::{positions, triangle_indices},
+};
+
+pub use crate::util::Rng;
+
+/// Transforms for `rows` (along Z) by `cols` (along X) copies of one mesh, `spacing`
+/// apart and centered on the origin, for spawning many entities that share a single
+/// `Handle<Mesh>` so the renderer can batch them.
+pub fn grid_instance_transforms(rows: u32, cols: u32, spacing: f32) -> Vec<Transform> {
+    let offset = |count: u32, i: u32| (i as f32 - (count as f32 - 1.0) / 2.0) * spacing;
+    (0..rows)
+        .flat_map(|row| {
+            (0..cols).map(move |col| Transform::from_xyz(offset(cols, col), 0.0, offset(rows, row)))
+        })
+        .collect()
+}
+
+/// A transform per point, moved onto the nearest spot on the surface of `mesh` and
+/// turned so its Y axis follows the surface normal there, for placing instances of a
+/// shared mesh (grass, rocks, trees) over terrain instead of merging copies of it.
+///
+/// Points usually come from
+/// [`sample_surface_points`](crate::sampling::sample_surface_points). Normals are
+/// interpolated from the mesh's vertex normals, or taken from the faces when it has
+/// none. Each point scans every triangle, so this is meant to run once when a scene is
+/// built rather than every frame. Points are kept where they are, unrotated, when the
+/// mesh has no triangles.
+pub fn scatter_transforms(mesh: &Mesh, points: &[Vec3]) -> Vec<Transform> {
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+    let attributes = HitAttributes {
+        positions,
+        normals: normals(mesh),
+        uvs: uvs(mesh),
+    };
+
+    points
+        .iter()
+        .map(|&point| {
+            let mut nearest: Option<(usize, [usize; 3], Vec3, f32, Vec2)> = None;
+            for (i, tri) in triangles.iter().enumerate() {
+                let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+                if (b - a).cross(c - a).length_squared() <= 0.0 {
+                    continue;
+                }
+                let weights = closest_point_on_triangle(point, a, b, c);
+                let closest = a + (b - a) * weights.x + (c - a) * weights.y;
+                let distance = closest.distance_squared(point);
+                if nearest.map_or(true, |(_, _, _, nearest, _)| distance < nearest) {
+                    nearest = Some((i, *tri, closest, distance, weights));
+                }
+            }
+
+            let Some((triangle, tri, closest, distance, weights)) = nearest else {
+                return Transform::from_translation(point);
+            };
+            let normal = attributes
+                .hit(triangle, tri, closest, distance.sqrt(), weights)
+                .normal;
+            let rotation = if normal == Vec3::ZERO {
+                Quat::IDENTITY
+            } else {
+                Quat::from_rotation_arc(Vec3::Y, normal)
+            };
+            Transform::from_translation(closest).with_rotation(rotation)
+        })
+        .collect()
+}
+
+/// `count` varied copies of a procedural mesh merged into one, for forests, asteroid
+/// fields and debris drawn in a single call rather than as separate entities.
+///
+/// For each instance in turn, `prototype` builds its mesh and then `placement` picks
+/// its transform, both given the instance's number and one generator seeded with
+/// `seed` that runs through the whole field, so the same seed and functions always
+/// build the same mesh. Transforms are baked in with
+/// [`apply_transform`](crate::merge::apply_transform) and the copies joined with
+/// [`merge_meshes`](crate::merge::merge_meshes), which keeps the attributes they all
+/// have.
+pub fn generate_scatter_field(
+    prototype: impl Fn(u32, &mut Rng) -> Mesh,
+    count: u32,
+    placement: impl Fn(u32, &mut Rng) -> Transform,
+    seed: u32,
+) -> Mesh {
+    let mut rng = Rng::new(seed);
+    let instances: Vec<Mesh> = (0..count)
+        .map(|i| {
+            let mut mesh = prototype(i, &mut rng);
+            apply_transform(&mut mesh, placement(i, &mut rng));
+            mesh
+        })
+        .collect();
+    merge_meshes(&instances)
+}
+
+// the weights of `b` and `c` at the point on the triangle nearest `p`, checking which
+// corner, edge or the face itself it lands on (after Ericson's Real-Time Collision
+// Detection)
+fn closest_point_on_triangle(p: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Vec2 {
+    let (ab, ac) = (b - a, c - a);
+    let ap = p - a;
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return Vec2::ZERO;
+    }
+
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return Vec2::X;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return Vec2::new(d1 / (d1 - d3), 0.0);
+    }
+
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return Vec2::Y;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return Vec2::new(0.0, d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        let w = (d4 - d3) / ((d4 - d3) + (d5 - d6));
+        return Vec2::new(1.0 - w, w);
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    Vec2::new(vb * denom, vc * denom)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere::{generate_uv_sphere, UvSphereParams};
+
+    #[test]
+    fn same_seed_builds_a_byte_identical_field() {
+        let field = |seed: u32| {
+            generate_scatter_field(
+                |_, rng| {
+                    let params = UvSphereParams {
+                        radius: rng.range(0.5, 1.5),
+                        sectors: 8,
+                        stacks: 4,
+                        ..default()
+                    };
+                    generate_uv_sphere(&params).unwrap()
+                },
+                20,
+                |_, rng| {
+                    Transform::from_xyz(rng.range(-10.0, 10.0), 0.0, rng.range(-10.0, 10.0))
+                        .with_rotation(Quat::from_rotation_y(rng.range(0.0, 6.0)))
+                },
+                seed,
+            )
+        };
+        // every attribute's bytes followed by the indices', so any difference shows
+        let bytes = |mesh: &Mesh| -> Vec<u8> {
+            let mut bytes: Vec<u8> = mesh
+                .attributes()
+                .flat_map(|(_, values)| values.get_bytes().to_vec())
+                .collect();
+            bytes.extend(
+                mesh.indices()
+                    .unwrap()
+                    .iter()
+                    .flat_map(|i| (i as u32).to_le_bytes()),
+            );
+            bytes
+        };
+
+        let first = bytes(&field(42));
+        assert!(!first.is_empty());
+        assert_eq!(first, bytes(&field(42)));
+        assert_ne!(first, bytes(&field(43)));
+    }
+}
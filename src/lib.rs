@@ -0,0 +1,81 @@
+//! Procedural meshes for Bevy: generators that build a `Mesh` from a few settings, and
+//! operations for reshaping, texturing, analyzing and exporting meshes, generated or not.
+//!
+//! Generators for shapes described by their settings alone take a `...Params` struct,
+//! such as [`UvSphereParams`](sphere::UvSphereParams) or
+//! [`HeightmapParams`](terrain::HeightmapParams), whose `Default` matches the demo and
+//! which derives `Serialize` and `Deserialize` with the `serde` feature, so a library of
+//! shapes can be kept in RON or JSON files. The rest take their arguments positionally
+//! on purpose:
+//!
+//! - shapes given by a handful of numbers that don't make up a shape worth saving on
+//!   its own, usually one part of a bigger one: [`generate_ramp`](polyhedra::generate_ramp),
+//!   [`generate_frustum`](polyhedra::generate_frustum),
+//!   [`generate_strut`](cylinder::generate_strut),
+//!   [`generate_half_pipe`](cylinder::generate_half_pipe),
+//!   [`generate_subdivided_cube`](sphere::generate_subdivided_cube),
+//!   [`generate_diamond_plane`](plane::generate_diamond_plane),
+//!   [`generate_grid_strip`](plane::generate_grid_strip),
+//!   [`generate_torus_knot`](path::generate_torus_knot),
+//!   [`generate_arrow`](gizmo::generate_arrow), [`generate_skybox`](skybox::generate_skybox)
+//!   and [`generate_grass_clump`](grass::generate_grass_clump)
+//! - shapes that come from data, a closure or another mesh, which a params struct
+//!   couldn't serialize anyway: paths and point lists such as
+//!   [`generate_ribbon`](path::generate_ribbon), [`generate_fence`](fence::generate_fence)
+//!   and [`generate_staircase`](stairs::generate_staircase), height and surface functions
+//!   such as [`generate_parametric_surface`](parametric::generate_parametric_surface) and
+//!   [`generate_heightmap_adaptive`](terrain::generate_heightmap_adaptive), and
+//!   [`generate_outline_hull`](topology::generate_outline_hull)
+
+pub mod attributes;
+pub mod bounds;
+pub mod builder;
+pub mod bvh;
+pub mod chunks;
+pub mod color;
+pub mod compose;
+pub mod csg;
+pub mod cylinder;
+pub mod editor;
+pub mod error;
+pub mod export;
+pub mod extrude;
+pub mod fence;
+pub mod geometry;
+pub mod gizmo;
+pub mod grass;
+pub mod halfedge;
+pub mod hex;
+pub mod instancing;
+pub mod live;
+pub mod lod;
+pub mod material;
+pub mod merge;
+pub mod morph;
+pub mod noise;
+pub mod normals;
+pub mod ocean;
+pub mod parametric;
+pub mod path;
+pub mod plane;
+pub mod polyhedra;
+pub mod prelude;
+pub mod raycast;
+pub mod rock;
+pub mod sampling;
+#[cfg(feature = "scene")]
+pub mod scene;
+pub mod sdf;
+pub mod skybox;
+pub mod slice;
+pub mod spawn;
+pub mod sphere;
+pub mod stairs;
+pub mod terrain;
+pub mod topology;
+pub mod tree;
+pub mod uv;
+pub mod wall;
+pub mod wireframe;
+
+mod util;
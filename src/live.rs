@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+
+use crate::{
+    bounds::mesh_aabb,
+    cylinder::{
+        generate_cone, generate_cone_raw, generate_cylinder, generate_cylinder_raw, generate_disc,
+        generate_disc_raw, CapStyle, ConeParams, CylinderParams, DiscParams,
+    },
+    error::{at_least, positive, GeometryError},
+    geometry::GeometryData,
+    hex::{generate_hex_grid, HexGridParams},
+    normals::NormalMode,
+    ocean::GerstnerWave,
+    plane::{generate_radial_lod_plane, Anchor, RadialLodPlaneParams},
+    polyhedra::{
+        generate_dodecahedron, generate_octahedron, generate_pyramid, generate_tetrahedron,
+        PlatonicParams, PyramidParams,
+    },
+    rock::RockParams,
+    sphere::{
+        generate_cube_sphere, generate_cube_sphere_raw, generate_uv_sphere, generate_uv_sphere_raw,
+        CubeSphereParams, CubeSphereUvs, Poles, StackDistribution, UvSphereParams,
+    },
+    stairs::{generate_spiral_stairs, FlightSpec, SpiralStairsParams},
+    terrain::{
+        generate_heightmap, generate_heightmap_raw, BiomeTerrainParams, ErosionParams,
+        HeightmapParams, QuadSplit,
+    },
+    tree::{Foliage, TreeParams},
+    uv::UvMode,
+};
+
+/// A generator and its params as a component, so the mesh can be tweaked while the app
+/// runs: edited through reflection by an inspector such as `bevy-inspector-egui`,
+/// loaded from a Bevy scene, or changed by a system.
+///
+/// With [`ProceduralMeshPlugin`] added, the entity's mesh is regenerated whenever the
+/// component changes, including when it's first added: the entity gets a handle to the
+/// new mesh in place of whatever `Handle<Mesh>` it had, which frees the old mesh once
+/// nothing else holds it.
+///
+/// With the `serde` feature it's also what a scene file lists objects as (see
+/// `scene::SceneObject`).
+#[derive(Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[reflect(Component)]
+pub enum ProceduralMesh {
+    UvSphere(UvSphereParams),
+    CubeSphere(CubeSphereParams),
+    Disc(DiscParams),
+    Cylinder(CylinderParams),
+    Cone(ConeParams),
+    Pyramid(PyramidParams),
+    Tetrahedron(PlatonicParams),
+    Octahedron(PlatonicParams),
+    Dodecahedron(PlatonicParams),
+    SpiralStairs(SpiralStairsParams),
+    RadialLodPlane(RadialLodPlaneParams),
+    HexGrid(HexGridParams),
+    /// a flat heightmap, since a height function can't be reflected or written down in
+    /// data
+    Grid(HeightmapParams),
+}
+
+impl Default for ProceduralMesh {
+    fn default() -> Self {
+        ProceduralMesh::UvSphere(UvSphereParams::default())
+    }
+}
+
+impl ProceduralMesh {
+    /// The mesh these params describe, failing like its generator does. A grid fails with
+    /// [`GeometryError::InvalidParameter`] unless its rows, columns, cell size and UV tiles
+    /// are positive.
+    pub fn generate(&self) -> Result<Mesh, GeometryError> {
+        match self {
+            ProceduralMesh::UvSphere(params) => generate_uv_sphere(params),
+            ProceduralMesh::CubeSphere(params) => generate_cube_sphere(params),
+            ProceduralMesh::Disc(params) => generate_disc(params),
+            ProceduralMesh::Cylinder(params) => generate_cylinder(params),
+            ProceduralMesh::Cone(params) => generate_cone(params),
+            ProceduralMesh::Pyramid(params) => generate_pyramid(params),
+            ProceduralMesh::Tetrahedron(params) => generate_tetrahedron(params),
+            ProceduralMesh::Octahedron(params) => generate_octahedron(params),
+            ProceduralMesh::Dodecahedron(params) => generate_dodecahedron(params),
+            ProceduralMesh::SpiralStairs(params) => Ok(generate_spiral_stairs(params)),
+            ProceduralMesh::RadialLodPlane(params) => Ok(generate_radial_lod_plane(params)),
+            ProceduralMesh::HexGrid(params) => Ok(generate_hex_grid(params).0),
+            ProceduralMesh::Grid(params) => {
+                check_grid(params)?;
+                Ok(generate_heightmap(params, |_, _| 0.0))
+            }
+        }
+    }
+
+    /// [`ProceduralMesh::generate`] as plain arrays rather than a `Mesh`, from the
+    /// generator's own `_raw` variant where it has one.
+    pub fn generate_raw(&self) -> Result<GeometryData, GeometryError> {
+        match self {
+            ProceduralMesh::UvSphere(params) => generate_uv_sphere_raw(params),
+            ProceduralMesh::CubeSphere(params) => generate_cube_sphere_raw(params),
+            ProceduralMesh::Disc(params) => generate_disc_raw(params),
+            ProceduralMesh::Cylinder(params) => generate_cylinder_raw(params),
+            ProceduralMesh::Cone(params) => generate_cone_raw(params),
+            ProceduralMesh::Grid(params) => {
+                check_grid(params)?;
+                Ok(generate_heightmap_raw(params, |_, _| 0.0))
+            }
+            _ => self.generate().map(|mesh| GeometryData::from_mesh(&mesh)),
+        }
+    }
+}
+
+// the heightmap generators assume their params are usable, so a grid typed into an
+// inspector or a scene file is checked first
+fn check_grid(params: &HeightmapParams) -> Result<(), GeometryError> {
+    at_least("rows", params.rows, 1)?;
+    at_least("cols", params.cols, 1)?;
+    positive("cell_size", params.cell_size)?;
+    positive("uv_tiles", params.uv_tiles)
+}
+
+/// Registers [`ProceduralMesh`] and every params type with Bevy's type registry, for
+/// inspectors and scenes, and regenerates the meshes of entities whose
+/// [`ProceduralMesh`] changed.
+///
+/// Params that can't build a mesh, like a sphere with no stacks halfway through typing
+/// a number, leave the previous mesh in place and log a warning.
+pub struct ProceduralMeshPlugin;
+
+impl Plugin for ProceduralMeshPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ProceduralMesh>()
+            .register_type::<UvSphereParams>()
+            .register_type::<CubeSphereParams>()
+            .register_type::<DiscParams>()
+            .register_type::<CylinderParams>()
+            .register_type::<ConeParams>()
+            .register_type::<PyramidParams>()
+            .register_type::<PlatonicParams>()
+            .register_type::<SpiralStairsParams>()
+            .register_type::<FlightSpec>()
+            .register_type::<RadialLodPlaneParams>()
+            .register_type::<HexGridParams>()
+            .register_type::<HeightmapParams>()
+            .register_type::<BiomeTerrainParams>()
+            .register_type::<ErosionParams>()
+            .register_type::<RockParams>()
+            .register_type::<TreeParams>()
+            .register_type::<GerstnerWave>()
+            .register_type::<CapStyle>()
+            .register_type::<NormalMode>()
+            .register_type::<Anchor>()
+            .register_type::<CubeSphereUvs>()
+            .register_type::<Poles>()
+            .register_type::<StackDistribution>()
+            .register_type::<QuadSplit>()
+            .register_type::<Foliage>()
+            .register_type::<UvMode>()
+            .add_system(regenerate_procedural_meshes);
+    }
+}
+
+// redoes the mesh, and the bounds Bevy culls it with (see `spawn_mesh`), of every
+// entity whose params changed
+fn regenerate_procedural_meshes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    changed: Query<(Entity, &ProceduralMesh), Changed<ProceduralMesh>>,
+) {
+    for (entity, procedural) in &changed {
+        let mesh = match procedural.generate() {
+            Ok(mesh) => mesh,
+            Err(err) => {
+                warn!("couldn't regenerate {procedural:?}: {err}");
+                continue;
+            }
+        };
+        let aabb = mesh_aabb(&mesh).unwrap_or_default();
+        commands.entity(entity).insert((meshes.add(mesh), aabb));
+    }
+}
@@ -0,0 +1,105 @@
+use bevy::prelude::*;
+
+/// About the angle one pixel covers on a 1080 pixel tall screen with a 60 degree
+/// vertical field of view, as a screen-space error [`select_lod`] tolerates.
+pub const ONE_PIXEL: f32 = 0.001;
+
+/// Which of `lods`, ordered from the full mesh to the coarsest, to draw at `distance` from
+/// the camera: the coarsest one whose error looks no bigger than [`ONE_PIXEL`] from
+/// there.
+///
+/// `error_thresholds[i]` is how far `lods[i]` strays from the full mesh in world units,
+/// such as how far [`decimate_flat_regions`](crate::topology::decimate_flat_regions)
+/// or a simplifier moved its surface, usually 0 for the first. On screen that error
+/// shrinks with distance, as `error / distance`. A LOD with a smaller error than one
+/// before it is taken to be as coarse as that one, so the pick only ever gets coarser
+/// as the camera moves away. LODs without a threshold are never picked. Use a
+/// [`LodSelector`] to keep LODs from popping back and forth at the switching distances.
+pub fn select_lod(lods: &[Mesh], distance: f32, error_thresholds: &[f32]) -> usize {
+    pick(lods, distance, error_thresholds, ONE_PIXEL)
+}
+
+/// [`select_lod`] with a tolerance of its own and some stickiness: once a LOD is shown,
+/// the camera has to move `hysteresis` (a fraction, such as 0.1 for 10%) further away
+/// than a switching distance before a coarser one takes over, while a finer one takes
+/// over as soon as the current one's error gets too big. That way a camera hovering
+/// around one distance doesn't flicker between two LODs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LodSelector {
+    /// largest screen-space error to show, as an angle in radians, like [`ONE_PIXEL`]
+    pub max_error: f32,
+    pub hysteresis: f32,
+    current: usize,
+}
+
+impl Default for LodSelector {
+    fn default() -> Self {
+        LodSelector::new(ONE_PIXEL, 0.1)
+    }
+}
+
+impl LodSelector {
+    pub fn new(max_error: f32, hysteresis: f32) -> Self {
+        LodSelector {
+            max_error,
+            hysteresis,
+            current: 0,
+        }
+    }
+
+    /// The LOD last picked, starting from the full mesh.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// The LOD to draw at `distance`, with `lods` and `error_thresholds` as in
+    /// [`select_lod`].
+    pub fn select(&mut self, lods: &[Mesh], distance: f32, error_thresholds: &[f32]) -> usize {
+        let target = pick(lods, distance, error_thresholds, self.max_error);
+        if target < self.current {
+            self.current = target;
+        } else if target > self.current {
+            let lagging = distance / (1.0 + self.hysteresis.max(0.0));
+            self.current = pick(lods, lagging, error_thresholds, self.max_error).max(self.current);
+        }
+        self.current
+    }
+}
+
+// the coarsest LOD whose error, counting any finer LOD's bigger error as its own, looks
+// no bigger than `max_error` from `distance`
+fn pick(lods: &[Mesh], distance: f32, error_thresholds: &[f32], max_error: f32) -> usize {
+    let mut error = 0.0_f32;
+    let mut best = 0;
+    for (i, threshold) in error_thresholds.iter().take(lods.len()).enumerate() {
+        error = error.max(*threshold);
+        if error <= max_error * distance.max(0.0) {
+            best = i;
+        } else {
+            break;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::mesh::PrimitiveTopology;
+
+    use super::*;
+
+    #[test]
+    fn farther_cameras_pick_coarser_lods() {
+        let lods = vec![Mesh::new(PrimitiveTopology::TriangleList); 4];
+        let thresholds = [0.0, 0.01, 0.05, 0.2];
+        let mut picked = Vec::new();
+        for step in 0..2000 {
+            picked.push(select_lod(&lods, step as f32 * 0.25, &thresholds));
+        }
+
+        assert!(picked.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!((0..4).all(|lod| picked.contains(&lod)));
+        assert_eq!(picked[0], 0);
+        assert_eq!(picked.last(), Some(&3));
+    }
+}
@@ -1,7 +1,28 @@
 use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
     pbr::wireframe::{Wireframe, WireframeConfig, WireframePlugin},
     prelude::*,
-    render::{mesh::PrimitiveTopology, render_resource::WgpuFeatures, settings::WgpuSettings},
+    reflect::TypeUuid,
+    render::{
+        render_resource::{AsBindGroup, ShaderRef, WgpuFeatures},
+        settings::WgpuSettings,
+    },
+    sprite::MaterialMesh2dBundle,
+    utils::HashMap,
+};
+use bevy_procedural_geometry::{
+    attributes::{normals_mut, positions_mut},
+    cylinder::{ConeParams, CylinderParams},
+    export::mesh_memory_bytes,
+    live::{ProceduralMesh, ProceduralMeshPlugin},
+    merge::{apply_transform, merge_meshes},
+    morph::morph,
+    normals::NormalMode,
+    plane::generate_diamond_plane,
+    polyhedra::PlatonicParams,
+    spawn::spawn_mesh,
+    sphere::{generate_subdivided_cube, UvSphereParams},
+    uv::{uv_layout_lines, UvMode},
 };
 use smooth_bevy_cameras::{
     controllers::orbit::{OrbitCameraBundle, OrbitCameraController, OrbitCameraPlugin},
@@ -9,8 +30,8 @@ use smooth_bevy_cameras::{
 };
 
 fn main() {
-    App::new()
-        .insert_resource(Msaa::default())
+    let mut app = App::new();
+    app.insert_resource(Msaa::default())
         .insert_resource(WgpuSettings {
             features: WgpuFeatures::POLYGON_MODE_LINE,
             ..default()
@@ -19,12 +40,123 @@ fn main() {
         .add_plugin(LookTransformPlugin)
         .add_plugin(OrbitCameraPlugin::default())
         .add_plugin(WireframePlugin)
+        .add_plugin(MaterialPlugin::<InspectionMaterial>::default())
+        .add_plugin(ProceduralMeshPlugin)
+        .init_resource::<PreviewMaterials>()
         .add_startup_system(setup_camera)
         .add_startup_system(setup_lighting)
         .add_startup_system(setup_plane)
-        .run();
+        .add_startup_system(setup_overlay_camera)
+        .add_startup_system(setup_morph)
+        .add_startup_system(setup_batching)
+        .add_system(toggle_uv_overlay)
+        .add_system(cycle_preview_material)
+        .add_system(show_mesh_stats)
+        .add_system(animate_morph)
+        .add_system(toggle_batching);
+
+    #[cfg(feature = "scene")]
+    app.add_startup_system(setup_scene_file);
+
+    app.run();
+}
+
+const UV_OVERLAY_SIZE: f32 = 256.0;
+const UV_OVERLAY_MARGIN: f32 = 16.0;
+
+// the mesh whose unwrap the uv overlay shows
+#[derive(Component)]
+struct UvOverlayTarget;
+
+#[derive(Component)]
+struct UvOverlay;
+
+// meshes whose material `M` switches between the inspection views
+#[derive(Component)]
+struct PreviewTarget;
+
+// checkers the UVs, or shows world normals or vertex colors as flat color, depending
+// on `mode`
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+#[uuid = "5b1c3c1e-7d39-4c8f-9a0e-2f6d8b4e1a73"]
+struct InspectionMaterial {
+    // 0 for the checkerboard, 1 for normals and 2 for vertex colors, as in the shader
+    #[uniform(0)]
+    mode: u32,
+    // checker squares across one unit of UV
+    #[uniform(0)]
+    checker_scale: f32,
+}
+
+impl Material for InspectionMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/inspect.wgsl".into()
+    }
+}
+
+// the materials `M` cycles through, starting from the lit white every mesh has at
+// first
+#[derive(Resource)]
+struct PreviewMaterials {
+    current: usize,
+    lit: Handle<StandardMaterial>,
+    unlit: Handle<StandardMaterial>,
+    checker: Handle<InspectionMaterial>,
+    normals: Handle<InspectionMaterial>,
+    vertex_colors: Handle<InspectionMaterial>,
+}
+
+impl FromWorld for PreviewMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let mut standard = world.resource_mut::<Assets<StandardMaterial>>();
+        let lit = standard.add(Color::rgb(1.0, 1.0, 1.0).into());
+        let unlit = standard.add(StandardMaterial {
+            unlit: true,
+            ..default()
+        });
+
+        let mut inspection = world.resource_mut::<Assets<InspectionMaterial>>();
+        let mut add = |mode| {
+            inspection.add(InspectionMaterial {
+                mode,
+                checker_scale: 8.0,
+            })
+        };
+        PreviewMaterials {
+            current: 0,
+            lit,
+            unlit,
+            checker: add(0),
+            normals: add(1),
+            vertex_colors: add(2),
+        }
+    }
 }
 
+// the mesh `Space` inflates from a cube into a sphere and back
+#[derive(Component)]
+struct MorphTarget;
+
+// the two ends of the morph and how far along it the animation is
+#[derive(Resource)]
+struct MorphDemo {
+    cube: Mesh,
+    sphere: Mesh,
+    playing: bool,
+    // seconds the animation has played for, not counting pauses
+    elapsed: f32,
+}
+
+// seconds to go from the cube to the sphere, or back
+const MORPH_SECONDS: f32 = 2.0;
+
+// a merged mesh `B` made out of the procedural meshes that share a material
+#[derive(Component)]
+struct Batch;
+
+// procedural meshes in the ring around the plane, for `B` to merge
+const BATCH_SHAPES: usize = 24;
+
 fn setup_camera(mut commands: Commands) {
     commands
     .spawn(Camera3dBundle::default())
@@ -35,6 +167,310 @@ fn setup_camera(mut commands: Commands) {
     ));
 }
 
+// draws 2d overlays on top of the 3d scene
+fn setup_overlay_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            priority: 1,
+            ..default()
+        },
+        camera_2d: Camera2d {
+            clear_color: ClearColorConfig::None,
+        },
+        ..default()
+    });
+}
+
+fn toggle_uv_overlay(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    targets: Query<&Handle<Mesh>, With<UvOverlayTarget>>,
+    overlays: Query<Entity, With<UvOverlay>>,
+) {
+    if !keys.just_pressed(KeyCode::U) {
+        return;
+    }
+
+    if let Ok(overlay) = overlays.get_single() {
+        commands.entity(overlay).despawn();
+        return;
+    }
+
+    let Some(lines) = targets
+        .iter()
+        .next()
+        .and_then(|target| meshes.get(target))
+        .map(|target| uv_layout_lines(target, UV_OVERLAY_SIZE, Color::GREEN, Color::RED))
+    else {
+        return;
+    };
+
+    // bottom left corner of the window
+    let corner = windows.get_primary().map_or(Vec3::ZERO, |window| {
+        Vec3::new(
+            -window.width() / 2.0 + UV_OVERLAY_MARGIN,
+            -window.height() / 2.0 + UV_OVERLAY_MARGIN,
+            0.0,
+        )
+    });
+
+    commands
+        .spawn(MaterialMesh2dBundle {
+            mesh: meshes.add(lines).into(),
+            material: materials.add(ColorMaterial::from(Color::WHITE)),
+            transform: Transform::from_translation(corner),
+            ..default()
+        })
+        .insert(UvOverlay);
+}
+
+fn cycle_preview_material(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut preview: ResMut<PreviewMaterials>,
+    targets: Query<Entity, With<PreviewTarget>>,
+) {
+    if !keys.just_pressed(KeyCode::M) {
+        return;
+    }
+    preview.current = (preview.current + 1) % 5;
+
+    for target in &targets {
+        let mut target = commands.entity(target);
+        target
+            .remove::<Handle<StandardMaterial>>()
+            .remove::<Handle<InspectionMaterial>>();
+        match preview.current {
+            0 => target.insert(preview.lit.clone()),
+            1 => target.insert(preview.unlit.clone()),
+            2 => target.insert(preview.checker.clone()),
+            3 => target.insert(preview.normals.clone()),
+            _ => target.insert(preview.vertex_colors.clone()),
+        };
+    }
+}
+
+// a subdivided cube over the plane's peak, and the sphere made by pulling each of its
+// vertices in to the same distance from the middle, so they share a topology
+fn setup_morph(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cube = generate_subdivided_cube(2.0, 8, UvMode::PerFace, NormalMode::Flat)
+        .expect("the cube's size and subdivisions are positive");
+    let mut sphere = cube.clone();
+    let directions: Vec<Vec3> = positions_mut(&mut sphere)
+        .unwrap()
+        .iter_mut()
+        .map(|position| {
+            let direction = Vec3::from(*position).normalize();
+            *position = direction.into();
+            direction
+        })
+        .collect();
+    for (normal, direction) in normals_mut(&mut sphere).unwrap().iter_mut().zip(directions) {
+        *normal = direction.into();
+    }
+
+    let bundle = PbrBundle {
+        material: materials.add(Color::rgb(1.0, 1.0, 1.0).into()),
+        transform: Transform::from_xyz(2.5, 5.0, -2.5),
+        ..default()
+    };
+    let target = spawn_mesh(&mut commands, &mut meshes, cube.clone(), bundle);
+    commands
+        .entity(target)
+        .insert(MorphTarget)
+        .insert(PreviewTarget);
+    commands.insert_resource(MorphDemo {
+        cube,
+        sphere,
+        playing: false,
+        elapsed: 0.0,
+    });
+}
+
+// `Space` starts and stops the cube easing into the sphere and back, redoing the mesh
+// every frame while it plays
+fn animate_morph(
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut demo: ResMut<MorphDemo>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    targets: Query<&Handle<Mesh>, With<MorphTarget>>,
+) {
+    if keys.just_pressed(KeyCode::Space) {
+        demo.playing = !demo.playing;
+    }
+    if !demo.playing {
+        return;
+    }
+
+    demo.elapsed += time.delta_seconds();
+    let t = (1.0 - (demo.elapsed / MORPH_SECONDS * std::f32::consts::PI).cos()) / 2.0;
+    let morphed = morph(&demo.cube, &demo.sphere, t).expect("the sphere is a copy of the cube");
+    for target in &targets {
+        if let Some(mesh) = meshes.get_mut(target) {
+            *mesh = morphed.clone();
+        }
+    }
+}
+
+// a ring of small procedural meshes around the plane, alternating between two
+// materials, each its own draw call until `B` batches them
+fn setup_batching(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let colors = [
+        materials.add(Color::rgb(0.9, 0.6, 0.2).into()),
+        materials.add(Color::rgb(0.2, 0.6, 0.9).into()),
+    ];
+    let shapes = [
+        ProceduralMesh::UvSphere(UvSphereParams {
+            radius: 0.4,
+            ..default()
+        }),
+        ProceduralMesh::Cylinder(CylinderParams {
+            radius: 0.3,
+            height: 0.8,
+            ..default()
+        }),
+        ProceduralMesh::Cone(ConeParams {
+            radius: 0.4,
+            height: 0.8,
+            ..default()
+        }),
+        ProceduralMesh::Octahedron(PlatonicParams {
+            size: 0.8,
+            ..default()
+        }),
+    ];
+
+    for i in 0..BATCH_SHAPES {
+        let angle = i as f32 / BATCH_SHAPES as f32 * std::f32::consts::TAU;
+        let transform = Transform::from_xyz(8.0 * angle.cos(), 0.5, 8.0 * angle.sin())
+            .with_rotation(Quat::from_rotation_y(-angle));
+        commands
+            .spawn(PbrBundle {
+                material: colors[i % colors.len()].clone(),
+                transform,
+                ..default()
+            })
+            .insert(shapes[i % shapes.len()].clone());
+    }
+}
+
+// `B` merges the procedural meshes into one mesh per material, each placed where its
+// pieces were, and hides the pieces; pressing it again brings them back
+fn toggle_batching(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut pieces: Query<
+        (
+            &Handle<Mesh>,
+            &Handle<StandardMaterial>,
+            &GlobalTransform,
+            &mut Visibility,
+        ),
+        With<ProceduralMesh>,
+    >,
+    batches: Query<Entity, With<Batch>>,
+) {
+    if !keys.just_pressed(KeyCode::B) {
+        return;
+    }
+
+    let unbatching = !batches.is_empty();
+    for batch in &batches {
+        commands.entity(batch).despawn();
+    }
+
+    // meshes can only share a draw call if they share a material
+    let mut groups: HashMap<Handle<StandardMaterial>, Vec<Mesh>> = HashMap::new();
+    for (mesh, material, transform, mut visibility) in &mut pieces {
+        visibility.is_visible = unbatching;
+        if unbatching {
+            continue;
+        }
+        let Some(mesh) = meshes.get(mesh) else {
+            continue;
+        };
+        let mut placed = mesh.clone();
+        apply_transform(&mut placed, transform.compute_transform());
+        groups.entry(material.clone()).or_default().push(placed);
+    }
+
+    for (material, group) in groups {
+        let bundle = PbrBundle {
+            material,
+            ..default()
+        };
+        let batch = spawn_mesh(&mut commands, &mut meshes, merge_meshes(&group), bundle);
+        commands.entity(batch).insert(Batch);
+    }
+}
+
+// puts how many meshes are drawn, each a draw call of its own in the main pass, and how
+// much memory the preview meshes take up in the window title whenever either changes
+fn show_mesh_stats(
+    mut windows: ResMut<Windows>,
+    mut shown: Local<String>,
+    meshes: Res<Assets<Mesh>>,
+    targets: Query<&Handle<Mesh>, With<PreviewTarget>>,
+    drawn: Query<&Visibility, With<Handle<Mesh>>>,
+) {
+    let draws = drawn
+        .iter()
+        .filter(|visibility| visibility.is_visible)
+        .count();
+    let bytes: usize = targets
+        .iter()
+        .filter_map(|target| meshes.get(target))
+        .map(mesh_memory_bytes)
+        .sum();
+    let title = format!(
+        "bevy-procedural-geometry: {draws} draw calls, {:.1} KiB of meshes",
+        bytes as f32 / 1024.0
+    );
+    if *shown == title {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_title(title.clone());
+        *shown = title;
+    }
+}
+
+// spawns the objects listed in the scene file passed as the first argument, if any
+#[cfg(feature = "scene")]
+fn setup_scene_file(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let Some(path) = std::env::args().nth(1) else {
+        return;
+    };
+
+    match bevy_procedural_geometry::scene::load_procedural_scene(&path) {
+        Ok(objects) => {
+            for (mesh, transform) in objects {
+                let bundle = PbrBundle {
+                    material: materials.add(Color::rgb(1.0, 1.0, 1.0).into()),
+                    transform,
+                    ..default()
+                };
+                let object = spawn_mesh(&mut commands, &mut meshes, mesh, bundle);
+                commands.entity(object).insert(PreviewTarget);
+            }
+        }
+        Err(err) => error!("couldn't load {path}: {err}"),
+    }
+}
+
 fn setup_lighting(mut commands: Commands) {
     commands.spawn(PointLightBundle {
         transform: Transform::from_xyz(2.5, 10.0, -2.5),
@@ -55,150 +491,16 @@ fn setup_plane(
     mut _wireframe_config: ResMut<WireframeConfig>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let map_side_len = 10.0;
-    let min_x = -5.0;
-    let tile_side_step = map_side_len / 2.0 / 2.0;
-
-    let mut complete_positions: Vec<[f32; 3]> = vec![];
-    let mut complete_uvs: Vec<[f32; 2]> = vec![];
-
-    //  (+,-)      (+.+)   (+,-)      (+.+)
-    //    a -------- d       a -------- d
-    //    | \        |       |        / |
-    //    |  \       |       |       /  |
-    //    |   \      |       |      /   |
-    //    |    \     |       |     /    |
-    //    |     \    |       |    /     |
-    //    |      \   |       |   /      |
-    //    |       \  |       |  /       |
-    //    |        \ |       | /        |
-    //    b -------- c       b -------- c
-    //  (-.-)      (-.+)   (-.-)      (-.+)
-    //
-    //  (+,-)      (+.+)   (+,-)      (+.+)
-    //    a -------- d       a -------- d
-    //    |        / |       | \        |
-    //    |       /  |       |  \       |
-    //    |      /   |       |   \      |
-    //    |     /    |       |    \     |
-    //    |    /     |       |     \    |
-    //    |   /      |       |      \   |
-    //    |  /       |       |       \  |
-    //    | /        |       |        \ |
-    //    b -------- c       b -------- c
-    //  (-.-)      (-.+)   (-.-)      (-.+)
-
-    let a: Vec3 = Vec3::new(tile_side_step, tile_side_step, -tile_side_step);
-    let b: Vec3 = Vec3::new(-tile_side_step, 0.0, -tile_side_step);
-    let c: Vec3 = Vec3::new(-tile_side_step, 0.0, tile_side_step);
-    let d: Vec3 = Vec3::new(tile_side_step, 0.0, tile_side_step);
-
-    let (positions, uvs) = plane_positions_and_uvs(a, b, c, d, map_side_len, min_x);
-
-    complete_positions = [complete_positions, positions].concat();
-    complete_uvs = [complete_uvs, uvs].concat();
-
-    let a: Vec3 = Vec3::new(a.x + tile_side_step * 2.0, a.y - tile_side_step, a.z);
-    let b: Vec3 = Vec3::new(b.x + tile_side_step * 2.0, b.y + tile_side_step, b.z);
-    let c: Vec3 = Vec3::new(c.x + tile_side_step * 2.0, c.y, c.z);
-    let d: Vec3 = Vec3::new(d.x + tile_side_step * 2.0, d.y, d.z);
-
-    let (positions, uvs) = plane_positions_and_uvs(b, c, d, a, map_side_len, min_x);
-
-    complete_positions = [complete_positions, positions].concat();
-    complete_uvs = [complete_uvs, uvs].concat();
-
-    let a: Vec3 = Vec3::new(a.x, a.y, a.z - tile_side_step * 2.0);
-    let b: Vec3 = Vec3::new(b.x, b.y - tile_side_step, b.z - tile_side_step * 2.0);
-    let c: Vec3 = Vec3::new(c.x, c.y + tile_side_step, c.z - tile_side_step * 2.0);
-    let d: Vec3 = Vec3::new(d.x, d.y, d.z - tile_side_step * 2.0);
-
-    let (positions, uvs) = plane_positions_and_uvs(a, b, c, d, map_side_len, min_x);
-
-    complete_positions = [complete_positions, positions].concat();
-    complete_uvs = [complete_uvs, uvs].concat();
-
-    let a: Vec3 = Vec3::new(a.x - tile_side_step * 2.0, a.y, a.z);
-    let b: Vec3 = Vec3::new(b.x - tile_side_step * 2.0, b.y, b.z);
-    let c: Vec3 = Vec3::new(c.x - tile_side_step * 2.0, c.y - tile_side_step, c.z);
-    let d: Vec3 = Vec3::new(d.x - tile_side_step * 2.0, d.y + tile_side_step, d.z);
-
-    let (positions, uvs) = plane_positions_and_uvs(b, c, d, a, map_side_len, min_x);
-
-    complete_positions = [complete_positions, positions].concat();
-    complete_uvs = [complete_uvs, uvs].concat();
-
-    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
-
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, complete_positions);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, complete_uvs);
-    mesh.compute_flat_normals();
+    let mesh = generate_diamond_plane(10.0, 2.5, NormalMode::Flat);
     let pbr_bundle = PbrBundle {
-        mesh: meshes.add(mesh),
         material: materials.add(Color::rgb(1.0, 1.0, 1.0).into()),
         ..Default::default()
     };
 
-    commands.spawn(pbr_bundle).insert(Wireframe);
-}
-
-fn plane_positions_and_uvs(
-    a: Vec3,
-    b: Vec3,
-    c: Vec3,
-    d: Vec3,
-    map_side_len: f32,
-    min_x: f32,
-) -> (Vec<[f32; 3]>, Vec<[f32; 2]>) {
-    let uv_calculate = |c: f32| -> f32 { (c + min_x.abs()) / map_side_len };
-    let get_uv = |x: f32, z: f32| -> [f32; 2] { [uv_calculate(z), uv_calculate(x)] };
-
-    let mut positions: Vec<[f32; 3]> = vec![];
-    let mut uvs: Vec<[f32; 2]> = vec![];
-
-    //  (+,-)
-    //    a
-    //    | \
-    //    |  \
-    //    |   \
-    //    |    \
-    //    |     \
-    //    |      \
-    //    |       \
-    //    |        \
-    //    b -------- c
-    //  (-.-)      (-.+)
-
-    positions.push(a.into());
-    uvs.push(get_uv(a.x, a.z));
-
-    positions.push(b.into());
-    uvs.push(get_uv(b.x, b.z));
-
-    positions.push(c.into());
-    uvs.push(get_uv(c.x, c.z));
-
-    //  (+,-)      (+.+)
-    //    a -------- d
-    //      \        |
-    //       \       |
-    //        \      |
-    //         \     |
-    //          \    |
-    //           \   |
-    //            \  |
-    //             \ |
-    //               c
-    //             (-.+)
-
-    positions.push(c.into());
-    uvs.push(get_uv(c.x, c.z));
-
-    positions.push(d.into());
-    uvs.push(get_uv(d.x, d.z));
-
-    positions.push(a.into());
-    uvs.push(get_uv(a.x, a.z));
-
-    (positions, uvs)
+    let plane = spawn_mesh(&mut commands, &mut meshes, mesh, pbr_bundle);
+    commands
+        .entity(plane)
+        .insert(Wireframe)
+        .insert(UvOverlayTarget)
+        .insert(PreviewTarget);
 }
@@ -0,0 +1,110 @@
+use bevy::{prelude::*, render::mesh::Indices, utils::HashMap};
+
+use crate::util::{gather_vertices, triangle_indices};
+
+/// A material index for each triangle of a mesh, kept next to it rather than in it, so
+/// triangles can be painted with different materials without splitting the mesh up
+/// until it's rendered with [`split_by_material`].
+///
+/// Triangles are numbered in index buffer order, or vertex order for a non-indexed
+/// mesh, the same way [`check_consistent_winding`](crate::topology::check_consistent_winding)
+/// reports them. Triangles past the end of the map use material 0, so a map made for a
+/// mesh stays usable after more triangles are added to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MaterialMap {
+    pub materials: Vec<u32>,
+}
+
+impl MaterialMap {
+    /// Every triangle of `mesh` on material 0.
+    pub fn new(mesh: &Mesh) -> Self {
+        MaterialMap {
+            materials: vec![0; triangle_indices(mesh).len()],
+        }
+    }
+
+    /// The material `triangle` is painted with.
+    pub fn material(&self, triangle: usize) -> u32 {
+        self.materials.get(triangle).copied().unwrap_or(0)
+    }
+
+    /// Puts `triangle` on `material`, growing the map if the triangle is past its end.
+    pub fn paint(&mut self, triangle: usize, material: u32) {
+        if triangle >= self.materials.len() {
+            self.materials.resize(triangle + 1, 0);
+        }
+        self.materials[triangle] = material;
+    }
+
+    /// One more than the highest material used, so every material is below it.
+    pub fn material_count(&self) -> u32 {
+        self.materials.iter().max().map_or(1, |highest| highest + 1)
+    }
+}
+
+/// A mesh per material of `map`, indexed by material, each holding the triangles painted
+/// with it and only the vertices they use.
+///
+/// Every attribute is carried over, and triangles keep their order within each submesh.
+/// Materials no triangle uses, below [`MaterialMap::material_count`], get a mesh with
+/// no vertices, so the result can always be zipped with a list of materials. Vertices
+/// shared by triangles on different materials are copied into each submesh they're
+/// used by. Each submesh is indexed.
+pub fn split_by_material(mesh: &Mesh, map: &MaterialMap) -> Vec<Mesh> {
+    let triangles = triangle_indices(mesh);
+    let count = map.material_count() as usize;
+
+    let mut order: Vec<Vec<u32>> = vec![vec![]; count];
+    let mut indices: Vec<Vec<u32>> = vec![vec![]; count];
+    let mut remap: Vec<HashMap<usize, u32>> = vec![HashMap::new(); count];
+    for (triangle, tri) in triangles.iter().enumerate() {
+        let material = map.material(triangle) as usize;
+        for &vertex in tri {
+            let index = *remap[material].entry(vertex).or_insert_with(|| {
+                order[material].push(vertex as u32);
+                order[material].len() as u32 - 1
+            });
+            indices[material].push(index);
+        }
+    }
+
+    order
+        .into_iter()
+        .zip(indices)
+        .map(|(order, indices)| {
+            let mut submesh = mesh.clone();
+            gather_vertices(&mut submesh, order);
+            submesh.set_indices(Some(Indices::U32(indices)));
+            submesh
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        merge::merge_meshes,
+        sphere::{generate_uv_sphere, UvSphereParams},
+    };
+
+    #[test]
+    fn splitting_then_merging_keeps_every_triangle() {
+        let sphere = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        let triangles = triangle_indices(&sphere).len();
+        let mut map = MaterialMap::new(&sphere);
+        for triangle in (0..triangles).step_by(3) {
+            map.paint(triangle, 2);
+        }
+
+        let submeshes = split_by_material(&sphere, &map);
+        let counts: Vec<usize> = submeshes
+            .iter()
+            .map(|submesh| triangle_indices(submesh).len())
+            .collect();
+        let painted = (triangles + 2) / 3;
+        assert_eq!(counts, [triangles - painted, 0, painted]);
+        assert_eq!(triangle_indices(&merge_meshes(&submeshes)).len(), triangles);
+    }
+}
@@ -0,0 +1,173 @@
+use std::ops::Range;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, MeshVertexAttribute, PrimitiveTopology, VertexAttributeValues},
+        render_resource::VertexFormat,
+    },
+};
+
+use crate::{
+    attributes::{colors, normals, normals_mut, uvs},
+    topology::flip_triangles,
+    util::{positions, positions_mut, triangle_indices},
+};
+
+/// All of `meshes` in one indexed mesh, one after the other, for drawing many generated
+/// pieces with a single draw call.
+///
+/// Positions are always kept, and normals, UVs and vertex colors are kept when every
+/// mesh with vertices has them. Only triangle lists add triangles; the vertices of any
+/// other mesh are carried over unused.
+pub fn merge_meshes(meshes: &[Mesh]) -> Mesh {
+    merge_meshes_tagged(meshes).0
+}
+
+/// [`merge_meshes`], along with the range of the merged mesh's triangles each of
+/// `meshes` became, to recolor, paint or take out one source's geometry afterwards.
+///
+/// Triangles are numbered in index buffer order, as in
+/// [`MaterialMap`](crate::material::MaterialMap), and the ranges follow each other in
+/// the order of `meshes`, so together they cover every triangle exactly once. A mesh
+/// without triangles gets an empty range.
+pub fn merge_meshes_tagged(meshes: &[Mesh]) -> (Mesh, Vec<Range<usize>>) {
+    let mut merged_positions: Vec<[f32; 3]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+    let mut ranges = vec![];
+    for mesh in meshes {
+        let first = merged_positions.len() as u32;
+        let triangles = indices.len() / 3;
+        merged_positions.extend_from_slice(positions(mesh));
+        indices.extend(
+            triangle_indices(mesh)
+                .iter()
+                .flatten()
+                .map(|v| first + *v as u32),
+        );
+        ranges.push(triangles..indices.len() / 3);
+    }
+
+    let mut merged = Mesh::new(PrimitiveTopology::TriangleList);
+    merged.insert_attribute(Mesh::ATTRIBUTE_POSITION, merged_positions);
+    if let Some(normals) = gather(meshes, normals) {
+        merged.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    }
+    if let Some(uvs) = gather(meshes, uvs) {
+        merged.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    }
+    if let Some(colors) = gather(meshes, colors) {
+        merged.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    }
+    merged.set_indices(Some(Indices::U32(indices)));
+    (merged, ranges)
+}
+
+/// [`merge_meshes`], also keeping each of `custom`, such as attributes set with
+/// [`set_custom_attribute`](crate::attributes::set_custom_attribute), when every mesh
+/// with vertices has it. Bevy can't tell what attributes a mesh holds beyond their ids,
+/// so they have to be named here. Only 32-bit float attributes, from `Float32` up to
+/// `Float32x4`, are carried over.
+pub fn merge_meshes_keeping(meshes: &[Mesh], custom: &[MeshVertexAttribute]) -> Mesh {
+    let mut merged = merge_meshes(meshes);
+    for attribute in custom {
+        let attribute = *attribute;
+        let values: Option<VertexAttributeValues> = match attribute.format {
+            VertexFormat::Float32 => gather(meshes, |mesh| match mesh.attribute(attribute) {
+                Some(VertexAttributeValues::Float32(values)) => Some(values.as_slice()),
+                _ => None,
+            })
+            .map(Into::into),
+            VertexFormat::Float32x2 => gather(meshes, |mesh| match mesh.attribute(attribute) {
+                Some(VertexAttributeValues::Float32x2(values)) => Some(values.as_slice()),
+                _ => None,
+            })
+            .map(Into::into),
+            VertexFormat::Float32x3 => gather(meshes, |mesh| match mesh.attribute(attribute) {
+                Some(VertexAttributeValues::Float32x3(values)) => Some(values.as_slice()),
+                _ => None,
+            })
+            .map(Into::into),
+            VertexFormat::Float32x4 => gather(meshes, |mesh| match mesh.attribute(attribute) {
+                Some(VertexAttributeValues::Float32x4(values)) => Some(values.as_slice()),
+                _ => None,
+            })
+            .map(Into::into),
+            _ => None,
+        };
+        if let Some(values) = values {
+            merged.insert_attribute(attribute, values);
+        }
+    }
+    merged
+}
+
+/// Moves every vertex of the mesh to where `transform` puts it, for baking the
+/// transforms of meshes spread around a scene into them before merging them into one
+/// with [`merge_meshes`], which otherwise piles them all up at the origin.
+///
+/// Normals are turned with the mesh and stay at right angles to its surface when it's
+/// scaled unevenly. A transform that mirrors the mesh, with an odd number of negative
+/// scales, also flips its triangles so their fronts still face out. Tangents aren't
+/// changed, so generate them afterwards if the mesh needs them.
+pub fn apply_transform(mesh: &mut Mesh, transform: Transform) {
+    for position in positions_mut(mesh) {
+        *position = transform.mul_vec3(Vec3::from(*position)).into();
+    }
+    if let Some(normals) = normals_mut(mesh) {
+        for normal in normals {
+            // the inverse transpose of the scale and rotation, which for a scale is
+            // dividing by it
+            let turned = transform.rotation * (Vec3::from(*normal) / transform.scale);
+            *normal = turned.normalize_or_zero().into();
+        }
+    }
+    if transform.scale.x * transform.scale.y * transform.scale.z < 0.0 {
+        let triangles: Vec<usize> = (0..triangle_indices(mesh).len()).collect();
+        flip_triangles(mesh, &triangles);
+    }
+}
+
+// the attribute `get` finds, for every vertex of every mesh in turn, or `None` if a mesh
+// with vertices doesn't have it
+fn gather<T: Copy>(meshes: &[Mesh], get: impl Fn(&Mesh) -> Option<&[T]>) -> Option<Vec<T>> {
+    let mut values = vec![];
+    for mesh in meshes.iter().filter(|mesh| !positions(mesh).is_empty()) {
+        values.extend_from_slice(get(mesh)?);
+    }
+    Some(values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::triangles,
+        cylinder::{generate_disc, DiscParams},
+        polyhedra::{generate_pyramid, PyramidParams},
+        sphere::{generate_uv_sphere, UvSphereParams},
+    };
+
+    #[test]
+    fn tagged_ranges_partition_the_merged_triangles() {
+        let sources = [
+            generate_uv_sphere(&UvSphereParams::default()).unwrap(),
+            generate_pyramid(&PyramidParams::default()).unwrap(),
+            Mesh::new(PrimitiveTopology::TriangleList),
+            generate_disc(&DiscParams::default()).unwrap(),
+        ];
+        let (merged, ranges) = merge_meshes_tagged(&sources);
+        let merged: Vec<[Vec3; 3]> = triangles(&merged).collect();
+
+        assert_eq!(ranges[0].start, 0);
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[0].end, pair[1].start);
+        }
+        assert_eq!(ranges[3].end, merged.len());
+        assert!(ranges[2].is_empty());
+        for (source, range) in sources.iter().zip(ranges) {
+            let own: Vec<[Vec3; 3]> = triangles(source).collect();
+            assert_eq!(merged[range], own[..]);
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::attributes::{
+    colors, colors_mut, normals, normals_mut, positions, positions_mut, uvs, uvs_mut,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MorphError {
+    /// the meshes don't have the same number of vertices
+    VertexCount { a: usize, b: usize },
+    /// the meshes have different primitive topologies or index buffers, so their
+    /// vertices don't make up the same triangles
+    Topology,
+}
+
+impl fmt::Display for MorphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MorphError::VertexCount { a, b } => {
+                write!(f, "can't morph between {a} and {b} vertices")
+            }
+            MorphError::Topology => write!(f, "the meshes' triangles don't match"),
+        }
+    }
+}
+
+impl std::error::Error for MorphError {}
+
+/// `a` blended towards `b` by `t`, from exactly `a` at 0 to exactly `b` at 1, for shape
+/// keys like a cube inflating into a sphere. `t` is clamped to that range.
+///
+/// The meshes need the same vertex count and the same index buffer, as when both come
+/// from one generator with the same resolution or one is a deformed copy of the other.
+/// Positions, UVs and vertex colors are blended linearly, and normals are blended and
+/// renormalized, wherever both meshes have them. Any other attributes, and ones only
+/// `a` has, are copied from `a`.
+pub fn morph(a: &Mesh, b: &Mesh, t: f32) -> Result<Mesh, MorphError> {
+    let (count_a, count_b) = (a.count_vertices(), b.count_vertices());
+    if count_a != count_b {
+        return Err(MorphError::VertexCount {
+            a: count_a,
+            b: count_b,
+        });
+    }
+    let indices = |mesh: &Mesh| {
+        mesh.indices()
+            .map(|indices| indices.iter().collect::<Vec<_>>())
+    };
+    if a.primitive_topology() != b.primitive_topology() || indices(a) != indices(b) {
+        return Err(MorphError::Topology);
+    }
+
+    let t = t.clamp(0.0, 1.0);
+    let mut morphed = a.clone();
+    if let (Some(to), Some(from)) = (positions(b), positions_mut(&mut morphed)) {
+        blend(from, to, t);
+    }
+    if let (Some(to), Some(from)) = (normals(b), normals_mut(&mut morphed)) {
+        blend(from, to, t);
+        // at either end they're a's or b's own normals, untouched
+        if t > 0.0 && t < 1.0 {
+            for normal in from {
+                *normal = Vec3::from(*normal).normalize_or_zero().into();
+            }
+        }
+    }
+    if let (Some(to), Some(from)) = (uvs(b), uvs_mut(&mut morphed)) {
+        blend(from, to, t);
+    }
+    if let (Some(to), Some(from)) = (colors(b), colors_mut(&mut morphed)) {
+        blend(from, to, t);
+    }
+    Ok(morphed)
+}
+
+// weighting each end rather than adding a share of the difference, so 0 and 1 land
+// exactly on either end
+fn blend<const N: usize>(from: &mut [[f32; N]], to: &[[f32; N]], t: f32) {
+    for (from, to) in from.iter_mut().zip(to) {
+        for (from, to) in from.iter_mut().zip(to) {
+            *from = *from * (1.0 - t) + to * t;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sphere::{generate_cube_sphere, CubeSphereParams};
+
+    #[test]
+    fn morph_ends_are_exactly_its_inputs() {
+        let params = CubeSphereParams {
+            subdivisions: 4,
+            ..default()
+        };
+        let a = generate_cube_sphere(&params).unwrap();
+        let mut b = a.clone();
+        for position in positions_mut(&mut b).unwrap() {
+            *position = position.map(|c| (c * 3.0).clamp(-1.0, 1.0));
+        }
+        for normal in normals_mut(&mut b).unwrap() {
+            *normal = [-normal[1], normal[0], normal[2]];
+        }
+
+        for (t, end) in [(0.0, &a), (1.0, &b)] {
+            let morphed = morph(&a, &b, t).unwrap();
+            assert_eq!(positions(&morphed), positions(end));
+            assert_eq!(normals(&morphed), normals(end));
+            assert_eq!(uvs(&morphed), uvs(end));
+        }
+
+        let coarser = generate_cube_sphere(&CubeSphereParams::default()).unwrap();
+        assert!(matches!(
+            morph(&a, &coarser, 0.5),
+            Err(MorphError::VertexCount { .. })
+        ));
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+/// Smooth random value in -1..1 varying over roughly one unit of `point`, the same for
+/// a given `seed` and point on every platform.
+///
+/// This is value noise: random values on the integer lattice, eased between with
+/// smoothstep. It's cheap and good enough for displacing procedural shapes, but shows
+/// some grid alignment next to gradient noise.
+pub fn value_noise(seed: u32, point: Vec3) -> f32 {
+    let cell = point.floor();
+    let t = point - cell;
+    let t = t * t * (Vec3::splat(3.0) - 2.0 * t);
+    let [x, y, z] = [cell.x as i32, cell.y as i32, cell.z as i32];
+
+    let corner = |dx: i32, dy: i32, dz: i32| lattice_value(seed, x + dx, y + dy, z + dz);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+
+    let x00 = lerp(corner(0, 0, 0), corner(1, 0, 0), t.x);
+    let x10 = lerp(corner(0, 1, 0), corner(1, 1, 0), t.x);
+    let x01 = lerp(corner(0, 0, 1), corner(1, 0, 1), t.x);
+    let x11 = lerp(corner(0, 1, 1), corner(1, 1, 1), t.x);
+    lerp(lerp(x00, x10, t.y), lerp(x01, x11, t.y), t.z)
+}
+
+/// [`value_noise`] summed over `octaves` layers, each at twice the frequency and half
+/// the strength of the last, for detail at several scales. Still in -1..1.
+pub fn fbm(seed: u32, point: Vec3, octaves: u32) -> f32 {
+    let mut total = 0.0;
+    let mut strength = 1.0;
+    let mut scale = 1.0;
+    let mut max = 0.0;
+    for octave in 0..octaves {
+        total += value_noise(seed.wrapping_add(octave), point * scale) * strength;
+        max += strength;
+        strength /= 2.0;
+        scale *= 2.0;
+    }
+
+    if max > 0.0 {
+        total / max
+    } else {
+        0.0
+    }
+}
+
+/// [`fbm`] sampled at a point pushed around by more fbm first, which bends its features
+/// into the swirls and folds of eroded ground where plain fbm looks like blobs.
+///
+/// Each of the `iterations` offsets the original point by `strength` times a vector of
+/// fbm sampled where the last iteration left it, so more iterations warp the warp
+/// itself. A `strength` of about 1 to 4 (in the units of `point`) reads as natural;
+/// zero iterations or strength is plain fbm. The offsets use seeds of their own derived
+/// from `seed`, so the result is still the same for a given seed on every platform, and
+/// still in -1..1.
+pub fn domain_warp(seed: u32, point: Vec3, octaves: u32, strength: f32, iterations: u32) -> f32 {
+    let mut warped = point;
+    for iteration in 0..iterations {
+        // one seed per axis and iteration, spaced far enough apart that fbm's per-octave
+        // seeds never overlap
+        let channel = |axis: u32| {
+            let index = iteration.wrapping_mul(3).wrapping_add(axis + 1);
+            seed.wrapping_add(index.wrapping_mul(0x1000))
+        };
+        let offset = Vec3::new(
+            fbm(channel(0), warped, octaves),
+            fbm(channel(1), warped, octaves),
+            fbm(channel(2), warped, octaves),
+        );
+        warped = point + offset * strength;
+    }
+    fbm(seed, warped, octaves)
+}
+
+// a random value in -1..1 for each lattice point
+fn lattice_value(seed: u32, x: i32, y: i32, z: i32) -> f32 {
+    let mut hash = seed
+        .wrapping_mul(0x27d4_eb2d)
+        .wrapping_add((x as u32).wrapping_mul(0x8da6_b343))
+        .wrapping_add((y as u32).wrapping_mul(0xd816_3841))
+        .wrapping_add((z as u32).wrapping_mul(0xcb1a_b31f));
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2c1b_3c6d);
+    hash ^= hash >> 12;
+    hash = hash.wrapping_mul(0x297a_2d39);
+    hash ^= hash >> 15;
+
+    (hash >> 8) as f32 / (1 << 23) as f32 - 1.0
+}
@@ -0,0 +1,427 @@
+use bevy::{prelude::*, render::mesh::Indices, utils::HashMap};
+
+use crate::{
+    geometry::GeometryData,
+    util::{gather_vertices, positions, quantize, triangle_indices, welded_position_ids, Groups},
+};
+
+/// Gives all three corners of every triangle the triangle's own normal, for flat
+/// shading, without touching UVs or any other attribute.
+///
+/// Needs a mesh without an index buffer, where every triangle has its own three
+/// vertices; split an indexed mesh with `Mesh::duplicate_vertices` first, which copies
+/// each shared vertex's UVs to every triangle that used it. An indexed mesh is left as
+/// it is, with a warning.
+pub fn flat_normals_preserving_uvs(mesh: &mut Mesh) {
+    if mesh.indices().is_some() {
+        warn!("flat_normals_preserving_uvs skipped an indexed mesh");
+        return;
+    }
+
+    let normals = flat_normals(positions(mesh));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+}
+
+/// Which normals a generator gives the mesh it builds, for picking the shading up front
+/// rather than recomputing it afterwards. Each generator's params default to whichever
+/// suits its shape.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalMode {
+    /// every triangle shaded as a flat facet, splitting up any vertices the shape's
+    /// triangles share so that each has three of its own
+    Flat,
+    /// averaged from the faces around each position, to within 0.0001 on each axis and
+    /// weighed by angle, across seams and edges alike, so the whole surface shades as one
+    /// rounded piece
+    Smooth,
+    /// those of the exact shape the mesh stands for, smooth over curved surfaces and hard
+    /// along real edges like a cylinder's rims
+    Analytic,
+    /// no normal attribute at all, for meshes that get their normals later or are drawn
+    /// unlit
+    None,
+}
+
+impl NormalMode {
+    /// Gives `mesh` these normals, taking the ones it already has as its analytic ones.
+    pub fn apply(self, mesh: &mut Mesh) {
+        match self {
+            NormalMode::Flat => {
+                if mesh.indices().is_some() {
+                    mesh.duplicate_vertices();
+                }
+                flat_normals_preserving_uvs(mesh);
+            }
+            NormalMode::Smooth => {
+                let ids = seam_welded_ids(positions(mesh));
+                let normals = averaged_normals(
+                    positions(mesh),
+                    &triangle_indices(mesh),
+                    NormalWeighting::ByAngle,
+                    &ids,
+                );
+                mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+            }
+            NormalMode::Analytic => {}
+            NormalMode::None => {
+                mesh.remove_attribute(Mesh::ATTRIBUTE_NORMAL);
+            }
+        }
+    }
+
+    /// [`NormalMode::apply`] for geometry that isn't a `Mesh` yet, as the `_raw`
+    /// generators use it. With [`NormalMode::Flat`], the split vertices are listed in
+    /// order by the index buffer rather than left without one.
+    pub fn apply_raw(self, data: &mut GeometryData) {
+        match self {
+            NormalMode::Flat => {
+                fn split<T: Copy>(values: &[T], order: &[u32]) -> Vec<T> {
+                    order.iter().map(|v| values[*v as usize]).collect()
+                }
+                let order = std::mem::take(&mut data.indices);
+                data.positions = split(&data.positions, &order);
+                if !data.uvs.is_empty() {
+                    data.uvs = split(&data.uvs, &order);
+                }
+                data.normals = flat_normals(&data.positions);
+                data.indices = (0..order.len() as u32).collect();
+            }
+            NormalMode::Smooth => {
+                let triangles: Vec<[usize; 3]> = data
+                    .indices
+                    .chunks_exact(3)
+                    .map(|tri| [tri[0], tri[1], tri[2]].map(|v| v as usize))
+                    .collect();
+                let ids = seam_welded_ids(&data.positions);
+                data.normals =
+                    averaged_normals(&data.positions, &triangles, NormalWeighting::ByAngle, &ids);
+            }
+            NormalMode::Analytic => {}
+            NormalMode::None => data.normals.clear(),
+        }
+    }
+}
+
+// an id per position rounded to 4 decimal places, so the two ends of a seam meet even
+// where they're a rounding error apart
+fn seam_welded_ids(positions: &[[f32; 3]]) -> Vec<usize> {
+    let mut keys = HashMap::new();
+    positions
+        .iter()
+        .map(|position| {
+            let next = keys.len();
+            *keys.entry(quantize(*position, 4)).or_insert(next)
+        })
+        .collect()
+}
+
+// every three positions' own face normal, for a triangle list without an index buffer
+fn flat_normals(positions: &[[f32; 3]]) -> Vec<[f32; 3]> {
+    let mut normals: Vec<[f32; 3]> = positions
+        .chunks_exact(3)
+        .flat_map(|tri| {
+            let [a, b, c] = [tri[0], tri[1], tri[2]].map(Vec3::from);
+            [(b - a).cross(c - a).normalize_or_zero().into(); 3]
+        })
+        .collect();
+    // a stray vertex or two past the last whole triangle isn't drawn anyway
+    normals.resize(positions.len(), [0.0; 3]);
+    normals
+}
+
+/// How [`compute_smooth_normals`] weighs the faces around a vertex against each other.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalWeighting {
+    /// every face counts the same, so a vertex with many small faces on one side and a
+    /// big one on the other leans towards the small ones
+    Uniform,
+    /// by the area of each face, so slivers hardly count, but a big face can still
+    /// swamp its neighbors where sizes vary a lot
+    ByArea,
+    /// by the angle each face makes at the vertex, which depends only on the shape of
+    /// the surface around it and not on how it was split into triangles, so it holds
+    /// up best on meshes with uneven triangles like a level-of-detail plane
+    #[default]
+    ByAngle,
+}
+
+/// Recomputes smooth normals for `mesh` by averaging the normals of the faces around
+/// each vertex, weighed against each other by `weighting`.
+///
+/// Only faces that share the vertex by index are averaged, so split vertices (along a
+/// UV seam, say) keep their own normals; weld the mesh first to smooth across them.
+pub fn compute_smooth_normals(mesh: &mut Mesh, weighting: NormalWeighting) {
+    let vertices: Vec<usize> = (0..positions(mesh).len()).collect();
+    let normals = averaged_normals(
+        positions(mesh),
+        &triangle_indices(mesh),
+        weighting,
+        &vertices,
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+}
+
+// each vertex's normal averaged from the faces around every vertex with the same id in
+// `ids`, weighed by `weighting`
+fn averaged_normals(
+    positions: &[[f32; 3]],
+    triangles: &[[usize; 3]],
+    weighting: NormalWeighting,
+    ids: &[usize],
+) -> Vec<[f32; 3]> {
+    let mut sums = vec![Vec3::ZERO; positions.len()];
+    for &tri in triangles {
+        let corners = tri.map(|v| Vec3::from(positions[v]));
+        let [a, b, c] = corners;
+        let face = (b - a).cross(c - a);
+        let Some(normal) = face.try_normalize() else {
+            continue;
+        };
+        for k in 0..3 {
+            let weight = match weighting {
+                NormalWeighting::Uniform => 1.0,
+                NormalWeighting::ByArea => face.length(),
+                NormalWeighting::ByAngle => {
+                    let corner = corners[k];
+                    (corners[(k + 1) % 3] - corner).angle_between(corners[(k + 2) % 3] - corner)
+                }
+            };
+            sums[ids[tri[k]]] += normal * weight;
+        }
+    }
+
+    ids.iter()
+        .map(|id| sums[*id].normalize_or_zero().into())
+        .collect()
+}
+
+/// A copy of `mesh` with normals smoothed across every edge where the faces meet at
+/// less than `crease_degrees`, and kept hard across sharper ones.
+///
+/// Curved surfaces shade smoothly while the edges of hard-surface shapes stay crisp.
+/// Faces are connected through shared positions, so a non-indexed mesh works the same
+/// as an indexed one. A vertex is split into one copy per smooth group of faces around
+/// it, with every other attribute copied across, and the result is indexed.
+pub fn compute_normals_with_crease(mesh: &Mesh, crease_degrees: f32) -> Mesh {
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+    let ids = welded_position_ids(positions);
+    let min_cos = crease_degrees.to_radians().cos();
+
+    // area-weighted, so small slivers don't pull on the result
+    let face_normals: Vec<Vec3> = triangles
+        .iter()
+        .map(|tri| {
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+            (b - a).cross(c - a)
+        })
+        .collect();
+
+    // corners are numbered `triangle * 3 + k`, and corners around the same position
+    // are merged into a group when the faces between them meet smoothly
+    let mut groups = Groups::new(triangles.len() * 3);
+    // each edge keyed low to high position id, with the corners at its low and high end
+    // in every triangle that uses it
+    let mut edges: HashMap<(usize, usize), Vec<(usize, usize)>> = HashMap::new();
+    for (triangle, tri) in triangles.iter().enumerate() {
+        for (from, to) in [(0, 1), (1, 2), (2, 0)] {
+            let (from, to) = (triangle * 3 + from, triangle * 3 + to);
+            let (from_id, to_id) = (ids[tri[from % 3]], ids[tri[to % 3]]);
+            if from_id < to_id {
+                edges.entry((from_id, to_id)).or_default().push((from, to));
+            } else {
+                edges.entry((to_id, from_id)).or_default().push((to, from));
+            }
+        }
+    }
+
+    for users in edges.values() {
+        for (i, &(first_low, first_high)) in users.iter().enumerate() {
+            for &(second_low, second_high) in &users[i + 1..] {
+                let a = face_normals[first_low / 3];
+                let b = face_normals[second_low / 3];
+                let smooth = match (a.try_normalize(), b.try_normalize()) {
+                    (Some(a), Some(b)) => a.dot(b) >= min_cos,
+                    _ => true,
+                };
+                if smooth {
+                    groups.join(first_low, second_low);
+                    groups.join(first_high, second_high);
+                }
+            }
+        }
+    }
+
+    let mut sums: HashMap<usize, Vec3> = HashMap::new();
+    for corner in 0..triangles.len() * 3 {
+        let group = groups.find(corner);
+        *sums.entry(group).or_default() += face_normals[corner / 3];
+    }
+
+    // one output vertex per input vertex and group it's used in
+    let mut vertices: HashMap<(usize, usize), u32> = HashMap::new();
+    let mut order: Vec<u32> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+    for (triangle, tri) in triangles.iter().enumerate() {
+        for (k, &vertex) in tri.iter().enumerate() {
+            let group = groups.find(triangle * 3 + k);
+            let index = *vertices.entry((vertex, group)).or_insert_with(|| {
+                order.push(vertex as u32);
+                normals.push(sums[&group].normalize_or_zero().into());
+                order.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    let mut smoothed = mesh.clone();
+    gather_vertices(&mut smoothed, order);
+    smoothed.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    smoothed.set_indices(Some(Indices::U32(indices)));
+    smoothed
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use bevy::render::mesh::PrimitiveTopology;
+
+    use super::*;
+    use crate::{
+        extrude::extrude_glyph,
+        polyhedra::{generate_pyramid, PyramidParams},
+        sphere::{generate_uv_sphere, UvSphereParams},
+    };
+
+    // an extruded circle: the rim should stay sharp, and the side wall shade round, so
+    // nearer the radius at each vertex than the faces either side of it (PI / 32 off)
+    #[test]
+    fn crease_keeps_the_cap_edge_hard_and_the_side_smooth() {
+        let circle: Vec<Vec2> = (0..32)
+            .map(|i| {
+                let angle = i as f32 / 16.0 * PI;
+                Vec2::new(angle.cos(), angle.sin())
+            })
+            .collect();
+        let cylinder = compute_normals_with_crease(&extrude_glyph(&[circle], 2.0), 30.0);
+        let normals = crate::attributes::normals(&cylinder).unwrap();
+        let (mut caps, mut sides) = (0, 0);
+        for (position, normal) in positions(&cylinder).iter().zip(normals) {
+            let (position, normal) = (Vec3::from(*position), Vec3::from(*normal));
+            if (normal.z.abs() - 1.0).abs() < 1e-5 {
+                caps += 1;
+            } else {
+                let outward = position.truncate().normalize().extend(0.0);
+                assert!(normal.z.abs() < 1e-5 && normal.dot(outward) > (PI / 64.0).cos());
+                sides += 1;
+            }
+        }
+        assert_eq!((caps, sides), (64, 128));
+    }
+
+    #[test]
+    fn flat_normals_leave_uvs_untouched() {
+        let mut sphere = Mesh::from(shape::Icosphere {
+            radius: 1.0,
+            subdivisions: 1,
+        });
+        sphere.duplicate_vertices();
+        let bits = |mesh: &Mesh| -> Vec<u32> {
+            let uvs = crate::attributes::uvs(mesh).unwrap();
+            uvs.iter().flatten().map(|f| f.to_bits()).collect()
+        };
+        let before = bits(&sphere);
+        flat_normals_preserving_uvs(&mut sphere);
+        assert_eq!(bits(&sphere), before);
+
+        let normals = crate::attributes::normals(&sphere).unwrap();
+        for (corners, normals) in positions(&sphere).chunks(3).zip(normals.chunks(3)) {
+            let center: Vec3 = corners.iter().copied().map(Vec3::from).sum();
+            assert!(normals.iter().all(|normal| *normal == normals[0]));
+            assert!(Vec3::from(normals[0]).dot(center) > 0.0);
+        }
+    }
+
+    // a corner where one big triangle meets a fan of eight small ones, both covering a
+    // right angle at the vertex, on faces square to each other
+    #[test]
+    fn weightings_disagree_on_one_big_face_against_many_small() {
+        let mut positions = vec![[0.0, 0.0, 0.0], [0.0, 0.0, 10.0], [-10.0, 0.0, 0.0]];
+        let mut indices = vec![0, 1, 2];
+        for i in 0..=8 {
+            let angle = i as f32 / 8.0 * FRAC_PI_2;
+            positions.push([0.0, -angle.sin(), angle.cos()]);
+        }
+        for i in 3..11 {
+            indices.extend([0, i + 1, i]);
+        }
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.set_indices(Some(Indices::U32(indices)));
+
+        let corner = |mesh: &mut Mesh, weighting| {
+            compute_smooth_normals(mesh, weighting);
+            Vec3::from(crate::attributes::normals(mesh).unwrap()[0])
+        };
+        let (big, small) = (Vec3::NEG_Y, Vec3::NEG_X);
+        let uniform = corner(&mut mesh, NormalWeighting::Uniform);
+        assert!(uniform.dot(small) > uniform.dot(big));
+        let by_area = corner(&mut mesh, NormalWeighting::ByArea);
+        assert!(by_area.dot(big) > by_area.dot(small));
+        let by_angle = corner(&mut mesh, NormalWeighting::ByAngle);
+        assert!(by_angle.abs_diff_eq((big + small).normalize(), 1e-4));
+    }
+
+    // every mode, on a generator that's smooth by default and one that's flat
+    #[test]
+    fn each_normal_mode_gives_the_normals_it_names() {
+        let sphere = |normals| {
+            generate_uv_sphere(&UvSphereParams {
+                normals,
+                ..default()
+            })
+            .unwrap()
+        };
+        let pyramid = |normals| {
+            generate_pyramid(&PyramidParams {
+                normals,
+                ..default()
+            })
+            .unwrap()
+        };
+        for generate in [&sphere as &dyn Fn(NormalMode) -> Mesh, &pyramid] {
+            let none = generate(NormalMode::None);
+            assert!(crate::attributes::normals(&none).is_none());
+
+            let flat = generate(NormalMode::Flat);
+            let normals = crate::attributes::normals(&flat).unwrap();
+            for tri in triangle_indices(&flat) {
+                let [a, b, c] = tri.map(|v| Vec3::from(positions(&flat)[v]));
+                let face = (b - a).cross(c - a).normalize();
+                assert!(tri
+                    .iter()
+                    .all(|v| Vec3::from(normals[*v]).abs_diff_eq(face, 1e-4)));
+            }
+
+            // one unit normal at each position, whichever copy of it a triangle uses
+            let smooth = generate(NormalMode::Smooth);
+            let mut at: HashMap<[u32; 3], Vec3> = HashMap::new();
+            let normals = crate::attributes::normals(&smooth).unwrap();
+            for (position, normal) in positions(&smooth).iter().zip(normals) {
+                let normal = Vec3::from(*normal);
+                let first = *at.entry(position.map(f32::to_bits)).or_insert(normal);
+                assert!(normal.abs_diff_eq(first, 1e-6) && (normal.length() - 1.0).abs() < 1e-4);
+            }
+        }
+
+        let analytic = sphere(NormalMode::Analytic);
+        let normals = crate::attributes::normals(&analytic).unwrap();
+        for (position, normal) in positions(&analytic).iter().zip(normals) {
+            assert!(Vec3::from(*normal).abs_diff_eq(Vec3::from(*position), 1e-5));
+        }
+    }
+}
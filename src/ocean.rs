@@ -0,0 +1,180 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    attributes::{normals_mut, record_velocities, uvs, velocities},
+    terrain::{generate_heightmap, HeightmapParams},
+    util::{positions, positions_mut},
+};
+
+// gravity, which sets how fast deep water waves of each length travel
+const GRAVITY: f32 = 9.81;
+
+/// One train of Gerstner waves, which roll the water in circles as they pass so crests
+/// come to a point and troughs flatten out, unlike a plain sine wave.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GerstnerWave {
+    /// which way the waves travel across XZ, normalized when used
+    pub direction: Vec2,
+    /// distance from one crest to the next
+    pub wavelength: f32,
+    /// height of a crest above the resting surface
+    pub amplitude: f32,
+    /// 0 for a sine wave, up to 1 for crests that come to a sharp point; with several
+    /// waves, keep the sum at most 1 or the surface folds over itself
+    pub steepness: f32,
+}
+
+impl Default for GerstnerWave {
+    fn default() -> Self {
+        GerstnerWave {
+            direction: Vec2::X,
+            wavelength: 4.0,
+            amplitude: 0.2,
+            steepness: 0.5,
+        }
+    }
+}
+
+impl GerstnerWave {
+    // radians a crest moves through per second, the speed of a deep water wave this
+    // long rounded to a whole number of cycles every `period`, and at least one
+    fn angular_speed(self, period: f32) -> f32 {
+        let k = TAU / self.wavelength;
+        let speed = (GRAVITY * k).sqrt();
+        if period > 0.0 {
+            (speed * period / TAU).round().max(1.0) * TAU / period
+        } else {
+            speed
+        }
+    }
+}
+
+/// Square of water `size` across, centered on the origin in the XZ plane, split into
+/// `resolution` cells along each side and shaped by `waves` as they are at time zero.
+///
+/// UVs are each vertex's resting X and Z, so a tiling texture repeats once per unit and
+/// rides along with the water instead of sliding over it. [`update_ocean`] moves the
+/// waves on from there.
+pub fn generate_ocean(size: f32, resolution: u32, waves: &[GerstnerWave]) -> Mesh {
+    let resolution = resolution.max(1);
+    let mut mesh = generate_heightmap(
+        &HeightmapParams {
+            rows: resolution,
+            cols: resolution,
+            cell_size: size / resolution as f32,
+            ..default()
+        },
+        |_, _| 0.0,
+    );
+    let rest: Vec<[f32; 2]> = positions(&mesh).iter().map(|[x, _, z]| [*x, *z]).collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, rest);
+    update_ocean(&mut mesh, waves, 0.0, 0.0);
+    mesh
+}
+
+/// Moves every vertex of a [`generate_ocean`] mesh to where `waves` carry it at `time`
+/// seconds, with the exact normals of the displaced surface, so calling it every frame
+/// animates the water.
+///
+/// Each wave travels at the speed of a deep water wave its length, nudged so it passes
+/// a whole number of times every `period` seconds: the surface at `time + period` is
+/// the one at `time`, and that many seconds of frames bake into a seamless loop. A
+/// `period` of zero or less leaves the speeds as they are, so the water never repeats.
+///
+/// A mesh with an [`ATTRIBUTE_VELOCITY`](crate::attributes::ATTRIBUTE_VELOCITY) also
+/// gets how far each vertex moved since the last update put in it, for motion blur.
+pub fn update_ocean(mesh: &mut Mesh, waves: &[GerstnerWave], period: f32, time: f32) {
+    let Some(rest) = uvs(mesh) else {
+        return;
+    };
+    let rest: Vec<Vec2> = rest.iter().map(|uv| Vec2::from(*uv)).collect();
+    let waves: Vec<(GerstnerWave, Vec2, f32, f32)> = waves
+        .iter()
+        .map(|wave| {
+            let direction = wave.direction.normalize_or_zero();
+            let k = TAU / wave.wavelength;
+            // the phase at `time`, kept within a turn so it stays precise over long runs
+            let phase = (wave.angular_speed(period) * time).rem_euclid(TAU);
+            (*wave, direction, k, phase)
+        })
+        .collect();
+
+    let mut surface = vec![];
+    for point in &rest {
+        let mut position = Vec3::new(point.x, 0.0, point.y);
+        // how the surface moves with the rest position, for the normal
+        let mut along_x = Vec3::X;
+        let mut along_z = Vec3::Z;
+        for (wave, direction, k, phase) in &waves {
+            let theta = k * direction.dot(*point) - phase;
+            let (sin, cos) = theta.sin_cos();
+            let reach = wave.steepness / k;
+            position += Vec3::new(
+                reach * direction.x * cos,
+                wave.amplitude * sin,
+                reach * direction.y * cos,
+            );
+
+            let rise = wave.amplitude * k * cos;
+            let pull = wave.steepness * sin;
+            along_x += Vec3::new(
+                -pull * direction.x * direction.x,
+                rise * direction.x,
+                -pull * direction.x * direction.y,
+            );
+            along_z += Vec3::new(
+                -pull * direction.x * direction.y,
+                rise * direction.y,
+                -pull * direction.y * direction.y,
+            );
+        }
+        let normal = along_z.cross(along_x).normalize_or_zero();
+        surface.push((position, normal));
+    }
+
+    // only copied when there's a velocity to work out from them
+    let previous = velocities(mesh).map(|_| positions(mesh).to_vec());
+    for (position, (new, _)) in positions_mut(mesh).iter_mut().zip(&surface) {
+        *position = (*new).into();
+    }
+    if let Some(normals) = normals_mut(mesh) {
+        for (normal, (_, new)) in normals.iter_mut().zip(&surface) {
+            *normal = (*new).into();
+        }
+    }
+    if let Some(previous) = previous {
+        record_velocities(mesh, &previous);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ocean_loops_after_one_period() {
+        let waves = [
+            GerstnerWave::default(),
+            GerstnerWave {
+                direction: Vec2::new(1.0, 2.0),
+                wavelength: 2.5,
+                amplitude: 0.1,
+                steepness: 0.3,
+            },
+        ];
+        let mut ocean = generate_ocean(10.0, 20, &waves);
+        let period = 8.0;
+        for time in [0.0, 1.3, 57.0] {
+            update_ocean(&mut ocean, &waves, period, time);
+            let start = positions(&ocean).to_vec();
+            update_ocean(&mut ocean, &waves, period, time + period);
+            for (start, end) in start.iter().zip(positions(&ocean)) {
+                assert!(Vec3::from(*start).distance(Vec3::from(*end)) < 1e-3);
+            }
+        }
+    }
+}
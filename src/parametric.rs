@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+
+use crate::{bounds::position_bounds, geometry::GeometryData};
+
+// how far either side of a point the surface is sampled to find its slope, as a
+// fraction of the UV domain
+const STEP: f32 = 1e-3;
+
+/// Surface traced out by `f` over the unit square of UV space, sampled at
+/// `u_segments` by `v_segments` cells, for shapes no other generator covers: a Möbius
+/// strip, a seashell, a surface of revolution for a vase.
+///
+/// Each vertex sits at `f(u, v)` and keeps that `u` and `v` as its UVs. Normals come
+/// from how `f` changes around the vertex, sampled a small step either way, so they
+/// follow the curved surface rather than the flat triangles standing in for it. The
+/// front faces the way `dv × du` points, which is up for `f(u, v) = Vec3::new(u, 0.0, v)`
+/// and out for a sphere with U going around it and V running from the top pole down;
+/// swap `u` and `v` in `f` to turn a surface inside out. Where a whole edge of the square
+/// collapses to one point, like the poles of a sphere, the normal there is taken just
+/// inside it, and triangles with two corners in exactly the same place are left out.
+///
+/// A closed surface meets itself along the edges of the square, where the vertices at
+/// each end of the seam land in nearly the same place but keep their own UVs. With
+/// `weld_seams`, every vertex on an edge that meets another is moved exactly onto it
+/// and they share their normal, so the shading is smooth across the seam and the mesh
+/// is watertight welded by position. Without it the edges are left as `f` puts them.
+/// At least one segment is used each way.
+pub fn generate_parametric_surface(
+    u_segments: u32,
+    v_segments: u32,
+    weld_seams: bool,
+    f: impl Fn(f32, f32) -> Vec3,
+) -> Mesh {
+    let (u_segments, v_segments) = (u_segments.max(1), v_segments.max(1));
+    let columns = u_segments + 1;
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    for j in 0..=v_segments {
+        for i in 0..=u_segments {
+            let (u, v) = (i as f32 / u_segments as f32, j as f32 / v_segments as f32);
+            positions.push(f(u, v).into());
+            normals.push(surface_normal(&f, u, v).into());
+            uvs.push([u, v]);
+        }
+    }
+
+    if weld_seams {
+        let edge: Vec<usize> = (0..positions.len())
+            .filter(|&k| {
+                let (i, j) = (k as u32 % columns, k as u32 / columns);
+                i == 0 || i == u_segments || j == 0 || j == v_segments
+            })
+            .collect();
+        weld_edge(&mut positions, &mut normals, &edge);
+    }
+
+    //  (i,j+1)  (i+1,j+1)
+    //     d ------ c
+    //     |      / |
+    //     |    /   |
+    //     a ------ b
+    //   (i,j)   (i+1,j)
+    let mut indices: Vec<u32> = vec![];
+    for j in 0..v_segments {
+        for i in 0..u_segments {
+            let a = j * columns + i;
+            let (b, c, d) = (a + 1, a + columns + 1, a + columns);
+            for tri in [[a, c, b], [a, d, c]] {
+                let [p, q, r] = tri.map(|v| positions[v as usize]);
+                if p != q && q != r && r != p {
+                    indices.extend(tri);
+                }
+            }
+        }
+    }
+
+    GeometryData {
+        positions,
+        normals,
+        uvs,
+        indices,
+    }
+    .into_mesh()
+}
+
+// the unit normal of the surface at (u, v), from its slope along U and V, stepping
+// towards the middle of the square where the slope vanishes, such as at a pole
+fn surface_normal(f: &impl Fn(f32, f32) -> Vec3, u: f32, v: f32) -> Vec3 {
+    let slope = |u: f32, v: f32| {
+        let du = f((u + STEP).min(1.0), v) - f((u - STEP).max(0.0), v);
+        let dv = f(u, (v + STEP).min(1.0)) - f(u, (v - STEP).max(0.0));
+        // next to nothing compared with the slopes means one of them has vanished,
+        // whatever rounding has left of it
+        let normal = dv.cross(du);
+        let scale = (du.length() + dv.length()).powi(2);
+        (normal.length() > 1e-6 * scale).then(|| normal.normalize())
+    };
+    slope(u, v)
+        .or_else(|| slope(u + (0.5 - u) * STEP * 10.0, v + (0.5 - v) * STEP * 10.0))
+        .unwrap_or(Vec3::ZERO)
+}
+
+// moves the vertices in `edge` that are nearly in the same place onto the first of
+// them, giving each group the average of their normals
+fn weld_edge(positions: &mut [[f32; 3]], normals: &mut [[f32; 3]], edge: &[usize]) {
+    let Some((min, max)) = position_bounds(positions) else {
+        return;
+    };
+    let tolerance = 1e-4 * (max - min).max_element();
+
+    let mut welded = vec![false; edge.len()];
+    for first in 0..edge.len() {
+        if welded[first] {
+            continue;
+        }
+        let at = Vec3::from(positions[edge[first]]);
+        let group: Vec<usize> = (first..edge.len())
+            .filter(|&k| !welded[k] && Vec3::from(positions[edge[k]]).distance(at) <= tolerance)
+            .collect();
+        let normal: Vec3 = group.iter().map(|&k| Vec3::from(normals[edge[k]])).sum();
+        let normal = normal.normalize_or_zero();
+        for k in group {
+            welded[k] = true;
+            positions[edge[k]] = at.into();
+            normals[edge[k]] = normal.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{PI, TAU};
+
+    use super::*;
+    use crate::{
+        attributes::{normals, triangles},
+        topology::is_watertight,
+        util::positions,
+    };
+
+    #[test]
+    fn sphere_parametrization_gives_a_sphere() {
+        let radius = 2.0;
+        let sphere = generate_parametric_surface(32, 16, true, |u, v| {
+            let (azimuth, polar) = (u * TAU, v * PI);
+            Vec3::new(
+                polar.sin() * azimuth.cos(),
+                polar.cos(),
+                -polar.sin() * azimuth.sin(),
+            ) * radius
+        });
+
+        for (position, normal) in positions(&sphere).iter().zip(normals(&sphere).unwrap()) {
+            let (position, normal) = (Vec3::from(*position), Vec3::from(*normal));
+            assert!((position.length() - radius).abs() < 1e-4, "{position}");
+            assert!(
+                normal.dot(position / radius) > 0.999,
+                "{position}: {normal}"
+            );
+        }
+        assert!(triangles(&sphere).all(|[a, b, c]| (b - a).cross(c - a).dot(a + b + c) > 0.0));
+        assert!(is_watertight(&sphere));
+    }
+}
@@ -0,0 +1,297 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+use crate::error::{positive, GeometryError};
+
+/// Flat strip `width` wide following `points`, such as a motion trail or a path drawn
+/// over terrain, with its front facing as close to `up` as the path allows.
+///
+/// Every point gets a vertex either side of it, offset at right angles to the direction
+/// of travel. U runs across the strip and V along it, in multiples of `width` so a
+/// square texture keeps its shape and can be scrolled along the path.
+///
+/// Fails with [`GeometryError::EmptyInput`] with fewer than two points, and with
+/// [`GeometryError::InvalidParameter`] unless `width` is positive.
+pub fn generate_ribbon(points: &[Vec3], width: f32, up: Vec3) -> Result<Mesh, GeometryError> {
+    if points.len() < 2 {
+        return Err(GeometryError::EmptyInput);
+    }
+    positive("width", width)?;
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    let mut side = up.any_orthonormal_vector();
+    let mut travelled = 0.0;
+
+    for (i, point) in points.iter().enumerate() {
+        let prev = points[i.saturating_sub(1)];
+        let next = points[(i + 1).min(points.len() - 1)];
+        let direction = (next - prev).normalize_or_zero();
+
+        // keep the last good side where the path doubles back on itself or runs
+        // straight along `up`
+        if let Some(new_side) = direction.cross(up).try_normalize() {
+            side = new_side;
+        }
+        let normal = side.cross(direction).try_normalize().unwrap_or(up);
+
+        if i > 0 {
+            travelled += point.distance(prev);
+        }
+        let v = travelled / width;
+
+        for (offset, u) in [(-0.5, 0.0), (0.5, 1.0)] {
+            positions.push((*point + side * width * offset).into());
+            normals.push(normal.into());
+            uvs.push([u, v]);
+        }
+    }
+
+    //  left     right
+    //  2i+2 --- 2i+3
+    //    |   \    |
+    //    2i --- 2i+1
+    for i in 0..points.len() as u32 - 1 {
+        let (left, right) = (i * 2, i * 2 + 1);
+        let (next_left, next_right) = (left + 2, right + 2);
+        indices.extend([left, right, next_left, next_left, right, next_right]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    Ok(mesh)
+}
+
+/// [`generate_ribbon`] with vertex colors from `ramp`, which is given how far along the
+/// path each point is, from 0.0 at the first to 1.0 at the last, for gradient trails
+/// and rainbow ribbons without a texture.
+///
+/// The distance is measured along the path rather than counted in points, so unevenly
+/// spaced points don't bunch the gradient up. Colors are stored in linear space, like
+/// the ones Bevy keeps, to be multiplied with the material's color.
+pub fn generate_ribbon_with_colors(
+    points: &[Vec3],
+    width: f32,
+    up: Vec3,
+    ramp: impl Fn(f32) -> Color,
+) -> Result<Mesh, GeometryError> {
+    let mut mesh = generate_ribbon(points, width, up)?;
+    let colors = ramp_colors(&distances_along(points, false), 2, ramp);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    Ok(mesh)
+}
+
+// around the tube of a torus knot
+const TUBE_SIDES: u32 = 16;
+
+/// `segments` evenly spaced points along the (`p`, `q`) torus knot around the Y axis,
+/// in order around the loop, for placing objects along the knot or sweeping other
+/// shapes along it. The last point leads back to the first.
+///
+/// The knot winds `p` times around the Y axis and `q` times through the hole of a
+/// torus `radius` across from its center to the middle of its tube, whose own radius
+/// is half that. `p` and `q` should have no common factor; otherwise the curve
+/// retraces itself, and (2, 3) is the trefoil.
+pub fn torus_knot_points(p: u32, q: u32, radius: f32, segments: u32) -> Vec<Vec3> {
+    let (p, q) = (p.max(1) as f32, q.max(1) as f32);
+    let segments = segments.max(3);
+    (0..segments)
+        .map(|i| {
+            let angle = i as f32 / segments as f32 * TAU;
+            let around = radius * (1.0 + 0.5 * (q * angle).cos());
+            Vec3::new(
+                around * (p * angle).cos(),
+                radius * 0.5 * (q * angle).sin(),
+                around * (p * angle).sin(),
+            )
+        })
+        .collect()
+}
+
+/// A tube `tube_radius` thick around [`torus_knot_points`], closed on itself and
+/// smooth shaded all the way around.
+///
+/// The tube's cross-section is carried along the curve by parallel transport, so it
+/// doesn't twist as the knot loops through 3D, and the small twist that still builds
+/// up by the time it gets back to the start is spread evenly along the loop so the
+/// ends meet. U runs along the knot and V around the tube, with a seam of duplicate
+/// vertices at the start of the loop and one around the tube; welded by position, the
+/// tube is watertight.
+pub fn generate_torus_knot(p: u32, q: u32, radius: f32, tube_radius: f32, segments: u32) -> Mesh {
+    let points = torus_knot_points(p, q, radius, segments);
+    let count = points.len();
+    let tangents: Vec<Vec3> = (0..count)
+        .map(|i| (points[(i + 1) % count] - points[(i + count - 1) % count]).normalize())
+        .collect();
+
+    // the tube's "up" at each point, each one the last turned to follow the curve
+    let mut sides = vec![tangents[0].any_orthonormal_vector()];
+    for i in 1..count {
+        let turn = Quat::from_rotation_arc(tangents[i - 1], tangents[i]);
+        sides.push((turn * sides[i - 1]).normalize());
+    }
+    let back_at_start =
+        Quat::from_rotation_arc(tangents[count - 1], tangents[0]) * sides[count - 1];
+    let twist = tangents[0]
+        .dot(sides[0].cross(back_at_start))
+        .atan2(sides[0].dot(back_at_start));
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    for i in 0..=count {
+        let k = i % count;
+        let untwist = Quat::from_axis_angle(tangents[k], -twist * k as f32 / count as f32);
+        let side = untwist * sides[k];
+        let other_side = tangents[k].cross(side);
+        for j in 0..=TUBE_SIDES {
+            // the last copy of the seam lands exactly on the first
+            let angle = (j % TUBE_SIDES) as f32 / TUBE_SIDES as f32 * TAU;
+            let normal = side * angle.cos() + other_side * angle.sin();
+            positions.push((points[k] + normal * tube_radius).into());
+            normals.push(normal.into());
+            uvs.push([i as f32 / count as f32, j as f32 / TUBE_SIDES as f32]);
+        }
+    }
+
+    //  (i,j+1)  (i+1,j+1)
+    //     d ------ c
+    //     | \      |
+    //     |   \    |
+    //     a ------ b
+    //   (i,j)   (i+1,j)
+    let ring = TUBE_SIDES + 1;
+    let mut indices: Vec<u32> = vec![];
+    for i in 0..count as u32 {
+        for j in 0..TUBE_SIDES {
+            let a = i * ring + j;
+            let (b, c, d) = (a + ring, a + ring + 1, a + 1);
+            indices.extend([a, d, b, b, d, c]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// [`generate_torus_knot`] with vertex colors from `ramp`, which is given how far each
+/// ring of the tube is along the knot, from 0.0 at the start of the loop to 1.0 back
+/// at it, for heat-colored pipes and the like.
+///
+/// The seam at the start of the loop gets `ramp(0.0)` on one side and `ramp(1.0)` on
+/// the other, so a ramp that doesn't end where it starts shows a hard edge there.
+/// Colors are stored in linear space, like the ones Bevy keeps.
+pub fn generate_torus_knot_with_colors(
+    p: u32,
+    q: u32,
+    radius: f32,
+    tube_radius: f32,
+    segments: u32,
+    ramp: impl Fn(f32) -> Color,
+) -> Mesh {
+    let mut mesh = generate_torus_knot(p, q, radius, tube_radius, segments);
+    let points = torus_knot_points(p, q, radius, segments);
+    let colors = ramp_colors(
+        &distances_along(&points, true),
+        TUBE_SIDES as usize + 1,
+        ramp,
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh
+}
+
+// how far along the path each point is as a fraction of its length, with the first
+// point repeated at the end of a closed one; points are counted instead when they're
+// all in the same place
+fn distances_along(points: &[Vec3], closed: bool) -> Vec<f32> {
+    let mut path = points.to_vec();
+    if closed {
+        path.extend(points.first());
+    }
+    let mut travelled = vec![0.0];
+    for pair in path.windows(2) {
+        travelled.push(travelled[travelled.len() - 1] + pair[0].distance(pair[1]));
+    }
+    let length = travelled[travelled.len() - 1];
+    let last = (travelled.len() - 1).max(1) as f32;
+    travelled
+        .iter()
+        .enumerate()
+        .map(|(i, along)| {
+            if length > f32::EPSILON {
+                along / length
+            } else {
+                i as f32 / last
+            }
+        })
+        .collect()
+}
+
+// `per_point` vertices colored by `ramp` for each point along the path
+fn ramp_colors(along: &[f32], per_point: usize, ramp: impl Fn(f32) -> Color) -> Vec<[f32; 4]> {
+    along
+        .iter()
+        .flat_map(|t| vec![ramp(*t).as_linear_rgba_f32(); per_point])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::colors,
+        bounds::volume,
+        topology::{check_consistent_winding, is_watertight, vertex_distances_from, weld_vertices},
+    };
+
+    #[test]
+    fn trefoil_knot_is_one_closed_tube() {
+        let mut knot = generate_torus_knot(2, 3, 1.0, 0.15, 200);
+        assert!(is_watertight(&knot));
+        assert_eq!(check_consistent_winding(&knot), Ok(()));
+        assert!(volume(&knot) > 0.0);
+
+        // joined up along the seams, every vertex can be reached from the first
+        weld_vertices(&mut knot, 5);
+        assert!(vertex_distances_from(&knot, 0)
+            .iter()
+            .all(|d| d.is_finite()));
+    }
+
+    #[test]
+    fn color_ramps_run_from_start_to_end_of_the_path() {
+        let ramp = |t: f32| Color::rgb(t, 1.0 - t, 0.5);
+        let (start, end) = (
+            ramp(0.0).as_linear_rgba_f32(),
+            ramp(1.0).as_linear_rgba_f32(),
+        );
+
+        let points = [Vec3::ZERO, Vec3::X, Vec3::new(3.0, 0.0, 1.0)];
+        let ribbon = generate_ribbon_with_colors(&points, 0.5, Vec3::Y, ramp).unwrap();
+        let shades = colors(&ribbon).unwrap();
+        assert_eq!(shades.len(), points.len() * 2);
+        assert_eq!(shades[..2], [start; 2]);
+        assert_eq!(shades[shades.len() - 2..], [end; 2]);
+
+        let knot = generate_torus_knot_with_colors(2, 3, 1.0, 0.15, 64, ramp);
+        let shades = colors(&knot).unwrap();
+        let ring = TUBE_SIDES as usize + 1;
+        assert_eq!(shades.len(), knot.count_vertices());
+        assert!(shades[..ring].iter().all(|c| *c == start));
+        assert!(shades[shades.len() - ring..].iter().all(|c| *c == end));
+    }
+}
@@ -0,0 +1,607 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    utils::HashMap,
+};
+
+use crate::normals::NormalMode;
+
+/// Which point of a plane or grid sits on the origin.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Anchor {
+    #[default]
+    Center,
+    /// the corner with the lowest X and Z, so the whole plane has positive coordinates,
+    /// for lining up with tilemaps and other grids that start at zero
+    Corner,
+    /// a point given as a fraction of the plane's extent along X and Z, so `(0.5, 0.5)`
+    /// is the center and `(0.0, 0.0)` the corner
+    Custom(Vec2),
+}
+
+impl Anchor {
+    // where the corner with the lowest X and Z ends up, for a plane `size` across
+    pub(crate) fn min_corner(self, size: Vec2) -> Vec2 {
+        let fraction = match self {
+            Anchor::Center => Vec2::splat(0.5),
+            Anchor::Corner => Vec2::ZERO,
+            Anchor::Custom(fraction) => fraction,
+        };
+        -fraction * size
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RadialLodPlaneParams {
+    /// side length of the square plane
+    pub size: f32,
+    /// cells across the plane at the finest level of detail, in the middle
+    pub center_res: u32,
+    /// cells across the plane at the coarsest level of detail, at the edges
+    pub edge_res: u32,
+    /// how many times a texture repeats across the plane, so UVs run 0..`uv_tiles`
+    pub uv_tiles: f32,
+    /// which point of the plane is at the origin; UVs don't depend on it
+    pub anchor: Anchor,
+}
+
+impl Default for RadialLodPlaneParams {
+    fn default() -> Self {
+        RadialLodPlaneParams {
+            size: 10.0,
+            center_res: 64,
+            edge_res: 4,
+            uv_tiles: 1.0,
+            anchor: Anchor::Center,
+        }
+    }
+}
+
+/// Flat square on the XZ plane, placed on the origin by `anchor` and facing +Y, whose
+/// cells are smallest in the middle and double in size with each step out towards the
+/// edges.
+///
+/// Meant for a terrain tile that follows the player, keeping vertices where they're
+/// seen. Both resolutions are rounded up to powers of two. Neighboring cells never
+/// differ by more than one level, and a coarse cell next to finer ones is fanned from
+/// its center to meet their extra vertices, so there are no T-junctions to crack open
+/// once the plane is displaced. UVs span 0..`uv_tiles` across the whole plane, like
+/// [`generate_heightmap`](crate::terrain::generate_heightmap).
+pub fn generate_radial_lod_plane(params: &RadialLodPlaneParams) -> Mesh {
+    let max_depth = params
+        .center_res
+        .max(1)
+        .next_power_of_two()
+        .trailing_zeros();
+    let min_depth = params
+        .edge_res
+        .max(1)
+        .next_power_of_two()
+        .trailing_zeros()
+        .min(max_depth);
+    // cells are addressed in units of the finest cell, so every vertex has integer
+    // coordinates
+    let finest = 1_u32 << max_depth;
+
+    let mut leaves = vec![];
+    split_by_distance(Cell::ROOT, min_depth, max_depth, &mut leaves);
+
+    // the depth of the leaf covering each finest cell, used to find neighbors
+    let mut depth_at = vec![0; (finest * finest) as usize];
+    let cover = |depth_at: &mut Vec<u32>, cell: Cell| {
+        let span = cell.span(max_depth);
+        for z in cell.z * span..(cell.z + 1) * span {
+            for x in cell.x * span..(cell.x + 1) * span {
+                depth_at[(z * finest + x) as usize] = cell.depth;
+            }
+        }
+    };
+    for cell in &leaves {
+        cover(&mut depth_at, *cell);
+    }
+
+    let neighbor_depth = |depth_at: &[u32], x: i64, z: i64| {
+        let inside = (0..finest as i64).contains(&x) && (0..finest as i64).contains(&z);
+        inside.then(|| depth_at[(z as u32 * finest + x as u32) as usize])
+    };
+
+    // split any leaf with a neighbor more than one level finer, until there are none
+    let mut balanced = false;
+    while !balanced {
+        balanced = true;
+        let mut next = vec![];
+        for cell in leaves {
+            let too_coarse = cell.outside(max_depth).into_iter().flatten().any(|(x, z)| {
+                neighbor_depth(&depth_at, x, z).map_or(false, |d| d > cell.depth + 1)
+            });
+            if too_coarse {
+                balanced = false;
+                for child in cell.children() {
+                    cover(&mut depth_at, child);
+                    next.push(child);
+                }
+            } else {
+                next.push(cell);
+            }
+        }
+        leaves = next;
+    }
+
+    let min = params.anchor.min_corner(Vec2::splat(params.size));
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut vertices: HashMap<(u32, u32), u32> = HashMap::new();
+    let mut vertex = |x: u32, z: u32| {
+        *vertices.entry((x, z)).or_insert_with(|| {
+            let (u, v) = (x as f32 / finest as f32, z as f32 / finest as f32);
+            positions.push([min.x + u * params.size, 0.0, min.y + v * params.size]);
+            uvs.push([u * params.uv_tiles, v * params.uv_tiles]);
+            positions.len() as u32 - 1
+        })
+    };
+
+    let indices = triangulate_leaves(
+        &leaves,
+        max_depth,
+        |x, z| neighbor_depth(&depth_at, x, z),
+        &mut vertex,
+    );
+
+    let normals = vec![[0.0, 1.0, 0.0]; positions.len()];
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+/// The demo's plane: four square tiles `side / 2` across around a vertex raised to
+/// `center_height`, each split along the diagonal through it, so the plane rises to a
+/// four-sided peak. Seen from above, with +X to the right and -Z up:
+///
+/// ```text
+/// +-----+-----+
+/// | \   |   / |
+/// |   \ | /   |
+/// +-----+-----+
+/// |   / | \   |
+/// | /   |   \ |
+/// +-----+-----+
+/// ```
+///
+/// The peak is at `(side / 4, center_height, -side / 4)`, with the first tile centered
+/// on the origin and the rest placed around it towards +X and -Z. UVs come from world
+/// X and Z alone, V along X and U along Z with `-side / 2` at zero, so every copy of a
+/// corner gets the same UV and the texture lines up across the tiles. The tiles don't
+/// share vertices, and since every face is flat, [`NormalMode::Analytic`] gives the
+/// same flat normals as [`NormalMode::Flat`].
+pub fn generate_diamond_plane(side: f32, center_height: f32, normals: NormalMode) -> Mesh {
+    let step = side / 4.0;
+    let shift = step * 2.0;
+    let uv = |c: f32| (c + side / 2.0) / side;
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    // two triangles meeting along the diagonal from `a` to `c`
+    let mut tile = |a: Vec3, b: Vec3, c: Vec3, d: Vec3| {
+        for corner in [a, b, c, c, d, a] {
+            positions.push(corner.into());
+            uvs.push([uv(corner.z), uv(corner.x)]);
+        }
+    };
+
+    let mut a = Vec3::new(step, center_height, -step);
+    let mut b = Vec3::new(-step, 0.0, -step);
+    let mut c = Vec3::new(-step, 0.0, step);
+    let mut d = Vec3::new(step, 0.0, step);
+    tile(a, b, c, d);
+
+    // each next tile over hands the peak on to whichever of its corners sits where
+    // the first tile's `a` did
+    for corner in [&mut a, &mut b, &mut c, &mut d] {
+        corner.x += shift;
+    }
+    a.y -= center_height;
+    b.y += center_height;
+    tile(b, c, d, a);
+
+    for corner in [&mut a, &mut b, &mut c, &mut d] {
+        corner.z -= shift;
+    }
+    b.y -= center_height;
+    c.y += center_height;
+    tile(a, b, c, d);
+
+    for corner in [&mut a, &mut b, &mut c, &mut d] {
+        corner.x -= shift;
+    }
+    c.y -= center_height;
+    d.y += center_height;
+    tile(b, c, d, a);
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.compute_flat_normals();
+    if normals != NormalMode::Flat {
+        normals.apply(&mut mesh);
+    }
+    mesh
+}
+
+/// How [`generate_grid_strip`] joins the strip of one row of cells to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StripJoin {
+    /// a primitive restart index, `u32::MAX`, between rows. wgpu only restarts the
+    /// strip there for a pipeline whose `strip_index_format` is `Uint32`, which Bevy's
+    /// own mesh pipelines leave unset, so this needs a material that sets it when
+    /// specializing its pipeline. glTF forbids restart indices too, so use
+    /// [`StripJoin::Degenerate`] for meshes drawn with `StandardMaterial` or exported
+    #[default]
+    Restart,
+    /// the last vertex of each row and the first of the next repeated, making four
+    /// zero-area triangles per join that every GPU skips over, at two more indices per
+    /// row than a restart
+    Degenerate,
+}
+
+/// Flat grid of `rows` by `cols` square cells centered on the origin and facing +Y,
+/// as a single indexed triangle strip, which takes about a third of the indices of
+/// the same grid as a triangle list.
+///
+/// Each row of cells along X is its own run of the strip, split into triangles the
+/// same way as [`generate_heightmap`](crate::terrain::generate_heightmap)'s, with rows
+/// separated as `join` says. UVs span 0..1 across the grid with V along Z. The rest of
+/// the crate only works on triangle lists, so use this for meshes that go straight
+/// to rendering.
+pub fn generate_grid_strip(rows: u32, cols: u32, cell_size: f32, join: StripJoin) -> Mesh {
+    let (rows, cols) = (rows.max(1), cols.max(1));
+    let min = -Vec2::new(cols as f32, rows as f32) * cell_size / 2.0;
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    for row in 0..=rows {
+        for col in 0..=cols {
+            let offset = Vec2::new(col as f32, row as f32) * cell_size;
+            positions.push([min.x + offset.x, 0.0, min.y + offset.y]);
+            uvs.push([col as f32 / cols as f32, row as f32 / rows as f32]);
+        }
+    }
+
+    let vertex = |row: u32, col: u32| row * (cols + 1) + col;
+    let mut indices: Vec<u32> = vec![];
+    for row in 0..rows {
+        if row > 0 {
+            match join {
+                StripJoin::Restart => indices.push(u32::MAX),
+                // a pair keeps the next row starting on an even index, so its triangles
+                // wind the same way as the first row's
+                StripJoin::Degenerate => indices.extend([vertex(row, cols), vertex(row, 0)]),
+            }
+        }
+        // the top then the bottom of each column in turn, seen from above with -Z up
+        for col in 0..=cols {
+            indices.extend([vertex(row, col), vertex(row + 1, col)]);
+        }
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleStrip);
+    mesh.insert_attribute(
+        Mesh::ATTRIBUTE_NORMAL,
+        vec![[0.0, 1.0, 0.0]; positions.len()],
+    );
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+// the triangles of quadtree `leaves` that never differ by more than a level from their
+// neighbors, with `depth_at` giving the depth of the leaf over a finest cell (or `None`
+// outside the tree) and `vertex` the index of a point in finest cells, adding it if
+// it's new. A leaf next to finer ones is fanned from its center to meet their extra
+// vertices, so there are no T-junctions to crack open once the grid is displaced
+pub(crate) fn triangulate_leaves(
+    leaves: &[Cell],
+    max_depth: u32,
+    depth_at: impl Fn(i64, i64) -> Option<u32>,
+    mut vertex: impl FnMut(u32, u32) -> u32,
+) -> Vec<u32> {
+    let mut indices: Vec<u32> = vec![];
+    for cell in leaves {
+        let span = cell.span(max_depth);
+        let (x0, z0) = (cell.x * span, cell.z * span);
+        let (x1, z1) = (x0 + span, z0 + span);
+
+        //  (x0,z0)  (x1,z0)
+        //     a ------ d
+        //     |      / |
+        //     |    /   |
+        //     |  /     |
+        //     b ------ c
+        //  (x0,z1)  (x1,z1)
+        let corners = [(x0, z0), (x0, z1), (x1, z1), (x1, z0)];
+        let finer_sides: Vec<bool> = cell
+            .outside(max_depth)
+            .iter()
+            .map(|[(x, z), _]| depth_at(*x, *z).map_or(false, |depth| depth > cell.depth))
+            .collect();
+
+        if !finer_sides.contains(&true) {
+            let [a, b, c, d] = corners.map(|(x, z)| vertex(x, z));
+            indices.extend([a, b, d, d, b, c]);
+            continue;
+        }
+
+        // counter-clockwise seen from above, picking up the midpoint of each side that
+        // a finer neighbor has a vertex on
+        let mut ring = vec![];
+        for (side, &(x, z)) in corners.iter().enumerate() {
+            ring.push(vertex(x, z));
+            if finer_sides[side] {
+                let (next_x, next_z) = corners[(side + 1) % 4];
+                ring.push(vertex((x + next_x) / 2, (z + next_z) / 2));
+            }
+        }
+        let center = vertex(x0 + span / 2, z0 + span / 2);
+        for i in 0..ring.len() {
+            indices.extend([center, ring[i], ring[(i + 1) % ring.len()]]);
+        }
+    }
+    indices
+}
+
+// a quadtree cell, `2^depth` of which fit across the plane
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct Cell {
+    pub(crate) depth: u32,
+    pub(crate) x: u32,
+    pub(crate) z: u32,
+}
+
+impl Cell {
+    pub(crate) const ROOT: Cell = Cell {
+        depth: 0,
+        x: 0,
+        z: 0,
+    };
+
+    // width in finest cells
+    pub(crate) fn span(self, max_depth: u32) -> u32 {
+        1 << (max_depth - self.depth)
+    }
+
+    pub(crate) fn children(self) -> [Cell; 4] {
+        let (x, z) = (self.x * 2, self.z * 2);
+        [(x, z), (x + 1, z), (x, z + 1), (x + 1, z + 1)].map(|(x, z)| Cell {
+            depth: self.depth + 1,
+            x,
+            z,
+        })
+    }
+
+    // the finest cells just outside each side, next to either end of it, in the same
+    // order as the corners: -X, +Z, +X, -Z
+    pub(crate) fn outside(self, max_depth: u32) -> [[(i64, i64); 2]; 4] {
+        let span = self.span(max_depth) as i64;
+        let (x0, z0) = (self.x as i64 * span, self.z as i64 * span);
+        let (x1, z1) = (x0 + span - 1, z0 + span - 1);
+        [
+            [(x0 - 1, z0), (x0 - 1, z1)],
+            [(x0, z1 + 1), (x1, z1 + 1)],
+            [(x1 + 1, z1), (x1 + 1, z0)],
+            [(x1, z0 - 1), (x0, z0 - 1)],
+        ]
+    }
+}
+
+// a cell is split when it's no more than its own width from the plane's center, so
+// each level of detail makes a ring two of its cells wide
+fn split_by_distance(cell: Cell, min_depth: u32, max_depth: u32, leaves: &mut Vec<Cell>) {
+    // in units of this cell's width
+    let half = (1_u32 << cell.depth) as f32 / 2.0;
+    let gap = |start: u32| {
+        (start as f32 - half)
+            .max(half - (start + 1) as f32)
+            .max(0.0)
+    };
+    let distance = gap(cell.x).max(gap(cell.z));
+
+    if cell.depth < min_depth || (cell.depth < max_depth && distance <= 1.0) {
+        for child in cell.children() {
+            split_by_distance(child, min_depth, max_depth, leaves);
+        }
+    } else {
+        leaves.push(cell);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::{positions, uvs},
+        bounds::position_bounds,
+        terrain::{generate_heightmap, HeightmapParams},
+        topology::{find_boundary_edges, find_t_junctions},
+    };
+
+    #[test]
+    fn diamond_plane_uvs_match_at_shared_positions() {
+        let mesh = generate_diamond_plane(10.0, 2.5, NormalMode::Flat);
+        let (positions, uvs) = (positions(&mesh).unwrap(), uvs(&mesh).unwrap());
+
+        let mut seen: HashMap<[u32; 3], [f32; 2]> = HashMap::new();
+        for (position, uv) in positions.iter().zip(uvs) {
+            let first = *seen.entry(position.map(f32::to_bits)).or_insert(*uv);
+            assert_eq!(first, *uv, "{position:?}");
+        }
+        // the tiles only meet by position, so there has to be something to compare
+        assert!(seen.len() < positions.len());
+    }
+
+    // what the demo's `setup_plane` built tile by tile before the generator replaced it
+    #[test]
+    fn diamond_plane_matches_the_old_demo_tiles() {
+        let expected: [([f32; 3], [f32; 2]); 24] = [
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([-2.5, 0.0, -2.5], [0.25, 0.25]),
+            ([-2.5, 0.0, 2.5], [0.75, 0.25]),
+            ([-2.5, 0.0, 2.5], [0.75, 0.25]),
+            ([2.5, 0.0, 2.5], [0.75, 0.75]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([2.5, 0.0, 2.5], [0.75, 0.75]),
+            ([7.5, 0.0, 2.5], [0.75, 1.25]),
+            ([7.5, 0.0, 2.5], [0.75, 1.25]),
+            ([7.5, 0.0, -2.5], [0.25, 1.25]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([7.5, 0.0, -7.5], [-0.25, 1.25]),
+            ([2.5, 0.0, -7.5], [-0.25, 0.75]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([7.5, 0.0, -2.5], [0.25, 1.25]),
+            ([7.5, 0.0, -7.5], [-0.25, 1.25]),
+            ([-2.5, 0.0, -7.5], [-0.25, 0.25]),
+            ([-2.5, 0.0, -2.5], [0.25, 0.25]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([2.5, 2.5, -2.5], [0.25, 0.75]),
+            ([2.5, 0.0, -7.5], [-0.25, 0.75]),
+            ([-2.5, 0.0, -7.5], [-0.25, 0.25]),
+        ];
+
+        let mesh = generate_diamond_plane(10.0, 2.5, NormalMode::Flat);
+        let (positions, uvs) = (positions(&mesh).unwrap(), uvs(&mesh).unwrap());
+        assert_eq!(positions.len(), expected.len());
+        for ((position, uv), (expected_position, expected_uv)) in
+            positions.iter().zip(uvs).zip(expected)
+        {
+            assert_eq!(*position, expected_position);
+            assert_eq!(*uv, expected_uv);
+        }
+    }
+
+    #[test]
+    fn corner_anchor_puts_the_plane_min_at_the_origin() {
+        let centered = generate_radial_lod_plane(&RadialLodPlaneParams::default());
+        let cornered = generate_radial_lod_plane(&RadialLodPlaneParams {
+            anchor: Anchor::Corner,
+            ..default()
+        });
+        let (min, max) = position_bounds(positions(&cornered).unwrap()).unwrap();
+        assert!(min.abs_diff_eq(Vec3::ZERO, 1e-5));
+        assert!(max.abs_diff_eq(Vec3::new(10.0, 0.0, 10.0), 1e-4));
+        assert_eq!(uvs(&cornered), uvs(&centered));
+
+        let grid = generate_heightmap(
+            &HeightmapParams {
+                anchor: Anchor::Corner,
+                ..default()
+            },
+            |_, _| 0.0,
+        );
+        let (min, _) = position_bounds(positions(&grid).unwrap()).unwrap();
+        assert!(min.abs_diff_eq(Vec3::ZERO, 1e-5));
+    }
+
+    // read as one strip, the degenerate joins should leave just the grid's own cells, all
+    // facing up, with nothing drawn twice
+    #[test]
+    fn degenerate_joins_make_one_continuous_strip() {
+        let strip = generate_grid_strip(5, 7, 0.5, StripJoin::Degenerate);
+        let Some(Indices::U32(indices)) = strip.indices() else {
+            panic!("strips are indexed with u32");
+        };
+        assert!(!indices.contains(&u32::MAX));
+
+        let positions = positions(&strip).unwrap();
+        let mut cells = vec![];
+        let mut area = 0.0;
+        for (k, window) in indices.windows(3).enumerate() {
+            let tri = if k % 2 == 0 {
+                [window[0], window[1], window[2]]
+            } else {
+                [window[1], window[0], window[2]]
+            };
+            if tri[0] == tri[1] || tri[1] == tri[2] || tri[0] == tri[2] {
+                continue;
+            }
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v as usize]));
+            let normal = (b - a).cross(c - a);
+            assert!(normal.normalize().abs_diff_eq(Vec3::Y, 1e-6));
+            area += normal.length() / 2.0;
+            let mut sorted = tri;
+            sorted.sort_unstable();
+            cells.push(sorted);
+        }
+        assert_eq!(cells.len(), 5 * 7 * 2);
+        cells.sort_unstable();
+        cells.dedup();
+        assert_eq!(cells.len(), 5 * 7 * 2);
+        assert!((area - 3.5 * 2.5).abs() < 1e-4);
+    }
+
+    // the only open edges of a crack-free plane are the ones around its outside
+    #[test]
+    fn radial_lod_plane_has_no_cracks_between_levels() {
+        for (center_res, edge_res) in [(64, 4), (16, 1), (32, 16), (8, 8), (5, 3)] {
+            let params = RadialLodPlaneParams {
+                size: 4.0,
+                center_res,
+                edge_res,
+                ..default()
+            };
+            let plane = generate_radial_lod_plane(&params);
+            assert!(
+                find_t_junctions(&plane).is_empty(),
+                "{center_res} {edge_res}"
+            );
+
+            let positions = positions(&plane).unwrap();
+            let half = params.size / 2.0;
+            let on_side = |p: Vec3, side: usize| (p[side].abs() - half).abs() < 1e-5;
+            let mut perimeter = 0.0;
+            for (a, b) in find_boundary_edges(&plane) {
+                let [a, b] = [a, b].map(|v| Vec3::from(positions[v as usize]));
+                let side = [0, 2]
+                    .into_iter()
+                    .find(|&side| on_side(a, side) && on_side(b, side) && a[side] == b[side]);
+                assert!(side.is_some(), "{center_res} {edge_res}: {a} {b}");
+                perimeter += a.distance(b);
+            }
+            assert!((perimeter - 4.0 * params.size).abs() < 1e-4, "{perimeter}");
+        }
+    }
+
+    #[test]
+    fn tiling_puts_the_far_corner_uv_at_the_tile_count() {
+        let params = RadialLodPlaneParams {
+            center_res: 8,
+            edge_res: 2,
+            uv_tiles: 3.0,
+            ..default()
+        };
+        let plane = generate_radial_lod_plane(&params);
+        let (positions, plane_uvs) = (positions(&plane).unwrap(), uvs(&plane).unwrap());
+        let (min, max) = position_bounds(positions).unwrap();
+        for (position, uv) in positions.iter().zip(plane_uvs) {
+            let along = (Vec3::from(*position) - min).xz() / (max - min).xz();
+            let expected = along * params.uv_tiles;
+            assert!(
+                Vec2::from(*uv).distance(expected) < 1e-5,
+                "{position:?}: {uv:?}"
+            );
+        }
+        let corner = positions
+            .iter()
+            .position(|p| Vec3::from(*p) == max)
+            .unwrap();
+        assert_eq!(plane_uvs[corner], [3.0, 3.0]);
+    }
+}
@@ -0,0 +1,467 @@
+use bevy::prelude::*;
+
+use crate::{
+    builder::CustomMeshBuilder,
+    error::{positive, GeometryError},
+    normals::NormalMode,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PyramidParams {
+    /// side length of the square base
+    pub base: f32,
+    pub height: f32,
+    pub normals: NormalMode,
+}
+
+impl Default for PyramidParams {
+    fn default() -> Self {
+        PyramidParams {
+            base: 1.0,
+            height: 1.0,
+            normals: NormalMode::Flat,
+        }
+    }
+}
+
+/// Shared by the regular solids, which only differ in size.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PlatonicParams {
+    /// length of every edge
+    pub size: f32,
+    pub normals: NormalMode,
+}
+
+impl Default for PlatonicParams {
+    fn default() -> Self {
+        PlatonicParams {
+            size: 1.0,
+            normals: NormalMode::Flat,
+        }
+    }
+}
+
+/// Square-based pyramid sitting on the XZ plane, centered on the Y axis.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the base and height are
+/// positive.
+pub fn generate_pyramid(params: &PyramidParams) -> Result<Mesh, GeometryError> {
+    positive("base", params.base)?;
+    positive("height", params.height)?;
+    let half = params.base / 2.0;
+    let apex = Vec3::new(0.0, params.height, 0.0);
+
+    //  (+,-)      (+.+)
+    //    d -------- c
+    //    |          |
+    //    |   apex   |
+    //    |          |
+    //    a -------- b
+    //  (-.-)      (-.+)
+    let a = Vec3::new(-half, 0.0, -half);
+    let b = Vec3::new(-half, 0.0, half);
+    let c = Vec3::new(half, 0.0, half);
+    let d = Vec3::new(half, 0.0, -half);
+
+    let mut faces = FlatFaces::default();
+
+    for (from, to) in [(a, b), (b, c), (c, d), (d, a)] {
+        faces.triangle(from, to, apex);
+    }
+
+    faces.quad(a, d, c, b);
+
+    Ok(faces.into_mesh(params.normals))
+}
+
+/// Wedge for ramps and slopes, `width` along X, `length` along Z and `height` along Y,
+/// sitting on the XZ plane centered on the Y axis: a box cut diagonally, rising from
+/// its front edge at +Z to a vertical back face at -Z as tall as `height`.
+///
+/// The five faces (the slope, bottom, back and two triangular sides) each have their own
+/// vertices, flat normals and UVs. The slope, bottom and back cover the 0..1 square,
+/// and each side covers the half of it under the slope, upright as seen from outside.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless all three sizes are positive.
+pub fn generate_ramp(width: f32, length: f32, height: f32) -> Result<Mesh, GeometryError> {
+    positive("width", width)?;
+    positive("length", length)?;
+    positive("height", height)?;
+    let (x, z) = (width / 2.0, length / 2.0);
+
+    // seen from -X, with b, c and f behind a, d and e
+    //
+    //    e
+    //    | \
+    //    |   \
+    //    |     \
+    //    d ------ a
+    //  (-z)      (+z)
+    let a = Vec3::new(-x, 0.0, z);
+    let b = Vec3::new(x, 0.0, z);
+    let c = Vec3::new(x, 0.0, -z);
+    let d = Vec3::new(-x, 0.0, -z);
+    let e = Vec3::new(-x, height, -z);
+    let f = Vec3::new(x, height, -z);
+
+    let mut faces = FlatFaces::default();
+    faces.quad(a, b, f, e);
+    faces.quad(a, d, c, b);
+    faces.quad(c, d, e, f);
+    // the right angle in each side's lower back corner, with V running down
+    faces.triangle_with_uvs([a, e, d], [[1.0, 1.0], [0.0, 0.0], [0.0, 1.0]]);
+    faces.triangle_with_uvs([b, c, f], [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0]]);
+
+    Ok(faces.into_mesh(NormalMode::Flat))
+}
+
+/// Truncated pyramid, for buildings, lampshades and planters: a `bottom` rectangle
+/// sitting on the XZ plane and a `top` one `height` above it, both centered on the Y
+/// axis with their `x` along X and `y` along Z, joined by four sloping sides.
+///
+/// Every face has its own vertices, flat normals tilted with its slope, and UVs
+/// covering the 0..1 square, with V running up the sides. A rectangle with no area
+/// leaves its cap out, and sides meeting at a point become triangles, so a `top` equal
+/// to `bottom` gives a box and a zero `top` a pyramid like [`generate_pyramid`]'s.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the height is positive, no
+/// size is negative, and the solid is wider than nothing along both axes.
+pub fn generate_frustum(bottom: Vec2, top: Vec2, height: f32) -> Result<Mesh, GeometryError> {
+    positive("height", height)?;
+    for (name, size) in [
+        ("bottom.x", bottom.x),
+        ("bottom.y", bottom.y),
+        ("top.x", top.x),
+        ("top.y", top.y),
+    ] {
+        if !(size.is_finite() && size >= 0.0) {
+            return Err(GeometryError::InvalidParameter {
+                name,
+                reason: format!("must be zero or more, got {size}"),
+            });
+        }
+    }
+    let widest = bottom.max(top);
+    if widest.x == 0.0 || widest.y == 0.0 {
+        return Err(GeometryError::InvalidParameter {
+            name: "top",
+            reason: format!("leaves no solid with bottom {bottom}, got {top}"),
+        });
+    }
+
+    // each ring counter-clockwise seen from above, starting at -X -Z
+    let ring = |size: Vec2, y: f32| {
+        let (x, z) = (size.x / 2.0, size.y / 2.0);
+        [
+            Vec3::new(-x, y, -z),
+            Vec3::new(-x, y, z),
+            Vec3::new(x, y, z),
+            Vec3::new(x, y, -z),
+        ]
+    };
+    let below = ring(bottom, 0.0);
+    let above = ring(top, height);
+
+    let mut faces = FlatFaces::default();
+    for i in 0..4 {
+        let next = (i + 1) % 4;
+        let [a, b, c, d] = [below[i], below[next], above[next], above[i]];
+        if c == d {
+            faces.triangle(a, b, c);
+        } else if a == b {
+            faces.triangle_with_uvs([a, c, d], [[0.5, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        } else {
+            faces.quad(a, b, c, d);
+        }
+    }
+    if bottom.x > 0.0 && bottom.y > 0.0 {
+        let [a, b, c, d] = below;
+        faces.quad(a, d, c, b);
+    }
+    if top.x > 0.0 && top.y > 0.0 {
+        let [a, b, c, d] = above;
+        faces.quad(b, c, d, a);
+    }
+
+    Ok(faces.into_mesh(NormalMode::Flat))
+}
+
+/// Regular tetrahedron centered on the origin.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the size is positive.
+pub fn generate_tetrahedron(params: &PlatonicParams) -> Result<Mesh, GeometryError> {
+    positive("size", params.size)?;
+    // alternate corners of a cube, whose edges are 2 * sqrt(2) long
+    let scale = params.size / (2.0 * 2.0_f32.sqrt());
+    let corners = [
+        Vec3::new(1.0, 1.0, 1.0),
+        Vec3::new(1.0, -1.0, -1.0),
+        Vec3::new(-1.0, 1.0, -1.0),
+        Vec3::new(-1.0, -1.0, 1.0),
+    ]
+    .map(|corner| corner * scale);
+
+    let mut faces = FlatFaces::default();
+    for [i, j, k] in [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]] {
+        faces.outward_triangle(corners[i], corners[j], corners[k]);
+    }
+
+    Ok(faces.into_mesh(params.normals))
+}
+
+/// Regular octahedron centered on the origin.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the size is positive.
+pub fn generate_octahedron(params: &PlatonicParams) -> Result<Mesh, GeometryError> {
+    positive("size", params.size)?;
+    // the unit axis points, whose edges are sqrt(2) long
+    let scale = params.size / 2.0_f32.sqrt();
+
+    let mut faces = FlatFaces::default();
+    for x in [-scale, scale] {
+        for y in [-scale, scale] {
+            for z in [-scale, scale] {
+                faces.outward_triangle(Vec3::X * x, Vec3::Y * y, Vec3::Z * z);
+            }
+        }
+    }
+
+    Ok(faces.into_mesh(params.normals))
+}
+
+/// Regular dodecahedron centered on the origin.
+///
+/// Each pentagon is fanned from one of its corners, so there are 3 triangles per face
+/// and 36 in total. Fails with [`GeometryError::InvalidParameter`] unless the size is
+/// positive.
+pub fn generate_dodecahedron(params: &PlatonicParams) -> Result<Mesh, GeometryError> {
+    positive("size", params.size)?;
+    let phi = (1.0 + 5.0_f32.sqrt()) / 2.0;
+
+    // the corners of a cube plus three golden rectangles, whose edges are 2 / phi long
+    let mut corners = vec![];
+    for a in [-1.0, 1.0] {
+        for b in [-1.0, 1.0] {
+            for c in [-1.0, 1.0] {
+                corners.push(Vec3::new(a, b, c));
+            }
+            corners.push(Vec3::new(0.0, a / phi, b * phi));
+            corners.push(Vec3::new(a / phi, b * phi, 0.0));
+            corners.push(Vec3::new(a * phi, 0.0, b / phi));
+        }
+    }
+    let corners: Vec<Vec3> = corners
+        .into_iter()
+        .map(|corner| corner * params.size * phi / 2.0)
+        .collect();
+
+    // each face is centered on a vertex of the dual icosahedron, and is made of the
+    // five corners nearest to it
+    let mut faces = FlatFaces::default();
+    for a in [-1.0, 1.0] {
+        for b in [-1.0, 1.0] {
+            for normal in [
+                Vec3::new(0.0, a * phi, b),
+                Vec3::new(b, 0.0, a * phi),
+                Vec3::new(a * phi, b, 0.0),
+            ] {
+                let normal = normal.normalize();
+                let mut face = corners.clone();
+                face.sort_by(|p, q| q.dot(normal).total_cmp(&p.dot(normal)));
+                face.truncate(5);
+                faces.polygon(normal, &mut face);
+            }
+        }
+    }
+
+    Ok(faces.into_mesh(params.normals))
+}
+
+// every face gets its own vertices and its own normal, so its edges stay crisp
+#[derive(Default)]
+struct FlatFaces {
+    mesh: CustomMeshBuilder,
+}
+
+impl FlatFaces {
+    // counter-clockwise as seen from the front, unwrapped to a 0..1 triangle
+    fn triangle(&mut self, a: Vec3, b: Vec3, c: Vec3) {
+        self.triangle_with_uvs([a, b, c], [[0.0, 0.0], [1.0, 0.0], [0.5, 1.0]]);
+    }
+
+    // counter-clockwise as seen from the front, with the given UV at each corner
+    fn triangle_with_uvs(&mut self, corners: [Vec3; 3], uvs: [[f32; 2]; 3]) {
+        let [a, b, c] = corners;
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        let [i0, i1, i2] = [0, 1, 2].map(|k| self.mesh.vertex(corners[k], uvs[k].into(), normal));
+        self.mesh.triangle(i0, i1, i2);
+    }
+
+    // like `triangle`, but flipped if needed to face away from the origin, which
+    // saves working out the winding by hand for solids centered on it
+    fn outward_triangle(&mut self, a: Vec3, b: Vec3, c: Vec3) {
+        if (b - a).cross(c - a).dot(a + b + c) < 0.0 {
+            self.triangle(a, c, b);
+        } else {
+            self.triangle(a, b, c);
+        }
+    }
+
+    // counter-clockwise as seen from the front, unwrapped to the 0..1 square
+    fn quad(&mut self, a: Vec3, b: Vec3, c: Vec3, d: Vec3) {
+        self.triangle_with_uvs([a, b, c], [[0.0, 0.0], [1.0, 0.0], [1.0, 1.0]]);
+        self.triangle_with_uvs([c, d, a], [[1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]);
+    }
+
+    // a convex polygon facing `normal`, fanned from its first corner around its center
+    // and unwrapped to a regular polygon inscribed in the 0..1 square
+    fn polygon(&mut self, normal: Vec3, corners: &mut [Vec3]) {
+        let center = corners.iter().sum::<Vec3>() / corners.len() as f32;
+        let u = (corners[0] - center).normalize();
+        let v = normal.cross(u);
+        let angle = |corner: &Vec3| (*corner - center).dot(v).atan2((*corner - center).dot(u));
+        corners.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+
+        let step = std::f32::consts::TAU / corners.len() as f32;
+        let corner_uv = |i: usize| {
+            let angle = std::f32::consts::FRAC_PI_2 + step * i as f32;
+            [0.5 + angle.cos() / 2.0, 0.5 + angle.sin() / 2.0]
+        };
+
+        for i in 1..corners.len() - 1 {
+            self.triangle_with_uvs(
+                [corners[0], corners[i], corners[i + 1]],
+                [corner_uv(0), corner_uv(i), corner_uv(i + 1)],
+            );
+        }
+    }
+
+    // every face is flat, so its flat normals are its analytic ones too
+    fn into_mesh(self, normals: NormalMode) -> Mesh {
+        let mut mesh = self
+            .mesh
+            .build()
+            .expect("face triangles only use vertices added alongside them");
+        if normals != NormalMode::Flat {
+            normals.apply(&mut mesh);
+        }
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::normals,
+        topology::{check_consistent_winding, is_watertight, weld_vertices},
+        util::{positions, triangle_indices},
+    };
+
+    #[test]
+    fn ramp_is_closed_with_five_faces_in_eight_triangles() {
+        let mut ramp = generate_ramp(2.0, 4.0, 1.0).unwrap();
+        let positions = positions(&ramp);
+        let triangles = triangle_indices(&ramp);
+        assert_eq!(triangles.len(), 8);
+
+        let normals = normals(&ramp).unwrap();
+        let mut faces: Vec<Vec3> = vec![];
+        for tri in &triangles {
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+            let face = (b - a).cross(c - a).normalize();
+            assert!(tri
+                .iter()
+                .all(|v| Vec3::from(normals[*v]).abs_diff_eq(face, 1e-5)));
+            if !faces.iter().any(|seen| seen.abs_diff_eq(face, 1e-4)) {
+                faces.push(face);
+            }
+        }
+        assert_eq!(faces.len(), 5);
+        let slope = Vec3::new(0.0, 4.0, 1.0).normalize();
+        assert!(faces.iter().any(|face| face.abs_diff_eq(slope, 1e-5)));
+
+        assert!(is_watertight(&ramp));
+        weld_vertices(&mut ramp, 4);
+        assert_eq!(check_consistent_winding(&ramp), Ok(()));
+    }
+
+    #[test]
+    fn frustum_ends_at_a_box_and_a_pyramid() {
+        // each triangle's normal, rounded so the same faces compare equal
+        let face_normals = |mesh: &Mesh| -> Vec<[i32; 3]> {
+            let normals = normals(mesh).unwrap();
+            let mut faces: Vec<[i32; 3]> = triangle_indices(mesh)
+                .iter()
+                .map(|tri| normals[tri[0]].map(|n| (n * 1000.0).round() as i32))
+                .collect();
+            faces.sort_unstable();
+            faces
+        };
+
+        let mut cube = generate_frustum(Vec2::ONE, Vec2::ONE, 1.0).unwrap();
+        let mut sides: Vec<[i32; 3]> = [Vec3::X, Vec3::Y, Vec3::Z]
+            .iter()
+            .flat_map(|axis| [*axis, -*axis])
+            .flat_map(|side| [side.to_array().map(|n| n as i32 * 1000); 2])
+            .collect();
+        sides.sort_unstable();
+        assert_eq!(face_normals(&cube), sides);
+        weld_vertices(&mut cube, 4);
+        assert_eq!(cube.count_vertices(), 8);
+        assert!(is_watertight(&cube));
+
+        let mut pyramid = generate_frustum(Vec2::ONE, Vec2::ZERO, 1.0).unwrap();
+        let expected = generate_pyramid(&PyramidParams::default()).unwrap();
+        assert_eq!(face_normals(&pyramid), face_normals(&expected));
+        weld_vertices(&mut pyramid, 4);
+        assert_eq!(pyramid.count_vertices(), 5);
+        assert!(positions(&pyramid).contains(&[0.0, 1.0, 0.0]));
+        assert!(is_watertight(&pyramid));
+    }
+
+    // triangles grouped by the face their flat normal belongs to
+    fn faces(mesh: &Mesh) -> Vec<Vec<[usize; 3]>> {
+        let normals = normals(mesh).unwrap();
+        let mut faces: Vec<(Vec3, Vec<[usize; 3]>)> = vec![];
+        for tri in triangle_indices(mesh) {
+            let normal = Vec3::from(normals[tri[0]]);
+            match faces.iter_mut().find(|(n, _)| n.distance(normal) < 1e-4) {
+                Some((_, face)) => face.push(tri),
+                None => faces.push((normal, vec![tri])),
+            }
+        }
+        faces.into_iter().map(|(_, face)| face).collect()
+    }
+
+    #[test]
+    fn octahedron_has_eight_triangular_faces() {
+        let octahedron = generate_octahedron(&PlatonicParams::default()).unwrap();
+        assert_eq!(triangle_indices(&octahedron).len(), 8);
+        assert_eq!(faces(&octahedron).len(), 8);
+    }
+
+    #[test]
+    fn dodecahedron_has_twelve_pentagons_in_36_triangles() {
+        let dodecahedron = generate_dodecahedron(&PlatonicParams::default()).unwrap();
+        assert_eq!(triangle_indices(&dodecahedron).len(), 36);
+        let positions = positions(&dodecahedron);
+        let faces = faces(&dodecahedron);
+        assert_eq!(faces.len(), 12);
+        for face in faces {
+            assert_eq!(face.len(), 3);
+            let mut corners: Vec<Vec3> = vec![];
+            for v in face.iter().flatten() {
+                let corner = Vec3::from(positions[*v]);
+                if !corners.iter().any(|c| c.distance(corner) < 1e-5) {
+                    corners.push(corner);
+                }
+            }
+            assert_eq!(corners.len(), 5);
+        }
+    }
+}
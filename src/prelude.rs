@@ -0,0 +1,60 @@
+//! The generators, their params and the handful of operations most meshes go through,
+//! for `use bevy_procedural_geometry::prelude::*;` alongside `bevy::prelude::*`.
+//!
+//! Everything here builds a mesh from nothing, configures one of those builds, or is
+//! something nearly every generated mesh ends up passing through: merging, welding,
+//! cleaning up, smoothing normals and spawning. The rest of the API (attribute access,
+//! analysis, UV and topology helpers, export, CSG, slicing, terrain editing and the like)
+//! stays in its own module, to be imported by path where it's needed. That keeps the
+//! prelude from claiming short names like `positions`, `slice` or `morph` in every file
+//! that glob-imports it, and keeps anything here from clashing with Bevy's own prelude.
+
+pub use crate::{
+    builder::CustomMeshBuilder,
+    chunks::{LodTerrain, LodTerrainParams, LodTerrainPlugin, LodViewer},
+    compose::Composition,
+    cylinder::{
+        generate_cone, generate_cylinder, generate_cylinder_between, generate_disc,
+        generate_half_pipe, generate_strut, CapStyle, ConeParams, CylinderParams, DiscParams,
+    },
+    error::GeometryError,
+    fence::generate_fence,
+    geometry::{GeometryData, QuadMesh},
+    gizmo::{generate_arrow, generate_axis_gizmo},
+    grass::generate_grass_clump,
+    hex::{generate_hex_columns, generate_hex_grid, HexCoord, HexGridParams},
+    live::{ProceduralMesh, ProceduralMeshPlugin},
+    merge::{apply_transform, merge_meshes},
+    normals::{compute_smooth_normals, NormalMode},
+    ocean::{generate_ocean, update_ocean, GerstnerWave},
+    parametric::generate_parametric_surface,
+    path::{
+        generate_ribbon, generate_ribbon_with_colors, generate_torus_knot,
+        generate_torus_knot_with_colors,
+    },
+    plane::{generate_diamond_plane, generate_radial_lod_plane, Anchor, RadialLodPlaneParams},
+    polyhedra::{
+        generate_dodecahedron, generate_frustum, generate_octahedron, generate_pyramid,
+        generate_ramp, generate_tetrahedron, PlatonicParams, PyramidParams,
+    },
+    rock::{generate_rock, RockParams},
+    sdf::mesh_from_sdf,
+    skybox::{generate_skybox, SkyboxLayout},
+    spawn::spawn_mesh,
+    sphere::{
+        generate_cube_sphere, generate_subdivided_cube, generate_uv_sphere, CubeSphereParams,
+        CubeSphereUvs, Poles, StackDistribution, UvSphereParams,
+    },
+    stairs::{generate_spiral_stairs, generate_staircase, FlightSpec, SpiralStairsParams},
+    terrain::{
+        generate_heightmap, generate_spherical_terrain_patch, generate_terrain_with_biome_colors,
+        hydraulic_erosion, BiomeTerrainParams, ErosionParams, HeightmapParams,
+    },
+    topology::{clean_mesh, weld_vertices},
+    tree::{generate_tree, Foliage, TreeParams},
+    uv::UvMode,
+    wall::generate_brick_wall,
+};
+
+#[cfg(feature = "scene")]
+pub use crate::scene::load_procedural_scene;
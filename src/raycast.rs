@@ -0,0 +1,137 @@
+use bevy::prelude::*;
+
+use crate::{
+    attributes::{normals, uvs},
+    util::{positions, triangle_indices},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// index of the hit triangle, counting in threes through the index (or vertex) buffer
+    pub triangle: usize,
+    pub distance: f32,
+    pub point: Vec3,
+    /// weights of the triangle's first, second and third vertex at `point`
+    pub barycentric: Vec3,
+    /// `None` when the mesh has no `ATTRIBUTE_UV_0`
+    pub uv: Option<Vec2>,
+    /// interpolated vertex normal, or the face normal when the mesh has no `ATTRIBUTE_NORMAL`
+    pub normal: Vec3,
+}
+
+/// Nearest intersection of the ray with any triangle of a `TriangleList` mesh.
+///
+/// This tests every triangle in the mesh, so it's a linear scan. That's fine for
+/// one-off queries, but repeated queries against a large mesh should build a
+/// [`MeshBvh`](crate::bvh::MeshBvh) once and query that instead.
+pub fn raycast_mesh(mesh: &Mesh, origin: Vec3, dir: Vec3) -> Option<RayHit> {
+    let dir = dir.try_normalize()?;
+    let positions = positions(mesh);
+
+    let mut nearest: Option<(usize, [usize; 3], f32, Vec2)> = None;
+    for (i, tri) in triangle_indices(mesh).into_iter().enumerate() {
+        let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+        if let Some((t, u, v)) = intersect_triangle(origin, dir, a, b, c) {
+            if nearest.map_or(true, |(_, _, nearest_t, _)| t < nearest_t) {
+                nearest = Some((i, tri, t, Vec2::new(u, v)));
+            }
+        }
+    }
+
+    let (triangle, tri, distance, uv) = nearest?;
+    let attributes = HitAttributes {
+        positions,
+        normals: normals(mesh),
+        uvs: uvs(mesh),
+    };
+    Some(attributes.hit(triangle, tri, origin + dir * distance, distance, uv))
+}
+
+pub(crate) struct HitAttributes<'a> {
+    pub positions: &'a [[f32; 3]],
+    pub normals: Option<&'a [[f32; 3]]>,
+    pub uvs: Option<&'a [[f32; 2]]>,
+}
+
+impl HitAttributes<'_> {
+    pub fn hit(
+        &self,
+        triangle: usize,
+        tri: [usize; 3],
+        point: Vec3,
+        distance: f32,
+        uv: Vec2,
+    ) -> RayHit {
+        let barycentric = Vec3::new(1.0 - uv.x - uv.y, uv.x, uv.y);
+        let interpolate3 = |values: &[[f32; 3]]| -> Vec3 {
+            tri.iter()
+                .zip(barycentric.to_array())
+                .map(|(v, w)| Vec3::from(values[*v]) * w)
+                .sum()
+        };
+
+        let normal = match self.normals {
+            Some(normals) => interpolate3(normals).normalize_or_zero(),
+            None => {
+                let [a, b, c] = tri.map(|v| Vec3::from(self.positions[v]));
+                (b - a).cross(c - a).normalize_or_zero()
+            }
+        };
+
+        let uv = self.uvs.map(|uvs| {
+            tri.iter()
+                .zip(barycentric.to_array())
+                .map(|(v, w)| Vec2::from(uvs[*v]) * w)
+                .sum()
+        });
+
+        RayHit {
+            triangle,
+            distance,
+            point,
+            barycentric,
+            uv,
+            normal,
+        }
+    }
+}
+
+// Möller–Trumbore, returning the distance along `dir` and the (u, v) weights of `b` and `c`.
+// Both faces count as hits.
+pub(crate) fn intersect_triangle(
+    origin: Vec3,
+    dir: Vec3,
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+) -> Option<(f32, f32, f32)> {
+    let edge_ab = b - a;
+    let edge_ac = c - a;
+
+    let p = dir.cross(edge_ac);
+    let det = edge_ab.dot(p);
+    if det.abs() < f32::EPSILON {
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let to_origin = origin - a;
+
+    let u = to_origin.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = to_origin.cross(edge_ab);
+    let v = dir.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = edge_ac.dot(q) * inv_det;
+    if t < 0.0 {
+        return None;
+    }
+
+    Some((t, u, v))
+}
@@ -0,0 +1,77 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+
+use crate::{
+    noise::fbm,
+    util::{positions_mut, Rng},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RockParams {
+    /// how far the surface is pushed in or out, as a fraction of the rock's radius
+    pub roughness: f32,
+    /// passed on to the `shape::Icosphere` the rock is carved from; more makes smaller
+    /// facets
+    pub subdivisions: u32,
+}
+
+impl Default for RockParams {
+    fn default() -> Self {
+        RockParams {
+            roughness: 0.3,
+            subdivisions: 2,
+        }
+    }
+}
+
+/// Faceted, flat-shaded rock about one unit across, centered on the origin, along with
+/// its bounds so callers can scale it to size.
+///
+/// It starts from an icosphere, pushes each vertex in or out by seeded noise, and
+/// squashes the result a little differently along each axis. The same `seed` always
+/// gives the same rock.
+pub fn generate_rock(seed: u32, params: &RockParams) -> (Mesh, Aabb) {
+    let mut mesh = Mesh::from(shape::Icosphere {
+        radius: 0.5,
+        subdivisions: params.subdivisions as usize,
+    });
+
+    let mut rng = Rng::new(seed);
+    let scale = Vec3::new(
+        rng.range(0.75, 1.25),
+        rng.range(0.6, 1.0),
+        rng.range(0.75, 1.25),
+    );
+    // moves the noise somewhere different for every seed, on top of reseeding it
+    let offset = Vec3::new(rng.range(-100.0, 100.0), 0.0, rng.range(-100.0, 100.0));
+
+    // the icosphere shares vertices between faces, so displacing them keeps the rock
+    // closed
+    for position in positions_mut(&mut mesh) {
+        let position_on_sphere = Vec3::from(*position);
+        let bump = fbm(seed, position_on_sphere * 3.0 + offset, 3);
+        *position = (position_on_sphere * (1.0 + params.roughness * bump) * scale).into();
+    }
+
+    mesh.duplicate_vertices();
+    mesh.compute_flat_normals();
+
+    let aabb = mesh.compute_aabb().unwrap_or_default();
+    (mesh, aabb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::positions;
+
+    #[test]
+    fn seed_decides_the_shape() {
+        let params = RockParams::default();
+        let rock = |seed| positions(&generate_rock(seed, &params).0).to_vec();
+
+        assert_eq!(rock(7), rock(7));
+        assert_ne!(rock(7), rock(8));
+    }
+}
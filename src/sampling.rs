@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+
+use crate::{attributes::triangles, util::Rng};
+
+/// `count` points spread uniformly over the surface of the mesh, so any patch of the
+/// surface gets a share of them in proportion to its area, however it's triangulated.
+///
+/// Each point picks a triangle with odds weighted by area, then a uniformly random spot
+/// inside it. The same `seed` always gives the same points. Meshes with no area give no
+/// points.
+pub fn sample_surface_points(mesh: &Mesh, count: u32, seed: u32) -> Vec<Vec3> {
+    let triangles: Vec<[Vec3; 3]> = triangles(mesh).collect();
+
+    // running total of triangle areas, searched to turn a random area into a triangle
+    let mut total = 0.0;
+    let cumulative: Vec<f32> = triangles
+        .iter()
+        .map(|[a, b, c]| {
+            total += (*b - *a).cross(*c - *a).length() / 2.0;
+            total
+        })
+        .collect();
+    if total <= 0.0 || !total.is_finite() {
+        return vec![];
+    }
+
+    let mut rng = Rng::new(seed);
+    (0..count)
+        .map(|_| {
+            let target = rng.next_f32() * total;
+            let i = cumulative
+                .partition_point(|&area| area <= target)
+                .min(triangles.len() - 1);
+            let [a, b, c] = triangles[i];
+
+            // without the square root, points would bunch up towards `a`
+            let (r1, r2) = (rng.next_f32().sqrt(), rng.next_f32());
+            a * (1.0 - r1) + b * (r1 * (1.0 - r2)) + c * (r1 * r2)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+    // a unit square with its left half two big triangles and its right half 400 small
+    // ones should still get half the points on each side
+    #[test]
+    fn surface_points_follow_area_not_triangle_count() {
+        let mut positions: Vec<[f32; 3]> = vec![];
+        let mut indices: Vec<u32> = vec![];
+        let mut quad = |x: f32, z: f32, width: f32, depth: f32| {
+            let first = positions.len() as u32;
+            positions.extend([
+                [x, 0.0, z],
+                [x, 0.0, z + depth],
+                [x + width, 0.0, z + depth],
+                [x + width, 0.0, z],
+            ]);
+            indices.extend([first, first + 1, first + 3, first + 3, first + 1, first + 2]);
+        };
+        quad(0.0, 0.0, 0.5, 1.0);
+        for i in 0..10 {
+            for j in 0..20 {
+                quad(0.5 + i as f32 * 0.05, j as f32 * 0.05, 0.05, 0.05);
+            }
+        }
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.set_indices(Some(Indices::U32(indices)));
+
+        let points = sample_surface_points(&mesh, 20000, 3);
+        assert_eq!(points, sample_surface_points(&mesh, 20000, 3));
+        let share = |keep: fn(&&Vec3) -> bool| points.iter().filter(keep).count() as f32 / 20000.0;
+        assert!((share(|p| p.x < 0.5) - 0.5).abs() < 0.02);
+        assert!((share(|p| p.x < 0.25 && p.z < 0.5) - 0.125).abs() < 0.01);
+    }
+}
@@ -0,0 +1,111 @@
+use std::{fmt, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::live::ProceduralMesh;
+
+/// One object in a scene file: what to generate and where to put it.
+///
+/// ```ron
+/// [
+///     (
+///         primitive: Pyramid((base: 2.0, height: 1.5)),
+///         transform: (translation: (0.0, 0.0, -3.0), rotation_degrees: (0.0, 45.0, 0.0)),
+///     ),
+///     (primitive: UvSphere((radius: 0.5, stacks: 24))),
+///     (primitive: Grid((rows: 20, cols: 20, cell_size: 0.5))),
+/// ]
+/// ```
+///
+/// The primitive is any [`ProceduralMesh`], written the same way it's reflected. Params
+/// and transform fields that are left out take their defaults.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SceneObject {
+    pub primitive: ProceduralMesh,
+    #[serde(default)]
+    pub transform: SceneTransform,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SceneTransform {
+    pub translation: (f32, f32, f32),
+    /// applied about X, then Y, then Z
+    pub rotation_degrees: (f32, f32, f32),
+    pub scale: (f32, f32, f32),
+}
+
+impl Default for SceneTransform {
+    fn default() -> Self {
+        SceneTransform {
+            translation: (0.0, 0.0, 0.0),
+            rotation_degrees: (0.0, 0.0, 0.0),
+            scale: (1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl From<SceneTransform> for Transform {
+    fn from(transform: SceneTransform) -> Self {
+        let (x, y, z) = transform.rotation_degrees;
+        Transform {
+            translation: transform.translation.into(),
+            rotation: Quat::from_euler(
+                EulerRot::XYZ,
+                x.to_radians(),
+                y.to_radians(),
+                z.to_radians(),
+            ),
+            scale: transform.scale.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    /// malformed RON, including unknown primitive types
+    Parse(ron::error::SpannedError),
+    /// params that the primitive can't be generated from, for the object at `index`
+    InvalidParams {
+        index: usize,
+        reason: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "couldn't read scene file: {err}"),
+            SceneError::Parse(err) => write!(f, "couldn't parse scene file: {err}"),
+            SceneError::InvalidParams { index, reason } => {
+                write!(f, "invalid params for scene object {index}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+/// Reads a RON list of [`SceneObject`]s and generates each one, ready to spawn with its
+/// transform.
+pub fn load_procedural_scene(path: impl AsRef<Path>) -> Result<Vec<(Mesh, Transform)>, SceneError> {
+    let source = std::fs::read_to_string(path).map_err(SceneError::Io)?;
+    let objects: Vec<SceneObject> = ron::from_str(&source).map_err(SceneError::Parse)?;
+
+    objects
+        .into_iter()
+        .enumerate()
+        .map(|(index, object)| {
+            let mesh = object
+                .primitive
+                .generate()
+                .map_err(|err| SceneError::InvalidParams {
+                    index,
+                    reason: err.to_string(),
+                })?;
+            Ok((mesh, object.transform.into()))
+        })
+        .collect()
+}
@@ -0,0 +1,119 @@
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+};
+
+/// How the six faces of [`generate_skybox`] are arranged in the texture.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SkyboxLayout {
+    /// a horizontal cross, 4 faces wide and 3 high, with the front (-Z, the way Bevy
+    /// cameras face) in the middle:
+    ///
+    /// ```text
+    ///        +------+
+    ///        |  +Y  |
+    /// +------+------+------+------+
+    /// |  -X  |  -Z  |  +X  |  +Z  |
+    /// +------+------+------+------+
+    ///        |  -Y  |
+    ///        +------+
+    /// ```
+    #[default]
+    Cross,
+    /// one row of six faces, in the order cubemaps number them: +X, -X, +Y, -Y, +Z, -Z
+    Strip,
+}
+
+// each face's outward axis, and the directions that are right and up on it seen from
+// inside, in strip order
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::Z, Vec3::Y),
+    (Vec3::NEG_X, Vec3::NEG_Z, Vec3::Y),
+    (Vec3::Y, Vec3::X, Vec3::Z),
+    (Vec3::NEG_Y, Vec3::X, Vec3::NEG_Z),
+    (Vec3::Z, Vec3::NEG_X, Vec3::Y),
+    (Vec3::NEG_Z, Vec3::X, Vec3::Y),
+];
+
+// where each face in strip order sits in the cross, and how many cells it has
+const CROSS_CELLS: [(f32, f32); 6] = [
+    (2.0, 1.0),
+    (0.0, 1.0),
+    (1.0, 0.0),
+    (1.0, 2.0),
+    (3.0, 1.0),
+    (1.0, 1.0),
+];
+const CROSS_SIZE: Vec2 = Vec2::new(4.0, 3.0);
+
+/// Cube `size` across, centered on the origin, that faces inward with normals pointing
+/// at the middle, for a sky texture to be painted on from inside.
+///
+/// Each face is painted as it's seen from the middle of the cube: the sides with +Y
+/// up, the top with +Z up and the bottom with -Z up, so in the cross layout every face
+/// carries on into the faces next to it. Faces have their own vertices. Put the mesh
+/// on the camera so it's always around it, with an unlit material so the sky isn't
+/// shaded, and make it smaller than the camera's far plane.
+pub fn generate_skybox(size: f32, layout: SkyboxLayout) -> Mesh {
+    let half = size / 2.0;
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut indices: Vec<u32> = vec![];
+
+    for (face, (outward, right, up)) in FACES.into_iter().enumerate() {
+        let (cell, cells) = match layout {
+            SkyboxLayout::Cross => (Vec2::from(CROSS_CELLS[face]), CROSS_SIZE),
+            SkyboxLayout::Strip => (Vec2::new(face as f32, 0.0), Vec2::new(6.0, 1.0)),
+        };
+
+        //  (0,0)   (1,0)
+        //    a ----- d
+        //    |     / |
+        //    |   /   |
+        //    | /     |
+        //    b ----- c
+        //  (0,1)   (1,1)
+        let first = positions.len() as u32;
+        for (s, t) in [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)] {
+            let corner = outward + right * (s * 2.0 - 1.0) + up * (1.0 - t * 2.0);
+            positions.push((corner * half).into());
+            normals.push((-outward).into());
+            uvs.push(((cell + Vec2::new(s, t)) / cells).into());
+        }
+        let [a, b, c, d] = [first, first + 1, first + 2, first + 3];
+        indices.extend([a, b, d, d, b, c]);
+    }
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::normals,
+        util::{positions, triangle_indices},
+    };
+
+    #[test]
+    fn skybox_faces_point_at_the_center() {
+        for layout in [SkyboxLayout::Cross, SkyboxLayout::Strip] {
+            let skybox = generate_skybox(10.0, layout);
+            let positions = positions(&skybox);
+            for (position, normal) in positions.iter().zip(normals(&skybox).unwrap()) {
+                assert!(Vec3::from(*normal).dot(-Vec3::from(*position)) > 0.0);
+            }
+            for tri in triangle_indices(&skybox) {
+                let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+                assert!((b - a).cross(c - a).dot(-(a + b + c)) > 0.0);
+            }
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    attributes::{normals, uvs},
+    bounds::position_bounds,
+    builder::CustomMeshBuilder,
+    extrude::{triangulate_contours, Region},
+    util::{positions, smooth_normals, triangle_indices, welded_position_ids},
+};
+
+/// The plane through `point` facing along `normal`, which needn't be normalized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3) -> Self {
+        Plane { point, normal }
+    }
+}
+
+/// Cuts a mesh in two along `plane`, returning the part in front of it (the side its
+/// normal points to) and the part behind, each with the opening closed by a flat cap.
+///
+/// Triangles crossing the plane are split along it, interpolating normals and UVs, and
+/// anything lying exactly in the plane is dropped in favour of the caps. Caps are
+/// UV-mapped by their position on the plane in world units. Both halves come out closed
+/// as long as the mesh was: the outline of each cap is traced through the cut edges,
+/// which only joins up into loops for a closed mesh whose faces agree on winding, and
+/// caps are filled with the same even-odd rule as
+/// [`extrude_glyph`](crate::extrude::extrude_glyph), so cuts through a torus or a hollow
+/// box get their holes. Meshes that cross themselves or touch the plane along an edge
+/// that only part of the mesh continues past can leave gaps in a cap. Vertex colors and
+/// other custom attributes are dropped.
+pub fn slice(mesh: &Mesh, plane: Plane) -> (Mesh, Mesh) {
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+    let vertex_normals = match normals(mesh) {
+        Some(normals) => normals.to_vec(),
+        None => smooth_normals(positions, &triangles),
+    };
+    let vertex_uvs = uvs(mesh);
+    let welded = welded_position_ids(positions);
+
+    let normal = plane.normal.normalize_or_zero();
+    let distances = plane_distances(positions, plane);
+
+    // a corner of a clipped triangle: an original vertex, or the point an edge crosses
+    // the plane, with its ends ordered by welded position so every triangle sharing that
+    // edge gets the same point
+    let cut = |a: usize, b: usize| {
+        if (welded[a], a) < (welded[b], b) {
+            Corner::Cut(a, b)
+        } else {
+            Corner::Cut(b, a)
+        }
+    };
+    let on_plane = |corner: Corner| match corner {
+        Corner::Vertex(v) => distances[v] == 0.0,
+        Corner::Cut(..) => true,
+    };
+    let plane_key = |corner: Corner| match corner {
+        Corner::Vertex(v) => (welded[v], welded[v]),
+        Corner::Cut(a, b) => (welded[a], welded[b]),
+    };
+
+    let halves = [1.0, -1.0].map(|side: f32| {
+        let mut half = Half::default();
+        // cut edges, as the cap needs to run along them
+        let mut outline: HashMap<(usize, usize), ((usize, usize), Vec3)> = HashMap::new();
+
+        for tri in &triangles {
+            if tri.iter().all(|&v| distances[v] == 0.0) {
+                continue;
+            }
+
+            let mut polygon = vec![];
+            for k in 0..3 {
+                let (a, b) = (tri[k], tri[(k + 1) % 3]);
+                if distances[a] * side >= 0.0 {
+                    polygon.push(Corner::Vertex(a));
+                }
+                if distances[a] * distances[b] < 0.0 {
+                    polygon.push(cut(a, b));
+                }
+            }
+            if polygon.len() < 3 {
+                continue;
+            }
+
+            let vertices: Vec<(u32, Vec3)> = polygon
+                .iter()
+                .map(|&corner| {
+                    half.vertex(corner, positions, &vertex_normals, vertex_uvs, &distances)
+                })
+                .collect();
+            for k in 1..vertices.len() - 1 {
+                half.mesh
+                    .triangle(vertices[0].0, vertices[k].0, vertices[k + 1].0);
+            }
+
+            for k in 0..polygon.len() {
+                let (from, to) = (polygon[k], polygon[(k + 1) % polygon.len()]);
+                if on_plane(from) && on_plane(to) {
+                    // the cap runs the other way along the edge it shares with this face
+                    let (_, start) = vertices[(k + 1) % polygon.len()];
+                    outline.insert(plane_key(to), (plane_key(from), start));
+                }
+            }
+        }
+
+        half.cap(outline, normal * -side);
+        half.into_mesh()
+    });
+
+    let [front, back] = halves;
+    (front, back)
+}
+
+/// The outlines where the surface of a mesh crosses `plane`, as polylines through the
+/// points where its edges cross it, such as the contour lines of
+/// [`generate_contours`](crate::terrain::generate_contours).
+///
+/// Each polyline runs with the part of the surface in front of the plane on its left,
+/// seen from the side the surface faces. Outlines that close up repeat their first point
+/// at the end, and ones that run off an open edge of the mesh start and end there.
+/// Vertices with the same position are treated as one, so flat-shaded meshes give the
+/// same outlines as smooth ones.
+pub fn section(mesh: &Mesh, plane: Plane) -> Vec<Vec<Vec3>> {
+    let positions = positions(mesh);
+    let welded = welded_position_ids(positions);
+    let distances = plane_distances(positions, plane);
+    let key = |(a, b): (usize, usize)| (welded[a].min(welded[b]), welded[a].max(welded[b]));
+
+    // where each crossed edge meets the plane, and the edge the outline crosses next
+    let mut points: HashMap<(usize, usize), Vec3> = HashMap::new();
+    let mut next: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    for tri in triangle_indices(mesh) {
+        // a triangle crossing the plane has one edge going from in front to behind it
+        // and one coming back
+        let (mut leaving, mut entering) = (None, None);
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            match (distances[a] >= 0.0, distances[b] >= 0.0) {
+                (true, false) => leaving = Some((a, b)),
+                (false, true) => entering = Some((a, b)),
+                _ => {}
+            }
+        }
+        let (Some(leaving), Some(entering)) = (leaving, entering) else {
+            continue;
+        };
+
+        for (a, b) in [leaving, entering] {
+            points.entry(key((a, b))).or_insert_with(|| {
+                let t = distances[a] / (distances[a] - distances[b]);
+                Vec3::from(positions[a]).lerp(Vec3::from(positions[b]), t)
+            });
+        }
+        next.insert(key(leaving), key(entering));
+    }
+
+    let mut outlines = vec![];
+    let mut trace = |start: (usize, usize), next: &mut HashMap<_, _>| {
+        let mut outline = vec![points[&start]];
+        let mut key = start;
+        while let Some(following) = next.remove(&key) {
+            outline.push(points[&following]);
+            key = following;
+        }
+        // edges crossed right at a vertex all meet the plane there
+        outline.dedup();
+        if outline.len() >= 2 {
+            outlines.push(outline);
+        }
+    };
+
+    // outlines that run off the edge of the mesh start where none leads in; sorting
+    // them, and starting loops from the lowest key, keeps the output the same every run
+    let ends: HashSet<(usize, usize)> = next.values().copied().collect();
+    let mut open: Vec<(usize, usize)> = next
+        .keys()
+        .filter(|key| !ends.contains(key))
+        .copied()
+        .collect();
+    open.sort_unstable();
+    for start in open {
+        trace(start, &mut next);
+    }
+    while let Some(&start) = next.keys().min() {
+        trace(start, &mut next);
+    }
+    outlines
+}
+
+// how far in front of the plane each position is, with anything close enough to count
+// as on it, so near misses don't leave slivers, at exactly zero
+fn plane_distances(positions: &[[f32; 3]], plane: Plane) -> Vec<f32> {
+    let normal = plane.normal.normalize_or_zero();
+    let tolerance =
+        position_bounds(positions).map_or(0.0, |(min, max)| (max - min).max_element() * 1e-6);
+    positions
+        .iter()
+        .map(|position| {
+            let distance = (Vec3::from(*position) - plane.point).dot(normal);
+            if distance.abs() <= tolerance {
+                0.0
+            } else {
+                distance
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+enum Corner {
+    Vertex(usize),
+    Cut(usize, usize),
+}
+
+#[derive(Default)]
+struct Half {
+    mesh: CustomMeshBuilder,
+    // each corner's vertex in `mesh`, and where it is
+    corners: HashMap<Corner, (u32, Vec3)>,
+}
+
+impl Half {
+    fn vertex(
+        &mut self,
+        corner: Corner,
+        positions: &[[f32; 3]],
+        normals: &[[f32; 3]],
+        uvs: Option<&[[f32; 2]]>,
+        distances: &[f32],
+    ) -> (u32, Vec3) {
+        if let Some(&vertex) = self.corners.get(&corner) {
+            return vertex;
+        }
+
+        let uv = |v: usize| uvs.map_or(Vec2::ZERO, |uvs| Vec2::from(uvs[v]));
+        let (position, normal, uv) = match corner {
+            Corner::Vertex(v) => (Vec3::from(positions[v]), Vec3::from(normals[v]), uv(v)),
+            Corner::Cut(a, b) => {
+                let t = distances[a] / (distances[a] - distances[b]);
+                let (pa, pb) = (Vec3::from(positions[a]), Vec3::from(positions[b]));
+                let (na, nb) = (Vec3::from(normals[a]), Vec3::from(normals[b]));
+                (
+                    pa + (pb - pa) * t,
+                    na.lerp(nb, t).normalize_or_zero(),
+                    uv(a).lerp(uv(b), t),
+                )
+            }
+        };
+
+        let vertex = (self.mesh.vertex(position, uv, normal), position);
+        self.corners.insert(corner, vertex);
+        vertex
+    }
+
+    // chains the cut edges (keyed from start to end) into loops and fills them with a
+    // face towards `facing`
+    fn cap(&mut self, mut outline: HashMap<(usize, usize), ((usize, usize), Vec3)>, facing: Vec3) {
+        if facing == Vec3::ZERO {
+            return;
+        }
+        let u = facing.any_orthonormal_vector();
+        // so counter-clockwise on the plane is counter-clockwise seen from `facing`
+        let v = facing.cross(u);
+
+        let mut loops: Vec<Vec<Vec3>> = vec![];
+        // starting from the lowest key keeps the output the same from run to run
+        while let Some(&start) = outline.keys().min() {
+            let mut ring = vec![];
+            let mut key = start;
+            while let Some((next, position)) = outline.remove(&key) {
+                ring.push(position);
+                key = next;
+            }
+            // an outline that doesn't close up can't be filled
+            if key == start && ring.len() >= 3 {
+                loops.push(ring);
+            }
+        }
+
+        let mut points = HashMap::new();
+        let contours: Vec<Vec<Vec2>> = loops
+            .iter()
+            .map(|ring| {
+                ring.iter()
+                    .map(|position| {
+                        let point = Vec2::new(position.dot(u), position.dot(v));
+                        points.insert(point.to_array().map(f32::to_bits), *position);
+                        point
+                    })
+                    .collect()
+            })
+            .collect();
+
+        for Region { rings, triangles } in triangulate_contours(&contours) {
+            let vertices: Vec<u32> = rings
+                .iter()
+                .flatten()
+                .map(|point| {
+                    let position = points[&point.to_array().map(f32::to_bits)];
+                    self.mesh.vertex(position, *point, facing)
+                })
+                .collect();
+            for [a, b, c] in triangles {
+                self.mesh.triangle(vertices[a], vertices[b], vertices[c]);
+            }
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        self.mesh
+            .build()
+            .expect("slice triangles only use vertices added alongside them")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        bounds::volume,
+        topology::{check_consistent_winding, is_watertight},
+    };
+
+    #[test]
+    fn slicing_a_cube_gives_two_closed_boxes() {
+        let cube = Mesh::from(shape::Cube { size: 2.0 });
+        let (front, back) = slice(&cube, Plane::new(Vec3::new(0.0, 0.3, 0.0), Vec3::Y));
+        for (half, height) in [(&front, 0.7), (&back, 1.3)] {
+            assert!(is_watertight(half));
+            assert_eq!(check_consistent_winding(half), Ok(()));
+            assert!((volume(half) - 4.0 * height).abs() < 1e-4);
+            let (min, max) = position_bounds(positions(half)).unwrap();
+            assert!((max.y - min.y - height).abs() < 1e-5);
+        }
+    }
+}
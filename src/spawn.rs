@@ -0,0 +1,30 @@
+use bevy::prelude::*;
+
+use crate::bounds::mesh_aabb;
+
+/// Adds `mesh` to `meshes` and spawns `bundle` with its handle and an [`Aabb`] computed
+/// from the mesh's positions now, returning the new entity.
+///
+/// Bevy only works out an entity's `Aabb` the first time it sees the entity, so one
+/// spawned before its mesh was finished, or whose mesh is swapped for a bigger one,
+/// keeps bounds that no longer fit and gets culled while still in view. `bundle` can be
+/// a `PbrBundle` or anything else with a `Handle<Mesh>`, whose handle is replaced with
+/// the new one. After changing the mesh later, insert a fresh [`mesh_aabb`] the same
+/// way.
+///
+/// The mesh's vertices stay in `meshes` after they've been sent to the GPU, as every
+/// mesh's do in Bevy 0.9, so [`raycast_mesh`](crate::raycast::raycast_mesh) and other
+/// queries can go on reading them through the handle; there's no setting to free them,
+/// and so nothing to choose here. Later Bevy versions drop that copy unless the mesh's
+/// `RenderAssetUsages` include `MAIN_WORLD`, which meshes used for queries need to keep.
+///
+/// [`Aabb`]: bevy::render::primitives::Aabb
+pub fn spawn_mesh(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    mesh: Mesh,
+    bundle: impl Bundle,
+) -> Entity {
+    let aabb = mesh_aabb(&mesh).unwrap_or_default();
+    commands.spawn(bundle).insert((meshes.add(mesh), aabb)).id()
+}
@@ -0,0 +1,566 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::{
+    error::{at_least, positive, GeometryError},
+    geometry::{GeometryData, QuadMesh},
+    normals::NormalMode,
+    uv::{spherical_uvs, UvMode},
+};
+
+/// How [`generate_cube_sphere`] lays out its UVs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CubeSphereUvs {
+    /// each face of the cube gets its own cell of a 3 by 2 atlas, with no seams inside a
+    /// face and no pinching at the poles
+    Atlas,
+    /// latitude and longitude for an equirectangular texture, as in
+    /// [`spherical_uvs`](crate::uv::spherical_uvs)
+    Spherical,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CubeSphereParams {
+    pub radius: f32,
+    /// cells along each edge of each face of the cube
+    pub subdivisions: u32,
+    pub uvs: CubeSphereUvs,
+    pub normals: NormalMode,
+}
+
+impl Default for CubeSphereParams {
+    fn default() -> Self {
+        CubeSphereParams {
+            radius: 1.0,
+            subdivisions: 16,
+            uvs: CubeSphereUvs::Atlas,
+            normals: NormalMode::Smooth,
+        }
+    }
+}
+
+/// How [`generate_uv_sphere`] closes off its poles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Poles {
+    /// a fan of one long, thin triangle per sector meeting at each pole, where the
+    /// texture pinches to a point
+    Fan,
+    /// a disc of rings with fewer vertices the closer they are to the pole, filling the
+    /// first stack with triangles about as wide as they are tall
+    Cap,
+}
+
+/// How [`generate_uv_sphere`] spaces its rings of latitude from pole to pole.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StackDistribution {
+    /// the same angle apart, so the stacks near the poles are thin slivers with far
+    /// less area than the ones around the equator
+    EqualAngle,
+    /// the same height apart (the cosine of the angle from the pole evenly spaced),
+    /// which gives every stack the same area, so triangles come out much the same size
+    /// everywhere but the poles, where the stacks are widest
+    EqualArea,
+}
+
+impl StackDistribution {
+    // the angle down from the top pole of the ring `t` of the way from it to the bottom
+    fn polar_angle(self, t: f32) -> f32 {
+        match self {
+            StackDistribution::EqualAngle => PI * t,
+            StackDistribution::EqualArea => (1.0 - 2.0 * t).clamp(-1.0, 1.0).acos(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct UvSphereParams {
+    pub radius: f32,
+    /// segments around each ring of latitude
+    pub sectors: u32,
+    /// bands from pole to pole
+    pub stacks: u32,
+    pub stack_distribution: StackDistribution,
+    pub poles: Poles,
+    pub normals: NormalMode,
+}
+
+impl Default for UvSphereParams {
+    fn default() -> Self {
+        UvSphereParams {
+            radius: 1.0,
+            sectors: 32,
+            stacks: 16,
+            stack_distribution: StackDistribution::EqualAngle,
+            poles: Poles::Fan,
+            normals: NormalMode::Smooth,
+        }
+    }
+}
+
+/// Sphere centered on the origin, built from rings of latitude, with UVs for an
+/// equirectangular texture laid out like [`spherical_uvs`](crate::uv::spherical_uvs):
+/// U goes once around and V runs from the top pole at +Y down to the bottom one.
+///
+/// The rings are spread out as [`StackDistribution`] says. V follows the angle from the
+/// top pole either way, so the texture stays put when they're spaced by area; only
+/// where the vertices sample it changes.
+///
+/// With [`Poles::Cap`], the first stack at each end is a disc whose rings have a vertex
+/// count in proportion to their size, down to about six around the pole, rather than
+/// dozens of slivers all meeting there. The rings are still rings of latitude with
+/// normals pointing straight out, so the caps blend into the stacks next to them with
+/// nothing to show where they start. Either way, the pole vertex is repeated for each
+/// triangle touching it with that triangle's U, so no triangle has zero area.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the radius is positive and
+/// there are at least 3 sectors and 2 stacks.
+pub fn generate_uv_sphere(params: &UvSphereParams) -> Result<Mesh, GeometryError> {
+    generate_uv_sphere_raw(params).map(GeometryData::into_mesh)
+}
+
+/// [`generate_uv_sphere`] as plain arrays rather than a `Mesh`.
+pub fn generate_uv_sphere_raw(params: &UvSphereParams) -> Result<GeometryData, GeometryError> {
+    positive("radius", params.radius)?;
+    at_least("sectors", params.sectors, 3)?;
+    at_least("stacks", params.stacks, 2)?;
+    let (sectors, stacks) = (params.sectors, params.stacks);
+    let mut sphere = RingBuilder {
+        radius: params.radius,
+        ..default()
+    };
+
+    let polar_angle = |stack: u32| {
+        params
+            .stack_distribution
+            .polar_angle(stack as f32 / stacks as f32)
+    };
+    let rings: Vec<Vec<u32>> = (1..stacks)
+        .map(|stack| sphere.ring(polar_angle(stack), sectors))
+        .collect();
+    for pair in rings.windows(2) {
+        sphere.zip(&pair[0], &pair[1]);
+    }
+
+    let first_stack = polar_angle(1);
+    let (top, bottom) = (&rings[0], &rings[rings.len() - 1]);
+    match params.poles {
+        Poles::Fan => {
+            sphere.fan(0.0, top);
+            sphere.fan(PI, bottom);
+        }
+        Poles::Cap => {
+            // rings about a stack's height apart would have six segments each step in
+            // from the edge, like a hexagonal grid
+            let steps = (sectors as f32 / 6.0).round().max(1.0) as u32;
+            let cap = |sphere: &mut RingBuilder, pole: f32, edge: &[u32]| {
+                let mut inner_rings: Vec<Vec<u32>> = (1..steps)
+                    .map(|step| {
+                        let t = step as f32 / steps as f32;
+                        let theta = pole + (first_stack * t).copysign(PI / 2.0 - pole);
+                        let count = (sectors as f32 * t).round().max(3.0) as u32;
+                        sphere.ring(theta, count)
+                    })
+                    .collect();
+                inner_rings.push(edge.to_vec());
+                sphere.fan(pole, &inner_rings[0]);
+                for pair in inner_rings.windows(2) {
+                    // rings nearer the top pole come first along V
+                    if pole == 0.0 {
+                        sphere.zip(&pair[0], &pair[1]);
+                    } else {
+                        sphere.zip(&pair[1], &pair[0]);
+                    }
+                }
+            };
+            cap(&mut sphere, 0.0, top);
+            cap(&mut sphere, PI, bottom);
+        }
+    }
+
+    let mut sphere = sphere.into_geometry();
+    params.normals.apply_raw(&mut sphere);
+    Ok(sphere)
+}
+
+#[derive(Default)]
+struct RingBuilder {
+    radius: f32,
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    uvs: Vec<[f32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl RingBuilder {
+    fn vertex(&mut self, theta: f32, u: f32) -> u32 {
+        // the seam at U of 1 and every copy of a pole land exactly where the first one
+        // does, so the sphere welds shut by position
+        let phi = (u.fract() - 0.5) * TAU;
+        let across = if theta > 0.0 && theta < PI {
+            theta.sin()
+        } else {
+            0.0
+        };
+        let normal = Vec3::new(across * phi.sin(), theta.cos(), across * phi.cos());
+        self.positions.push((normal * self.radius).into());
+        self.normals.push(normal.into());
+        self.uvs.push([u, theta / PI]);
+        self.positions.len() as u32 - 1
+    }
+
+    // `count` segments around the ring at polar angle `theta`, with the first vertex
+    // repeated at the end to carry U from 0 to 1
+    fn ring(&mut self, theta: f32, count: u32) -> Vec<u32> {
+        (0..=count)
+            .map(|i| self.vertex(theta, i as f32 / count as f32))
+            .collect()
+    }
+
+    // fills between two rings, `upper` nearer the top pole, stepping along whichever
+    // ring's next vertex comes first around
+    fn zip(&mut self, upper: &[u32], lower: &[u32]) {
+        let u = |vertex: u32| self.uvs[vertex as usize][0];
+        let (mut i, mut j) = (0, 0);
+        while i + 1 < upper.len() || j + 1 < lower.len() {
+            let step_lower =
+                i + 1 == upper.len() || (j + 1 < lower.len() && u(lower[j + 1]) <= u(upper[i + 1]));
+            let tri = if step_lower {
+                j += 1;
+                [upper[i], lower[j - 1], lower[j]]
+            } else {
+                i += 1;
+                [upper[i - 1], lower[j], upper[i]]
+            };
+            self.indices.extend(tri);
+        }
+    }
+
+    // a triangle from each segment of `ring` to its own copy of the pole at `theta`
+    fn fan(&mut self, theta: f32, ring: &[u32]) {
+        for segment in ring.windows(2) {
+            let (a, b) = (segment[0], segment[1]);
+            let u = (self.uvs[a as usize][0] + self.uvs[b as usize][0]) / 2.0;
+            let pole = self.vertex(theta, u);
+            if theta == 0.0 {
+                self.indices.extend([pole, a, b]);
+            } else {
+                self.indices.extend([a, pole, b]);
+            }
+        }
+    }
+
+    fn into_geometry(self) -> GeometryData {
+        GeometryData {
+            positions: self.positions,
+            normals: self.normals,
+            uvs: self.uvs,
+            indices: self.indices,
+        }
+    }
+}
+
+// each face's outward axis, and the directions that are right and up on it seen from
+// outside, in atlas order
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+    (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+    (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+    (Vec3::Z, Vec3::X, Vec3::Y),
+    (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+];
+
+/// Sphere centered on the origin, made by inflating a subdivided cube, so its triangles
+/// stay close to the same size everywhere instead of bunching up at the poles like a UV
+/// sphere's.
+///
+/// With [`CubeSphereUvs::Atlas`], each face's own coordinates are its UVs, packed into
+/// a texture split into three columns and two rows:
+///
+/// ```text
+/// +------+------+------+
+/// |  +X  |  -X  |  +Y  |
+/// +------+------+------+
+/// |  -Y  |  +Z  |  -Z  |
+/// +------+------+------+
+/// ```
+///
+/// Each cell is painted as that face is seen from outside the sphere: the sides (±X,
+/// ±Z) with +Y up, the top (+Y) with -Z up and the bottom (-Y) with +Z up. The cells
+/// touch, so filtering and mipmapping blend a little of each face's neighbors in the
+/// atlas into its edges; paint a few texels past each cell's edge to hide that. Each
+/// face has its own vertices, so faces only meet by position along the cube's edges.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless the radius is positive and
+/// there's at least one subdivision.
+pub fn generate_cube_sphere(params: &CubeSphereParams) -> Result<Mesh, GeometryError> {
+    generate_cube_sphere_raw(params).map(GeometryData::into_mesh)
+}
+
+/// [`generate_cube_sphere`] as plain arrays rather than a `Mesh`.
+pub fn generate_cube_sphere_raw(params: &CubeSphereParams) -> Result<GeometryData, GeometryError> {
+    positive("radius", params.radius)?;
+    at_least("subdivisions", params.subdivisions, 1)?;
+    let mut sphere = cube_faces(params.subdivisions, UvMode::PerFace, |on_cube, _| {
+        let normal = spherify(on_cube);
+        (normal * params.radius, normal)
+    });
+    if params.uvs == CubeSphereUvs::Spherical {
+        // the projection splits vertices along the seam, which only works on a `Mesh`
+        let mut mesh = sphere.into_mesh();
+        spherical_uvs(&mut mesh, 0.0);
+        sphere = GeometryData::from_mesh(&mesh);
+    }
+    params.normals.apply_raw(&mut sphere);
+    Ok(sphere)
+}
+
+/// Cube `size` across, centered on the origin, with each face split into
+/// `subdivisions` by `subdivisions` cells.
+///
+/// The vertices, index buffer and atlas UVs are laid out the same way as
+/// [`generate_cube_sphere`]'s for the same subdivisions, so the two can be blended with
+/// [`morph`](crate::morph::morph). The cells also give displacement and deformers
+/// vertices to move, where a plain box has only its corners. Each face has its own
+/// vertices, so with [`NormalMode::Flat`] or [`NormalMode::Analytic`] the edges stay
+/// hard and the layout is left as it is. With [`UvMode::WorldSpace`], the UVs are
+/// projected instead of laid out in the atlas.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless `size` is positive and there's
+/// at least one subdivision.
+pub fn generate_subdivided_cube(
+    size: f32,
+    subdivisions: u32,
+    uvs: UvMode,
+    normals: NormalMode,
+) -> Result<Mesh, GeometryError> {
+    generate_subdivided_cube_raw(size, subdivisions, uvs, normals).map(GeometryData::into_mesh)
+}
+
+/// [`generate_subdivided_cube`] as plain arrays rather than a `Mesh`.
+pub fn generate_subdivided_cube_raw(
+    size: f32,
+    subdivisions: u32,
+    uvs: UvMode,
+    normals: NormalMode,
+) -> Result<GeometryData, GeometryError> {
+    positive("size", size)?;
+    at_least("subdivisions", subdivisions, 1)?;
+    let mut cube = cube_faces(subdivisions, uvs, |on_cube, outward| {
+        (on_cube * size / 2.0, outward)
+    });
+    // its faces already have their own vertices and flat normals
+    if normals != NormalMode::Flat {
+        normals.apply_raw(&mut cube);
+    }
+    Ok(cube)
+}
+
+/// [`generate_subdivided_cube`] with its cells left whole as quads and flat normals,
+/// for exporting a box to model further, with
+/// [`to_obj_quads`](crate::export::to_obj_quads) for one.
+///
+/// Split as [`QuadMesh::into_mesh`] does, the quads give the same mesh as
+/// [`generate_subdivided_cube`] with [`NormalMode::Flat`]. Fails the same way it does.
+pub fn generate_subdivided_cube_quads(
+    size: f32,
+    subdivisions: u32,
+    uvs: UvMode,
+) -> Result<QuadMesh, GeometryError> {
+    positive("size", size)?;
+    at_least("subdivisions", subdivisions, 1)?;
+    Ok(cube_quads(subdivisions, uvs, |on_cube, outward| {
+        (on_cube * size / 2.0, outward)
+    }))
+}
+
+// a grid of `subdivisions` cells across each face of the cube from -1 to 1, in atlas
+// order with atlas UVs unless `mode` says otherwise, with `place` turning each point on
+// the cube and its face's outward axis into a position and normal
+fn cube_faces(
+    subdivisions: u32,
+    mode: UvMode,
+    place: impl Fn(Vec3, Vec3) -> (Vec3, Vec3),
+) -> GeometryData {
+    cube_quads(subdivisions, mode, place).into_geometry()
+}
+
+// the cells of `cube_faces` before they're split into triangles
+fn cube_quads(
+    subdivisions: u32,
+    mode: UvMode,
+    place: impl Fn(Vec3, Vec3) -> (Vec3, Vec3),
+) -> QuadMesh {
+    let cells = subdivisions.max(1);
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut quads: Vec<[u32; 4]> = vec![];
+
+    for (face, (outward, right, up)) in FACES.into_iter().enumerate() {
+        let cell = Vec2::new((face % 3) as f32, (face / 3) as f32);
+        let first = positions.len() as u32;
+        // `t` runs down the face, the way V does down the texture
+        for j in 0..=cells {
+            for i in 0..=cells {
+                let (s, t) = (i as f32 / cells as f32, j as f32 / cells as f32);
+                let on_cube = outward + right * (s * 2.0 - 1.0) + up * (1.0 - t * 2.0);
+                let (position, normal) = place(on_cube, outward);
+                positions.push(position.into());
+                normals.push(normal.into());
+                let atlas = (cell + Vec2::new(s, t)) / Vec2::new(3.0, 2.0);
+                uvs.push(mode.uv(atlas, position, outward).into());
+            }
+        }
+
+        let row = cells + 1;
+        for j in 0..cells {
+            for i in 0..cells {
+                let a = first + j * row + i;
+                quads.push([a, a + row, a + row + 1, a + 1]);
+            }
+        }
+    }
+
+    QuadMesh {
+        positions,
+        normals,
+        uvs,
+        quads,
+    }
+}
+
+// a point on the unit cube moved onto the unit sphere, spreading points more evenly
+// than normalizing would, which crowds them towards the middle of each face
+fn spherify(p: Vec3) -> Vec3 {
+    let squared = p * p;
+    Vec3::new(
+        p.x * (1.0 - squared.y / 2.0 - squared.z / 2.0 + squared.y * squared.z / 3.0).sqrt(),
+        p.y * (1.0 - squared.z / 2.0 - squared.x / 2.0 + squared.z * squared.x / 3.0).sqrt(),
+        p.z * (1.0 - squared.x / 2.0 - squared.y / 2.0 + squared.x * squared.y / 3.0).sqrt(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::utils::HashMap;
+
+    use super::*;
+    use crate::{
+        attributes::{normals, uvs},
+        util::{positions, triangle_indices},
+    };
+
+    // nothing here needs an `App`, so the generators work the same in a build script or
+    // on a server with the default features off
+    #[test]
+    fn uv_sphere_builds_without_an_app() {
+        let mesh = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        // 15 rings of 33 vertices, and a copy of each pole for each of the 32 sectors
+        assert_eq!(mesh.count_vertices(), 15 * 33 + 2 * 32);
+        // two triangles a sector between each pair of rings, and one at each pole
+        assert_eq!(mesh.indices().unwrap().len(), 3 * (14 * 2 * 32 + 2 * 32));
+    }
+
+    #[test]
+    fn uv_sphere_with_no_stacks_is_an_error() {
+        let params = UvSphereParams {
+            stacks: 0,
+            ..default()
+        };
+        assert!(matches!(
+            generate_uv_sphere(&params),
+            Err(GeometryError::InvalidParameter { name: "stacks", .. })
+        ));
+    }
+
+    #[test]
+    fn cube_sphere_atlas_keeps_each_triangle_in_one_cell() {
+        let mesh = generate_cube_sphere(&CubeSphereParams::default()).unwrap();
+        let uvs = uvs(&mesh).unwrap();
+        let cells = Vec2::new(3.0, 2.0);
+        let mut used = vec![];
+        for tri in triangle_indices(&mesh) {
+            let corners = tri.map(|v| Vec2::from(uvs[v]) * cells);
+            let min = corners
+                .iter()
+                .fold(Vec2::splat(f32::MAX), |min, uv| min.min(*uv));
+            let max = corners
+                .iter()
+                .fold(Vec2::splat(f32::MIN), |max, uv| max.max(*uv));
+            let cell = (min + 1e-4).floor();
+            assert!(max.x <= cell.x + 1.0 + 1e-4 && max.y <= cell.y + 1.0 + 1e-4);
+            used.push((cell.x as i32, cell.y as i32));
+        }
+        used.sort_unstable();
+        used.dedup();
+        assert_eq!(used.len(), 6);
+    }
+
+    #[test]
+    fn capped_poles_have_no_zero_area_triangles() {
+        for (sectors, stacks) in [(32, 16), (7, 2), (64, 5)] {
+            let params = UvSphereParams {
+                sectors,
+                stacks,
+                poles: Poles::Cap,
+                ..default()
+            };
+            let mesh = generate_uv_sphere(&params).unwrap();
+            let positions = positions(&mesh);
+            for tri in triangle_indices(&mesh) {
+                let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+                let normal = (b - a).cross(c - a);
+                assert!(normal.length() > 1e-6, "{sectors} {stacks}");
+                assert!(normal.dot(a + b + c) > 0.0, "{sectors} {stacks}");
+            }
+            // the cap's edge shares its normals with the stack below, as do the copies of
+            // any vertex along the UV seam
+            let mut at: HashMap<[u32; 3], Vec3> = HashMap::new();
+            for (position, normal) in positions.iter().zip(normals(&mesh).unwrap()) {
+                let normal = Vec3::from(*normal);
+                let first = *at.entry(position.map(f32::to_bits)).or_insert(normal);
+                assert!(normal.abs_diff_eq(first, 1e-6));
+                assert!(normal.dot(Vec3::from(*position)) > 0.9);
+            }
+        }
+    }
+
+    #[test]
+    fn equal_area_stacks_even_out_the_triangles() {
+        // how far triangle areas stray from their mean, as a fraction of it
+        let spread = |stack_distribution: StackDistribution| {
+            let params = UvSphereParams {
+                stack_distribution,
+                ..default()
+            };
+            let sphere = generate_uv_sphere(&params).unwrap();
+            let positions = positions(&sphere);
+            let areas: Vec<f32> = triangle_indices(&sphere)
+                .iter()
+                .map(|tri| {
+                    let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+                    (b - a).cross(c - a).length() / 2.0
+                })
+                .collect();
+            let mean = areas.iter().sum::<f32>() / areas.len() as f32;
+            let variance =
+                areas.iter().map(|area| (area - mean).powi(2)).sum::<f32>() / areas.len() as f32;
+            variance.sqrt() / mean
+        };
+
+        let (equal_angle, equal_area) = (
+            spread(StackDistribution::EqualAngle),
+            spread(StackDistribution::EqualArea),
+        );
+        assert!(equal_area < equal_angle, "{equal_area} vs {equal_angle}");
+    }
+}
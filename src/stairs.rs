@@ -0,0 +1,551 @@
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::*;
+
+use crate::{builder::CustomMeshBuilder, fence::generate_fence, merge::merge_meshes, uv::UvMode};
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SpiralStairsParams {
+    pub steps: u32,
+    pub inner_radius: f32,
+    pub outer_radius: f32,
+    /// rise from one step to the next
+    pub step_height: f32,
+    /// how far the stairs wind around the axis, over all the steps
+    pub total_radians: f32,
+    /// how thick each step's slab is; the same as `step_height` gives solid stairs
+    pub thickness: f32,
+    /// fills the middle with a column as tall as the stairs
+    pub column: bool,
+    pub uvs: UvMode,
+}
+
+impl Default for SpiralStairsParams {
+    fn default() -> Self {
+        SpiralStairsParams {
+            steps: 16,
+            inner_radius: 0.25,
+            outer_radius: 1.5,
+            step_height: 0.2,
+            total_radians: 2.0 * PI,
+            thickness: 0.2,
+            column: true,
+            uvs: UvMode::PerFace,
+        }
+    }
+}
+
+// the longest arc one flat piece of a curved wall covers
+const MAX_SEGMENT_RADIANS: f32 = PI / 16.0;
+
+/// Steps winding counter-clockwise (seen from above) up around the Y axis, starting
+/// along +X at the origin.
+///
+/// Each step is a slab shaped like a slice of a ring, and step `i` covers exactly the
+/// `i`th share of `total_radians`, so consecutive steps meet edge to edge with no gap
+/// or overlap. The top of step `i` is at `(i + 1) * step_height`. Flat faces are flat
+/// shaded and the curved walls smooth. With [`UvMode::PerFace`], each piece of a step
+/// covers its share of 0..1, and the column's side wraps once around.
+pub fn generate_spiral_stairs(params: &SpiralStairsParams) -> Mesh {
+    let SpiralStairsParams {
+        steps,
+        inner_radius,
+        outer_radius,
+        step_height,
+        total_radians,
+        thickness,
+        column,
+        uvs,
+    } = *params;
+
+    let mut builder = Builder {
+        mesh: CustomMeshBuilder::new(),
+        uvs,
+    };
+    let step_radians = total_radians / steps.max(1) as f32;
+    let segments = (step_radians.abs() / MAX_SEGMENT_RADIANS).ceil().max(1.0) as u32;
+
+    for step in 0..steps {
+        let top = (step + 1) as f32 * step_height;
+        let bottom = top - thickness;
+        let start = step as f32 * step_radians;
+
+        let point = |radius: f32, angle: f32, y: f32| {
+            Vec3::new(radius * angle.cos(), y, -radius * angle.sin())
+        };
+        let angle = |segment: u32| start + step_radians * segment as f32 / segments as f32;
+
+        for segment in 0..segments {
+            let (from, to) = (angle(segment), angle(segment + 1));
+            let (v0, v1) = (
+                segment as f32 / segments as f32,
+                (segment + 1) as f32 / segments as f32,
+            );
+
+            for (y, normal) in [(top, Vec3::Y), (bottom, Vec3::NEG_Y)] {
+                builder.quad(
+                    [
+                        point(inner_radius, from, y),
+                        point(outer_radius, from, y),
+                        point(outer_radius, to, y),
+                        point(inner_radius, to, y),
+                    ],
+                    [normal; 4],
+                    [[0.0, v0], [1.0, v0], [1.0, v1], [0.0, v1]],
+                );
+            }
+
+            for (radius, outward) in [(outer_radius, 1.0), (inner_radius, -1.0)] {
+                let (from_normal, to_normal) = (point(outward, from, 0.0), point(outward, to, 0.0));
+                builder.quad(
+                    [
+                        point(radius, from, bottom),
+                        point(radius, to, bottom),
+                        point(radius, to, top),
+                        point(radius, from, top),
+                    ],
+                    [from_normal, to_normal, to_normal, from_normal],
+                    [[v0, 1.0], [v1, 1.0], [v1, 0.0], [v0, 0.0]],
+                );
+            }
+        }
+
+        // the flat faces at either end, the first of which is the step's riser
+        for (edge, sign) in [(start, -1.0), (start + step_radians, 1.0)] {
+            let normal = Vec3::new(-edge.sin(), 0.0, -edge.cos()) * sign * step_radians.signum();
+            builder.quad(
+                [
+                    point(inner_radius, edge, bottom),
+                    point(outer_radius, edge, bottom),
+                    point(outer_radius, edge, top),
+                    point(inner_radius, edge, top),
+                ],
+                [normal; 4],
+                [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+            );
+        }
+    }
+
+    if column && inner_radius > 0.0 {
+        let height = steps as f32 * step_height;
+        let sides = (2.0 * PI / MAX_SEGMENT_RADIANS) as u32;
+        let around = |side: u32| {
+            let angle = side as f32 / sides as f32 * 2.0 * PI;
+            Vec3::new(angle.cos(), 0.0, angle.sin())
+        };
+
+        for side in 0..sides {
+            let (from, to) = (around(side), around(side + 1));
+            let (u0, u1) = (side as f32 / sides as f32, (side + 1) as f32 / sides as f32);
+            builder.quad(
+                [
+                    from * inner_radius,
+                    to * inner_radius,
+                    to * inner_radius + Vec3::Y * height,
+                    from * inner_radius + Vec3::Y * height,
+                ],
+                [from, to, to, from],
+                [[u0, 1.0], [u1, 1.0], [u1, 0.0], [u0, 0.0]],
+            );
+
+            for (y, normal) in [(height, Vec3::Y), (0.0, Vec3::NEG_Y)] {
+                let uv = |corner: Vec3| [0.5 + corner.x / 2.0, 0.5 + corner.z / 2.0];
+                builder.triangle(
+                    [
+                        Vec3::Y * y,
+                        from * inner_radius + Vec3::Y * y,
+                        to * inner_radius + Vec3::Y * y,
+                    ],
+                    normal,
+                    [[0.5, 0.5], uv(from), uv(to)],
+                );
+            }
+        }
+    }
+
+    builder.into_mesh()
+}
+
+/// One straight flight of a [`generate_staircase`], and how the stairs turn at the
+/// landing after it.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct FlightSpec {
+    pub steps: u32,
+    /// rise from one step to the next
+    pub step_height: f32,
+    /// how far each step's top reaches along the flight
+    pub step_depth: f32,
+    pub width: f32,
+    /// radians the stairs turn at the landing after this flight, to the left
+    /// (counter-clockwise seen from above) for positive and the right for negative, at
+    /// most a quarter turn either way; ignored for the last flight
+    pub turn: f32,
+    /// handrails along both sides of the flight and around the landing after it
+    pub railings: bool,
+}
+
+impl Default for FlightSpec {
+    fn default() -> Self {
+        FlightSpec {
+            steps: 8,
+            step_height: 0.18,
+            step_depth: 0.28,
+            width: 1.0,
+            turn: FRAC_PI_2,
+            railings: true,
+        }
+    }
+}
+
+// how tall the handrails stand above the steps, and the most room between their posts
+const RAILING_HEIGHT: f32 = 0.9;
+const POST_SPACING: f32 = 1.0;
+// rails between each pair of posts, the top one being the handrail
+const RAILS: u32 = 2;
+
+/// Straight flights of steps one after another, joined by flat landings where the stairs
+/// turn, with optional handrails from [`generate_fence`], for buildings with more than
+/// one level.
+///
+/// The first flight starts at the origin and climbs towards -Z, Bevy's forward, centered
+/// on the Z axis. Each step is a block as tall as the flight's `step_height`, and the
+/// top of the last one is level with the landing after it, which is a slab as thick as a
+/// step. Landings are squares as wide as the flight before them, and the next flight
+/// leaves from the middle of the side it turns towards: straight on, off to the side for
+/// a quarter turn as in an L-shaped staircase, or in between from across a corner, with
+/// the landing reshaped to reach its bottom edge. Each flight's top edge and the next
+/// one's bottom edge are edges of the landing, so there are no gaps between them. Turns
+/// are clamped to a quarter turn either way; for a switchback, turn a quarter of the way
+/// twice with a flight of no steps in between, which makes a landing twice as long.
+///
+/// Railings run up both sides of a flight parallel to the slope, and around the edges of
+/// the landing after it that no flight leaves from. UVs are projected from world space,
+/// repeating once per unit, so a tiling texture runs on unbroken from step to step.
+/// Steps and landings are flat shaded. No flights gives a mesh with no vertices.
+pub fn generate_staircase(flights: &[FlightSpec]) -> Mesh {
+    let mut builder = Builder {
+        mesh: CustomMeshBuilder::new(),
+        uvs: UvMode::WorldSpace(1.0),
+    };
+    let mut railings = vec![];
+    // the middle of the current flight's bottom edge, and the way it climbs
+    let mut start = Vec3::ZERO;
+    let mut forward = Vec3::NEG_Z;
+
+    for (i, flight) in flights.iter().enumerate() {
+        let right = forward.cross(Vec3::Y);
+        let half = right * flight.width / 2.0;
+        let rise = Vec3::Y * flight.step_height;
+        let run = forward * flight.step_depth;
+
+        for step in 0..flight.steps {
+            let front = start + (run + rise) * step as f32;
+            builder.block(front - half, [half * 2.0, run, rise]);
+        }
+        // the middle of the flight's top edge
+        let end = start + (run + rise) * flight.steps as f32;
+
+        // the fence's posts are a twentieth of their height across
+        let post_radius = RAILING_HEIGHT / 40.0;
+        if flight.railings {
+            for side in [-1.0, 1.0] {
+                let inset = right * side * (flight.width / 2.0 - post_radius);
+                railings.push(railing(start + inset, end + inset));
+            }
+        }
+        if i + 1 == flights.len() {
+            break;
+        }
+
+        let next = Quat::from_rotation_y(flight.turn.clamp(-FRAC_PI_2, FRAC_PI_2)) * forward;
+        let middle = end + forward * flight.width / 2.0;
+        let next_start = middle + next * flight.width / 2.0;
+        let next_half = next.cross(Vec3::Y) * flights[i + 1].width / 2.0;
+
+        let outline = landing_outline(
+            [
+                end - half,
+                end + half,
+                middle + forward * flight.width / 2.0 + half,
+                middle + forward * flight.width / 2.0 - half,
+                next_start - next_half,
+                next_start + next_half,
+            ],
+            next_start,
+            next,
+        );
+        builder.slab(&outline, flight.step_height);
+
+        if flight.railings {
+            // edges the flights meet the landing along are left open
+            let open = |p: Vec3, q: Vec3| {
+                let on = |origin: Vec3, normal: Vec3| {
+                    [p, q]
+                        .iter()
+                        .all(|corner| (*corner - origin).dot(normal).abs() < flight.width * 1e-4)
+                };
+                on(end, forward) || on(next_start, next)
+            };
+            let center = outline.iter().sum::<Vec3>() / outline.len() as f32;
+            for k in 0..outline.len() {
+                let (p, q) = (outline[k], outline[(k + 1) % outline.len()]);
+                if open(p, q) {
+                    continue;
+                }
+                let mut inward = (q - p).cross(Vec3::Y).normalize_or_zero();
+                if inward.dot(center - p) < 0.0 {
+                    inward = -inward;
+                }
+                railings.push(railing(p + inward * post_radius, q + inward * post_radius));
+            }
+        }
+
+        start = next_start;
+        forward = next;
+    }
+
+    let mut parts = vec![builder.into_mesh()];
+    parts.extend(railings);
+    merge_meshes(&parts)
+}
+
+fn railing(from: Vec3, to: Vec3) -> Mesh {
+    generate_fence(&[from, to], POST_SPACING, RAILING_HEIGHT, RAILS)
+}
+
+// the top of a landing, as the convex hull of `corners` with whatever's past the line
+// the next flight starts along cut off, in order around its edge
+fn landing_outline(corners: [Vec3; 6], next_start: Vec3, next: Vec3) -> Vec<Vec3> {
+    let y = corners[0].y;
+    let mut points: Vec<Vec2> = corners.iter().map(|corner| corner.xz()).collect();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+
+    // Andrew's monotone chain, one half of the hull at a time
+    let cross = |o: Vec2, a: Vec2, b: Vec2| (a - o).perp_dot(b - o);
+    let mut hull: Vec<Vec2> = vec![];
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let floor = hull.len();
+        for point in pass {
+            while hull.len() >= floor + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+    }
+
+    // how far past the start of the next flight each corner is
+    let past = |point: Vec2| (point - next_start.xz()).dot(next.xz());
+    let mut outline: Vec<Vec2> = vec![];
+    for k in 0..hull.len() {
+        let (p, q) = (hull[k], hull[(k + 1) % hull.len()]);
+        if past(p) <= 0.0 {
+            outline.push(p);
+        }
+        if (past(p) < 0.0 && past(q) > 0.0) || (past(p) > 0.0 && past(q) < 0.0) {
+            outline.push(p.lerp(q, past(p) / (past(p) - past(q))));
+        }
+    }
+    // corners that land on top of each other, like the two where a flight leaves from a
+    // whole side of the landing
+    let close = |a: Vec2, b: Vec2| a.distance(b) < 1e-5 * (1.0 + a.length());
+    outline.dedup_by(|b, a| close(*a, *b));
+    if outline.len() > 1 && close(outline[0], outline[outline.len() - 1]) {
+        outline.pop();
+    }
+
+    outline
+        .into_iter()
+        .map(|point| Vec3::new(point.x, y, point.y))
+        .collect()
+}
+
+struct Builder {
+    mesh: CustomMeshBuilder,
+    uvs: UvMode,
+}
+
+impl Builder {
+    // wound to face whichever way the normals point, so corners can be listed in
+    // either direction
+    fn quad(&mut self, corners: [Vec3; 4], normals: [Vec3; 4], uvs: [[f32; 2]; 4]) {
+        // projected the same for every corner, so a curved wall's quads aren't split
+        // between two axes
+        let face: Vec3 = normals.iter().sum();
+        let [a, b, c, d] = [0, 1, 2, 3].map(|i| {
+            let uv = self.uvs.uv(Vec2::from(uvs[i]), corners[i], face);
+            self.mesh.vertex(corners[i], uv, normals[i])
+        });
+
+        let facing = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+        if facing.dot(face) < 0.0 {
+            self.mesh.triangle(a, c, b).triangle(a, d, c);
+        } else {
+            self.mesh.triangle(a, b, c).triangle(a, c, d);
+        }
+    }
+
+    fn triangle(&mut self, corners: [Vec3; 3], normal: Vec3, uvs: [[f32; 2]; 3]) {
+        let [a, b, c] = [0, 1, 2].map(|i| {
+            let uv = self.uvs.uv(Vec2::from(uvs[i]), corners[i], normal);
+            self.mesh.vertex(corners[i], uv, normal)
+        });
+
+        let facing = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+        if facing.dot(normal) < 0.0 {
+            self.mesh.triangle(a, c, b);
+        } else {
+            self.mesh.triangle(a, b, c);
+        }
+    }
+
+    // a box with a corner at `origin` and its three edges from there, flat shaded
+    fn block(&mut self, origin: Vec3, edges: [Vec3; 3]) {
+        for axis in 0..3 {
+            let (u, v) = (edges[(axis + 1) % 3], edges[(axis + 2) % 3]);
+            let mut normal = u.cross(v).normalize_or_zero();
+            if normal.dot(edges[axis]) < 0.0 {
+                normal = -normal;
+            }
+            for (corner, facing) in [(origin, -normal), (origin + edges[axis], normal)] {
+                self.quad(
+                    [corner, corner + u, corner + u + v, corner + v],
+                    [facing; 4],
+                    [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+                );
+            }
+        }
+    }
+
+    // a slab `thickness` deep under the convex, level `outline`, flat shaded
+    fn slab(&mut self, outline: &[Vec3], thickness: f32) {
+        let down = Vec3::Y * thickness;
+        let center = outline.iter().sum::<Vec3>() / outline.len().max(1) as f32;
+        for k in 1..outline.len().saturating_sub(1) {
+            let top = [outline[0], outline[k], outline[k + 1]];
+            self.triangle(top, Vec3::Y, [[0.0, 0.0]; 3]);
+            self.triangle(
+                top.map(|corner| corner - down),
+                Vec3::NEG_Y,
+                [[0.0, 0.0]; 3],
+            );
+        }
+        for k in 0..outline.len() {
+            let (p, q) = (outline[k], outline[(k + 1) % outline.len()]);
+            let mut normal = (q - p).cross(Vec3::Y).normalize_or_zero();
+            if normal.dot(p - center) < 0.0 {
+                normal = -normal;
+            }
+            self.quad(
+                [p - down, q - down, q, p],
+                [normal; 4],
+                [[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]],
+            );
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        self.mesh
+            .build()
+            .expect("stair triangles only use vertices added alongside them")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{attributes::normals, util::positions};
+
+    // each step's top should span exactly its share of the turn, so one step ends at the
+    // angle where the next begins
+    #[test]
+    fn spiral_steps_meet_without_gaps_or_overlap() {
+        for total_radians in [2.0 * PI, -3.0] {
+            let params = SpiralStairsParams {
+                total_radians,
+                column: false,
+                ..default()
+            };
+            let mesh = generate_spiral_stairs(&params);
+            let step_radians = total_radians / params.steps as f32;
+            let tops: Vec<Vec3> = positions(&mesh)
+                .iter()
+                .zip(normals(&mesh).unwrap())
+                .filter(|(_, normal)| Vec3::from(**normal) == Vec3::Y)
+                .map(|(position, _)| Vec3::from(*position))
+                .collect();
+
+            for step in 0..params.steps {
+                let top = (step + 1) as f32 * params.step_height;
+                let start = Quat::from_rotation_y(-(step as f32 * step_radians));
+                let (mut min, mut max) = (f32::MAX, f32::MIN);
+                for position in tops.iter().filter(|p| (p.y - top).abs() < 1e-5) {
+                    let along = start * *position;
+                    let angle = (-along.z).atan2(along.x);
+                    (min, max) = (min.min(angle), max.max(angle));
+                }
+                assert!((min - step_radians.min(0.0)).abs() < 1e-4, "{step}: {min}");
+                assert!((max - step_radians.max(0.0)).abs() < 1e-4, "{step}: {max}");
+            }
+        }
+    }
+
+    // the top of the first flight, the landing and the bottom of the second flight should
+    // share their edges, so an L-shaped staircase has no gap where it turns
+    #[test]
+    fn l_shaped_flights_meet_the_landing_edge_to_edge() {
+        let flight = FlightSpec {
+            railings: false,
+            ..default()
+        };
+        let mesh = generate_staircase(&[flight, flight]);
+        let rise = flight.steps as f32 * flight.step_height;
+        let run = flight.steps as f32 * flight.step_depth;
+        let half = flight.width / 2.0;
+        let facing = |direction: Vec3| -> Vec<Vec3> {
+            positions(&mesh)
+                .iter()
+                .zip(normals(&mesh).unwrap())
+                .filter(|(_, normal)| Vec3::from(**normal).distance(direction) < 1e-5)
+                .map(|(position, _)| Vec3::from(*position))
+                .collect()
+        };
+        let has = |points: &[Vec3], corner: Vec3| points.iter().any(|p| p.distance(corner) < 1e-4);
+
+        // the landing's near edge is the far edge of the first flight's last step
+        let level: Vec<Vec3> = facing(Vec3::Y)
+            .into_iter()
+            .filter(|p| (p.y - rise).abs() < 1e-4)
+            .collect();
+        let landing = [
+            Vec3::new(-half, rise, -run),
+            Vec3::new(half, rise, -run),
+            Vec3::new(half, rise, -run - flight.width),
+            Vec3::new(-half, rise, -run - flight.width),
+        ];
+        assert!(landing.iter().all(|&corner| has(&level, corner)));
+        let last_step = Vec3::new(half, rise, -run + flight.step_depth);
+        assert!(has(&level, last_step));
+
+        // the second flight turns left, to -X, and its first riser stands on the landing's
+        // left edge
+        let riser: Vec<Vec3> = facing(Vec3::X)
+            .into_iter()
+            .filter(|p| (p.x + half).abs() < 1e-4)
+            .collect();
+        assert!(has(&riser, landing[0]) && has(&riser, landing[3]));
+        let top = rise + flight.step_height;
+        assert!(riser
+            .iter()
+            .all(|p| p.y >= rise - 1e-4 && p.y <= top + 1e-4));
+        assert!(riser
+            .iter()
+            .all(|p| p.z <= -run + 1e-4 && p.z >= -run - flight.width - 1e-4));
+    }
+}
@@ -0,0 +1,2006 @@
+use std::{
+    collections::BinaryHeap,
+    f32::consts::{PI, TAU},
+};
+
+use bevy::{
+    math::Rect,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    attributes::{normals, normals_mut, uvs},
+    bounds::position_bounds,
+    bvh::MeshBvh,
+    editor::GridRegion,
+    error::{at_least, positive, GeometryError},
+    geometry::{GeometryData, QuadMesh},
+    noise::fbm,
+    plane::{triangulate_leaves, Anchor, Cell},
+    raycast::raycast_mesh,
+    slice::{section, Plane},
+    topology::find_boundary_edges,
+    util::{
+        positions, positions_mut, quantize, refresh_normals_around, smooth_normals,
+        triangle_indices, welded_position_ids, Closest, Rng,
+    },
+};
+
+/// Height of the topmost surface of the mesh at a world XZ position, linearly
+/// interpolated across the triangle found there. `None` when the position is
+/// outside the mesh's footprint.
+///
+/// Each call scans every triangle once, which is cheaper than building a
+/// [`MeshBvh`](crate::bvh::MeshBvh) for a single query. Gameplay code sampling the same
+/// terrain over and over should build one once and use
+/// [`MeshBvh::sample_height`](crate::bvh::MeshBvh::sample_height) instead.
+pub fn sample_terrain_height(mesh: &Mesh, x: f32, z: f32) -> Option<f32> {
+    let top = positions(mesh)
+        .iter()
+        .map(|position| position[1])
+        .reduce(f32::max)?
+        + 1.0;
+
+    raycast_mesh(mesh, Vec3::new(x, top, z), Vec3::NEG_Y).map(|hit| hit.point.y)
+}
+
+/// Heights of the terrain on a regular grid of `rows` by `cols` points across its
+/// footprint, for a physics engine's heightfield collider, which is far cheaper than
+/// colliding with the triangles themselves.
+///
+/// The heights come row by row, rows running along X and following each other along Z,
+/// with the first and last rows and columns on the edges of the mesh's bounds, so
+/// the collider is scaled to the size of
+/// [`mesh_aabb`](crate::bounds::mesh_aabb) across XZ. A [`generate_heightmap`] grid
+/// with as many rows and columns of vertices has its heights read straight off; any
+/// other terrain is sampled from above at each point like [`sample_terrain_height`],
+/// just inside its edges so the outermost points don't slip past it, and points with
+/// nothing under them get the terrain's lowest height. At least one row and column is
+/// sampled, a single one through the middle.
+pub fn to_heightfield(mesh: &Mesh, rows: u32, cols: u32) -> Vec<f32> {
+    let (rows, cols) = (rows.max(1) as usize, cols.max(1) as usize);
+    let points = positions(mesh);
+    let Some((min, max)) = position_bounds(points) else {
+        return vec![0.0; rows * cols];
+    };
+    let extent = max - min;
+    let at = |i: usize, count: usize, min: f32, extent: f32| {
+        if count > 1 {
+            min + extent * i as f32 / (count - 1) as f32
+        } else {
+            min + extent / 2.0
+        }
+    };
+
+    // a heightmap grid of the same size, with every vertex where a sample goes
+    let tolerance = 1e-5 * extent.max_element();
+    let is_grid = grid_size(points) == Some((rows - 1, cols - 1))
+        && points.iter().enumerate().all(|(vertex, [x, _, z])| {
+            let (row, col) = (vertex / cols, vertex % cols);
+            (x - at(col, cols, min.x, extent.x)).abs() <= tolerance
+                && (z - at(row, rows, min.z, extent.z)).abs() <= tolerance
+        });
+    if is_grid {
+        return points.iter().map(|position| position[1]).collect();
+    }
+
+    let bvh = MeshBvh::build(mesh);
+    let inset = extent * 1e-4;
+    let (inner_min, inner_extent) = (min + inset, extent - inset * 2.0);
+    (0..rows)
+        .flat_map(|row| (0..cols).map(move |col| (row, col)))
+        .map(|(row, col)| {
+            let x = at(col, cols, inner_min.x, inner_extent.x);
+            let z = at(row, rows, inner_min.z, inner_extent.z);
+            bvh.sample_height(x, z).unwrap_or(min.y)
+        })
+        .collect()
+}
+
+/// Contour lines of terrain at every multiple of `interval` in height between its lowest
+/// and highest point, for map-style rendering or showing elevation bands.
+///
+/// Each line is the [`section`](crate::slice::section) of the mesh at that height, so
+/// it runs with higher ground on its left seen from above, loops repeat their first
+/// point at the end, and lines that run off the edge of the terrain end there. Lines
+/// come out grouped by height, lowest first. A non-positive `interval` gives no lines.
+pub fn generate_contours(mesh: &Mesh, interval: f32) -> Vec<Vec<Vec3>> {
+    let heights = positions(mesh).iter().map(|position| position[1]);
+    let (Some(low), Some(high)) = (heights.clone().reduce(f32::min), heights.reduce(f32::max))
+    else {
+        return vec![];
+    };
+    if interval <= 0.0 || !interval.is_finite() {
+        return vec![];
+    }
+
+    let (first, last) = (
+        (low / interval).ceil() as i64,
+        (high / interval).floor() as i64,
+    );
+    (first..=last)
+        .flat_map(|level| {
+            let height = level as f32 * interval;
+            section(mesh, Plane::new(Vec3::Y * height, Vec3::Y))
+        })
+        .collect()
+}
+
+/// Blasts a crater into terrain: vertices within `radius` of `center` (a world XZ
+/// position) sink by up to `depth` in the middle and are pushed up by up to `rim_height`
+/// towards the edge, easing back to their old height at `radius` so there's no seam.
+///
+/// Normals are recomputed only around the vertices that moved. On a grid still laid out
+/// as [`generate_heightmap`] made it, only the rows and columns within `radius` are
+/// visited at all, so repeated small impacts on a large terrain stay cheap; other
+/// meshes have every vertex checked.
+pub fn apply_crater(mesh: &mut Mesh, center: Vec2, radius: f32, depth: f32, rim_height: f32) {
+    let crater = |position: &mut [f32; 3]| {
+        let t = Vec2::new(position[0], position[2]).distance(center) / radius;
+        if t >= 1.0 || t.is_nan() {
+            return false;
+        }
+
+        // both terms fall to zero with zero slope at the edge, and the rim peaks about
+        // 70% of the way out
+        let t2 = t * t;
+        let bowl = (1.0 - t2) * (1.0 - t2);
+        let rim = (4.0 * t2 * (1.0 - t2)).powi(2);
+        position[1] += rim_height * rim - depth * bowl;
+        true
+    };
+
+    let Some(grid) = GridLayout::of(mesh) else {
+        let moved: Vec<usize> = positions_mut(mesh)
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, position)| crater(position).then_some(i))
+            .collect();
+        refresh_normals_around(mesh, &moved);
+        return;
+    };
+    let Some(region) = grid.region(center, radius) else {
+        return;
+    };
+
+    let points = positions_mut(mesh);
+    for row in region.min_row..=region.max_row {
+        for col in region.min_col..=region.max_col {
+            crater(&mut points[grid.vertex(row, col)]);
+        }
+    }
+    grid.refresh_normals(mesh, grid.ring_around(region));
+}
+
+/// Cuts a channel along `path`, a polyline of world XZ positions, for a riverbed or a
+/// road cut: vertices within `width` of the path sink by `depth` right on it, easing
+/// back to their old height at `width` with a rounded cross-section and no seam.
+///
+/// Each vertex is lowered once, by its distance to the nearest segment, so the channel
+/// stays the same depth through bends and where the path crosses itself. A path of a
+/// single point digs a round pit. Like [`apply_crater`], only the normals around the
+/// vertices that moved are recomputed.
+pub fn carve_path(mesh: &mut Mesh, path: &[Vec2], width: f32, depth: f32) {
+    if path.is_empty() {
+        return;
+    }
+
+    carve(mesh, width, depth, |_, point| {
+        if path.len() == 1 {
+            point.distance(path[0])
+        } else {
+            path.windows(2)
+                .map(|segment| distance_to_segment(point, segment[0], segment[1]))
+                .fold(f32::INFINITY, f32::min)
+        }
+    });
+}
+
+// lowers every vertex by `depth` at a `distance` of zero from whatever's being carved,
+// easing back to nothing at `width`, given each vertex's index and XZ position
+fn carve(mesh: &mut Mesh, width: f32, depth: f32, distance: impl Fn(usize, Vec2) -> f32) {
+    let mut moved = vec![];
+    for (i, position) in positions_mut(mesh).iter_mut().enumerate() {
+        let t = distance(i, Vec2::new(position[0], position[2])) / width;
+        if t >= 1.0 || t.is_nan() {
+            continue;
+        }
+
+        // the crater's bowl, flat along the bottom and at the banks
+        let t2 = t * t;
+        position[1] -= depth * (1.0 - t2) * (1.0 - t2);
+        moved.push(i);
+    }
+
+    refresh_normals_around(mesh, &moved);
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HeightmapParams {
+    /// cells along Z
+    pub rows: u32,
+    /// cells along X
+    pub cols: u32,
+    pub cell_size: f32,
+    /// how many times a texture repeats across the grid, so UVs run 0..`uv_tiles`
+    pub uv_tiles: f32,
+    /// how far past 0..`uv_tiles` the UVs of the outermost ring of vertices reach on
+    /// every side, or inside it when negative, for lining up texels across the seams
+    /// between tiles laid edge to edge; 0 leaves the edges at 0 and `uv_tiles`
+    pub uv_collar: f32,
+    /// which point of the grid is at the origin; UVs don't depend on it
+    pub anchor: Anchor,
+    pub split: QuadSplit,
+}
+
+impl Default for HeightmapParams {
+    // the same 10 x 10 footprint as the demo's plane
+    fn default() -> Self {
+        HeightmapParams {
+            rows: 10,
+            cols: 10,
+            cell_size: 1.0,
+            uv_tiles: 1.0,
+            uv_collar: 0.0,
+            anchor: Anchor::Center,
+            split: QuadSplit::Fixed,
+        }
+    }
+}
+
+/// Which diagonal a grid cell is split into two triangles along, seen from above with
+/// -Z up, so the cell's first row is along its top.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Diagonal {
+    /// from the bottom-left corner `(row + 1, col)` to the top-right one `(row, col + 1)`
+    #[default]
+    Rising,
+    /// from the top-left corner `(row, col)` to the bottom-right one `(row + 1, col + 1)`
+    Falling,
+}
+
+/// How [`generate_heightmap`] picks the [`Diagonal`] each cell is split along.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QuadSplit {
+    /// always [`Diagonal::Rising`], like every other grid in the crate
+    #[default]
+    Fixed,
+    /// whichever diagonal is shorter once the corners are raised, so creases run along
+    /// ridges and valleys instead of cutting across them
+    ShorterDiagonal,
+}
+
+impl QuadSplit {
+    /// The diagonal for a cell whose `corners` are, in order, `(row, col)`,
+    /// `(row + 1, col)`, `(row + 1, col + 1)` and `(row, col + 1)`. Ties go to
+    /// [`Diagonal::Rising`], so flat ground is split the same either way.
+    pub fn diagonal(self, corners: [Vec3; 4]) -> Diagonal {
+        let [a, b, c, d] = corners;
+        match self {
+            QuadSplit::ShorterDiagonal if a.distance_squared(c) < b.distance_squared(d) => {
+                Diagonal::Falling
+            }
+            _ => Diagonal::Rising,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BiomeTerrainParams {
+    pub grid: HeightmapParams,
+    /// heights paired with the color the terrain has there
+    pub bands: Vec<(f32, Color)>,
+}
+
+/// Indexed grid of square cells placed on the origin by `anchor`, with each vertex
+/// raised to `height_fn(x, z)` and smooth normals.
+///
+/// Columns run along X and rows along Z. UVs span 0..`uv_tiles` across the whole grid,
+/// with the edges pushed out by `uv_collar`: a collar of `0.5 / texture_width` (times
+/// `uv_tiles`) puts the edges on texel centers, so neighboring tiles sample the same
+/// edge texels instead of one showing a row the other doesn't. Each cell is split
+/// along the diagonal `split` picks.
+pub fn generate_heightmap(params: &HeightmapParams, height_fn: impl Fn(f32, f32) -> f32) -> Mesh {
+    generate_heightmap_raw(params, height_fn).into_mesh()
+}
+
+/// [`generate_heightmap`] as plain arrays rather than a `Mesh`, such as for a physics
+/// engine's heightfield or trimesh collider.
+pub fn generate_heightmap_raw(
+    params: &HeightmapParams,
+    height_fn: impl Fn(f32, f32) -> f32,
+) -> GeometryData {
+    heightmap(params, height_fn, |_, _, corners| {
+        params.split.diagonal(corners)
+    })
+}
+
+/// [`generate_heightmap`] with its cells left whole as quads, for exporting terrain to
+/// be edited further, with [`to_obj_quads`](crate::export::to_obj_quads) for one.
+///
+/// Each quad starts at the cell's `(row, col)` corner, so splitting it as
+/// [`QuadMesh::into_mesh`] does gives [`Diagonal::Rising`] and the same mesh as
+/// [`generate_heightmap`] with [`QuadSplit::Fixed`]. `params.split` is ignored, and the
+/// normals are the ones that mesh has.
+pub fn generate_heightmap_quads(
+    params: &HeightmapParams,
+    height_fn: impl Fn(f32, f32) -> f32,
+) -> QuadMesh {
+    let grid = heightmap(params, height_fn, |_, _, _| Diagonal::Rising);
+    let (rows, cols) = (params.rows, params.cols);
+    let vertex = |row: u32, col: u32| row * (cols + 1) + col;
+    QuadMesh {
+        positions: grid.positions,
+        normals: grid.normals,
+        uvs: grid.uvs,
+        quads: (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (row, col)))
+            .map(|(row, col)| {
+                [
+                    vertex(row, col),
+                    vertex(row + 1, col),
+                    vertex(row + 1, col + 1),
+                    vertex(row, col + 1),
+                ]
+            })
+            .collect(),
+    }
+}
+
+/// Square patch of a planet's surface, `angular_size` radians across, centered on
+/// `center_dir` from the middle of a sphere of `radius` at the origin and split into
+/// `resolution` by `resolution` cells, for terrain too big to be laid out flat.
+///
+/// Each vertex is raised straight out from the middle of the planet by
+/// `height_fn(direction)`, given its unit direction from there, so patches built
+/// separately agree wherever they meet. The patch is laid out like a face of
+/// [`generate_cube_sphere`](crate::sphere::generate_cube_sphere): seen from outside, U
+/// runs right along the direction perpendicular to `center_dir` and +Y (+X for the
+/// patches straight above and below), and V down from the side nearest +Y. Vertices are
+/// spaced evenly by angle both ways, so the edges are great circles, and patches of the
+/// same size whose middles are `angular_size` apart along U or V line up along them.
+/// Normals start out pointing straight away from the middle of the planet and tilt
+/// with the slope of `height_fn` across each cell, so the shading follows the curve of
+/// the planet as well as the hills on it.
+///
+/// Positions are relative to the middle of the planet. On one as big as the Earth,
+/// `f32` only places them to within about a meter, so for a closer look subtract the
+/// patch's middle from them and spawn it there instead.
+///
+/// Fails with [`GeometryError::InvalidParameter`] unless `center_dir` isn't zero,
+/// `radius` is positive, `angular_size` is more than nothing and less than half a turn,
+/// and there's at least one cell.
+pub fn generate_spherical_terrain_patch(
+    center_dir: Vec3,
+    angular_size: f32,
+    resolution: u32,
+    radius: f32,
+    height_fn: impl Fn(Vec3) -> f32,
+) -> Result<Mesh, GeometryError> {
+    let Some(outward) = center_dir.try_normalize() else {
+        return Err(GeometryError::InvalidParameter {
+            name: "center_dir",
+            reason: "must not be zero".to_string(),
+        });
+    };
+    positive("radius", radius)?;
+    positive("angular_size", angular_size)?;
+    if angular_size >= PI {
+        return Err(GeometryError::InvalidParameter {
+            name: "angular_size",
+            reason: format!("must be less than half a turn, got {angular_size}"),
+        });
+    }
+    at_least("resolution", resolution, 1)?;
+
+    // right and up seen from outside, right-handed with `outward` as a cube face's are
+    let toward_y = Vec3::Y - outward * outward.y;
+    let up = toward_y
+        .try_normalize()
+        .unwrap_or(Vec3::new(0.0, 0.0, -outward.y.signum()));
+    let right = up.cross(outward);
+    let half = angular_size / 2.0;
+    // half a cell, as an angle, to sample the slope over
+    let step = half / resolution as f32;
+
+    let mut data = GeometryData::default();
+    for row in 0..=resolution {
+        for col in 0..=resolution {
+            let (u, v) = (
+                col as f32 / resolution as f32,
+                row as f32 / resolution as f32,
+            );
+            let across = ((u * 2.0 - 1.0) * half).tan();
+            let down = ((1.0 - v * 2.0) * half).tan();
+            let direction = (outward + right * across + up * down).normalize();
+            let height = height_fn(direction);
+
+            // how fast the height changes along two directions across the surface here
+            let east = (right - direction * direction.dot(right)).normalize();
+            let north = direction.cross(east);
+            let slope = |along: Vec3| {
+                let towards = |angle: f32| direction * angle.cos() + along * angle.sin();
+                (height_fn(towards(step)) - height_fn(towards(-step)))
+                    / (2.0 * step * (radius + height))
+            };
+            let normal = (direction - east * slope(east) - north * slope(north)).normalize();
+
+            data.positions.push((direction * (radius + height)).into());
+            data.normals.push(normal.into());
+            data.uvs.push([u, v]);
+        }
+    }
+    data.indices = grid_triangles(resolution, resolution)
+        .into_iter()
+        .flatten()
+        .map(|v| v as u32)
+        .collect();
+    Ok(data.into_mesh())
+}
+
+/// [`generate_heightmap`] with `split(row, col, corners)` choosing the diagonal of each
+/// cell instead of `params.split`, given its corners in the order
+/// [`QuadSplit::diagonal`] takes them, for splitting on slope, on a pattern, or
+/// anything else.
+pub fn generate_heightmap_with_split(
+    params: &HeightmapParams,
+    height_fn: impl Fn(f32, f32) -> f32,
+    split: impl Fn(u32, u32, [Vec3; 4]) -> Diagonal,
+) -> Mesh {
+    heightmap(params, height_fn, split).into_mesh()
+}
+
+// the grid both of the plain heightmap generators build
+fn heightmap(
+    params: &HeightmapParams,
+    height_fn: impl Fn(f32, f32) -> f32,
+    split: impl Fn(u32, u32, [Vec3; 4]) -> Diagonal,
+) -> GeometryData {
+    let HeightmapParams {
+        rows,
+        cols,
+        cell_size,
+        uv_tiles,
+        uv_collar,
+        anchor,
+        ..
+    } = *params;
+    let min = anchor.min_corner(Vec2::new(cols as f32, rows as f32) * cell_size);
+    // pushes the first and last of `count + 1` UVs out by the collar
+    let collar = |i: u32, count: u32| {
+        if i == 0 {
+            -uv_collar
+        } else if i == count {
+            uv_collar
+        } else {
+            0.0
+        }
+    };
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    for row in 0..=rows {
+        for col in 0..=cols {
+            let x = min.x + col as f32 * cell_size;
+            let z = min.y + row as f32 * cell_size;
+            positions.push([x, height_fn(x, z), z]);
+            uvs.push([
+                col as f32 / cols.max(1) as f32 * uv_tiles + collar(col, cols),
+                row as f32 / rows.max(1) as f32 * uv_tiles + collar(row, rows),
+            ]);
+        }
+    }
+
+    let corner = |row: u32, col: u32| Vec3::from(positions[(row * (cols + 1) + col) as usize]);
+    let triangles = split_grid_triangles(rows, cols, |row, col| {
+        let corners = [
+            corner(row, col),
+            corner(row + 1, col),
+            corner(row + 1, col + 1),
+            corner(row, col + 1),
+        ];
+        split(row, col, corners)
+    });
+    let normals = smooth_normals(&positions, &triangles);
+    let indices = triangles.iter().flatten().map(|v| *v as u32).collect();
+
+    GeometryData {
+        positions,
+        normals,
+        uvs,
+        indices,
+    }
+}
+
+/// Terrain over `bounds` on the XZ plane (its Y being Z) with `height_fn(x, z)` for
+/// heights, whose cells are split finer wherever the surface bends and left big where
+/// it's flat, in as much detail as fits in `max_triangles`, for large worlds that only
+/// need their vertices where the ground is bumpy.
+///
+/// The grid is a quadtree down to 4096 cells across. A cell is split when heights
+/// sampled across it stray from the plane through its corners by more than a
+/// threshold, and the threshold is the lowest that keeps the mesh within budget, found
+/// by bisection. A budget under 2 still gets the 2 triangles of a single cell. As with
+/// [`generate_radial_lod_plane`](crate::plane::generate_radial_lod_plane), neighboring
+/// cells never differ by more than one level and a cell next to finer ones is fanned
+/// to meet their vertices, so there are no T-junctions. Normals are smooth, and UVs
+/// span 0..1 across `bounds`.
+pub fn generate_heightmap_adaptive(
+    height_fn: impl Fn(f32, f32) -> f32,
+    bounds: Rect,
+    max_triangles: u32,
+) -> Mesh {
+    let finest = 1_u32 << ADAPTIVE_DEPTH;
+    let to_world =
+        |x: u32, z: u32| bounds.min + Vec2::new(x as f32, z as f32) / finest as f32 * bounds.size();
+    let height_at = |x: u32, z: u32| {
+        let point = to_world(x, z);
+        height_fn(point.x, point.y)
+    };
+
+    let mut heights: HashMap<(u32, u32), f32> = HashMap::new();
+    let mut errors: HashMap<Cell, f32> = HashMap::new();
+    let mut error = |cell: Cell| {
+        *errors.entry(cell).or_insert_with(|| {
+            flatness_error(cell, |x, z| {
+                *heights.entry((x, z)).or_insert_with(|| height_at(x, z))
+            })
+        })
+    };
+
+    let budget = max_triangles.max(2) as usize;
+    let (leaves, tree) = match adaptive_tree(0.0, budget, &mut error) {
+        Some(tree) => tree,
+        None => {
+            // a threshold of the root's own error never splits it, so always fits
+            let mut fits = error(Cell::ROOT);
+            let mut tree =
+                adaptive_tree(fits, budget, &mut error).expect("a single cell fits in any budget");
+            let mut over = 0.0;
+            for _ in 0..32 {
+                let threshold = (over + fits) / 2.0;
+                match adaptive_tree(threshold, budget, &mut error) {
+                    Some(smaller) => {
+                        fits = threshold;
+                        tree = smaller;
+                    }
+                    None => over = threshold,
+                }
+            }
+            tree
+        }
+    };
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut vertices: HashMap<(u32, u32), u32> = HashMap::new();
+    let vertex = |x: u32, z: u32| {
+        *vertices.entry((x, z)).or_insert_with(|| {
+            let point = to_world(x, z);
+            positions.push([point.x, height_at(x, z), point.y]);
+            uvs.push([x as f32 / finest as f32, z as f32 / finest as f32]);
+            positions.len() as u32 - 1
+        })
+    };
+    let indices = triangulate_leaves(
+        &leaves,
+        ADAPTIVE_DEPTH,
+        |x, z| leaf_depth_at(&tree, x, z),
+        vertex,
+    );
+
+    let triangles: Vec<[usize; 3]> = indices
+        .chunks_exact(3)
+        .map(|tri| [tri[0] as usize, tri[1] as usize, tri[2] as usize])
+        .collect();
+    let normals = smooth_normals(&positions, &triangles);
+
+    GeometryData {
+        positions,
+        normals,
+        uvs,
+        indices,
+    }
+    .into_mesh()
+}
+
+/// What [`generate_grid_plane_with`]'s callback decides for each vertex.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VertexData {
+    /// added to the vertex's Y
+    pub height: f32,
+    pub color: Color,
+    pub uv: Vec2,
+}
+
+impl Default for VertexData {
+    fn default() -> Self {
+        VertexData {
+            height: 0.0,
+            color: Color::WHITE,
+            uv: Vec2::ZERO,
+        }
+    }
+}
+
+/// Indexed grid of `rows` by `cols` square cells centered on the origin, like
+/// [`generate_heightmap`], with each vertex's height, color and UV from `f`.
+///
+/// `f` gets the vertex's row and column (from 0 to `rows` and `cols` inclusive) and its
+/// position on the flat grid, so biomes, rivers and roads can all be worked out in
+/// the one pass, from [`fbm`](crate::noise::fbm) or anything else. Normals are smoothed
+/// from the final, raised positions. Colors are stored in linear space.
+pub fn generate_grid_plane_with(
+    rows: u32,
+    cols: u32,
+    cell_size: f32,
+    f: impl Fn(u32, u32, Vec3) -> VertexData,
+) -> Mesh {
+    let min = Anchor::Center.min_corner(Vec2::new(cols as f32, rows as f32) * cell_size);
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut colors: Vec<[f32; 4]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    for row in 0..=rows {
+        for col in 0..=cols {
+            let base = Vec3::new(
+                min.x + col as f32 * cell_size,
+                0.0,
+                min.y + row as f32 * cell_size,
+            );
+            let vertex = f(row, col, base);
+            positions.push((base + Vec3::Y * vertex.height).into());
+            colors.push(vertex.color.as_linear_rgba_f32());
+            uvs.push(vertex.uv.into());
+        }
+    }
+
+    let triangles = grid_triangles(rows, cols);
+    let normals = smooth_normals(&positions, &triangles);
+    let indices = triangles.iter().flatten().map(|v| *v as u32).collect();
+
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+// two triangles per cell of a grid whose vertices go row by row, `cols + 1` to a row
+pub(crate) fn grid_triangles(rows: u32, cols: u32) -> Vec<[usize; 3]> {
+    split_grid_triangles(rows, cols, |_, _| Diagonal::Rising)
+}
+
+// two triangles per cell, split along `diagonal(row, col)`
+fn split_grid_triangles(
+    rows: u32,
+    cols: u32,
+    diagonal: impl Fn(u32, u32) -> Diagonal,
+) -> Vec<[usize; 3]> {
+    //  (r,c)    (r,c+1)
+    //    a -------- d
+    //    |        / |
+    //    |      /   |
+    //    |    /     |
+    //    |  /       |
+    //    b -------- c
+    // (r+1,c)  (r+1,c+1)
+    let vertex = |row: u32, col: u32| (row * (cols + 1) + col) as usize;
+    let mut triangles: Vec<[usize; 3]> = vec![];
+    for row in 0..rows {
+        for col in 0..cols {
+            let a = vertex(row, col);
+            let b = vertex(row + 1, col);
+            let c = vertex(row + 1, col + 1);
+            let d = vertex(row, col + 1);
+            match diagonal(row, col) {
+                Diagonal::Rising => triangles.extend([[a, b, d], [d, b, c]]),
+                Diagonal::Falling => triangles.extend([[a, b, c], [a, c, d]]),
+            }
+        }
+    }
+    triangles
+}
+
+/// Makes the normals of neighboring terrain chunks agree where they meet, so lighting
+/// doesn't show a seam along chunk borders. Each chunk's mesh is placed at its entry in
+/// `offsets`, the translation it's spawned with.
+///
+/// Chunks generated separately only smooth their edge normals over their own triangles,
+/// so the two copies of a shared edge vertex point different ways. Every vertex whose
+/// placed position (to within 0.0001) turns up in more than one chunk gets the
+/// area-weighted average of the triangles around it in all of them, which is just what
+/// it would have had in one big mesh, and the copies come out bit-identical. Vertices
+/// inside a chunk keep their normals. Chunks without an offset stay where they are.
+pub fn stitch_chunk_normals(chunks: &mut [Mesh], offsets: &[Vec3]) {
+    let placed = |chunk: usize, position: [f32; 3]| {
+        let offset = offsets.get(chunk).copied().unwrap_or_default();
+        quantize((Vec3::from(position) + offset).into(), 4)
+    };
+
+    // which chunks each placed position is in, and the sum of its triangles' normals
+    let mut shared: HashMap<[i64; 3], (Option<usize>, bool, Vec3)> = HashMap::new();
+    for (chunk, mesh) in chunks.iter().enumerate() {
+        for position in positions(mesh) {
+            let (first, several, _) = shared.entry(placed(chunk, *position)).or_default();
+            match first {
+                None => *first = Some(chunk),
+                Some(first) if *first != chunk => *several = true,
+                _ => {}
+            }
+        }
+    }
+    shared.retain(|_, (_, several, _)| *several);
+
+    for (chunk, mesh) in chunks.iter().enumerate() {
+        let positions = positions(mesh);
+        for tri in triangle_indices(mesh) {
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+            let normal = (b - a).cross(c - a);
+            for v in tri {
+                if let Some((_, _, sum)) = shared.get_mut(&placed(chunk, positions[v])) {
+                    *sum += normal;
+                }
+            }
+        }
+    }
+
+    for (chunk, mesh) in chunks.iter_mut().enumerate() {
+        let keys: Vec<[i64; 3]> = positions(mesh)
+            .iter()
+            .map(|position| placed(chunk, *position))
+            .collect();
+        let Some(normals) = normals_mut(mesh) else {
+            continue;
+        };
+        for (normal, key) in normals.iter_mut().zip(keys) {
+            if let Some((_, _, sum)) = shared.get(&key) {
+                *normal = sum.normalize_or_zero().into();
+            }
+        }
+    }
+}
+
+/// Composites two terrains with the same grid, taking each vertex's height from `a`
+/// where `mask(x, z)` is 0, from `b` where it's 1 and easing between them with
+/// smoothstep in between, for blending rocky ground into grassland across a region.
+///
+/// The mask is clamped to 0..1, and 0 or 1 gives exactly that terrain's height. Every
+/// other attribute comes from `a`, and the normals are recomputed from the blended
+/// heights. Returns `None` unless both meshes have the same vertices in the same places
+/// on the XZ plane and the same triangles, as two [`generate_heightmap`] calls with the
+/// same params do.
+pub fn blend_heightmaps(a: &Mesh, b: &Mesh, mask: impl Fn(f32, f32) -> f32) -> Option<Mesh> {
+    let (from, to) = (positions(a), positions(b));
+    let same_grid = from.len() == to.len()
+        && from
+            .iter()
+            .zip(to)
+            .all(|(p, q)| p[0] == q[0] && p[2] == q[2])
+        && triangle_indices(a) == triangle_indices(b);
+    if !same_grid {
+        return None;
+    }
+
+    let blended: Vec<[f32; 3]> = from
+        .iter()
+        .zip(to)
+        .map(|(p, q)| {
+            let t = mask(p[0], p[2]).clamp(0.0, 1.0);
+            let t = t * t * (3.0 - 2.0 * t);
+            // weighting both ends, so 0 and 1 land exactly on either height
+            [p[0], p[1] * (1.0 - t) + q[1] * t, p[2]]
+        })
+        .collect();
+
+    let mut mesh = a.clone();
+    let normals = smooth_normals(&blended, &triangle_indices(a));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, blended);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    Some(mesh)
+}
+
+/// [`generate_heightmap`] with vertex colors picked from the params' `bands` by height.
+///
+/// Each band's color is exact at its height and eases into the next band's color
+/// between the two, so there are no hard steps at band edges. Bands don't need to be
+/// sorted. Heights below the lowest band or above the highest take that band's color.
+pub fn generate_terrain_with_biome_colors(
+    params: &BiomeTerrainParams,
+    height_fn: impl Fn(f32, f32) -> f32,
+) -> Mesh {
+    let mut mesh = generate_heightmap(&params.grid, height_fn);
+    if params.bands.is_empty() {
+        return mesh;
+    }
+
+    let mut bands = params.bands.clone();
+    bands.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let colors: Vec<[f32; 4]> = positions(&mesh)
+        .iter()
+        .map(|position| band_color(&bands, position[1]))
+        .collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+
+    mesh
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ErosionParams {
+    /// how much a droplet keeps going the way it was rather than straight downhill, 0..1
+    pub inertia: f32,
+    /// sediment a droplet can carry per unit of speed, water and drop in height
+    pub capacity: f32,
+    /// the least a droplet can carry, so it still wears away nearly flat ground
+    pub min_capacity: f32,
+    /// share of its spare capacity a droplet picks up each step
+    pub erosion_rate: f32,
+    /// share of its excess sediment a droplet drops each step
+    pub deposition_rate: f32,
+    /// share of its water a droplet loses each step
+    pub evaporation_rate: f32,
+    pub gravity: f32,
+    /// steps before a droplet gives up
+    pub max_steps: u32,
+}
+
+impl Default for ErosionParams {
+    fn default() -> Self {
+        ErosionParams {
+            inertia: 0.05,
+            capacity: 4.0,
+            min_capacity: 0.01,
+            erosion_rate: 0.3,
+            deposition_rate: 0.3,
+            evaporation_rate: 0.01,
+            gravity: 4.0,
+            max_steps: 30,
+        }
+    }
+}
+
+/// Wears valleys and ridges into a [`generate_heightmap`] grid by letting `droplets`
+/// drops of rain run downhill one at a time, each picking up sediment where it speeds
+/// up and dropping it where it slows down or fills a pit.
+///
+/// Only heights change, and the normals are redone at the end. Droplets land and move in
+/// grid cells and trade height with the four vertices around them, so the rates are per
+/// cell rather than per world unit. The same `seed` always erodes the same way. Meshes
+/// that aren't laid out like a heightmap grid are left alone.
+pub fn hydraulic_erosion(mesh: &mut Mesh, droplets: u32, seed: u32, params: &ErosionParams) {
+    let Some((rows, cols)) = grid_size(positions(mesh)) else {
+        return;
+    };
+    let mut heights: Vec<f32> = positions(mesh).iter().map(|position| position[1]).collect();
+    let vertex = |row: usize, col: usize| row * (cols + 1) + col;
+
+    // the height and downhill-facing gradient at a point in grid cells, along with
+    // the cell it's in and how far across it
+    let sample = |heights: &[f32], point: Vec2| {
+        let (col, row) = (point.x as usize, point.y as usize);
+        let (u, v) = (point.x - col as f32, point.y - row as f32);
+        let [a, b, c, d] = [
+            heights[vertex(row, col)],
+            heights[vertex(row, col + 1)],
+            heights[vertex(row + 1, col)],
+            heights[vertex(row + 1, col + 1)],
+        ];
+        let gradient = Vec2::new(
+            (b - a) * (1.0 - v) + (d - c) * v,
+            (c - a) * (1.0 - u) + (d - b) * u,
+        );
+        let height = a * (1.0 - u) * (1.0 - v) + b * u * (1.0 - v) + c * (1.0 - u) * v + d * u * v;
+        (height, gradient, (row, col), (u, v))
+    };
+    let inside = |point: Vec2| {
+        point.x >= 0.0 && point.y >= 0.0 && point.x < cols as f32 && point.y < rows as f32
+    };
+
+    let mut rng = Rng::new(seed);
+    for _ in 0..droplets {
+        let mut point = Vec2::new(rng.range(0.0, cols as f32), rng.range(0.0, rows as f32));
+        let mut direction = Vec2::ZERO;
+        let (mut speed, mut water, mut sediment) = (1.0_f32, 1.0_f32, 0.0_f32);
+
+        for _ in 0..params.max_steps {
+            if !inside(point) {
+                break;
+            }
+            let (height, gradient, (row, col), (u, v)) = sample(&heights, point);
+            let corners = [
+                (vertex(row, col), (1.0 - u) * (1.0 - v)),
+                (vertex(row, col + 1), u * (1.0 - v)),
+                (vertex(row + 1, col), (1.0 - u) * v),
+                (vertex(row + 1, col + 1), u * v),
+            ];
+
+            direction = direction * params.inertia - gradient * (1.0 - params.inertia);
+            let Some(heading) = direction.try_normalize() else {
+                break;
+            };
+            direction = heading;
+            let next = point + direction;
+
+            // a step off the edge of the grid counts as level ground
+            let drop = if inside(next) {
+                sample(&heights, next).0 - height
+            } else {
+                0.0
+            };
+
+            let capacity = (-drop * speed * water * params.capacity).max(params.min_capacity);
+            if sediment > capacity || drop > 0.0 {
+                // uphill, it fills the pit it's in as far as its sediment allows
+                let deposit = if drop > 0.0 {
+                    drop.min(sediment)
+                } else {
+                    (sediment - capacity) * params.deposition_rate
+                };
+                sediment -= deposit;
+                for (vertex, weight) in corners {
+                    heights[vertex] += deposit * weight;
+                }
+            } else {
+                // never digging deeper than the drop, so it can't carve a hole behind it
+                let erode = ((capacity - sediment) * params.erosion_rate).min(-drop);
+                sediment += erode;
+                for (vertex, weight) in corners {
+                    heights[vertex] -= erode * weight;
+                }
+            }
+
+            speed = (speed * speed - drop * params.gravity).max(0.0).sqrt();
+            water *= 1.0 - params.evaporation_rate;
+            point = next;
+        }
+    }
+
+    for (position, height) in positions_mut(mesh).iter_mut().zip(heights) {
+        position[1] = height;
+    }
+    let normals = smooth_normals(positions(mesh), &triangle_indices(mesh));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+}
+
+/// Lets material slide down a [`generate_heightmap`] grid wherever it's steeper than
+/// `max_slope_degrees`, its angle of repose, for the scree slopes and worn-down look of
+/// real ground rather than raw noise's spikes and cliffs.
+///
+/// Each of up to `iterations` passes goes over every pair of neighboring vertices,
+/// diagonals included, and moves half of whatever the higher one is above the slope
+/// limit onto the lower one, so the total height stays the same. It stops early once
+/// nothing is too steep, and if it runs out of passes before that, the steepest slopes
+/// are left only partly settled. Only heights change, and the normals are redone at the
+/// end. Meshes that aren't laid out like a heightmap grid are left alone.
+pub fn apply_talus(mesh: &mut Mesh, max_slope_degrees: f32, iterations: u32) {
+    let Some((rows, cols)) = grid_size(positions(mesh)) else {
+        return;
+    };
+    let points = positions(mesh);
+    let mut heights: Vec<f32> = points.iter().map(|position| position[1]).collect();
+    let vertex = |row: usize, col: usize| row * (cols + 1) + col;
+    let max_slope = max_slope_degrees.clamp(0.0, 90.0).to_radians().tan();
+
+    // every pair of neighbors once, with the most height the first can have above the
+    // second
+    let mut pairs = vec![];
+    for row in 0..=rows {
+        for col in 0..=cols {
+            let from = vertex(row, col);
+            let mut neighbors = vec![];
+            if col < cols {
+                neighbors.push(vertex(row, col + 1));
+            }
+            if row < rows {
+                neighbors.push(vertex(row + 1, col));
+                if col < cols {
+                    neighbors.push(vertex(row + 1, col + 1));
+                }
+                if col > 0 {
+                    neighbors.push(vertex(row + 1, col - 1));
+                }
+            }
+            for to in neighbors {
+                let (a, b) = (points[from], points[to]);
+                let distance = Vec2::new(a[0] - b[0], a[2] - b[2]).length();
+                pairs.push((from, to, distance * max_slope));
+            }
+        }
+    }
+
+    for _ in 0..iterations {
+        let mut settled = true;
+        for &(a, b, limit) in &pairs {
+            let drop = heights[a] - heights[b];
+            let excess = drop.abs() - limit;
+            if excess > 0.0 {
+                settled = false;
+                let moved = (excess / 2.0).copysign(drop);
+                heights[a] -= moved;
+                heights[b] += moved;
+            }
+        }
+        if settled {
+            break;
+        }
+    }
+
+    for (position, height) in positions_mut(mesh).iter_mut().zip(heights) {
+        position[1] = height;
+    }
+    let normals = smooth_normals(positions(mesh), &triangle_indices(mesh));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+}
+
+/// Nudges every vertex inside the mesh's open boundary up to `amount` in a seeded
+/// random direction across XZ, for terrain and ground planes that don't show the rows
+/// of a regular grid. The same `seed` always moves them the same way.
+///
+/// The boundary stays where it was so neighboring tiles still meet, and vertices in the
+/// same place move together, so hard edges and UV seams don't open up. A vertex moves
+/// less than it was dealt, or not at all, where that would turn a triangle over or
+/// squash it to under a quarter of its area seen from above, so a large `amount` just
+/// jitters the grid as far as it can go. Heights and UVs stay with their vertices,
+/// and the normals around the ones that moved are redone.
+pub fn jitter_grid(mesh: &mut Mesh, amount: f32, seed: u32) {
+    if amount.is_nan() || amount <= 0.0 {
+        return;
+    }
+    let points = positions(mesh);
+    let ids = welded_position_ids(points);
+    let welded = ids.iter().max().map_or(0, |id| id + 1);
+    let mut boundary = vec![false; welded];
+    for (from, to) in find_boundary_edges(mesh) {
+        boundary[ids[from as usize]] = true;
+        boundary[ids[to as usize]] = true;
+    }
+
+    let mut rng = Rng::new(seed);
+    let mut offsets: Vec<Vec2> = boundary
+        .iter()
+        .map(|on_boundary| {
+            // drawn for the boundary too, so where it is doesn't change the rest
+            let angle = rng.range(0.0, TAU);
+            let reach = amount * rng.next_f32().sqrt();
+            if *on_boundary {
+                Vec2::ZERO
+            } else {
+                Vec2::new(angle.cos(), angle.sin()) * reach
+            }
+        })
+        .collect();
+
+    let mut corners: Vec<usize> = vec![0; welded];
+    for (vertex, id) in ids.iter().enumerate() {
+        corners[*id] = vertex;
+    }
+    let at = |id: usize| Vec2::new(points[corners[id]][0], points[corners[id]][2]);
+    let area = |[a, b, c]: [Vec2; 3]| (b - a).perp_dot(c - a);
+    let triangles: Vec<([usize; 3], f32)> = triangle_indices(mesh)
+        .iter()
+        .map(|tri| tri.map(|v| ids[v]))
+        .map(|tri| (tri, area(tri.map(at))))
+        .filter(|(_, before)| before.abs() > f32::EPSILON)
+        .collect();
+
+    // halving the offsets around any triangle that's gone wrong until none are, then
+    // giving up on them, which leaves that triangle as it was
+    for pass in 0.. {
+        let wrong: Vec<[usize; 3]> = triangles
+            .iter()
+            .filter(|(tri, before)| area(tri.map(|id| at(id) + offsets[id])) / before < 0.25)
+            .map(|(tri, _)| *tri)
+            .collect();
+        if wrong.is_empty() {
+            break;
+        }
+        for id in wrong.into_iter().flatten() {
+            offsets[id] = if pass < 8 {
+                offsets[id] / 2.0
+            } else {
+                Vec2::ZERO
+            };
+        }
+    }
+
+    let mut moved = vec![];
+    for (vertex, position) in positions_mut(mesh).iter_mut().enumerate() {
+        let offset = offsets[ids[vertex]];
+        if offset != Vec2::ZERO {
+            position[0] += offset.x;
+            position[2] += offset.y;
+            moved.push(vertex);
+        }
+    }
+    refresh_normals_around(mesh, &moved);
+}
+
+/// Square terrain about `size` across, rounded to whole cells `cell_size` wide and
+/// centered on the origin, made of seeded [`fbm`] noise with `river_count` rivers
+/// carved into it that run downhill from high ground to the edge of the map, joining up
+/// where they meet.
+///
+/// Pits in the noise are filled first so water can always find its way down to an
+/// edge: each vertex drains into the neighbor, diagonals included, that leads there
+/// along the gentlest route, and is raised just above it if it was lower, which leaves
+/// still-water basins as very shallow slopes. Rivers start at random vertices in the
+/// upper half of the heights and follow that drainage, so a river ends where it reaches
+/// an edge or flows into one traced before it. The channels are cut like
+/// [`carve_path`]'s, a cell and a half wide and a twentieth of the terrain's relief
+/// deep, all at once so they stay the same depth where they join, and their beds
+/// always descend. The relief is a tenth of `size`. Fewer rivers than asked for are
+/// carved if there's not enough room for them. The same `seed` always gives the same
+/// terrain.
+pub fn generate_terrain_with_rivers(
+    size: f32,
+    cell_size: f32,
+    seed: u32,
+    river_count: u32,
+) -> Mesh {
+    let cells = (size / cell_size).round().max(1.0) as u32;
+    let params = HeightmapParams {
+        rows: cells,
+        cols: cells,
+        cell_size,
+        ..default()
+    };
+    let relief = size / 10.0;
+    let mut mesh = generate_heightmap(&params, |x, z| {
+        fbm(seed, Vec3::new(x, 0.0, z) * 4.0 / size, 5) * relief
+    });
+
+    let side = cells as usize + 1;
+    let mut heights: Vec<f32> = positions(&mesh)
+        .iter()
+        .map(|position| position[1])
+        .collect();
+    let neighbors = |vertex: usize| {
+        let (row, col) = ((vertex / side) as i64, (vertex % side) as i64);
+        (-1..=1)
+            .flat_map(move |i| (-1..=1).map(move |j| (row + i, col + j)))
+            .filter(move |&(r, c)| {
+                (r, c) != (row, col)
+                    && (0..side as i64).contains(&r)
+                    && (0..side as i64).contains(&c)
+            })
+            .map(move |(r, c)| r as usize * side + c as usize)
+    };
+
+    // flooded in from the edges lowest first, so every vertex is reached from the
+    // neighbor on its lowest way out
+    let rise = relief * 1e-4;
+    let mut downstream: Vec<Option<usize>> = vec![None; heights.len()];
+    let mut reached = vec![false; heights.len()];
+    let mut queue = BinaryHeap::new();
+    for vertex in 0..heights.len() {
+        let (row, col) = (vertex / side, vertex % side);
+        if row == 0 || col == 0 || row == side - 1 || col == side - 1 {
+            reached[vertex] = true;
+            queue.push(Closest(heights[vertex], vertex));
+        }
+    }
+    while let Some(Closest(height, vertex)) = queue.pop() {
+        for neighbor in neighbors(vertex) {
+            if !reached[neighbor] {
+                reached[neighbor] = true;
+                heights[neighbor] = heights[neighbor].max(height + rise);
+                downstream[neighbor] = Some(vertex);
+                queue.push(Closest(heights[neighbor], neighbor));
+            }
+        }
+    }
+    for (position, height) in positions_mut(&mut mesh).iter_mut().zip(&heights) {
+        position[1] = *height;
+    }
+    let normals = smooth_normals(positions(&mesh), &triangle_indices(&mesh));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+
+    let (low, high) = heights
+        .iter()
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), height| {
+            (low.min(*height), high.max(*height))
+        });
+    let middle = (low + high) / 2.0;
+    let point = |vertex: usize| {
+        let position = positions(&mesh)[vertex];
+        Vec2::new(position[0], position[2])
+    };
+
+    let mut rng = Rng::new(seed);
+    let mut on_river = vec![false; heights.len()];
+    let mut segments: Vec<(Vec2, Vec2)> = vec![];
+    let mut rivers = 0;
+    for _ in 0..river_count.saturating_mul(16) {
+        if rivers == river_count {
+            break;
+        }
+        let source = (rng.next_u32() as usize) % heights.len();
+        if heights[source] < middle || on_river[source] || downstream[source].is_none() {
+            continue;
+        }
+
+        rivers += 1;
+        let mut at = source;
+        on_river[at] = true;
+        while let Some(next) = downstream[at] {
+            segments.push((point(at), point(next)));
+            if on_river[next] {
+                break;
+            }
+            on_river[next] = true;
+            at = next;
+        }
+    }
+
+    // only the vertices within a channel's width of a segment can be carved at all
+    let width = cell_size * 1.5;
+    let min = Vec2::splat(-(cells as f32) * cell_size / 2.0);
+    let mut distances = vec![f32::INFINITY; heights.len()];
+    for &(a, b) in &segments {
+        let from = ((a.min(b) - min - width) / cell_size)
+            .floor()
+            .max(Vec2::ZERO);
+        let to = ((a.max(b) - min + width) / cell_size)
+            .ceil()
+            .min(Vec2::splat(cells as f32));
+        for row in from.y as usize..=to.y as usize {
+            for col in from.x as usize..=to.x as usize {
+                let vertex = row * side + col;
+                let distance = distance_to_segment(point(vertex), a, b);
+                distances[vertex] = distances[vertex].min(distance);
+            }
+        }
+    }
+    carve(&mut mesh, width, relief / 20.0, |vertex, _| {
+        distances[vertex]
+    });
+    mesh
+}
+
+/// The terrain's normals drawn into a `resolution` by `resolution` normal map over its
+/// UVs, for giving a flat or coarser version of the terrain the shading of this one,
+/// such as a distant LOD or a plane displaced in a shader.
+///
+/// Each triangle is filled in where it lies in UV space, with the normals of its
+/// corners blended across it, or smooth normals worked out from the triangles when the
+/// mesh has none. Normals are stored relative to the flat ground under the terrain:
+/// red along the way U runs across XZ, green along V, as Bevy's own tangents have them,
+/// and blue straight up, so flat ground comes out an even (0.5, 0.5, 1.0). The image is
+/// `Rgba8Unorm`, since normal maps are data rather than colors, with texels no
+/// triangle covers left flat, and only UVs from 0 to 1 are drawn, with V down the
+/// image.
+pub fn bake_normal_map(mesh: &Mesh, resolution: u32) -> Image {
+    let size = resolution.max(1) as usize;
+    let flat = [128, 128, 255, 255];
+    let mut data: Vec<u8> = flat.repeat(size * size);
+
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+    let normals = match normals(mesh) {
+        Some(normals) => normals.to_vec(),
+        None => smooth_normals(positions, &triangles),
+    };
+    if let Some(uvs) = uvs(mesh) {
+        for tri in &triangles {
+            let [a, b, c] = tri.map(|v| Vec2::from(uvs[v]));
+            let [pa, pb, pc] = tri.map(|v| Vec3::from(positions[v]));
+            let [na, nb, nc] = tri.map(|v| Vec3::from(normals[v]));
+
+            // which ways U and V run over the ground under this triangle
+            let (du1, du2) = (b - a, c - a);
+            let det = du1.perp_dot(du2);
+            if det.abs() <= f32::EPSILON {
+                continue;
+            }
+            let (e1, e2) = (
+                (pb - pa) * Vec3::new(1.0, 0.0, 1.0),
+                (pc - pa) * Vec3::new(1.0, 0.0, 1.0),
+            );
+            let along_u = ((e1 * du2.y - e2 * du1.y) / det).normalize_or_zero();
+            let along_v = ((e2 * du1.x - e1 * du2.x) / det).normalize_or_zero();
+
+            let texels = |uv: f32| (uv * size as f32 - 0.5).clamp(0.0, size as f32 - 1.0);
+            let (low, high) = (a.min(b).min(c), a.max(b).max(c));
+            for y in texels(low.y).floor() as usize..=texels(high.y).ceil() as usize {
+                for x in texels(low.x).floor() as usize..=texels(high.x).ceil() as usize {
+                    let center = (Vec2::new(x as f32, y as f32) + 0.5) / size as f32;
+                    // barycentric weights of b and c, with a little slack so texels
+                    // right on an edge between two triangles aren't missed by both
+                    let offset = center - a;
+                    let (wb, wc) = (offset.perp_dot(du2) / det, du1.perp_dot(offset) / det);
+                    if wb < -1e-5 || wc < -1e-5 || wb + wc > 1.0 + 1e-5 {
+                        continue;
+                    }
+                    let normal = (na * (1.0 - wb - wc) + nb * wb + nc * wc).normalize_or_zero();
+                    let encode = |c: f32| ((c.clamp(-1.0, 1.0) * 0.5 + 0.5) * 255.0).round() as u8;
+                    let texel = (y * size + x) * 4;
+                    data[texel..texel + 4].copy_from_slice(&[
+                        encode(normal.dot(along_u)),
+                        encode(normal.dot(along_v)),
+                        encode(normal.y),
+                        255,
+                    ]);
+                }
+            }
+        }
+    }
+
+    Image::new(
+        Extent3d {
+            width: size as u32,
+            height: size as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+    )
+}
+
+// where the vertices of a `generate_heightmap` grid are, for edits that only visit the
+// part of it they reach
+#[derive(Clone, Copy)]
+pub(crate) struct GridLayout {
+    pub(crate) rows: u32,
+    pub(crate) cols: u32,
+    // XZ position of the first vertex, at row and column 0
+    pub(crate) origin: Vec2,
+    pub(crate) cell_size: f32,
+}
+
+impl GridLayout {
+    // the layout of `mesh` if it's still the grid `generate_heightmap` made, with its
+    // vertices on their regular XZ spots and two indexed triangles to a cell in order
+    fn of(mesh: &Mesh) -> Option<GridLayout> {
+        let points = positions(mesh);
+        let (rows, cols) = grid_size(points)?;
+        let origin = Vec2::new(points[0][0], points[0][2]);
+        let grid = GridLayout {
+            rows: rows as u32,
+            cols: cols as u32,
+            origin,
+            cell_size: points[1][0] - origin.x,
+        };
+
+        let [x, _, z] = points[points.len() - 1];
+        let far = origin + Vec2::new(cols as f32, rows as f32) * grid.cell_size;
+        let regular = grid.cell_size > 0.0
+            && Vec2::new(x, z).abs_diff_eq(far, grid.cell_size * 1e-3)
+            && mesh.primitive_topology() == PrimitiveTopology::TriangleList
+            && mesh.indices().map(|indices| indices.len()) == Some(rows * cols * 6);
+        regular.then_some(grid)
+    }
+
+    pub(crate) fn vertex(&self, row: u32, col: u32) -> usize {
+        (row * (self.cols + 1) + col) as usize
+    }
+
+    // the vertices within `radius` of `center`, clamped to the grid, or `None` if the
+    // circle misses it entirely
+    pub(crate) fn region(&self, center: Vec2, radius: f32) -> Option<GridRegion> {
+        let min = ((center - radius - self.origin) / self.cell_size).ceil();
+        let max = ((center + radius - self.origin) / self.cell_size).floor();
+        let limit = Vec2::new(self.cols as f32, self.rows as f32);
+        if !(min.is_finite() && max.is_finite())
+            || max.x < 0.0
+            || max.y < 0.0
+            || min.x > limit.x
+            || min.y > limit.y
+        {
+            return None;
+        }
+
+        let min = min.max(Vec2::ZERO);
+        let max = max.min(limit);
+        if min.x > max.x || min.y > max.y {
+            return None;
+        }
+        Some(GridRegion {
+            min_row: min.y as u32,
+            min_col: min.x as u32,
+            max_row: max.y as u32,
+            max_col: max.x as u32,
+        })
+    }
+
+    // `region` and the ring of vertices around it, whose normals change when it moves
+    pub(crate) fn ring_around(&self, region: GridRegion) -> GridRegion {
+        GridRegion {
+            min_row: region.min_row.saturating_sub(1),
+            min_col: region.min_col.saturating_sub(1),
+            max_row: (region.max_row + 1).min(self.rows),
+            max_col: (region.max_col + 1).min(self.cols),
+        }
+    }
+
+    // the same area-weighted normals as `generate_heightmap`, but only for the vertices
+    // in `region`, summing the cells that touch it
+    pub(crate) fn refresh_normals(&self, mesh: &mut Mesh, region: GridRegion) {
+        let GridLayout { rows, cols, .. } = *self;
+        if rows == 0 || cols == 0 {
+            return;
+        }
+        let points = positions(mesh);
+        // each cell's two triangles, as the grid was split when it was made
+        let corner = |k: usize| match mesh.indices() {
+            Some(Indices::U32(indices)) => indices[k] as usize,
+            Some(Indices::U16(indices)) => indices[k] as usize,
+            None => k,
+        };
+        let width = (region.max_col - region.min_col + 1) as usize;
+        let mut sums = vec![Vec3::ZERO; width * (region.max_row - region.min_row + 1) as usize];
+
+        let in_region = |row: u32, col: u32| {
+            (region.min_row..=region.max_row).contains(&row)
+                && (region.min_col..=region.max_col).contains(&col)
+        };
+
+        for row in region.min_row.saturating_sub(1)..region.max_row.min(rows - 1) + 1 {
+            for col in region.min_col.saturating_sub(1)..region.max_col.min(cols - 1) + 1 {
+                let cell = (row * cols + col) as usize;
+                for triangle in [cell * 2, cell * 2 + 1] {
+                    let tri = [0, 1, 2].map(|k| corner(triangle * 3 + k));
+                    let [p, q, r] = tri.map(|vertex| Vec3::from(points[vertex]));
+                    let normal = (q - p).cross(r - p);
+                    for vertex in tri {
+                        let (row, col) = (vertex as u32 / (cols + 1), vertex as u32 % (cols + 1));
+                        if in_region(row, col) {
+                            let local = (row - region.min_row) as usize * width
+                                + (col - region.min_col) as usize;
+                            sums[local] += normal;
+                        }
+                    }
+                }
+            }
+        }
+
+        let vertices: Vec<usize> = (region.min_row..=region.max_row)
+            .flat_map(|row| (region.min_col..=region.max_col).map(move |col| (row, col)))
+            .map(|(row, col)| self.vertex(row, col))
+            .collect();
+        if let Some(normals) = normals_mut(mesh) {
+            for (vertex, sum) in vertices.into_iter().zip(sums) {
+                normals[vertex] = sum.normalize_or_zero().into();
+            }
+        }
+    }
+}
+
+// rows and columns of cells in a grid laid out like `generate_heightmap`'s, worked out
+// from where its second row of vertices starts
+fn grid_size(positions: &[[f32; 3]]) -> Option<(usize, usize)> {
+    let first_z = positions.first()?[2];
+    let row_len = positions
+        .iter()
+        .position(|position| position[2] != first_z)?;
+    if row_len < 2 || positions.len() % row_len != 0 || positions.len() / row_len < 2 {
+        return None;
+    }
+    Some((positions.len() / row_len - 1, row_len - 1))
+}
+
+fn band_color(bands: &[(f32, Color)], height: f32) -> [f32; 4] {
+    let above = bands.partition_point(|(band_height, _)| *band_height <= height);
+    if above == 0 {
+        return bands[0].1.as_linear_rgba_f32();
+    }
+    if above == bands.len() {
+        return bands[bands.len() - 1].1.as_linear_rgba_f32();
+    }
+
+    let (low_height, low) = bands[above - 1];
+    let (high_height, high) = bands[above];
+    let t = (height - low_height) / (high_height - low_height);
+    let t = t * t * (3.0 - 2.0 * t);
+
+    let low = Vec4::from(low.as_linear_rgba_f32());
+    let high = Vec4::from(high.as_linear_rgba_f32());
+    low.lerp(high, t).into()
+}
+
+fn distance_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let along = b - a;
+    let t = (point - a).dot(along) / along.length_squared();
+    // a zero-length segment gives NaN, and clamping that would still leave it NaN
+    let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+    point.distance(a + along * t)
+}
+
+// levels of the adaptive heightmap's quadtree, 4096 of its finest cells across
+const ADAPTIVE_DEPTH: u32 = 12;
+
+// how far heights on a 5 by 5 grid across `cell` are from blending its corners
+fn flatness_error(cell: Cell, mut height: impl FnMut(u32, u32) -> f32) -> f32 {
+    let span = cell.span(ADAPTIVE_DEPTH);
+    if span < 4 {
+        return 0.0;
+    }
+    let (x0, z0) = (cell.x * span, cell.z * span);
+    let [a, b, c, d] =
+        [(0, 0), (0, 4), (4, 4), (4, 0)].map(|(i, j)| height(x0 + i * span / 4, z0 + j * span / 4));
+
+    let mut error: f32 = 0.0;
+    for j in 0..=4 {
+        for i in 0..=4 {
+            let (s, t) = (i as f32 / 4.0, j as f32 / 4.0);
+            let flat = (a * (1.0 - t) + b * t) * (1.0 - s) + (d * (1.0 - t) + c * t) * s;
+            let actual = height(x0 + i * span / 4, z0 + j * span / 4);
+            error = error.max((actual - flat).abs());
+        }
+    }
+    error
+}
+
+// the leaves split wherever `error` is over `threshold` and then balanced, with a set of
+// them for finding neighbors, or `None` if they'd make more than `budget` triangles
+fn adaptive_tree(
+    threshold: f32,
+    budget: usize,
+    error: &mut impl FnMut(Cell) -> f32,
+) -> Option<(Vec<Cell>, HashSet<Cell>)> {
+    // every leaf takes at least two triangles
+    let max_leaves = budget / 2;
+    let mut leaves = vec![];
+    let mut stack = vec![Cell::ROOT];
+    while let Some(cell) = stack.pop() {
+        if cell.depth < ADAPTIVE_DEPTH && error(cell) > threshold {
+            stack.extend(cell.children());
+        } else {
+            leaves.push(cell);
+            if leaves.len() > max_leaves {
+                return None;
+            }
+        }
+    }
+
+    // split any leaf with a neighbor more than one level finer, until there are none
+    let mut tree: HashSet<Cell> = leaves.iter().copied().collect();
+    let mut balanced = false;
+    while !balanced {
+        balanced = true;
+        let mut next = vec![];
+        for cell in leaves {
+            let too_coarse = cell
+                .outside(ADAPTIVE_DEPTH)
+                .into_iter()
+                .flatten()
+                .any(|(x, z)| leaf_depth_at(&tree, x, z).map_or(false, |d| d > cell.depth + 1));
+            if too_coarse {
+                balanced = false;
+                tree.remove(&cell);
+                for child in cell.children() {
+                    tree.insert(child);
+                    next.push(child);
+                }
+            } else {
+                next.push(cell);
+            }
+        }
+        if next.len() > max_leaves {
+            return None;
+        }
+        leaves = next;
+    }
+
+    let triangles = triangulate_leaves(
+        &leaves,
+        ADAPTIVE_DEPTH,
+        |x, z| leaf_depth_at(&tree, x, z),
+        |_, _| 0,
+    )
+    .len()
+        / 3;
+    (triangles <= budget).then_some((leaves, tree))
+}
+
+// the depth of the leaf over finest cell `(x, z)`, or `None` outside the tree
+fn leaf_depth_at(tree: &HashSet<Cell>, x: i64, z: i64) -> Option<u32> {
+    let finest = 1_i64 << ADAPTIVE_DEPTH;
+    if !(0..finest).contains(&x) || !(0..finest).contains(&z) {
+        return None;
+    }
+    (0..=ADAPTIVE_DEPTH).find(|depth| {
+        let shift = ADAPTIVE_DEPTH - depth;
+        tree.contains(&Cell {
+            depth: *depth,
+            x: (x >> shift) as u32,
+            z: (z >> shift) as u32,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_terrain_height_follows_a_sloped_plane() {
+        let mesh = generate_heightmap(&HeightmapParams::default(), |x, z| x * 0.5 - z * 0.25);
+        for (x, z) in [(0.0, 0.0), (1.3, -2.7), (-4.9, 4.1)] {
+            let height = sample_terrain_height(&mesh, x, z).unwrap();
+            assert!((height - (x * 0.5 - z * 0.25)).abs() < 1e-4);
+        }
+        assert_eq!(sample_terrain_height(&mesh, 6.0, 0.0), None);
+    }
+
+    // the grid path and the general one should both leave the normals a full
+    // recompute would give
+    #[test]
+    fn apply_crater_refreshes_normals_like_a_full_recompute() {
+        let grid = generate_heightmap(&HeightmapParams::default(), |x, z| {
+            (x * 0.3).sin() + z * 0.1
+        });
+        let mut loose = grid.clone();
+        loose.duplicate_vertices();
+        let flat = smooth_normals(positions(&loose), &triangle_indices(&loose));
+        loose.insert_attribute(Mesh::ATTRIBUTE_NORMAL, flat);
+
+        for mut mesh in [grid, loose] {
+            apply_crater(&mut mesh, Vec2::new(1.2, -0.7), 2.5, 1.0, 0.3);
+            let expected = smooth_normals(positions(&mesh), &triangle_indices(&mesh));
+            for (normal, expected) in normals(&mesh).unwrap().iter().zip(expected) {
+                assert!(Vec3::from(*normal).abs_diff_eq(Vec3::from(expected), 1e-5));
+            }
+        }
+    }
+
+    #[test]
+    fn apply_crater_only_moves_vertices_inside_the_radius() {
+        let mut mesh = generate_heightmap(&HeightmapParams::default(), |_, _| 0.0);
+        let center = Vec2::new(-2.0, 1.5);
+        apply_crater(&mut mesh, center, 2.0, 1.0, 0.2);
+        for [x, y, z] in positions(&mesh) {
+            let inside = Vec2::new(*x, *z).distance(center) < 2.0;
+            assert_eq!(*y != 0.0, inside, "{x} {z}");
+        }
+    }
+
+    #[test]
+    fn cone_contours_are_concentric_circles() {
+        let params = HeightmapParams {
+            rows: 60,
+            cols: 60,
+            cell_size: 0.2,
+            ..default()
+        };
+        let cone = generate_heightmap(&params, |x, z| 5.0 - Vec2::new(x, z).length());
+        // below 0 the circles run off the edge of the grid
+        let loops: Vec<Vec<Vec3>> = generate_contours(&cone, 1.0)
+            .into_iter()
+            .filter(|line| line[0].y > -0.5)
+            .collect();
+        assert_eq!(loops.len(), 5);
+        for line in loops {
+            assert_eq!(line.first(), line.last());
+            let radius = 5.0 - line[0].y;
+            for point in line {
+                assert!((Vec2::new(point.x, point.z).length() - radius).abs() < 0.05);
+            }
+        }
+    }
+
+    #[test]
+    fn stitched_chunks_share_normals_along_their_border() {
+        let height = |x: f32, z: f32| (x * 0.3).sin() * (z * 0.2).cos() * 2.0;
+        let params = HeightmapParams {
+            rows: 10,
+            cols: 10,
+            ..default()
+        };
+        let offsets = [Vec3::ZERO, Vec3::X * 10.0];
+        let mut chunks: Vec<Mesh> = offsets
+            .iter()
+            .map(|offset| generate_heightmap(&params, |x, z| height(x + offset.x, z + offset.z)))
+            .collect();
+        stitch_chunk_normals(&mut chunks, &offsets);
+
+        let placed = |chunk: usize| -> Vec<(Vec3, [f32; 3])> {
+            let mesh = &chunks[chunk];
+            let normals = normals(mesh).unwrap();
+            positions(mesh)
+                .iter()
+                .map(|p| Vec3::from(*p) + offsets[chunk])
+                .zip(normals.iter().copied())
+                .collect()
+        };
+        let (left, right) = (placed(0), placed(1));
+        let mut shared = 0;
+        for (position, normal) in &left {
+            for (other, other_normal) in &right {
+                if position.distance(*other) < 1e-4 {
+                    assert_eq!(normal, other_normal);
+                    shared += 1;
+                }
+            }
+        }
+        assert_eq!(shared, 11);
+    }
+
+    #[test]
+    fn carve_path_leaves_distant_vertices_alone() {
+        let params = HeightmapParams {
+            rows: 40,
+            cols: 40,
+            cell_size: 0.25,
+            ..default()
+        };
+        let mut mesh = generate_heightmap(&params, |_, _| 1.0);
+        let before = mesh.clone();
+        carve_path(
+            &mut mesh,
+            &[Vec2::new(-5.0, 0.0), Vec2::new(5.0, 0.0)],
+            1.0,
+            0.5,
+        );
+
+        let moved = positions(&before).iter().zip(positions(&mesh));
+        for (vertex, (old, new)) in moved.enumerate() {
+            let distance = old[2].abs();
+            if distance >= 1.0 {
+                assert_eq!(old, new);
+            }
+            // and past the triangles around the ones that moved, so are their normals
+            if distance > 1.25 {
+                assert_eq!(
+                    normals(&before).unwrap()[vertex],
+                    normals(&mesh).unwrap()[vertex]
+                );
+            }
+        }
+        let deepest = positions(&mesh)
+            .iter()
+            .map(|p| p[1])
+            .fold(f32::MAX, f32::min);
+        assert_eq!(deepest, 0.5);
+    }
+
+    #[test]
+    fn blending_with_an_even_mask_gives_back_either_input() {
+        let params = HeightmapParams::default();
+        let a = generate_heightmap(&params, |x, z| (x * 0.7).sin() + z * 0.1);
+        let b = generate_heightmap(&params, |x, z| (z * 1.3).cos() * 2.0 - x * 0.05);
+        for (mask, expected) in [(0.0, &a), (1.0, &b)] {
+            let blended = blend_heightmaps(&a, &b, |_, _| mask).unwrap();
+            assert_eq!(positions(&blended), positions(expected));
+            assert_eq!(normals(&blended), normals(expected));
+        }
+
+        let shorter = generate_heightmap(&HeightmapParams { rows: 5, ..params }, |_, _| 0.0);
+        assert!(blend_heightmaps(&a, &shorter, |_, _| 0.5).is_none());
+    }
+
+    #[test]
+    fn adaptive_heightmap_spends_its_budget_on_the_bumps() {
+        let bounds = Rect::new(-10.0, -10.0, 10.0, 10.0);
+        let flat = generate_heightmap_adaptive(|_, _| 1.0, bounds, 1000);
+        let bump = |x: f32, z: f32| 3.0 * (-(x * x + z * z) / 4.0).exp();
+        let bumpy = generate_heightmap_adaptive(bump, bounds, 1000);
+
+        let (flat, bumpy) = (
+            triangle_indices(&flat).len(),
+            triangle_indices(&bumpy).len(),
+        );
+        assert_eq!(flat, 2);
+        assert!(bumpy > 500 && bumpy <= 1000);
+    }
+
+    // deterministic noise from 0 to 4 on a half-unit grid, far too steep to stand
+    #[test]
+    fn talus_leaves_no_edge_steeper_than_the_limit() {
+        let params = HeightmapParams {
+            rows: 20,
+            cols: 30,
+            cell_size: 0.5,
+            ..default()
+        };
+        let mut mesh = generate_heightmap(&params, |x, z| {
+            ((x * 12.9898 + z * 78.233).sin() * 43758.545).fract() * 4.0
+        });
+        let total = |mesh: &Mesh| positions(mesh).iter().map(|p| p[1]).sum::<f32>();
+        let before = total(&mesh);
+        apply_talus(&mut mesh, 30.0, 10000);
+        assert!((total(&mesh) - before).abs() < 1e-2);
+
+        let limit = 30.0_f32.to_radians().tan();
+        let positions = positions(&mesh);
+        for tri in triangle_indices(&mesh) {
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                let run = Vec2::new(from.x - to.x, from.z - to.z).length();
+                assert!((from.y - to.y).abs() <= run * limit + 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn shorter_diagonal_avoids_the_raised_corner() {
+        let flat = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+        ];
+        assert_eq!(QuadSplit::ShorterDiagonal.diagonal(flat), Diagonal::Rising);
+        for (corner, expected) in [(0, Diagonal::Rising), (1, Diagonal::Falling)] {
+            let mut raised = flat;
+            raised[corner].y = 1.0;
+            assert_eq!(QuadSplit::ShorterDiagonal.diagonal(raised), expected);
+            assert_eq!(QuadSplit::Fixed.diagonal(raised), Diagonal::Rising);
+        }
+
+        // one cell with its (row + 1, col) corner raised
+        let params = HeightmapParams {
+            rows: 1,
+            cols: 1,
+            split: QuadSplit::ShorterDiagonal,
+            ..default()
+        };
+        let mesh = generate_heightmap(&params, |x, z| if x < 0.0 && z > 0.0 { 1.0 } else { 0.0 });
+        assert_eq!(triangle_indices(&mesh), [[0, 2, 3], [0, 3, 1]]);
+    }
+
+    #[test]
+    fn uv_collar_pushes_the_corners_out_past_the_tiles() {
+        let params = HeightmapParams {
+            rows: 4,
+            cols: 3,
+            uv_tiles: 2.0,
+            uv_collar: 0.01,
+            ..default()
+        };
+        let mesh = generate_heightmap(&params, |_, _| 0.0);
+        let grid_uvs = uvs(&mesh).unwrap();
+        let at =
+            |row: u32, col: u32| Vec2::from(grid_uvs[(row * (params.cols + 1) + col) as usize]);
+        let (low, high) = (-0.01, 2.01);
+        for (row, col, corner) in [
+            (0, 0, Vec2::new(low, low)),
+            (0, params.cols, Vec2::new(high, low)),
+            (params.rows, 0, Vec2::new(low, high)),
+            (params.rows, params.cols, Vec2::new(high, high)),
+        ] {
+            assert!(at(row, col).distance(corner) < 1e-6, "{row} {col}");
+        }
+        assert!(at(2, 1).distance(Vec2::new(2.0 / 3.0, 1.0)) < 1e-6);
+
+        let plain = generate_heightmap(&HeightmapParams::default(), |_, _| 0.0);
+        assert!(uvs(&plain)
+            .unwrap()
+            .iter()
+            .flatten()
+            .all(|c| (0.0..=1.0).contains(c)));
+    }
+
+    #[test]
+    fn jitter_keeps_the_boundary_and_moves_the_rest_at_most_the_amount() {
+        let params = HeightmapParams {
+            rows: 6,
+            cols: 6,
+            ..default()
+        };
+        let mut mesh = generate_heightmap(&params, |x, z| (x + z).sin());
+        let before = positions(&mesh).to_vec();
+        let amount = 0.3;
+        jitter_grid(&mut mesh, amount, 7);
+
+        let mut moved = 0;
+        for (i, (old, new)) in before.iter().zip(positions(&mesh)).enumerate() {
+            let (row, col) = (i as u32 / (params.cols + 1), i as u32 % (params.cols + 1));
+            let (old, new) = (Vec3::from(*old), Vec3::from(*new));
+            if row == 0 || col == 0 || row == params.rows || col == params.cols {
+                assert_eq!(old, new, "{row} {col}");
+            } else {
+                assert_eq!(old.y, new.y);
+                assert!(old.distance(new) <= amount + 1e-5, "{row} {col}");
+                moved += usize::from(old != new);
+            }
+        }
+        assert!(moved > 0);
+    }
+
+    #[test]
+    fn flat_ground_bakes_to_an_even_normal_map() {
+        let texels = |image: &Image| -> Vec<[u8; 4]> {
+            image
+                .data
+                .chunks(4)
+                .map(|texel| [texel[0], texel[1], texel[2], texel[3]])
+                .collect()
+        };
+        let flat = generate_heightmap(&HeightmapParams::default(), |_, _| 0.0);
+        let baked = bake_normal_map(&flat, 16);
+        assert_eq!(baked.size(), Vec2::splat(16.0));
+        assert!(texels(&baked)
+            .iter()
+            .all(|texel| *texel == [128, 128, 255, 255]));
+
+        // a slope rising along X leans every texel's normal back towards -U
+        let sloped = generate_heightmap(&HeightmapParams::default(), |x, _| x * 0.5);
+        let texels = texels(&bake_normal_map(&sloped, 16));
+        assert!(texels.iter().all(|texel| *texel == texels[0]));
+        assert!(texels[0][0] < 128 && texels[0][1] == 128 && texels[0][2] < 255);
+    }
+
+    #[test]
+    fn heightfield_round_trips_a_heightmap() {
+        let params = HeightmapParams {
+            rows: 4,
+            cols: 6,
+            ..default()
+        };
+        let height = |x: f32, z: f32| (x * 0.7).sin() + z * 0.3;
+        let mesh = generate_heightmap(&params, height);
+        let heights = to_heightfield(&mesh, params.rows + 1, params.cols + 1);
+        let expected: Vec<f32> = positions(&mesh)
+            .iter()
+            .map(|p| height(p[0], p[2]))
+            .collect();
+        assert_eq!(heights, expected);
+
+        // sampled rather than read off when the grids don't line up
+        let slope = generate_heightmap(&params, |x, z| x * 0.5 - z * 0.25);
+        let heights = to_heightfield(&slope, 3, 4);
+        let (min, max) = position_bounds(positions(&slope)).unwrap();
+        for (i, sampled) in heights.iter().enumerate() {
+            let x = min.x + (max.x - min.x) * (i % 4) as f32 / 3.0;
+            let z = min.z + (max.z - min.z) * (i / 4) as f32 / 2.0;
+            assert!(
+                (sampled - (x * 0.5 - z * 0.25)).abs() < 1e-3,
+                "{i}: {sampled}"
+            );
+        }
+    }
+
+    #[test]
+    fn flat_planet_patch_lies_on_the_sphere() {
+        let radius = 500.0;
+        let center_dir = Vec3::new(1.0, 2.0, -0.5);
+        let patch = generate_spherical_terrain_patch(center_dir, 0.4, 8, radius, |_| 0.0).unwrap();
+        assert_eq!(patch.count_vertices(), 9 * 9);
+        for (position, normal) in positions(&patch).iter().zip(normals(&patch).unwrap()) {
+            let (position, normal) = (Vec3::from(*position), Vec3::from(*normal));
+            assert!(
+                (position.length() - radius).abs() < radius * 1e-6,
+                "{position}"
+            );
+            assert!(normal.dot(position.normalize()) > 1.0 - 1e-5, "{normal}");
+            assert!(
+                position.normalize().angle_between(center_dir) < 0.4,
+                "{position}"
+            );
+        }
+    }
+
+    #[test]
+    fn tiling_puts_the_far_corner_uv_at_the_tile_count() {
+        let params = HeightmapParams {
+            rows: 3,
+            cols: 5,
+            uv_tiles: 4.0,
+            ..default()
+        };
+        let mesh = generate_heightmap(&params, |x, z| x - z);
+        let grid_uvs = uvs(&mesh).unwrap();
+        assert_eq!(grid_uvs[0], [0.0, 0.0]);
+        assert_eq!(grid_uvs[params.cols as usize], [4.0, 0.0]);
+        assert_eq!(grid_uvs[grid_uvs.len() - 1], [4.0, 4.0]);
+        let middle = (params.cols + 1 + 2) as usize;
+        assert!(Vec2::from(grid_uvs[middle]).distance(Vec2::new(1.6, 4.0 / 3.0)) < 1e-6);
+    }
+}
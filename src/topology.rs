@@ -0,0 +1,1295 @@
+use std::{collections::BinaryHeap, f32::consts::PI};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    utils::{HashMap, HashSet},
+};
+
+use crate::{
+    attributes::{colors, colors_mut, normals, normals_mut, uvs, uvs_mut},
+    bounds::position_bounds,
+    util::{
+        dequantize, gather_vertices, positions, positions_mut, quantize, refresh_normals_around,
+        smooth_normals, triangle_indices, welded_position_ids, Closest,
+    },
+};
+
+/// Checks that every pair of triangles sharing an edge traverse it in opposite
+/// directions, and that each connected piece of the mesh winds outward (counter-clockwise
+/// seen from outside, which is what Bevy treats as front-facing).
+///
+/// Triangles are connected through shared positions rather than shared indices, so
+/// non-indexed meshes are checked the same way as indexed ones. On failure, returns the
+/// indices of the triangles that would have to be flipped to fix the mesh. For pieces
+/// that aren't closed "outward" is ambiguous, and the larger consistently-wound group of
+/// triangles is taken to be correct.
+pub fn check_consistent_winding(mesh: &Mesh) -> Result<(), Vec<usize>> {
+    let flipped = inconsistent_triangles(mesh);
+    if flipped.is_empty() {
+        Ok(())
+    } else {
+        Err(flipped)
+    }
+}
+
+/// Flips every triangle reported by [`check_consistent_winding`], returning how many
+/// were flipped.
+///
+/// Only the winding changes. Normals aren't touched, so recompute them afterwards
+/// if they were derived from the old winding.
+pub fn fix_winding(mesh: &mut Mesh) -> usize {
+    let flipped = inconsistent_triangles(mesh);
+    flip_triangles(mesh, &flipped);
+    flipped.len()
+}
+
+/// What [`reorient_faces_outward`] takes as the outside of a mesh.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutwardReference {
+    /// away from the middle of the surface, for closed, roughly round meshes like
+    /// displaced spheres and rocks
+    Centroid,
+    /// away from a point, for meshes whose middle isn't inside them, like a bowl
+    Point(Vec3),
+    /// towards a direction, such as `Vec3::Y` for terrain
+    Up(Vec3),
+}
+
+/// Flips every triangle that faces inward by `reference`, returning how many were
+/// flipped, to clear up the dark triangles heavy displacement or deformation leaves
+/// behind where the surface folded over.
+///
+/// A triangle faces inward when its front face points back towards the middle of the
+/// mesh or the given point (seen from the middle of the triangle), or away from the
+/// given up direction. The middle of the mesh is the centroid of its surface, each
+/// triangle counting by its area. Triangles with no area are left alone. Unlike
+/// [`fix_winding`], each triangle is judged on its own, so this works on open and
+/// disconnected meshes and on ones that fold over themselves, as long as every part of
+/// the surface faces away from the reference point. A mesh with normals gets them recomputed
+/// around the flipped triangles, as the average of the faces around each vertex.
+pub fn reorient_faces_outward(mesh: &mut Mesh, reference: OutwardReference) -> usize {
+    let positions = positions(mesh);
+    let triangles: Vec<[Vec3; 3]> = triangle_indices(mesh)
+        .iter()
+        .map(|tri| tri.map(|v| Vec3::from(positions[v])))
+        .collect();
+
+    let centroid = || {
+        let (sum, area) = triangles
+            .iter()
+            .fold((Vec3::ZERO, 0.0), |(sum, area), tri| {
+                let [a, b, c] = *tri;
+                let size = (b - a).cross(c - a).length();
+                (sum + (a + b + c) / 3.0 * size, area + size)
+            });
+        if area > 0.0 {
+            sum / area
+        } else {
+            Vec3::ZERO
+        }
+    };
+    let inside = match reference {
+        OutwardReference::Centroid => centroid(),
+        OutwardReference::Point(point) => point,
+        OutwardReference::Up(_) => Vec3::ZERO,
+    };
+    let outward = |middle: Vec3| match reference {
+        OutwardReference::Up(up) => up,
+        _ => middle - inside,
+    };
+
+    let flipped: Vec<usize> = triangles
+        .iter()
+        .enumerate()
+        .filter(|(_, [a, b, c])| {
+            let facing = (*b - *a).cross(*c - *a);
+            facing.dot(outward((*a + *b + *c) / 3.0)) < 0.0
+        })
+        .map(|(triangle, _)| triangle)
+        .collect();
+    if flipped.is_empty() {
+        return 0;
+    }
+
+    let corners: Vec<usize> = {
+        let triangles = triangle_indices(mesh);
+        flipped.iter().flat_map(|t| triangles[*t]).collect()
+    };
+    flip_triangles(mesh, &flipped);
+    refresh_normals_around(mesh, &corners);
+    flipped.len()
+}
+
+// swaps two corners of each triangle in `flipped`, counting in threes through the index
+// (or vertex) buffer
+pub(crate) fn flip_triangles(mesh: &mut Mesh, flipped: &[usize]) {
+    if flipped.is_empty() {
+        return;
+    }
+
+    match mesh.indices_mut() {
+        Some(Indices::U16(indices)) => {
+            for triangle in flipped {
+                indices.swap(triangle * 3 + 1, triangle * 3 + 2);
+            }
+        }
+        Some(Indices::U32(indices)) => {
+            for triangle in flipped {
+                indices.swap(triangle * 3 + 1, triangle * 3 + 2);
+            }
+        }
+        None => {
+            let mut order: Vec<u32> = (0..mesh.count_vertices() as u32).collect();
+            for triangle in flipped {
+                order.swap(triangle * 3 + 1, triangle * 3 + 2);
+            }
+            gather_vertices(mesh, order);
+        }
+    }
+}
+
+/// Edges shared by more than two triangles, which break anything that walks from a
+/// triangle to the one across each edge: subdivision, the
+/// [`HalfEdgeMesh`](crate::halfedge::HalfEdgeMesh), CSG and winding checks.
+///
+/// Like [`check_consistent_winding`], this connects triangles through shared positions,
+/// so it finds the fins that concatenating separate pieces by hand can leave. Each edge
+/// is given by the lowest-numbered vertex at either end, the smaller first, and the
+/// edges come out sorted.
+pub fn find_non_manifold_edges(mesh: &Mesh) -> Vec<(u32, u32)> {
+    edges_used(mesh, |count| count > 2)
+}
+
+/// Edges on the open boundary of the mesh, used by only one triangle: the rim of a
+/// plane or an uncapped cylinder, or the cracks left between pieces that don't quite
+/// meet. Given the same way as [`find_non_manifold_edges`], sorted.
+pub fn find_boundary_edges(mesh: &Mesh) -> Vec<(u32, u32)> {
+    edges_used(mesh, |count| count == 1)
+}
+
+/// Whether the mesh is closed and manifold, with every edge shared by exactly two
+/// triangles, as [`volume`](crate::bounds::volume), CSG and export for 3D
+/// printing need. A mesh without triangles isn't.
+///
+/// Triangles are connected through shared positions, like [`find_boundary_edges`] and
+/// [`find_non_manifold_edges`], which point out the edges where this fails, so the UV
+/// seams of a sphere don't count as holes.
+pub fn is_watertight(mesh: &Mesh) -> bool {
+    let uses = edge_uses(mesh);
+    !uses.is_empty() && uses.values().all(|count| *count == 2)
+}
+
+/// A copy of `mesh` with a second, back-facing copy of every triangle, so thin surfaces
+/// like flags, leaves or a Möbius strip can be seen from both sides.
+///
+/// The back copies have reversed winding and negated normals, and share every other
+/// attribute with the front, so UVs map the same way on both sides. This doubles the
+/// vertex and triangle count. A `StandardMaterial` with `double_sided: true` and
+/// `cull_mode: None` gets the same look without the extra geometry, so this is mostly
+/// worth it for custom materials or meshes batched with one-sided ones.
+pub fn make_double_sided(mesh: &Mesh) -> Mesh {
+    let triangles = triangle_indices(mesh);
+    let vertices = mesh.count_vertices() as u32;
+
+    let mut doubled = mesh.clone();
+    gather_vertices(&mut doubled, (0..vertices).chain(0..vertices).collect());
+
+    let mut indices: Vec<u32> = triangles.iter().flatten().map(|v| *v as u32).collect();
+    indices.extend(
+        triangles
+            .iter()
+            .flat_map(|[a, b, c]| [a, c, b].map(|v| *v as u32 + vertices)),
+    );
+    doubled.set_indices(Some(Indices::U32(indices)));
+
+    if let Some(normals) = normals_mut(&mut doubled) {
+        for normal in &mut normals[vertices as usize..] {
+            *normal = normal.map(|c| -c);
+        }
+    }
+
+    doubled
+}
+
+/// A copy of the mesh pushed `thickness` out along its normals and turned inside out,
+/// for toon outlines: drawn behind the mesh with an unlit material in the outline color
+/// and Bevy's usual back-face culling, only the part of it sticking out around the
+/// silhouette shows.
+///
+/// Every vertex at the same position moves the same way, along the average of their
+/// normals, so hard edges like a cube's stay closed instead of splitting apart; smooth
+/// normals are computed for a mesh without any. The hull's normals point inward, towards
+/// the faces that are seen, and every other attribute is kept as it was.
+pub fn generate_outline_hull(mesh: &Mesh, thickness: f32) -> Mesh {
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+    let normals = match normals(mesh) {
+        Some(normals) if normals.len() == positions.len() => normals.to_vec(),
+        _ => smooth_normals(positions, &triangles),
+    };
+
+    let ids = welded_position_ids(positions);
+    let mut directions = vec![Vec3::ZERO; positions.len()];
+    for (id, normal) in ids.iter().zip(&normals) {
+        directions[*id] += Vec3::from(*normal).normalize_or_zero();
+    }
+    let directions: Vec<Vec3> = ids
+        .iter()
+        .map(|id| directions[*id].normalize_or_zero())
+        .collect();
+
+    let mut hull = mesh.clone();
+    for (position, direction) in positions_mut(&mut hull).iter_mut().zip(&directions) {
+        *position = (Vec3::from(*position) + *direction * thickness).into();
+    }
+    let inward: Vec<[f32; 3]> = directions.iter().map(|d| (-*d).into()).collect();
+    hull.insert_attribute(Mesh::ATTRIBUTE_NORMAL, inward);
+    let every: Vec<usize> = (0..triangles.len()).collect();
+    flip_triangles(&mut hull, &every);
+    hull
+}
+
+/// Merges vertices whose positions agree to `decimal_places` decimal places into one,
+/// so a mesh built from separate pieces (or a non-indexed one) shares vertices where
+/// the pieces meet. Returns how many vertices were removed.
+///
+/// Each coordinate is multiplied by an exact power of ten and rounded to an integer,
+/// and vertices are welded when those integers match, so the result doesn't depend on
+/// hashing floats or on anything but IEEE arithmetic, which every platform does the
+/// same way. Welded positions are snapped to the rounded values, so two machines whose
+/// inputs differ by small rounding errors (from adding offsets in a different order,
+/// say) still end up with bit-identical meshes for networked games, as long as no
+/// coordinate sits right on the halfway point between two steps. Up to 9 places are
+/// used, which keeps coordinates up to about 9 million in range.
+///
+/// Only positions are compared. The merged vertex keeps the other attributes of the
+/// first vertex using it, so normals and UVs on either side of a seam are lost; weld
+/// before computing normals. Triangles left with two corners on the same vertex are
+/// removed. The result is indexed.
+pub fn weld_vertices(mesh: &mut Mesh, decimal_places: u32) -> usize {
+    let corners: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions(mesh).len()).collect(),
+    };
+
+    let mut ids: HashMap<[i64; 3], u32> = HashMap::new();
+    let mut keys: Vec<[i64; 3]> = vec![];
+    let mut order: Vec<u32> = vec![];
+    let welded: Vec<u32> = positions(mesh)
+        .iter()
+        .enumerate()
+        .map(|(vertex, position)| {
+            let key = quantize(*position, decimal_places);
+            *ids.entry(key).or_insert_with(|| {
+                keys.push(key);
+                order.push(vertex as u32);
+                order.len() as u32 - 1
+            })
+        })
+        .collect();
+
+    let removed = welded.len() - order.len();
+    let mut indices: Vec<u32> = corners.iter().map(|v| welded[*v]).collect();
+    if mesh.primitive_topology() == PrimitiveTopology::TriangleList {
+        indices = indices
+            .chunks_exact(3)
+            .filter(|tri| tri[0] != tri[1] && tri[1] != tri[2] && tri[2] != tri[0])
+            .flatten()
+            .copied()
+            .collect();
+    }
+
+    gather_vertices(mesh, order);
+    for (position, key) in positions_mut(mesh).iter_mut().zip(keys) {
+        *position = dequantize(key, decimal_places);
+    }
+    mesh.set_indices(Some(Indices::U32(indices)));
+    removed
+}
+
+/// How close two vertices' attributes have to be for [`weld_vertices_smart`] to merge
+/// them, each as the distance between the two values.
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct WeldTolerances {
+    pub position: f32,
+    /// between unit normals, where 0.001 is about a twentieth of a degree
+    pub normal: f32,
+    pub uv: f32,
+    /// between linear RGBA colors
+    pub color: f32,
+}
+
+impl Default for WeldTolerances {
+    fn default() -> Self {
+        WeldTolerances {
+            position: 1e-5,
+            normal: 1e-3,
+            uv: 1e-5,
+            color: 1e-3,
+        }
+    }
+}
+
+/// Merges vertices whose positions, normals, UVs and colors all match to within
+/// `tolerances`, returning how many vertices were removed, for turning triangle soup
+/// into a clean indexed mesh without losing its seams.
+///
+/// Unlike [`weld_vertices`], vertices in the same place stay apart when anything else
+/// about them differs by more than its tolerance, so hard edges and UV seams survive
+/// and only copies that differ by rounding are unified. Attributes the mesh doesn't
+/// have are left out of the comparison. Each vertex merges into the first one before
+/// it that matches, which keeps all of its attributes, so vertices are never moved and
+/// a long run of slightly different ones doesn't drift. Triangles left with two
+/// corners on the same vertex are removed. The result is indexed.
+pub fn weld_vertices_smart(mesh: &mut Mesh, tolerances: &WeldTolerances) -> usize {
+    let positions = positions(mesh);
+    let corners: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions.len()).collect(),
+    };
+    let (normals, uvs, colors) = (normals(mesh), uvs(mesh), colors(mesh));
+    let close = |a: &[f32], b: &[f32], tolerance: f32| {
+        let squared: f32 = a.iter().zip(b).map(|(a, b)| (a - b) * (a - b)).sum();
+        squared <= tolerance * tolerance
+    };
+    let matches = |a: usize, b: usize| {
+        close(&positions[a], &positions[b], tolerances.position)
+            && normals
+                .into_iter()
+                .all(|n| close(&n[a], &n[b], tolerances.normal))
+            && uvs
+                .into_iter()
+                .all(|uv| close(&uv[a], &uv[b], tolerances.uv))
+            && colors
+                .into_iter()
+                .all(|c| close(&c[a], &c[b], tolerances.color))
+    };
+
+    // kept vertices by the cell of a grid as fine as the position tolerance they're in,
+    // so a match is always in the same cell or one next to it
+    let cell_size = if tolerances.position > 0.0 {
+        tolerances.position
+    } else {
+        1.0
+    };
+    let cell = |vertex: usize| positions[vertex].map(|c| (c / cell_size).floor() as i64);
+    let mut cells: HashMap<[i64; 3], Vec<u32>> = HashMap::new();
+    let mut order: Vec<u32> = vec![];
+    let mut welded: Vec<u32> = vec![];
+    for vertex in 0..positions.len() {
+        let [x, y, z] = cell(vertex);
+        let found = (-1..=1)
+            .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+            .filter_map(|(dx, dy, dz)| cells.get(&[x + dx, y + dy, z + dz]))
+            .flatten()
+            .copied()
+            .filter(|kept| matches(order[*kept as usize] as usize, vertex))
+            .min();
+        let id = found.unwrap_or_else(|| {
+            order.push(vertex as u32);
+            let id = order.len() as u32 - 1;
+            cells.entry([x, y, z]).or_default().push(id);
+            id
+        });
+        welded.push(id);
+    }
+    let removed = welded.len() - order.len();
+
+    let mut indices: Vec<u32> = corners.iter().map(|v| welded[*v]).collect();
+    if mesh.primitive_topology() == PrimitiveTopology::TriangleList {
+        indices = indices
+            .chunks_exact(3)
+            .filter(|tri| tri[0] != tri[1] && tri[1] != tri[2] && tri[2] != tri[0])
+            .flatten()
+            .copied()
+            .collect();
+    }
+
+    gather_vertices(mesh, order);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    removed
+}
+
+/// Removes triangles with no area and triangles that repeat another, then the vertices
+/// only they used, tidying up a hand-assembled mesh before exporting it or handing it
+/// to physics. Returns how many triangles were removed.
+///
+/// Like [`check_consistent_winding`], triangles are compared through shared positions,
+/// so a copy built from vertices of its own still counts as a repeat, and so does one
+/// wound the other way (like the back faces [`make_double_sided`] adds). The first of
+/// each set of repeats is kept. A triangle has no area when its corners are in a line,
+/// to within rounding. Surviving vertices keep every attribute, and the result is
+/// indexed.
+pub fn clean_mesh(mesh: &mut Mesh) -> usize {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return 0;
+    }
+    let positions = positions(mesh);
+    let ids = welded_position_ids(positions);
+    let triangles = triangle_indices(mesh);
+
+    let mut seen = HashSet::new();
+    let kept: Vec<[usize; 3]> = triangles
+        .iter()
+        .filter(|tri| {
+            let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+            let longest = (b - a)
+                .length_squared()
+                .max((c - b).length_squared())
+                .max((a - c).length_squared());
+            if (b - a).cross(c - a).length() <= f32::EPSILON * longest {
+                return false;
+            }
+            let mut key = tri.map(|v| ids[v]);
+            key.sort_unstable();
+            seen.insert(key)
+        })
+        .copied()
+        .collect();
+    let removed = triangles.len() - kept.len();
+
+    let mut used = vec![false; positions.len()];
+    for v in kept.iter().flatten() {
+        used[*v] = true;
+    }
+    let mut new_index = vec![0; positions.len()];
+    let mut order: Vec<u32> = vec![];
+    for (vertex, _) in used.iter().enumerate().filter(|(_, used)| **used) {
+        new_index[vertex] = order.len() as u32;
+        order.push(vertex as u32);
+    }
+    let indices: Vec<u32> = kept.iter().flatten().map(|v| new_index[*v]).collect();
+
+    gather_vertices(mesh, order);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    removed
+}
+
+/// A vertex lying partway along the edge of a triangle it isn't a corner of, so the
+/// triangle doesn't share an edge with the triangles around that vertex and a hairline
+/// crack can show through between them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TJunction {
+    /// the vertex on the edge
+    pub vertex: u32,
+    /// index of the triangle, counting in threes through the index (or vertex) buffer
+    pub triangle: usize,
+    /// the two corners of the triangle at the ends of the edge, in winding order
+    pub edge: (u32, u32),
+}
+
+/// Every T-junction in `mesh`: a vertex sitting on a triangle's edge but not at either of
+/// its ends, as when a large tile meets two smaller ones, or a coarse LOD chunk meets a
+/// finer one, and only the smaller pieces have a vertex where they meet in the middle.
+///
+/// A vertex counts as on an edge when it's within a hundred-thousandth of the size of the
+/// mesh's bounds of it. Vertices with the same position are reported once, as the
+/// lowest-numbered of them, and the junctions come out sorted by vertex and then
+/// triangle. [`fix_t_junctions`] splits the edges they lie on.
+pub fn find_t_junctions(mesh: &Mesh) -> Vec<TJunction> {
+    let epsilon = position_bounds(positions(mesh))
+        .map(|(min, max)| (max - min).max_element() * 1e-5)
+        .unwrap_or_default();
+    let mut junctions = t_junctions(mesh, epsilon);
+    junctions.sort_unstable_by_key(|junction| (junction.vertex, junction.triangle));
+    junctions
+}
+
+/// Splits every triangle edge that has a vertex within `epsilon` of it, other than near
+/// its ends, at that vertex, so the triangles on both sides of it meet corner to corner
+/// and the mesh is watertight there. Returns how many times an edge was split.
+///
+/// Each split turns a triangle into two. The new corner is a vertex of the split
+/// triangle's own, placed exactly where the vertex on its edge is, with normals, UVs and
+/// colors blended from the ends of the edge so the triangle looks the same as before;
+/// any other attribute is copied from the nearer end. A triangle is split as many times
+/// as there are vertices on its edges. Triangles lying against the edge from the same
+/// side share its new vertices. The result is indexed.
+pub fn fix_t_junctions(mesh: &mut Mesh, epsilon: f32) -> usize {
+    let junctions = t_junctions(mesh, epsilon.max(0.0));
+    if junctions.is_empty() {
+        return 0;
+    }
+
+    let mut points: Vec<Vec3> = positions(mesh).iter().map(|p| Vec3::from(*p)).collect();
+    let vertices = points.len();
+    let triangles = triangle_indices(mesh);
+    let mut on_edges: HashMap<usize, Vec<usize>> = HashMap::new();
+    for junction in &junctions {
+        on_edges
+            .entry(junction.triangle)
+            .or_default()
+            .push(junction.vertex as usize);
+    }
+
+    // each new vertex, with the ends of the edge it was put on and how far along it is,
+    // and where it is for each edge and junction vertex, so both sides share it
+    let mut added: Vec<(usize, usize, f32)> = vec![];
+    let mut made: HashMap<(usize, usize, usize), usize> = HashMap::new();
+    let mut splits = 0;
+
+    let mut fixed = vec![];
+    for (triangle, tri) in triangles.into_iter().enumerate() {
+        let Some(on_edge) = on_edges.get(&triangle) else {
+            fixed.push(tri);
+            continue;
+        };
+
+        let mut pieces = vec![tri];
+        while let Some(piece) = pieces.pop() {
+            let hit = (0..3).find_map(|k| {
+                let (a, b) = (piece[k], piece[(k + 1) % 3]);
+                on_edge.iter().find_map(|&v| {
+                    along_edge(points[v], points[a], points[b], epsilon).map(|t| (k, v, t))
+                })
+            });
+            let Some((k, v, t)) = hit else {
+                fixed.push(piece);
+                continue;
+            };
+
+            let (a, b, c) = (piece[k], piece[(k + 1) % 3], piece[(k + 2) % 3]);
+            let key = (a.min(b), a.max(b), v);
+            let middle = *made.entry(key).or_insert_with(|| {
+                points.push(points[v]);
+                if a < b {
+                    added.push((a, b, t));
+                } else {
+                    added.push((b, a, 1.0 - t));
+                }
+                points.len() - 1
+            });
+            pieces.push([a, middle, c]);
+            pieces.push([middle, b, c]);
+            splits += 1;
+        }
+    }
+
+    // new vertices start as copies of an original one, the nearer end for attributes
+    // that aren't blended; an end can itself be new, but only an earlier one
+    let mut source: Vec<u32> = (0..vertices as u32).collect();
+    for (a, b, t) in &added {
+        let nearer = if *t < 0.5 { *a } else { *b };
+        source.push(source[nearer]);
+    }
+    gather_vertices(mesh, source);
+
+    for (new, (a, b, t)) in added.iter().enumerate() {
+        let new = vertices + new;
+        positions_mut(mesh)[new] = points[new].into();
+        if let Some(normals) = normals_mut(mesh) {
+            let normal = Vec3::from(normals[*a]).lerp(Vec3::from(normals[*b]), *t);
+            normals[new] = normal.normalize_or_zero().into();
+        }
+        if let Some(uvs) = uvs_mut(mesh) {
+            uvs[new] = Vec2::from(uvs[*a]).lerp(Vec2::from(uvs[*b]), *t).into();
+        }
+        if let Some(colors) = colors_mut(mesh) {
+            colors[new] = Vec4::from(colors[*a])
+                .lerp(Vec4::from(colors[*b]), *t)
+                .into();
+        }
+    }
+
+    let indices: Vec<u32> = fixed.iter().flatten().map(|v| *v as u32).collect();
+    mesh.set_indices(Some(Indices::U32(indices)));
+    splits
+}
+
+/// Simplifies the nearly flat parts of a mesh by collapsing edges there, leaving curved
+/// areas, creases and the silhouette alone, for LODs of hard-surface meshes whose big
+/// flat faces carry most of their triangles. Returns how many vertices were removed.
+///
+/// A vertex is folded into one of its neighbors only when every triangle around it is
+/// within `max_angle` radians of facing the same way as their average, and the
+/// triangles left after the collapse would still be, so `max_angle` is how much
+/// curvature counts as flat. The nearest neighbor that keeps the mesh manifold is
+/// chosen, and collapsing repeats until nothing more can go. Vertices only ever merge
+/// into ones that are already there, so every attribute keeps its values.
+///
+/// Triangles connect through shared indices, and vertices on an open edge are never
+/// removed, so UV seams and the edges between a flat-shaded mesh's faces stay exactly
+/// where they are. Weld a mesh that has no shared vertices first. The result is
+/// indexed.
+pub fn decimate_flat_regions(mesh: &mut Mesh, max_angle: f32) -> usize {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return 0;
+    }
+
+    let positions: Vec<Vec3> = positions(mesh).iter().map(|p| Vec3::from(*p)).collect();
+    let mut triangles = triangle_indices(mesh);
+    let mut alive = vec![true; triangles.len()];
+    let mut faces: Vec<Vec<usize>> = vec![vec![]; positions.len()];
+    for (face, tri) in triangles.iter().enumerate() {
+        for v in tri {
+            faces[*v].push(face);
+        }
+    }
+
+    let normal = |tri: [usize; 3]| {
+        let [a, b, c] = tri.map(|v| positions[v]);
+        (b - a).cross(c - a)
+    };
+    let min_cos = max_angle.clamp(0.0, PI).cos();
+    let mut removed = vec![false; positions.len()];
+
+    let mut collapsed = true;
+    while collapsed {
+        collapsed = false;
+        for u in 0..positions.len() {
+            faces[u].retain(|face| alive[*face]);
+            let around = &faces[u];
+            if removed[u] || around.is_empty() {
+                continue;
+            }
+
+            // every edge out of `u` shared by exactly two of its triangles, or it's on a
+            // boundary (or a fin) and stays
+            let mut edges: HashMap<usize, u32> = HashMap::new();
+            for face in around {
+                for v in triangles[*face] {
+                    if v != u {
+                        *edges.entry(v).or_default() += 1;
+                    }
+                }
+            }
+            if edges.values().any(|count| *count != 2) {
+                continue;
+            }
+
+            let average = around
+                .iter()
+                .map(|face| normal(triangles[*face]))
+                .sum::<Vec3>()
+                .normalize_or_zero();
+            let flat = |tri: [usize; 3]| normal(tri).normalize_or_zero().dot(average) >= min_cos;
+            if average == Vec3::ZERO || !around.iter().all(|face| flat(triangles[*face])) {
+                continue;
+            }
+
+            let mut neighbors: Vec<usize> = edges.into_keys().collect();
+            neighbors.sort_by(|a, b| {
+                let distance = |v: &usize| positions[*v].distance_squared(positions[u]);
+                distance(a).total_cmp(&distance(b))
+            });
+            let target = neighbors.into_iter().find(|&v| {
+                // more than the two vertices across the collapsing edge shared by both
+                // rings would pinch the mesh
+                let ring_v: Vec<usize> = faces[v]
+                    .iter()
+                    .filter(|face| alive[**face])
+                    .flat_map(|face| triangles[*face])
+                    .collect();
+                let shared = around
+                    .iter()
+                    .flat_map(|face| triangles[*face])
+                    .filter(|w| *w != u && *w != v && ring_v.contains(w))
+                    .collect::<HashSet<_>>()
+                    .len();
+                shared == 2
+                    && around
+                        .iter()
+                        .map(|face| triangles[*face])
+                        .filter(|tri| !tri.contains(&v))
+                        .all(|tri| {
+                            let moved = tri.map(|w| if w == u { v } else { w });
+                            normal(moved).length_squared() > f32::EPSILON && flat(moved)
+                        })
+            });
+            let Some(v) = target else {
+                continue;
+            };
+
+            for face in std::mem::take(&mut faces[u]) {
+                if triangles[face].contains(&v) {
+                    alive[face] = false;
+                } else {
+                    for w in &mut triangles[face] {
+                        if *w == u {
+                            *w = v;
+                        }
+                    }
+                    faces[v].push(face);
+                }
+            }
+            removed[u] = true;
+            collapsed = true;
+        }
+    }
+
+    let mut used = vec![false; positions.len()];
+    for (tri, _) in triangles.iter().zip(&alive).filter(|(_, alive)| **alive) {
+        for v in tri {
+            used[*v] = true;
+        }
+    }
+    // only the vertices still in use, in their old order
+    let mut remap = vec![0; positions.len()];
+    let mut order: Vec<u32> = vec![];
+    for v in (0..positions.len()).filter(|v| used[*v]) {
+        remap[v] = order.len() as u32;
+        order.push(v as u32);
+    }
+    let indices: Vec<u32> = triangles
+        .iter()
+        .zip(&alive)
+        .filter(|(_, alive)| **alive)
+        .flat_map(|(tri, _)| tri.map(|v| remap[v]))
+        .collect();
+
+    let before = mesh.count_vertices();
+    gather_vertices(mesh, order);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    before - mesh.count_vertices()
+}
+
+/// Approximate distance across the surface from vertex `source` to every vertex, found
+/// by walking the edges of the index buffer.
+///
+/// Paths are restricted to mesh edges, so distances come out a little long wherever the
+/// shortest route would cut across a face. Vertices that can't be reached from `source`,
+/// including every vertex if `source` is out of range, get `f32::INFINITY`. Only indexed
+/// (or otherwise shared) vertices connect triangles, so weld a non-indexed mesh first.
+pub fn vertex_distances_from(mesh: &Mesh, source: u32) -> Vec<f32> {
+    let positions = positions(mesh);
+    let mut distances = vec![f32::INFINITY; positions.len()];
+    let source = source as usize;
+    if source >= positions.len() {
+        return distances;
+    }
+
+    let mut neighbors: Vec<Vec<usize>> = vec![vec![]; positions.len()];
+    for [a, b, c] in triangle_indices(mesh) {
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            neighbors[from].push(to);
+            neighbors[to].push(from);
+        }
+    }
+
+    distances[source] = 0.0;
+    let mut queue = BinaryHeap::from([Closest(0.0, source)]);
+    while let Some(Closest(distance, vertex)) = queue.pop() {
+        if distance > distances[vertex] {
+            continue;
+        }
+
+        let from = Vec3::from(positions[vertex]);
+        for &neighbor in &neighbors[vertex] {
+            let through = distance + from.distance(Vec3::from(positions[neighbor]));
+            if through < distances[neighbor] {
+                distances[neighbor] = through;
+                queue.push(Closest(through, neighbor));
+            }
+        }
+    }
+
+    distances
+}
+
+fn inconsistent_triangles(mesh: &Mesh) -> Vec<usize> {
+    let positions = positions(mesh);
+    let ids = welded_position_ids(positions);
+    let vertices = triangle_indices(mesh);
+    let triangles: Vec<[usize; 3]> = vertices.iter().map(|tri| tri.map(|v| ids[v])).collect();
+
+    // each undirected edge, with the triangles using it and whether they run low to high id
+    let mut edges: HashMap<(usize, usize), Vec<(usize, bool)>> = HashMap::new();
+    for (triangle, &[a, b, c]) in triangles.iter().enumerate() {
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            edges
+                .entry((from.min(to), from.max(to)))
+                .or_default()
+                .push((triangle, from < to));
+        }
+    }
+
+    // `Some(true)` once a triangle is known to need flipping relative to its piece's seed
+    let mut flip: Vec<Option<bool>> = vec![None; triangles.len()];
+    let mut inconsistent = vec![];
+
+    for seed in 0..triangles.len() {
+        if flip[seed].is_some() {
+            continue;
+        }
+
+        flip[seed] = Some(false);
+        let mut piece = vec![seed];
+        let mut stack = vec![seed];
+        let mut closed = true;
+
+        while let Some(triangle) = stack.pop() {
+            let flipped = flip[triangle] == Some(true);
+            let [a, b, c] = triangles[triangle];
+
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                let forward = (from < to) != flipped;
+                let users = &edges[&(from.min(to), from.max(to))];
+                closed &= users.len() > 1;
+                for &(neighbor, neighbor_forward) in users {
+                    if flip[neighbor].is_none() {
+                        // a consistent neighbor runs the shared edge the other way
+                        flip[neighbor] = Some(neighbor_forward == forward);
+                        piece.push(neighbor);
+                        stack.push(neighbor);
+                    }
+                }
+            }
+        }
+
+        // six times the signed volume enclosed by the piece as the seed winds it
+        let volume: f32 = piece
+            .iter()
+            .map(|&triangle| {
+                let [a, b, c] = vertices[triangle].map(|v| Vec3::from(positions[v]));
+                let volume = a.dot(b.cross(c));
+                if flip[triangle] == Some(true) {
+                    -volume
+                } else {
+                    volume
+                }
+            })
+            .sum();
+
+        let flipped_count = piece
+            .iter()
+            .filter(|&&triangle| flip[triangle] == Some(true))
+            .count();
+
+        let seed_is_outward = if closed && volume.abs() > f32::EPSILON {
+            volume > 0.0
+        } else {
+            flipped_count * 2 <= piece.len()
+        };
+
+        inconsistent.extend(
+            piece
+                .into_iter()
+                .filter(|&triangle| (flip[triangle] == Some(true)) == seed_is_outward),
+        );
+    }
+
+    inconsistent.sort_unstable();
+    inconsistent
+}
+
+// T-junctions within `epsilon`, looking for vertices near each edge in a grid of cells
+// about the size of an average edge
+fn t_junctions(mesh: &Mesh, epsilon: f32) -> Vec<TJunction> {
+    let positions = positions(mesh);
+    let ids = welded_position_ids(positions);
+    let triangles = triangle_indices(mesh);
+    if triangles.is_empty() {
+        return vec![];
+    }
+
+    // the first vertex any triangle uses at each position
+    let mut first: HashMap<usize, usize> = HashMap::new();
+    for v in triangles.iter().flatten() {
+        let entry = first.entry(ids[*v]).or_insert(*v);
+        *entry = (*entry).min(*v);
+    }
+    let point = |v: usize| Vec3::from(positions[v]);
+
+    let total: f32 = triangles
+        .iter()
+        .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)])
+        .map(|(a, b)| point(a).distance(point(b)))
+        .sum();
+    let cell = (total / (triangles.len() * 3) as f32).max(epsilon * 2.0);
+    if !(cell > 0.0 && cell.is_finite()) {
+        return vec![];
+    }
+    let cell_of = |p: Vec3| (p / cell).floor().as_ivec3();
+    let mut grid: HashMap<IVec3, Vec<usize>> = HashMap::new();
+    for v in first.values() {
+        grid.entry(cell_of(point(*v))).or_default().push(*v);
+    }
+
+    let mut junctions = vec![];
+    for (triangle, tri) in triangles.iter().enumerate() {
+        for k in 0..3 {
+            let (a, b) = (tri[k], tri[(k + 1) % 3]);
+            let (start, end) = (point(a), point(b));
+            let low = cell_of(start.min(end) - epsilon);
+            let high = cell_of(start.max(end) + epsilon);
+            let span = (high - low + IVec3::ONE).as_vec3();
+
+            let mut near = vec![];
+            if span.x * span.y * span.z > first.len() as f32 {
+                near.extend(first.values().copied());
+            } else {
+                for x in low.x..=high.x {
+                    for y in low.y..=high.y {
+                        for z in low.z..=high.z {
+                            near.extend(grid.get(&IVec3::new(x, y, z)).into_iter().flatten());
+                        }
+                    }
+                }
+            }
+
+            for v in near {
+                if tri.iter().any(|corner| ids[*corner] == ids[v]) {
+                    continue;
+                }
+                if along_edge(point(v), start, end, epsilon).is_some() {
+                    junctions.push(TJunction {
+                        vertex: v as u32,
+                        triangle,
+                        edge: (a as u32, b as u32),
+                    });
+                }
+            }
+        }
+    }
+    junctions
+}
+
+// how far along the edge from `start` to `end` a point within `epsilon` of it is, if it
+// isn't within `epsilon` of either end
+fn along_edge(point: Vec3, start: Vec3, end: Vec3, epsilon: f32) -> Option<f32> {
+    let edge = end - start;
+    let length_squared = edge.length_squared();
+    if length_squared == 0.0 {
+        return None;
+    }
+    let t = (point - start).dot(edge) / length_squared;
+    let on = point.distance(start + edge * t) <= epsilon
+        && point.distance(start) > epsilon
+        && point.distance(end) > epsilon;
+    (on && t > 0.0 && t < 1.0).then_some(t)
+}
+
+// how many triangles use each edge, connecting them through shared positions; edges
+// are given by the first vertex at either end, the smaller first
+fn edge_uses(mesh: &Mesh) -> HashMap<(u32, u32), u32> {
+    let ids = welded_position_ids(positions(mesh));
+    // the first vertex at each welded position
+    let mut first = vec![u32::MAX; ids.len()];
+    for (vertex, id) in ids.iter().enumerate().rev() {
+        first[*id] = vertex as u32;
+    }
+
+    let mut uses: HashMap<(u32, u32), u32> = HashMap::new();
+    for tri in triangle_indices(mesh) {
+        let [a, b, c] = tri.map(|v| first[ids[v]]);
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            *uses.entry((from.min(to), from.max(to))).or_default() += 1;
+        }
+    }
+    uses
+}
+
+// the edges whose number of triangles passes `keep`, sorted
+pub(crate) fn edges_used(mesh: &Mesh, keep: impl Fn(u32) -> bool) -> Vec<(u32, u32)> {
+    let mut edges: Vec<(u32, u32)> = edge_uses(mesh)
+        .into_iter()
+        .filter(|(_, count)| keep(*count))
+        .map(|(edge, _)| edge)
+        .collect();
+    edges.sort_unstable();
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        attributes::triangles,
+        sphere::{generate_uv_sphere, UvSphereParams},
+        terrain::{generate_heightmap, HeightmapParams},
+    };
+
+    #[test]
+    fn vertex_distances_grow_away_from_the_source() {
+        let params = HeightmapParams {
+            rows: 4,
+            cols: 4,
+            ..default()
+        };
+        let mesh = generate_heightmap(&params, |_, _| 0.0);
+        let distances = vertex_distances_from(&mesh, 0);
+        assert_eq!(distances[0], 0.0);
+        assert!((distances[1] - 1.0).abs() < 1e-5);
+        for row in 0..5 {
+            for col in 0..4 {
+                assert!(distances[row * 5 + col + 1] > distances[row * 5 + col]);
+            }
+        }
+        assert!(vertex_distances_from(&mesh, 99)
+            .iter()
+            .all(|d| d.is_infinite()));
+    }
+
+    #[test]
+    fn double_sided_mirrors_every_triangle() {
+        let mesh = generate_heightmap(&HeightmapParams::default(), |x, _| x.sin());
+        let doubled = make_double_sided(&mesh);
+        let triangles = triangle_indices(&mesh).len();
+        assert_eq!(triangle_indices(&doubled).len(), triangles * 2);
+
+        let normals = normals(&doubled).unwrap();
+        let half = normals.len() / 2;
+        for (front, back) in normals[..half].iter().zip(&normals[half..]) {
+            assert_eq!(Vec3::from(*front), -Vec3::from(*back));
+        }
+        let uvs = uvs(&doubled).unwrap();
+        assert_eq!(uvs[..half], uvs[half..]);
+
+        let positions = positions(&doubled);
+        let face = |[a, b, c]: [usize; 3]| {
+            let [a, b, c] = [a, b, c].map(|v| Vec3::from(positions[v]));
+            (b - a).cross(c - a)
+        };
+        let doubled = triangle_indices(&doubled);
+        for (front, back) in doubled[..triangles].iter().zip(&doubled[triangles..]) {
+            assert!((face(*front) + face(*back)).length() < 1e-5);
+        }
+    }
+
+    // the same 10x10 grid of loose quads, with its coordinates added up in a different
+    // order each time so they differ in the last bits
+    #[test]
+    fn welding_ignores_float_accumulation_order() {
+        let grid = |step: fn(u32) -> f32| {
+            let mut positions: Vec<[f32; 3]> = vec![];
+            for z in 0..10 {
+                for x in 0..10 {
+                    let [a, b, c, d] = [[x, z], [x, z + 1], [x + 1, z + 1], [x + 1, z]]
+                        .map(|[x, z]| [step(x) + 3.7, 0.0, step(z) - 1.3]);
+                    positions.extend([a, b, d, d, b, c]);
+                }
+            }
+            let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+            mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+            mesh
+        };
+        let mut multiplied = grid(|i| i as f32 * 0.1);
+        let mut summed = grid(|i| (0..i).fold(0.0, |sum, _| sum + 0.1));
+        assert_ne!(positions(&multiplied), positions(&summed));
+
+        assert_eq!(weld_vertices(&mut multiplied, 4), 600 - 121);
+        assert_eq!(weld_vertices(&mut summed, 4), 600 - 121);
+        assert_eq!(triangle_indices(&multiplied), triangle_indices(&summed));
+        let bits = |mesh: &Mesh| -> Vec<[u32; 3]> {
+            positions(mesh)
+                .iter()
+                .map(|p| p.map(f32::to_bits))
+                .collect()
+        };
+        assert_eq!(bits(&multiplied), bits(&summed));
+    }
+
+    // three triangles hinged on the edge from the origin along +X, the third through its
+    // own copy of the vertex at the far end
+    #[test]
+    fn non_manifold_edge_is_the_one_three_triangles_share() {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [0.0, -1.0, 0.0],
+                [0.0, 0.0, 1.0],
+                [1.0, 0.0, 0.0],
+            ],
+        );
+        mesh.set_indices(Some(Indices::U32(vec![0, 1, 2, 1, 0, 3, 0, 5, 4])));
+        assert_eq!(find_non_manifold_edges(&mesh), vec![(0, 1)]);
+
+        assert!(find_non_manifold_edges(&Mesh::from(shape::Cube { size: 1.0 })).is_empty());
+    }
+
+    // two flat slopes meeting in a crease along x = 0
+    #[test]
+    fn flat_decimation_collapses_planes_and_keeps_the_crease() {
+        let params = HeightmapParams {
+            rows: 20,
+            cols: 20,
+            cell_size: 0.5,
+            ..default()
+        };
+        let mut mesh = generate_heightmap(&params, |x, _| x.max(0.0));
+        let crease = |mesh: &Mesh| -> Vec<[f32; 3]> {
+            positions(mesh)
+                .iter()
+                .filter(|p| p[0] == 0.0)
+                .copied()
+                .collect()
+        };
+        let (triangles, before) = (triangle_indices(&mesh).len(), crease(&mesh));
+
+        decimate_flat_regions(&mut mesh, 0.05);
+        assert!(triangle_indices(&mesh).len() * 3 < triangles);
+        assert_eq!(crease(&mesh), before);
+        for [x, y, _] in positions(&mesh) {
+            assert!((y - x.max(0.0)).abs() < 1e-5);
+        }
+        assert_eq!(check_consistent_winding(&mesh), Ok(()));
+    }
+
+    #[test]
+    fn clean_mesh_drops_an_injected_duplicate_triangle() {
+        let mut sphere = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        let triangles = triangle_indices(&sphere);
+        let vertices = sphere.count_vertices();
+
+        let [a, b, c] = triangles[1].map(|v| v as u32);
+        let mut indices: Vec<u32> = triangles.iter().flatten().map(|v| *v as u32).collect();
+        indices.extend([c, a, b]);
+        sphere.set_indices(Some(Indices::U32(indices)));
+
+        assert_eq!(clean_mesh(&mut sphere), 1);
+        assert_eq!(triangle_indices(&sphere), triangles);
+        assert_eq!(sphere.count_vertices(), vertices);
+    }
+
+    #[test]
+    fn t_junction_edge_is_split_in_two() {
+        // one big triangle against two small ones that meet halfway along its long edge
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![
+                [0.0, 0.0, 0.0],
+                [2.0, 0.0, 0.0],
+                [0.0, 2.0, 0.0],
+                [2.0, 2.0, 0.0],
+                [1.0, 1.0, 0.0],
+            ],
+        );
+        mesh.set_indices(Some(Indices::U32(vec![0, 1, 2, 1, 3, 4, 4, 3, 2])));
+
+        let junctions = find_t_junctions(&mesh);
+        let expected = TJunction {
+            vertex: 4,
+            triangle: 0,
+            edge: (1, 2),
+        };
+        assert_eq!(junctions, vec![expected]);
+
+        assert_eq!(fix_t_junctions(&mut mesh, 1e-4), 1);
+        let triangles = triangle_indices(&mesh);
+        assert_eq!(triangles.len(), 4);
+        assert!(find_t_junctions(&mesh).is_empty());
+        assert!(find_non_manifold_edges(&mesh).is_empty());
+        let positions = positions(&mesh);
+        let split: Vec<_> = triangles.iter().filter(|tri| tri.contains(&0)).collect();
+        assert_eq!(split.len(), 2);
+        assert!(split
+            .iter()
+            .all(|tri| tri.iter().any(|&v| positions[v] == [1.0, 1.0, 0.0])));
+    }
+
+    #[test]
+    fn displaced_sphere_ends_up_facing_away_from_its_center() {
+        let mut sphere = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        for position in positions_mut(&mut sphere) {
+            let p = Vec3::from(*position);
+            *position = (p * (1.0 + 0.2 * (p.x * 7.0).sin() * (p.z * 5.0).cos())).into();
+        }
+        let mut indices: Vec<u32> = sphere.indices().unwrap().iter().map(|v| v as u32).collect();
+        let mut folded = 0;
+        for tri in indices.chunks_mut(3).step_by(5) {
+            tri.swap(1, 2);
+            folded += 1;
+        }
+        sphere.set_indices(Some(Indices::U32(indices)));
+
+        let flipped = reorient_faces_outward(&mut sphere, OutwardReference::Centroid);
+        assert!(flipped >= folded);
+        assert!(triangles(&sphere).all(|[a, b, c]| {
+            let area = (b - a).cross(c - a);
+            area == Vec3::ZERO || area.dot(a + b + c) > 0.0
+        }));
+        assert!((positions(&sphere).iter())
+            .zip(normals(&sphere).unwrap())
+            .all(|(p, n)| Vec3::from(*p).dot(Vec3::from(*n)) > 0.0));
+    }
+
+    #[test]
+    fn a_sphere_is_watertight_and_a_plane_is_not() {
+        let sphere = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        assert!(is_watertight(&sphere));
+        assert!(find_boundary_edges(&sphere).is_empty());
+
+        let params = HeightmapParams {
+            rows: 3,
+            cols: 5,
+            ..default()
+        };
+        let plane = generate_heightmap(&params, |_, _| 0.0);
+        assert!(!is_watertight(&plane));
+        assert_eq!(
+            find_boundary_edges(&plane).len(),
+            2 * (params.rows + params.cols) as usize
+        );
+    }
+
+    // two triangles of soup meeting along an edge, where one end of the edge only differs
+    // by rounding and the other is a UV seam
+    #[test]
+    fn smart_welding_keeps_vertices_apart_across_a_uv_seam() {
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        let (a, b) = ([0.0, 0.0, 0.0], [1.0, 0.0, 0.0]);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_POSITION,
+            vec![a, b, [0.0, 0.0, -1.0], b, a, [0.0, 0.0, 1.0]],
+        );
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; 6]);
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_UV_0,
+            vec![
+                [0.0, 0.0],
+                [1.0, 0.0],
+                [0.0, 1.0],
+                [1.0, 0.0000001],
+                [0.001, 0.0],
+                [0.0, 1.0],
+            ],
+        );
+
+        let mut loose = mesh.clone();
+        assert_eq!(weld_vertices_smart(&mut mesh, &default()), 1);
+        assert_eq!(mesh.count_vertices(), 5);
+        assert_eq!(triangle_indices(&mesh), vec![[0, 1, 2], [1, 3, 4]]);
+
+        let tolerances = WeldTolerances {
+            uv: 0.01,
+            ..default()
+        };
+        assert_eq!(weld_vertices_smart(&mut loose, &tolerances), 2);
+    }
+
+    #[test]
+    fn outline_hull_is_pushed_out_and_wound_the_other_way() {
+        let sphere = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        let thickness = 0.05;
+        let hull = generate_outline_hull(&sphere, thickness);
+
+        // each vertex moves out along the way its normal points, reversed in the hull
+        let moves = positions(&sphere).iter().zip(positions(&hull));
+        for ((before, after), inward) in moves.zip(normals(&hull).unwrap()) {
+            let (before, after) = (Vec3::from(*before), Vec3::from(*after));
+            let offset = after - before;
+            assert!(
+                offset.distance(-Vec3::from(*inward) * thickness) < 1e-6,
+                "{offset}"
+            );
+            assert!(offset.normalize().dot(before) > 0.99, "{before} {after}");
+        }
+        let (original, flipped) = (triangle_indices(&sphere), triangle_indices(&hull));
+        assert_eq!(original.len(), flipped.len());
+        for ([a, b, c], tri) in original.into_iter().zip(flipped) {
+            assert!([[a, c, b], [c, b, a], [b, a, c]].contains(&tri), "{tri:?}");
+        }
+        assert!(triangles(&hull).all(|[a, b, c]| {
+            let area = (b - a).cross(c - a);
+            area == Vec3::ZERO || area.dot(a + b + c) < 0.0
+        }));
+    }
+}
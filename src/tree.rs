@@ -0,0 +1,266 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    attributes::{normals, uvs},
+    builder::CustomMeshBuilder,
+    util::{positions, triangle_indices, Rng},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Foliage {
+    Cone,
+    Sphere,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TreeParams {
+    pub trunk_height: f32,
+    /// radius at the base of the trunk
+    pub trunk_radius: f32,
+    /// how much thinner each piece of wood is at its tip than at its base, 0..1
+    pub taper: f32,
+    /// how many times branches split into smaller branches; each level multiplies the
+    /// triangle count by about `branches`
+    pub max_depth: u32,
+    /// branches growing from the tip of the trunk and of each branch
+    pub branches: u32,
+    /// each branch's length as a fraction of its parent's
+    pub branch_length: f32,
+    /// how far branches lean away from their parent
+    pub branch_angle_degrees: f32,
+    /// sides around each piece of wood and each foliage cone
+    pub sides: u32,
+    pub foliage: Foliage,
+    /// radius of the foliage at the top of the trunk, shrinking on smaller branches
+    pub foliage_size: f32,
+    pub trunk_color: Color,
+    pub leaf_color: Color,
+}
+
+impl Default for TreeParams {
+    fn default() -> Self {
+        TreeParams {
+            trunk_height: 1.5,
+            trunk_radius: 0.12,
+            taper: 0.6,
+            max_depth: 2,
+            branches: 3,
+            branch_length: 0.6,
+            branch_angle_degrees: 35.0,
+            sides: 6,
+            foliage: Foliage::Sphere,
+            foliage_size: 0.6,
+            trunk_color: Color::rgb(0.4, 0.26, 0.13),
+            leaf_color: Color::rgb(0.2, 0.55, 0.2),
+        }
+    }
+}
+
+/// Flat-shaded low-poly tree growing up +Y from the origin: a tapered trunk that splits
+/// into branches up to `max_depth` times, with foliage at the end of every outermost
+/// branch.
+///
+/// Wood and foliage are one mesh told apart by vertex colors. The same `seed` always
+/// grows the same tree.
+pub fn generate_tree(seed: u32, params: &TreeParams) -> Mesh {
+    let mut rng = Rng::new(seed);
+    let mut builder = TreeBuilder::default();
+
+    builder.branch(
+        params,
+        &mut rng,
+        Branch {
+            base: Vec3::ZERO,
+            direction: Vec3::Y,
+            length: params.trunk_height,
+            radius: params.trunk_radius,
+            depth: 0,
+        },
+    );
+
+    builder.into_mesh()
+}
+
+#[derive(Clone, Copy)]
+struct Branch {
+    base: Vec3,
+    direction: Vec3,
+    length: f32,
+    radius: f32,
+    depth: u32,
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+    mesh: CustomMeshBuilder,
+    // one per vertex of `mesh`, which has no colors of its own
+    colors: Vec<[f32; 4]>,
+}
+
+impl TreeBuilder {
+    fn branch(&mut self, params: &TreeParams, rng: &mut Rng, branch: Branch) {
+        let tip = branch.base + branch.direction * branch.length;
+        let tip_radius = branch.radius * params.taper;
+        let sides = params.sides.max(3);
+        let color = params.trunk_color;
+
+        let side = branch.direction.any_orthonormal_vector();
+        let ring = |center: Vec3, radius: f32| -> Vec<Vec3> {
+            (0..sides)
+                .map(|k| {
+                    let rotation =
+                        Quat::from_axis_angle(branch.direction, k as f32 / sides as f32 * TAU);
+                    center + rotation * side * radius
+                })
+                .collect()
+        };
+        let bottom = ring(branch.base, branch.radius);
+        let top = ring(tip, tip_radius);
+
+        for k in 0..sides as usize {
+            let next = (k + 1) % sides as usize;
+            let (u0, u1) = (k as f32 / sides as f32, (k + 1) as f32 / sides as f32);
+            let outward = (bottom[k] + bottom[next]) / 2.0 - branch.base;
+            self.quad(
+                [bottom[k], bottom[next], top[next], top[k]],
+                [[u0, 1.0], [u1, 1.0], [u1, 0.0], [u0, 0.0]],
+                outward,
+                color,
+            );
+        }
+        self.polygon(&top, branch.direction, color);
+        if branch.depth == 0 {
+            self.polygon(&bottom, -branch.direction, color);
+        }
+
+        if branch.depth >= params.max_depth || params.branches == 0 {
+            let size = params.foliage_size * (branch.length / params.trunk_height).sqrt();
+            match params.foliage {
+                Foliage::Cone => self.cone(tip, branch.direction, size, sides, params.leaf_color),
+                Foliage::Sphere => self.sphere(tip, size, params.leaf_color),
+            }
+            return;
+        }
+
+        // branches spread evenly around their parent, each turned and sized a little
+        // at random
+        let spin = rng.range(0.0, TAU);
+        for i in 0..params.branches {
+            let around = spin + i as f32 / params.branches as f32 * TAU + rng.range(-0.3, 0.3);
+            let lean = (params.branch_angle_degrees * rng.range(0.8, 1.2)).to_radians();
+            let axis = Quat::from_axis_angle(branch.direction, around) * side;
+            let direction = (Quat::from_axis_angle(axis, lean) * branch.direction).normalize();
+            let length = branch.length * params.branch_length * rng.range(0.8, 1.2);
+
+            self.branch(
+                params,
+                rng,
+                Branch {
+                    base: tip,
+                    direction,
+                    length,
+                    radius: tip_radius,
+                    depth: branch.depth + 1,
+                },
+            );
+        }
+    }
+
+    fn cone(&mut self, base: Vec3, direction: Vec3, radius: f32, sides: u32, color: Color) {
+        let apex = base + direction * radius * 2.0;
+        let side = direction.any_orthonormal_vector();
+        let ring: Vec<Vec3> = (0..sides)
+            .map(|k| {
+                let rotation = Quat::from_axis_angle(direction, k as f32 / sides as f32 * TAU);
+                base + rotation * side * radius
+            })
+            .collect();
+
+        for k in 0..ring.len() {
+            let next = (k + 1) % ring.len();
+            let outward = (ring[k] + ring[next]) / 2.0 - base;
+            self.triangle(
+                [ring[k], ring[next], apex],
+                [[0.0, 1.0], [1.0, 1.0], [0.5, 0.0]],
+                outward,
+                color,
+            );
+        }
+        self.polygon(&ring, -direction, color);
+    }
+
+    fn sphere(&mut self, center: Vec3, radius: f32, color: Color) {
+        let mut sphere = Mesh::from(shape::Icosphere {
+            radius,
+            subdivisions: 1,
+        });
+        sphere.duplicate_vertices();
+        sphere.compute_flat_normals();
+
+        let (positions, normals, uvs) = (
+            positions(&sphere),
+            normals(&sphere).unwrap_or(&[]),
+            uvs(&sphere).unwrap_or(&[]),
+        );
+        for tri in triangle_indices(&sphere) {
+            let [a, b, c] = tri.map(|v| {
+                self.colors.push(color.as_linear_rgba_f32());
+                self.mesh.vertex(
+                    Vec3::from(positions[v]) + center,
+                    uvs.get(v).copied().unwrap_or_default().into(),
+                    normals.get(v).copied().unwrap_or_default().into(),
+                )
+            });
+            self.mesh.triangle(a, b, c);
+        }
+    }
+
+    // wound to face `outward`, with a flat normal
+    fn triangle(&mut self, corners: [Vec3; 3], uvs: [[f32; 2]; 3], outward: Vec3, color: Color) {
+        let [a, b, c] = corners;
+        let mut normal = (b - a).cross(c - a);
+        let (corners, uvs) = if normal.dot(outward) < 0.0 {
+            normal = -normal;
+            ([a, c, b], [uvs[0], uvs[2], uvs[1]])
+        } else {
+            (corners, uvs)
+        };
+
+        let normal = normal.normalize_or_zero();
+        let [i0, i1, i2] = [0, 1, 2].map(|k| self.mesh.vertex(corners[k], uvs[k].into(), normal));
+        self.mesh.triangle(i0, i1, i2);
+        self.colors.extend([color.as_linear_rgba_f32(); 3]);
+    }
+
+    fn quad(&mut self, corners: [Vec3; 4], uvs: [[f32; 2]; 4], outward: Vec3, color: Color) {
+        let [a, b, c, d] = corners;
+        self.triangle([a, b, c], [uvs[0], uvs[1], uvs[2]], outward, color);
+        self.triangle([a, c, d], [uvs[0], uvs[2], uvs[3]], outward, color);
+    }
+
+    // a convex ring of corners, fanned from the first
+    fn polygon(&mut self, corners: &[Vec3], outward: Vec3, color: Color) {
+        for k in 1..corners.len() - 1 {
+            self.triangle(
+                [corners[0], corners[k], corners[k + 1]],
+                [[0.5, 0.5]; 3],
+                outward,
+                color,
+            );
+        }
+    }
+
+    fn into_mesh(self) -> Mesh {
+        let mut mesh = self
+            .mesh
+            .build()
+            .expect("tree triangles only use vertices added alongside them");
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        mesh
+    }
+}
@@ -0,0 +1,217 @@
+use std::cmp::Ordering;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    utils::HashMap,
+};
+
+use crate::attributes::{self, normals_mut};
+
+// the crate's own code treats a mesh without positions as having no vertices
+pub(crate) fn positions(mesh: &Mesh) -> &[[f32; 3]] {
+    attributes::positions(mesh).unwrap_or_default()
+}
+
+pub(crate) fn positions_mut(mesh: &mut Mesh) -> &mut [[f32; 3]] {
+    attributes::positions_mut(mesh).unwrap_or_default()
+}
+
+// vertex indices of every triangle, whether or not the mesh has an index buffer
+pub(crate) fn triangle_indices(mesh: &Mesh) -> Vec<[usize; 3]> {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return vec![];
+    }
+
+    let indices: Vec<usize> = match mesh.indices() {
+        Some(indices) => indices.iter().collect(),
+        None => (0..positions(mesh).len()).collect(),
+    };
+
+    indices
+        .chunks_exact(3)
+        .map(|tri| [tri[0], tri[1], tri[2]])
+        .collect()
+}
+
+// redoes the smooth normals of every vertex sharing a triangle with one of the `moved`
+// vertices, leaving the rest of the mesh's normals as they were. The index buffer is
+// read once to find the triangles around each vertex, and only the triangles around
+// those vertices are summed
+pub(crate) fn refresh_normals_around(mesh: &mut Mesh, moved: &[usize]) {
+    if moved.is_empty() {
+        return;
+    }
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+
+    let mut around = vec![vec![]; positions.len()];
+    for (i, tri) in triangles.iter().enumerate() {
+        for v in tri {
+            around[*v].push(i);
+        }
+    }
+
+    let mut affected: Vec<usize> = moved
+        .iter()
+        .flat_map(|v| around[*v].iter().flat_map(|t| triangles[*t]))
+        .collect();
+    affected.sort_unstable();
+    affected.dedup();
+
+    let sums: Vec<(usize, Vec3)> = affected
+        .into_iter()
+        .map(|v| {
+            let sum = around[v].iter().fold(Vec3::ZERO, |sum, t| {
+                let [a, b, c] = triangles[*t].map(|v| Vec3::from(positions[v]));
+                sum + (b - a).cross(c - a)
+            });
+            (v, sum)
+        })
+        .collect();
+
+    if let Some(normals) = normals_mut(mesh) {
+        for (v, sum) in sums {
+            normals[v] = sum.normalize_or_zero().into();
+        }
+    }
+}
+
+// moves every vertex by `transform`, which mustn't scale the mesh unevenly, turning its
+// normals with it
+pub(crate) fn transform_mesh(mesh: &mut Mesh, transform: Transform) {
+    for position in positions_mut(mesh) {
+        *position = transform.mul_vec3(Vec3::from(*position)).into();
+    }
+    if let Some(normals) = normals_mut(mesh) {
+        for normal in normals {
+            *normal = (transform.rotation * Vec3::from(*normal)).into();
+        }
+    }
+}
+
+// rebuilds every attribute so vertex `i` becomes the old vertex `order[i]`, leaving the
+// mesh without an index buffer
+pub(crate) fn gather_vertices(mesh: &mut Mesh, order: Vec<u32>) {
+    mesh.set_indices(Some(Indices::U32(order)));
+    mesh.duplicate_vertices();
+}
+
+// an id per distinct position, so faces that only share corners by value (like the
+// demo's concatenated tiles) can still be treated as connected
+pub(crate) fn welded_position_ids(positions: &[[f32; 3]]) -> Vec<usize> {
+    let mut ids = HashMap::new();
+    positions
+        .iter()
+        .map(|position| {
+            // adding zero folds -0.0 into 0.0
+            let key = position.map(|c| (c + 0.0).to_bits());
+            let next = ids.len();
+            *ids.entry(key).or_insert(next)
+        })
+        .collect()
+}
+
+// a position rounded to `decimal_places` as integers, scaling by an exact power of ten
+// so the same position rounds the same way on every platform; up to 9 places are used
+pub(crate) fn quantize(position: [f32; 3], decimal_places: u32) -> [i64; 3] {
+    let scale = 10_i64.pow(decimal_places.min(9)) as f64;
+    position.map(|c| (f64::from(c) * scale).round() as i64)
+}
+
+// the position `quantize` rounded to
+pub(crate) fn dequantize(key: [i64; 3], decimal_places: u32) -> [f32; 3] {
+    let scale = 10_i64.pow(decimal_places.min(9)) as f64;
+    key.map(|q| (q as f64 / scale) as f32)
+}
+
+// area-weighted average of the faces around each vertex
+pub(crate) fn smooth_normals(positions: &[[f32; 3]], triangles: &[[usize; 3]]) -> Vec<[f32; 3]> {
+    let mut normals = vec![Vec3::ZERO; positions.len()];
+    for tri in triangles {
+        let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+        let normal = (b - a).cross(c - a);
+        for v in tri {
+            normals[*v] += normal;
+        }
+    }
+
+    normals
+        .into_iter()
+        .map(|normal| normal.normalize_or_zero().into())
+        .collect()
+}
+
+/// Small deterministic random number generator (splitmix64), so seeded output is the
+/// same on every platform without pulling in a rand dependency.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u32) -> Self {
+        Rng(u64::from(seed) ^ 0x853c_49e6_748f_ea9b)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        ((z ^ (z >> 31)) >> 32) as u32
+    }
+
+    /// uniform in 0..1
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+
+    /// uniform in `min..max`
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+}
+
+// union-find over anything numbered from 0, like corners or triangles
+pub(crate) struct Groups(Vec<usize>);
+
+impl Groups {
+    pub(crate) fn new(count: usize) -> Self {
+        Groups((0..count).collect())
+    }
+
+    pub(crate) fn find(&mut self, mut item: usize) -> usize {
+        while self.0[item] != item {
+            self.0[item] = self.0[self.0[item]];
+            item = self.0[item];
+        }
+        item
+    }
+
+    pub(crate) fn join(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        self.0[a] = b;
+    }
+}
+
+// orders a max-heap so the shortest distance (or lowest height, or any other key)
+// comes out first
+pub(crate) struct Closest(pub(crate) f32, pub(crate) usize);
+
+impl PartialEq for Closest {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Closest {}
+
+impl PartialOrd for Closest {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Closest {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
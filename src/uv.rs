@@ -0,0 +1,664 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology},
+    utils::HashMap,
+};
+
+use crate::{
+    attributes::{normals, uvs, uvs_mut},
+    bounds::position_bounds,
+    util::{gather_vertices, positions, triangle_indices, Groups},
+};
+
+// how close to the seam a vertex has to be to count as on it
+const SEAM_TOLERANCE: f32 = 1e-5;
+
+/// How the box-like generators, [`generate_brick_wall`](crate::wall::generate_brick_wall),
+/// [`generate_spiral_stairs`](crate::stairs::generate_spiral_stairs) and
+/// [`generate_subdivided_cube`](crate::sphere::generate_subdivided_cube), lay out UVs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UvMode {
+    /// each face mapped on its own, for decals and textures made to fit one face: most
+    /// faces cover 0..1, and the subdivided cube's its own cell of an atlas
+    #[default]
+    PerFace,
+    /// projected from world space along whichever axis each face is closest to facing,
+    /// repeating the texture this many times per unit, so a tiling texture runs on
+    /// unbroken across faces that face the same way. Seen from outside, U runs to the
+    /// right and V down, with the top and bottom seen as if stood in front of -Z
+    WorldSpace(f32),
+}
+
+impl UvMode {
+    // the UV for a vertex at `position` on a face facing `normal`, where `per_face` is
+    // what the generator gives it on its own
+    pub(crate) fn uv(self, per_face: Vec2, position: Vec3, normal: Vec3) -> Vec2 {
+        let UvMode::WorldSpace(scale) = self else {
+            return per_face;
+        };
+
+        let axis = normal.abs();
+        let projected = if axis.x >= axis.y && axis.x >= axis.z {
+            Vec2::new(-position.z * normal.x.signum(), -position.y)
+        } else if axis.y >= axis.z {
+            Vec2::new(position.x, position.z * normal.y.signum())
+        } else {
+            Vec2::new(position.x * normal.z.signum(), -position.y)
+        };
+        projected * scale
+    }
+}
+
+/// A `LineList` mesh tracing the outline of every triangle in UV space, laid out in the
+/// XY plane over a `size` by `size` square with the texture's top-left corner at
+/// `(0, size)`.
+///
+/// Triangles whose unwrap shows their front face are drawn in `front`, and ones that
+/// are mirrored in UV space (so a texture would appear on them flipped) in `back`. The
+/// lines carry normals and UVs as well as colors, so they can be drawn with a plain
+/// `ColorMaterial`.
+pub fn uv_layout_lines(mesh: &Mesh, size: f32, front: Color, back: Color) -> Mesh {
+    let uvs = uvs(mesh).unwrap_or(&[]);
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut line_uvs: Vec<[f32; 2]> = vec![];
+    let mut colors: Vec<[f32; 4]> = vec![];
+
+    for tri in triangle_indices(mesh) {
+        if tri.iter().any(|v| *v >= uvs.len()) {
+            continue;
+        }
+
+        let [a, b, c] = tri.map(|v| Vec2::from(uvs[v]));
+        // V runs down the texture, so a front face that's counter-clockwise in 3D is
+        // clockwise here
+        let color = if (b - a).perp_dot(c - a) <= 0.0 {
+            front
+        } else {
+            back
+        };
+
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            for uv in [from, to] {
+                positions.push([uv.x * size, (1.0 - uv.y) * size, 0.0]);
+                line_uvs.push(uv.into());
+                colors.push(color.as_linear_rgba_f32());
+            }
+        }
+    }
+
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+
+    let mut lines = Mesh::new(PrimitiveTopology::LineList);
+    lines.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    lines.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    lines.insert_attribute(Mesh::ATTRIBUTE_UV_0, line_uvs);
+    lines.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
+    lines
+}
+
+/// How many separate islands the mesh's UVs make up, such as when checking a mesh
+/// before sending it to be painted in another tool.
+///
+/// Two triangles are on the same island when they share an edge whose ends have the
+/// same position and the same UV on both sides, so an edge split by a UV seam divides
+/// islands even where the triangles share indices. A box with its own UV square per
+/// face has six islands and an unbroken grid one. A mesh without UVs has none.
+pub fn uv_island_count(mesh: &Mesh) -> usize {
+    let Some(islands) = uv_islands(mesh) else {
+        return 0;
+    };
+    islands
+        .iter()
+        .enumerate()
+        .filter(|(triangle, island)| *triangle == **island)
+        .count()
+}
+
+/// Scales the UVs of each island of the mesh (see [`uv_island_count`]) about the middle
+/// of its UV bounds so it covers `texels_per_unit` of the texture per unit of surface,
+/// giving big faces and small ones the same texture resolution.
+///
+/// Density is measured in texture widths, so a 1024 texel wide texture at 256 texels per
+/// unit is a `texels_per_unit` of 0.25, and an island's scale is picked so its area in UV
+/// space is its area on the surface times `texels_per_unit` squared. Islands keep their
+/// shape, so one stretched more along one axis than the other stays that way. Islands
+/// can grow into each other or out of the 0..1 square, which is fine for tiling
+/// textures but needs repacking for an atlas. Vertices shared by islands that only touch
+/// at a corner get a copy for each island after the first, added after the existing
+/// vertices. Islands with no area, meshes without UVs, and a `texels_per_unit` that
+/// isn't positive are left as they were.
+pub fn normalize_texel_density(mesh: &mut Mesh, texels_per_unit: f32) {
+    if !(texels_per_unit > 0.0 && texels_per_unit.is_finite()) {
+        return;
+    }
+    let (Some(islands), Some(uvs)) = (uv_islands(mesh), uvs(mesh)) else {
+        return;
+    };
+    let positions = positions(mesh);
+    let triangles = triangle_indices(mesh);
+
+    // surface area, UV area and UV bounds of each island
+    let mut areas: HashMap<usize, (f32, f32, Vec2, Vec2)> = HashMap::new();
+    for (tri, island) in triangles.iter().zip(&islands) {
+        let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+        let [ta, tb, tc] = tri.map(|v| Vec2::from(uvs[v]));
+        let (surface, uv, min, max) = areas.entry(*island).or_insert((
+            0.0,
+            0.0,
+            Vec2::splat(f32::INFINITY),
+            Vec2::splat(f32::NEG_INFINITY),
+        ));
+        *surface += (b - a).cross(c - a).length() / 2.0;
+        *uv += (tb - ta).perp_dot(tc - ta).abs() / 2.0;
+        *min = min.min(ta).min(tb).min(tc);
+        *max = max.max(ta).max(tb).max(tc);
+    }
+    let scales: HashMap<usize, (Vec2, f32)> = areas
+        .into_iter()
+        .map(|(island, (surface, uv, min, max))| {
+            let scale = texels_per_unit * (surface / uv).sqrt();
+            let scale = if scale.is_finite() && scale > 0.0 {
+                scale
+            } else {
+                1.0
+            };
+            (island, ((min + max) / 2.0, scale))
+        })
+        .collect();
+
+    // the island each vertex is scaled for, with a copy of it for each other island
+    // that uses it
+    let mut owners: Vec<Option<usize>> = vec![None; uvs.len()];
+    let mut copies: HashMap<(usize, usize), u32> = HashMap::new();
+    let mut order: Vec<u32> = (0..uvs.len() as u32).collect();
+    let mut indices = vec![];
+    for (tri, island) in triangles.iter().zip(&islands) {
+        for vertex in tri {
+            if *owners[*vertex].get_or_insert(*island) == *island {
+                indices.push(*vertex as u32);
+                continue;
+            }
+            let copy = *copies.entry((*vertex, *island)).or_insert_with(|| {
+                order.push(*vertex as u32);
+                owners.push(Some(*island));
+                order.len() as u32 - 1
+            });
+            indices.push(copy);
+        }
+    }
+
+    if order.len() > uvs.len() {
+        gather_vertices(mesh, order);
+        mesh.set_indices(Some(Indices::U32(indices)));
+    }
+    let Some(uvs) = uvs_mut(mesh) else {
+        return;
+    };
+    for (uv, owner) in uvs.iter_mut().zip(owners) {
+        if let Some((center, scale)) = owner.map(|island| scales[&island]) {
+            *uv = (center + (Vec2::from(*uv) - center) * scale).into();
+        }
+    }
+}
+
+/// Shifts every UV by `offset`, for flowing water or lava when called each frame with
+/// a small step.
+///
+/// UVs only ever move by whole tiles to keep them from growing without bound, and by
+/// the same amount across the mesh so no triangle gets stretched across a wrap. That
+/// means the texture has to repeat (an `AddressMode::Repeat` sampler) for the wrap to be
+/// invisible.
+pub fn scroll_uvs(mesh: &mut Mesh, offset: Vec2) {
+    let Some(uvs) = uvs_mut(mesh) else {
+        return;
+    };
+
+    let min = uvs.iter().fold(Vec2::splat(f32::INFINITY), |min, uv| {
+        min.min(Vec2::from(*uv))
+    });
+    // keeps the lowest UV on each axis within 0..1 after the shift
+    let shift = offset - (min + offset).floor();
+    if !shift.is_finite() {
+        return;
+    }
+
+    for uv in uvs {
+        *uv = (Vec2::from(*uv) + shift).into();
+    }
+}
+
+/// Gives every triangle with a seam between two of its corners its own copy of each of
+/// its vertices, so its UVs can be moved to the far side of the seam without tearing the
+/// triangles next to it, which keep the vertices they had.
+///
+/// `is_seam` is given the UVs at either end of each edge and decides whether the edge
+/// crosses a seam: `|a, b| (a.x - b.x).abs() > 0.5` finds the triangles that wrap around
+/// a texture's left and right edges. Existing vertices keep their indices and the copies
+/// come after them, so a mesh with no seams, or without UVs, is left as it was.
+pub fn split_seam_vertices(mesh: &mut Mesh, is_seam: impl Fn(Vec2, Vec2) -> bool) {
+    let Some(uvs) = uvs(mesh) else {
+        return;
+    };
+    let crosses = |tri: &[usize; 3]| {
+        (0..3).any(|k| is_seam(Vec2::from(uvs[tri[k]]), Vec2::from(uvs[tri[(k + 1) % 3]])))
+    };
+
+    let triangles = triangle_indices(mesh);
+    if !triangles.iter().any(crosses) {
+        return;
+    }
+    let mut order: Vec<u32> = (0..uvs.len() as u32).collect();
+    let mut indices = vec![];
+    for tri in &triangles {
+        if crosses(tri) {
+            for vertex in tri {
+                order.push(*vertex as u32);
+                indices.push(order.len() as u32 - 1);
+            }
+        } else {
+            indices.extend(tri.map(|vertex| vertex as u32));
+        }
+    }
+
+    gather_vertices(mesh, order);
+    mesh.set_indices(Some(Indices::U32(indices)));
+}
+
+/// Replaces a mesh's UVs with a projection from the center of its bounds onto a sphere
+/// around the Y axis, for an equirectangular texture: U goes once around (increasing to
+/// the right seen from outside, with the seam facing -Z) and V runs from the top pole
+/// down to the bottom one.
+///
+/// See [`cylindrical_uvs`] for how the seam is split and what `seam_gutter` does; no
+/// triangle is left stretching back across the texture. The corners at a pole take the
+/// middle U of the rest of their triangle, so each triangle around a pole gets its own
+/// copy of the pole vertex and the texture fans out from the pole rather than every
+/// triangle there squeezing into one column of it. This works on welded meshes and
+/// triangle soup alike, so it's all a sphere needs for an equirectangular texture.
+pub fn spherical_uvs(mesh: &mut Mesh, seam_gutter: f32) {
+    let Some((min, max)) = position_bounds(positions(mesh)) else {
+        return;
+    };
+    let center = (min + max) / 2.0;
+    project_around_y(mesh, center, seam_gutter, |position| {
+        (position - center)
+            .normalize_or_zero()
+            .y
+            .clamp(-1.0, 1.0)
+            .acos()
+            / PI
+    });
+}
+
+/// Replaces a mesh's UVs with a projection from the Y axis through the center of its
+/// bounds onto a cylinder around it: U goes once around (increasing to the right seen
+/// from outside, with the seam facing -Z) and V runs from the top of the mesh down to
+/// the bottom.
+///
+/// A texture can't wrap around without a seam, so vertices of triangles that cross it
+/// are split, giving those triangles U values past 1 that carry on from the other side
+/// of the texture rather than stretching back across it; vertices no triangle uses are
+/// dropped. With an `AddressMode::Repeat` sampler the texture continues across the
+/// seam, but filtering there blends in texels from the opposite edge, and mipmapping
+/// widens that to a whole texel of each level, which shows up as a line on textures
+/// that don't tile. A positive `seam_gutter` keeps every U at least that far from the
+/// seam on its own side of it instead, so filtering stays within the chart: it has to
+/// be at least half a texel of the smallest mip the seam is drawn at
+/// (`0.5 * 2^level / width`) to cover that level. The triangles crossing the seam keep
+/// their corners on either side of it, inset by the gutter, so they still carry on
+/// from one edge of the texture to the other.
+pub fn cylindrical_uvs(mesh: &mut Mesh, seam_gutter: f32) {
+    let Some((min, max)) = position_bounds(positions(mesh)) else {
+        return;
+    };
+    let height = max.y - min.y;
+    project_around_y(mesh, (min + max) / 2.0, seam_gutter, |position| {
+        if height > 0.0 {
+            (max.y - position.y) / height
+        } else {
+            0.0
+        }
+    });
+}
+
+/// Regenerates the mesh's tangents from its current UVs and normals, returning whether
+/// it could: the mesh has to be a triangle list with positions, normals and UVs.
+///
+/// Normal maps are read along the tangents, so they go wrong once the UVs under them
+/// turn or mirror. [`spherical_uvs`] and [`cylindrical_uvs`] refresh tangents a mesh
+/// already has, while [`scroll_uvs`] and [`normalize_texel_density`] only move and
+/// scale UVs, which leaves tangents as they were. Anything else that edits UVs directly
+/// has to call this afterwards. A mesh that can't get tangents is left as it was.
+pub fn refresh_tangents(mesh: &mut Mesh) -> bool {
+    if uvs(mesh).is_none() || normals(mesh).is_none() {
+        return false;
+    }
+    mesh.generate_tangents().is_ok()
+}
+
+// sets every vertex's U to its angle around the vertical axis through `center` and its
+// V from `v`, splitting vertices where triangles need different U values for the seam
+// or a pole
+fn project_around_y(mesh: &mut Mesh, center: Vec3, seam_gutter: f32, v: impl Fn(Vec3) -> f32) {
+    if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+        return;
+    }
+    let positions: Vec<Vec3> = positions(mesh).iter().map(|p| Vec3::from(*p)).collect();
+    let angle = |position: Vec3| {
+        let offset = position - center;
+        if offset.x.abs() <= SEAM_TOLERANCE && offset.z.abs() <= SEAM_TOLERANCE {
+            // straight above or below the center, at a pole
+            None
+        } else {
+            Some(0.5 + offset.x.atan2(offset.z) / TAU)
+        }
+    };
+    let on_seam = |u: f32| !(SEAM_TOLERANCE..=1.0 - SEAM_TOLERANCE).contains(&u);
+    let gutter = seam_gutter.clamp(0.0, 0.5);
+
+    let mut order: Vec<u32> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+    let mut split: HashMap<(usize, u32), u32> = HashMap::new();
+    let mut indices = vec![];
+    for tri in triangle_indices(mesh) {
+        let mut turns = tri.map(|vertex| angle(positions[vertex]));
+
+        // a corner on the seam belongs to whichever side the rest of its triangle is on
+        let off_seam: Vec<f32> = turns
+            .iter()
+            .flatten()
+            .copied()
+            .filter(|u| !on_seam(*u))
+            .collect();
+        let east = off_seam.iter().sum::<f32>() <= 0.5 * off_seam.len() as f32;
+        for u in turns.iter_mut().flatten() {
+            if on_seam(*u) {
+                *u = if east { 0.0 } else { 1.0 };
+            }
+        }
+
+        // a triangle more than half a turn wide is really a narrow one across the seam
+        let known: Vec<f32> = turns.iter().flatten().copied().collect();
+        let (low, high) = known
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(low, high), u| {
+                (low.min(*u), high.max(*u))
+            });
+        let wraps = high - low > 0.5;
+
+        // the gutter insets each corner from the seam on its own side of it, before
+        // the wrap, so a triangle carried past 1 is inset to 1 + gutter rather than
+        // squashed against 1 - gutter
+        for u in turns.iter_mut().flatten() {
+            *u = if *u < 0.5 {
+                u.max(gutter)
+            } else {
+                u.min(1.0 - gutter)
+            };
+        }
+        if wraps {
+            for u in turns.iter_mut().flatten() {
+                if *u < 0.5 {
+                    *u += 1.0;
+                }
+            }
+        }
+
+        let pole = if known.is_empty() {
+            0.5
+        } else {
+            turns.iter().flatten().sum::<f32>() / known.len() as f32
+        };
+        for (k, vertex) in tri.into_iter().enumerate() {
+            let u = turns[k].unwrap_or(pole);
+            let index = *split.entry((vertex, u.to_bits())).or_insert_with(|| {
+                order.push(vertex as u32);
+                uvs.push([u, v(positions[vertex])]);
+                order.len() as u32 - 1
+            });
+            indices.push(index);
+        }
+    }
+
+    gather_vertices(mesh, order);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    if mesh.attribute(Mesh::ATTRIBUTE_TANGENT).is_some() {
+        refresh_tangents(mesh);
+    }
+}
+
+// the island each triangle is on, as the lowest-numbered triangle on it
+fn uv_islands(mesh: &Mesh) -> Option<Vec<usize>> {
+    let uvs = uvs(mesh)?;
+    let positions = positions(mesh);
+
+    // an id per distinct position and UV pair
+    let mut ids: HashMap<([u32; 3], [u32; 2]), usize> = HashMap::new();
+    let corners: Vec<usize> = positions
+        .iter()
+        .zip(uvs)
+        .map(|(position, uv)| {
+            // adding zero folds -0.0 into 0.0
+            let key = (
+                position.map(|c| (c + 0.0).to_bits()),
+                uv.map(|c| (c + 0.0).to_bits()),
+            );
+            let next = ids.len();
+            *ids.entry(key).or_insert(next)
+        })
+        .collect();
+
+    let triangles = triangle_indices(mesh);
+    let mut islands = Groups::new(triangles.len());
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+    for (triangle, tri) in triangles.iter().enumerate() {
+        let [a, b, c] = tri.map(|v| corners[v]);
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            let first = *edges
+                .entry((from.min(to), from.max(to)))
+                .or_insert(triangle);
+            islands.join(first, triangle);
+        }
+    }
+
+    // the lowest triangle of each group, found as the first one to reach its root
+    let mut lowest: HashMap<usize, usize> = HashMap::new();
+    Some(
+        (0..triangles.len())
+            .map(|triangle| *lowest.entry(islands.find(triangle)).or_insert(triangle))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::render::mesh::VertexAttributeValues;
+
+    use super::*;
+    use crate::{
+        cylinder::{generate_cylinder, CylinderParams},
+        merge::apply_transform,
+        normals::NormalMode,
+        plane::{generate_radial_lod_plane, RadialLodPlaneParams},
+        sphere::{generate_subdivided_cube, generate_uv_sphere, UvSphereParams},
+        terrain::{generate_heightmap, HeightmapParams},
+        wall::generate_brick_wall,
+    };
+
+    #[test]
+    fn seam_gutter_insets_each_side_of_the_seam() {
+        // five segments leave no vertex on the seam, so the side has a face across it
+        let params = CylinderParams {
+            segments: 5,
+            ..default()
+        };
+        let gutter = 0.01;
+        let mut plain = generate_cylinder(&params).unwrap();
+        let mut inset = plain.clone();
+        cylindrical_uvs(&mut plain, 0.0);
+        cylindrical_uvs(&mut inset, gutter);
+
+        let us = |mesh: &Mesh| {
+            uvs(mesh)
+                .unwrap()
+                .iter()
+                .map(|uv| uv[0])
+                .collect::<Vec<_>>()
+        };
+        let (plain, inset) = (us(&plain), us(&inset));
+        assert_eq!(plain.len(), inset.len());
+        // the face across the seam is carried past 1 rather than squashed below it
+        assert!(inset.iter().any(|u| *u >= 1.0 + gutter));
+        for (before, after) in plain.into_iter().zip(inset) {
+            let seam = before.round();
+            let expected = if before < seam {
+                before.min(seam - gutter)
+            } else {
+                before.max(seam + gutter)
+            };
+            assert_eq!(after, expected, "{before}");
+        }
+    }
+
+    #[test]
+    fn box_faces_are_separate_islands_and_a_plane_is_one() {
+        let cube = generate_subdivided_cube(1.0, 3, UvMode::PerFace, NormalMode::Flat).unwrap();
+        assert_eq!(uv_island_count(&cube), 6);
+
+        let mut plane = generate_heightmap(&HeightmapParams::default(), |x, z| x * z);
+        assert_eq!(uv_island_count(&plane), 1);
+        plane.duplicate_vertices();
+        assert_eq!(uv_island_count(&plane), 1);
+    }
+
+    // bricks laid without mortar, so neighbors' faces meet edge to edge
+    #[test]
+    fn world_space_uvs_run_on_across_neighboring_faces() {
+        let wall =
+            generate_brick_wall(3, 2, Vec3::new(0.4, 0.2, 0.2), 0.0, UvMode::WorldSpace(2.0));
+        let (positions, world_uvs) = (positions(&wall), uvs(&wall).unwrap());
+        let normals = normals(&wall).unwrap();
+        let mut shared = 0;
+        for i in 0..positions.len() {
+            for j in 0..i {
+                let same_place = Vec3::from(positions[i]).distance(positions[j].into()) < 1e-5;
+                if same_place && normals[i] == normals[j] {
+                    assert!(Vec2::from(world_uvs[i]).distance(world_uvs[j].into()) < 1e-4);
+                    shared += 1;
+                }
+            }
+        }
+        assert!(shared >= 8);
+
+        let per_face = generate_brick_wall(3, 2, Vec3::new(0.4, 0.2, 0.2), 0.0, UvMode::PerFace);
+        assert!(uvs(&per_face)
+            .unwrap()
+            .iter()
+            .flatten()
+            .all(|c| (0.0..=1.0).contains(c)));
+    }
+
+    #[test]
+    fn splitting_seams_on_a_seamless_plane_changes_nothing() {
+        let mut plane = generate_radial_lod_plane(&RadialLodPlaneParams::default());
+        let before = plane.clone();
+        split_seam_vertices(&mut plane, |a, b| (a.x - b.x).abs() > 0.5);
+        assert_eq!(positions(&plane), positions(&before));
+        assert_eq!(uvs(&plane), uvs(&before));
+        assert_eq!(triangle_indices(&plane), triangle_indices(&before));
+    }
+
+    // a cube stretched unevenly has faces of three different sizes, all with the same UVs
+    #[test]
+    fn texel_density_comes_out_the_same_on_every_island() {
+        let mut cube = generate_subdivided_cube(1.0, 2, UvMode::PerFace, NormalMode::Flat).unwrap();
+        apply_transform(&mut cube, Transform::from_scale(Vec3::new(1.0, 2.0, 5.0)));
+        let density = |mesh: &Mesh| -> Vec<f32> {
+            let (positions, face_uvs) = (positions(mesh), uvs(mesh).unwrap());
+            triangle_indices(mesh)
+                .iter()
+                .map(|tri| {
+                    let [a, b, c] = tri.map(|v| Vec3::from(positions[v]));
+                    let [p, q, r] = tri.map(|v| Vec2::from(face_uvs[v]));
+                    (q - p).perp_dot(r - p).abs() / (b - a).cross(c - a).length()
+                })
+                .collect()
+        };
+        let before = density(&cube);
+        assert!(before.iter().any(|d| (d - before[0]).abs() > 0.01));
+
+        normalize_texel_density(&mut cube, 0.5);
+        assert_eq!(uv_island_count(&cube), 6);
+        for d in density(&cube) {
+            assert!((d - 0.25).abs() < 1e-4, "{d}");
+        }
+    }
+
+    #[test]
+    fn spherical_uvs_split_the_seam_and_fan_out_at_the_poles() {
+        let mut sphere = generate_uv_sphere(&UvSphereParams::default()).unwrap();
+        spherical_uvs(&mut sphere, 0.0);
+        let (positions, sphere_uvs) = (positions(&sphere), uvs(&sphere).unwrap());
+
+        // the U each triangle gives a pole, and whether it's the top one
+        let mut pole_us: Vec<(bool, f32)> = vec![];
+        for tri in triangle_indices(&sphere) {
+            let us = tri.map(|v| sphere_uvs[v][0]);
+            let (min, max) = (
+                us.iter().copied().fold(f32::MAX, f32::min),
+                us.iter().copied().fold(f32::MIN, f32::max),
+            );
+            assert!(max - min < 0.5, "{us:?}");
+
+            let at_pole = |v: usize| Vec3::from(positions[v]).normalize().y.abs() > 1.0 - 1e-6;
+            if let Some(pole) = (0..3).find(|&corner| at_pole(tri[corner])) {
+                let others = [us[(pole + 1) % 3], us[(pole + 2) % 3]];
+                assert!(
+                    (us[pole] - (others[0] + others[1]) / 2.0).abs() < 1e-5,
+                    "{us:?}"
+                );
+                pole_us.push((positions[tri[pole]][1] > 0.0, us[pole]));
+            }
+        }
+
+        let triangles_at_poles = pole_us.len();
+        assert!(triangles_at_poles > 0);
+        pole_us.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        pole_us.dedup_by(|a, b| a.0 == b.0 && (a.1 - b.1).abs() < 1e-5);
+        assert_eq!(pole_us.len(), triangles_at_poles);
+    }
+
+    #[test]
+    fn refreshed_tangents_follow_the_new_uvs() {
+        let tangent_directions = |mesh: &Mesh| -> Vec<Vec3> {
+            match mesh.attribute(Mesh::ATTRIBUTE_TANGENT) {
+                Some(VertexAttributeValues::Float32x4(tangents)) => {
+                    tangents.iter().map(|t| Vec4::from(*t).truncate()).collect()
+                }
+                _ => panic!("no tangents"),
+            }
+        };
+        let mut plane = generate_heightmap(&HeightmapParams::default(), |_, _| 0.0);
+        assert!(refresh_tangents(&mut plane));
+        assert!(tangent_directions(&plane)
+            .iter()
+            .all(|t| t.distance(Vec3::X) < 1e-4));
+
+        // with U running along Z instead of X, the tangents have to turn with it
+        for uv in uvs_mut(&mut plane).unwrap() {
+            *uv = [uv[1], uv[0]];
+        }
+        assert!(refresh_tangents(&mut plane));
+        assert!(tangent_directions(&plane)
+            .iter()
+            .all(|t| t.distance(Vec3::Z) < 1e-4));
+
+        plane.remove_attribute(Mesh::ATTRIBUTE_NORMAL);
+        assert!(!refresh_tangents(&mut plane));
+    }
+}
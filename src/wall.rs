@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use crate::{builder::CustomMeshBuilder, uv::UvMode};
+
+// each face's outward axis, and the directions that are right and up on it seen from
+// outside
+const FACES: [(Vec3, Vec3, Vec3); 6] = [
+    (Vec3::X, Vec3::NEG_Z, Vec3::Y),
+    (Vec3::NEG_X, Vec3::Z, Vec3::Y),
+    (Vec3::Y, Vec3::X, Vec3::NEG_Z),
+    (Vec3::NEG_Y, Vec3::X, Vec3::Z),
+    (Vec3::Z, Vec3::X, Vec3::Y),
+    (Vec3::NEG_Z, Vec3::NEG_X, Vec3::Y),
+];
+
+/// Wall of `bricks_wide` by `bricks_high` boxes `brick_size` across, laid in running
+/// bond with `mortar` between them, standing on the XZ plane and centered on the Y
+/// axis with its face towards +Z.
+///
+/// Every other row, starting with the second from the bottom, is shifted by half a
+/// brick so its joints sit over the middle of the bricks below, with a half brick at
+/// each end to keep the wall's ends straight, so those rows have one more piece. The
+/// gaps are left open for a mortar-colored backing to show through. With
+/// [`UvMode::PerFace`], each face of a brick has its own UVs covering 0..1, except that a
+/// half brick's faces only cover half of U across its width, so a brick texture isn't
+/// squashed on them.
+pub fn generate_brick_wall(
+    bricks_wide: u32,
+    bricks_high: u32,
+    brick_size: Vec3,
+    mortar: f32,
+    uvs: UvMode,
+) -> Mesh {
+    let pitch = brick_size.truncate() + Vec2::splat(mortar);
+    let width = bricks_wide as f32 * pitch.x - mortar;
+    let half_brick = (brick_size.x - mortar) / 2.0;
+
+    let mut builder = CustomMeshBuilder::new();
+    for row in 0..bricks_high {
+        let bottom = row as f32 * pitch.y;
+        // the left edges and widths of the bricks in this row
+        let mut pieces: Vec<(f32, f32)> = vec![];
+        if row % 2 == 0 {
+            pieces.extend((0..bricks_wide).map(|i| (i as f32 * pitch.x, brick_size.x)));
+        } else if bricks_wide > 0 {
+            pieces.push((0.0, half_brick));
+            let first = half_brick + mortar;
+            pieces.extend((0..bricks_wide - 1).map(|i| (first + i as f32 * pitch.x, brick_size.x)));
+            pieces.push((width - half_brick, half_brick));
+        }
+
+        for (left, brick_width) in pieces {
+            let min = Vec3::new(left - width / 2.0, bottom, -brick_size.z / 2.0);
+            let size = Vec3::new(brick_width, brick_size.y, brick_size.z);
+            brick(&mut builder, min, size, brick_width / brick_size.x, uvs);
+        }
+    }
+
+    builder
+        .build()
+        .expect("brick triangles only use vertices added alongside them")
+}
+
+// a box from `min` to `min + size`, whose faces' U covers `u_span` of a full brick
+// across the X axis when mapped per face
+fn brick(builder: &mut CustomMeshBuilder, min: Vec3, size: Vec3, u_span: f32, uvs: UvMode) {
+    let half = size / 2.0;
+    let center = min + half;
+    for (outward, right, up) in FACES {
+        let u_scale = if right.x != 0.0 { u_span } else { 1.0 };
+
+        //  (0,0)   (1,0)
+        //    a ----- d
+        //    |     / |
+        //    |   /   |
+        //    | /     |
+        //    b ----- c
+        //  (0,1)   (1,1)
+        let [a, b, c, d] = [(0.0, 0.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)].map(|(s, t)| {
+            let corner = outward + right * (s * 2.0 - 1.0) + up * (1.0 - t * 2.0);
+            let position = center + corner * half;
+            let uv = uvs.uv(Vec2::new(s * u_scale, t), position, outward);
+            builder.vertex(position, uv, outward)
+        });
+        builder.triangle(a, b, d).triangle(d, b, c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::{positions, triangle_indices};
+
+    #[test]
+    fn brick_wall_lays_every_other_row_half_a_brick_over() {
+        let size = Vec3::new(0.4, 0.1, 0.2);
+        let wall = generate_brick_wall(5, 4, size, 0.02, UvMode::PerFace);
+        // two rows of 5 bricks, and two of 4 bricks with a half at each end
+        assert_eq!(triangle_indices(&wall).len(), (5 + 6 + 5 + 6) * 12);
+
+        // the X of every brick edge along the bottom of a row
+        let edges = |bottom: f32| {
+            let mut xs: Vec<f32> = positions(&wall)
+                .iter()
+                .filter(|p| (p[1] - bottom).abs() < 1e-4)
+                .map(|p| p[0])
+                .collect();
+            xs.sort_by(f32::total_cmp);
+            xs.dedup_by(|a, b| (*a - *b).abs() < 1e-4);
+            xs
+        };
+        let (first, second) = (edges(0.0), edges(0.12));
+        assert_eq!((first.len(), second.len()), (10, 12));
+        assert!((first[0] - second[0]).abs() < 1e-4 && (first[9] - second[11]).abs() < 1e-4);
+        // the first joint of the second row is over the middle of the first brick
+        let joint = (second[1] + second[2]) / 2.0;
+        assert!((joint - (first[0] + first[1]) / 2.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,48 @@
+use bevy::prelude::*;
+
+use crate::{cylinder::generate_strut, merge::merge_meshes, topology::edges_used, util::positions};
+
+// around each strut, giving it a square cross-section
+const SEGMENTS: u32 = 4;
+
+/// Wireframe of `mesh` built out of triangles: a thin, capped strut `thickness` across
+/// along each of its edges, merged into one mesh. Unlike Bevy's line-mode wireframe it
+/// doesn't need the `POLYGON_MODE_LINE` GPU feature, has real thickness and takes
+/// lighting and materials like any other mesh.
+///
+/// Edges are found through shared positions, so one running along a UV seam or a hard
+/// edge, where the vertices at either end are split, gets a single strut rather than
+/// one for each copy. Edges with no length are left out, and a `thickness` that isn't
+/// positive gives a mesh with no vertices.
+pub fn to_wireframe_mesh(mesh: &Mesh, thickness: f32) -> Mesh {
+    let points = positions(mesh);
+    let struts: Vec<Mesh> = edges_used(mesh, |_| true)
+        .into_iter()
+        .filter(|(from, to)| from != to)
+        .map(|(from, to)| {
+            let [from, to] = [from, to].map(|v| Vec3::from(points[v as usize]));
+            generate_strut(from, to, thickness / 2.0, SEGMENTS)
+        })
+        .collect();
+    merge_meshes(&struts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compose::cuboid;
+
+    // a box has its vertices split at every corner, but twelve edges and a diagonal across
+    // each of its six faces
+    #[test]
+    fn wireframe_has_a_strut_per_unique_edge() {
+        let cube = cuboid(Vec3::ONE).unwrap();
+        let wireframe = to_wireframe_mesh(&cube, 0.05);
+        let strut = generate_strut(Vec3::ZERO, Vec3::X, 0.025, SEGMENTS);
+        assert_eq!(
+            wireframe.count_vertices(),
+            (12 + 6) * strut.count_vertices()
+        );
+        assert_eq!(to_wireframe_mesh(&cube, 0.0).count_vertices(), 0);
+    }
+}